@@ -0,0 +1,265 @@
+//! # schack_matchmaker
+//! Companion server of the Schack GUI for online games. Players connect to it rather than to each other, so
+//! two players behind NATs can play without exchanging addresses: only the server needs a reachable port.
+//!
+//! Connections are encrypted with TLS, with the certificate and key given as PEM files:
+//! `schack_matchmaker --cert cert.pem --key key.pem`. Clients trust the certificate when it is signed by a public
+//! certificate authority, or when it is self-signed and they were given a copy of it.
+//!
+//! The protocol is plain lines of text:
+//! - The client opens with `HELLO <name> <time control>`, the time control as "<initial>+<increment>" in seconds or "-"
+//!   within 5 seconds of connecting. Connections that do not, or send a line longer than 16 KiB, are dropped
+//! - The server answers `WAITING` until another player with the same time control arrives, then
+//!   `PAIRED <white|black> <opponent's name> <start>`, the player who waited longest taking White. The start is the
+//!   time of pairing in seconds since the Unix epoch, so both players date the game the same
+//! - From then on every line of one player is passed on to the other as it is, those sent before pairing first, and
+//!   `LEFT` is sent when the other player disconnected. Waiting players who disconnected are never paired
+
+use rustls::{Certificate, PrivateKey, ServerConfig, ServerConnection, StreamOwned};
+use std::{env, fs::File, io::{self, BufReader, Read, Write}, net::{Shutdown, TcpListener, TcpStream}, sync::{Arc, Mutex}, thread, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+
+/// Port listened on without `--port <port>`
+const DEFAULT_PORT: u16 = 7878;
+
+/// How long a relay waits for lines of one player before it looks at the other
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long a new player has to send their greeting, as long as the GUI waits for a guest's
+const GREETING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest line a player may send, whole move lists included. Whoever sends more without a line break is dropped.
+const MAX_LINE_LENGTH: usize = 16 * 1024;
+
+/// How long the connection of a waiting player is read before pairing, to see that they are still there
+const PROBE_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// Most lines a player may send before being paired, they are kept for the opponent until then
+const MAX_PENDING_LINES: usize = 16;
+
+/// The encrypted connection of a player
+type Stream = StreamOwned<ServerConnection, TcpStream>;
+
+/// A connected player
+struct Player {
+    name: String,
+    time_control: String, // Only players with the same time control are paired
+    stream: Stream,
+    partial: Vec<u8>, // What arrived of the line being read
+    pending: Vec<String> // Lines that arrived before pairing, passed on to the opponent first
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let value = |_flag: &str| args.iter().position(|_arg| _arg == _flag).map(|_index| args.get(_index + 1).cloned().unwrap_or_default());
+
+    let port = value("--port").map(|_port| _port.parse::<u16>().expect("--port needs a port.")).unwrap_or(DEFAULT_PORT);
+    let config = match (value("--cert"), value("--key")) {
+        (Some(cert), Some(key)) => server_config(&cert, &key).unwrap_or_else(|_error| panic!("Failed to load the certificate: {}", _error)),
+        _ => panic!("--cert <file> and --key <file> are needed, the PEM files of the certificate and its private key.")
+    };
+
+    let listener = TcpListener::bind(("0.0.0.0", port)).expect("Failed to listen.");
+    println!("matchmaker listening on port {}", port);
+
+    let waiting: Arc<Mutex<Vec<Player>>> = Arc::new(Mutex::new(vec![]));
+    for _socket in listener.incoming() {
+        if let Ok(socket) = _socket {
+            let (waiting, config) = (waiting.clone(), config.clone());
+            thread::spawn(move || {
+                // A broken connection only concerns that one player
+                let greeted = ServerConnection::new(config)
+                    .map_err(|_error| io::Error::new(io::ErrorKind::Other, _error))
+                    .and_then(|_connection| greet(StreamOwned::new(_connection, socket), &waiting));
+                if let Err(error) = greeted {
+                    eprintln!("connection dropped: {}", error);
+                }
+            });
+        }
+    }
+}
+
+/// ## `server_config`
+/// The TLS settings of the server, with its certificate
+/// ### Parameters
+/// - `_cert: &str`: PEM file of the certificate, followed by the certificates it was signed with if any
+/// - `_key: &str`: PEM file of the certificate's private key, as PKCS #8, RSA or EC key
+fn server_config(_cert: &str, _key: &str) -> Result<Arc<ServerConfig>, String> {
+    let read = |_path: &str| File::open(_path).map(BufReader::new).map_err(|_error| format!("{}: {}", _path, _error));
+
+    let certificates: Vec<Certificate> = rustls_pemfile::certs(&mut read(_cert)?)
+        .map_err(|_error| format!("{}: {}", _cert, _error))?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+    let key = rustls_pemfile::read_all(&mut read(_key)?)
+        .map_err(|_error| format!("{}: {}", _key, _error))?
+        .into_iter()
+        .find_map(|_item| match _item {
+            rustls_pemfile::Item::PKCS8Key(key) | rustls_pemfile::Item::RSAKey(key) | rustls_pemfile::Item::ECKey(key) => Some(PrivateKey(key)),
+            _ => None
+        })
+        .ok_or(format!("{} holds no private key", _key))?;
+
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certificates, key)
+        .map(Arc::new)
+        .map_err(|_error| _error.to_string())
+}
+
+/// ## `read_lines`
+/// The lines that arrived from a player, none when the read timeout passed first
+/// ### Parameters
+/// - `_stream: &mut Stream`: The connection of the player
+/// - `_partial: &mut Vec<u8>`: What arrived of the line being read, kept for the next read
+/// ### Return
+/// `Err` when the connection broke, or a line is longer than `MAX_LINE_LENGTH`
+fn read_lines(_stream: &mut Stream, _partial: &mut Vec<u8>) -> io::Result<Vec<String>> {
+    let mut buffer = [0; 1024];
+    match _stream.read(&mut buffer) {
+        Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed")),
+        Ok(read) => _partial.extend_from_slice(&buffer[..read]),
+        Err(error) if error.kind() == io::ErrorKind::WouldBlock || error.kind() == io::ErrorKind::TimedOut => {},
+        Err(error) => return Err(error)
+    }
+
+    let mut lines = vec![];
+    while let Some(end) = _partial.iter().position(|_byte| *_byte == b'\n') {
+        let line: Vec<u8> = _partial.drain(..=end).collect();
+        if line.len() > MAX_LINE_LENGTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "a line was too long"));
+        }
+        lines.push(String::from_utf8_lossy(&line).trim_end().to_string());
+    }
+
+    if _partial.len() > MAX_LINE_LENGTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "a line was too long"));
+    }
+
+    Ok(lines)
+}
+
+/// Writes a line to a player, right away
+fn send(_stream: &mut Stream, _line: &str) -> io::Result<()> {
+    writeln!(_stream, "{}", _line)?;
+    _stream.flush()
+}
+
+/// ## `greet`
+/// Reads the greeting of a new player, and either pairs them with a waiting player or puts them on the waiting list
+/// ### Parameters
+/// - `_stream: Stream`: The connection of the new player, the TLS handshake is done on the first read
+/// - `_waiting: &Mutex<Vec<Player>>`: Players waiting for an opponent, longest waiting first
+/// ### Return
+/// `Err` when the connection broke, or no greeting came within `GREETING_TIMEOUT`
+fn greet(mut _stream: Stream, _waiting: &Mutex<Vec<Player>>) -> io::Result<()> {
+    // Connections that never greet would otherwise hold a thread forever
+    _stream.sock.set_read_timeout(Some(GREETING_TIMEOUT))?;
+    let connected = Instant::now();
+
+    // Lines that arrived along with the greeting are kept
+    let mut partial = vec![];
+    let (greeting, pending) = loop {
+        let mut lines = read_lines(&mut _stream, &mut partial)?;
+        if !lines.is_empty() {
+            let greeting = lines.remove(0);
+            break (greeting, lines);
+        }
+        if connected.elapsed() >= GREETING_TIMEOUT {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "no greeting"));
+        }
+    };
+
+    let mut words = greeting.split_whitespace();
+    let mut player = match (words.next(), words.next(), words.next()) {
+        (Some("HELLO"), Some(name), Some(time_control)) =>
+            Player { name: name.to_string(), time_control: time_control.to_string(), stream: _stream, partial, pending },
+        _ => return send(&mut _stream, "ERROR expected HELLO <name> <time control>")
+    };
+
+    let started = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.as_secs());
+    let opponent = loop {
+        let mut waiting = _waiting.lock().map_err(|_| io::Error::new(io::ErrorKind::Other, "the waiting list broke"))?;
+        let mut found = match waiting.iter().position(|_waiting| _waiting.time_control == player.time_control) {
+            Some(index) => waiting.remove(index),
+            None => {
+                send(&mut player.stream, "WAITING")?;
+                waiting.push(player);
+                return Ok(());
+            }
+        };
+
+        // Waiting players who left since are dropped rather than paired, the next one is tried
+        if still_connected(&mut found) && send(&mut found.stream, &format!("PAIRED white {} {}", player.name, started)).is_ok() {
+            break found;
+        }
+        println!("{} left while waiting", found.name);
+    };
+    send(&mut player.stream, &format!("PAIRED black {} {}", opponent.name, started))?;
+    println!("paired {} and {} ({})", opponent.name, player.name, player.time_control);
+
+    relay([opponent, player]);
+    Ok(())
+}
+
+/// ## `still_connected`
+/// Reads what a waiting player sent, without waiting for more, to see that their connection is still open. Their
+/// lines are kept for the opponent.
+/// ### Return
+/// False when the connection was closed or broke, or they sent more than `MAX_PENDING_LINES` lines
+fn still_connected(_player: &mut Player) -> bool {
+    if _player.stream.sock.set_read_timeout(Some(PROBE_TIMEOUT)).is_err() {
+        return false;
+    }
+
+    match read_lines(&mut _player.stream, &mut _player.partial) {
+        Ok(lines) => {
+            _player.pending.extend(lines);
+            _player.pending.len() <= MAX_PENDING_LINES
+        },
+        Err(_) => false
+    }
+}
+
+/// Passes every line of one player on to the other, until one of them disconnects
+fn relay(mut _players: [Player; 2]) {
+    for _player in &_players {
+        if _player.stream.sock.set_read_timeout(Some(POLL_INTERVAL)).is_err() {
+            return;
+        }
+    }
+
+    // Lines that arrived before pairing go first
+    for &(_from, _to) in &[(0, 1), (1, 0)] {
+        for _line in std::mem::take(&mut _players[_from].pending) {
+            if send(&mut _players[_to].stream, &_line).is_err() {
+                return leave(&mut _players[_from]);
+            }
+        }
+    }
+
+    // Each side is read in turn, the lines of one are written to the other
+    loop {
+        for &(_from, _to) in &[(0, 1), (1, 0)] {
+            let from = &mut _players[_from];
+            let lines = match read_lines(&mut from.stream, &mut from.partial) {
+                Ok(lines) => lines,
+                Err(_) => return leave(&mut _players[_to])
+            };
+            for _line in lines {
+                if send(&mut _players[_to].stream, &_line).is_err() {
+                    return leave(&mut _players[_from]);
+                }
+            }
+        }
+    }
+}
+
+/// Tells a player that the opponent is gone, and closes their connection
+fn leave(_player: &mut Player) {
+    send(&mut _player.stream, "LEFT").ok();
+    _player.stream.conn.send_close_notify();
+    _player.stream.flush().ok();
+    _player.stream.sock.shutdown(Shutdown::Both).ok();
+}
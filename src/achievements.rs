@@ -0,0 +1,176 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::controller::GameController;
+use super::backend::GameStatus;
+use super::help_funcs::get_piece_colour;
+
+/// File the achievements are kept in, in the GGEZ user data directory
+pub const ACHIEVEMENTS_FILE: &str = "/achievements.json";
+
+/// Something worth a toast. Only sides played by a human can unlock them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Achievement {
+    FirstCheckmate,
+    PawnPower,
+    QuickWin,
+    QueensideCastle
+}
+
+impl Achievement {
+    /// Every achievement, in the order of the achievements page
+    pub const ALL: [Achievement; 4] = [Achievement::FirstCheckmate, Achievement::PawnPower, Achievement::QuickWin, Achievement::QueensideCastle];
+
+    /// Name in the achievements file
+    pub fn id(&self) -> &'static str {
+        match self {
+            Achievement::FirstCheckmate => "first_checkmate",
+            Achievement::PawnPower => "pawn_power",
+            Achievement::QuickWin => "quick_win",
+            Achievement::QueensideCastle => "queenside_castle"
+        }
+    }
+
+    /// Name shown in toasts and on the achievements page
+    pub fn title(&self) -> &'static str {
+        match self {
+            Achievement::FirstCheckmate => "First blood",
+            Achievement::PawnPower => "Pawn power",
+            Achievement::QuickWin => "Blitzkrieg",
+            Achievement::QueensideCastle => "Long way round"
+        }
+    }
+
+    /// How to unlock it
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstCheckmate => "Checkmate for the first time",
+            Achievement::PawnPower => "Checkmate with only the king, pawns and promoted pawns",
+            Achievement::QuickWin => "Checkmate in under 20 moves",
+            Achievement::QueensideCastle => "Castle queenside"
+        }
+    }
+
+    /// Looks an achievement up by its id
+    pub fn from_id(_id: &str) -> Option<Achievement> {
+        Achievement::ALL.iter().copied().find(|_achievement| _achievement.id() == _id)
+    }
+}
+
+/// The achievements unlocked so far, and how far into the current game they were checked
+#[derive(Debug, Clone, Default)]
+pub struct Achievements {
+    unlocked: Vec<(Achievement, u64)>, // With the time of unlocking, seconds since the Unix epoch
+    checked_moves: usize, // Moves of the current game already looked at
+    checked_end: bool // If the end of the current game was already looked at
+}
+
+impl Achievements {
+    /// Nothing unlocked yet
+    pub fn new() -> Achievements {
+        Achievements::default()
+    }
+
+    /// Reads the achievements file. Anything unreadable counts as nothing unlocked.
+    pub fn from_json(_text: &str) -> Achievements {
+        let mut achievements = Achievements::new();
+
+        if let Ok(Value::Object(unlocked)) = serde_json::from_str::<Value>(_text).map(|_json| _json["unlocked"].clone()) {
+            for (_id, _time) in unlocked {
+                if let Some(achievement) = Achievement::from_id(&_id) {
+                    achievements.unlocked.push((achievement, _time.as_u64().unwrap_or(0)));
+                }
+            }
+        }
+
+        achievements
+    }
+
+    /// Writes the achievements file
+    pub fn to_json(&self) -> String {
+        let unlocked: serde_json::Map<String, Value> = self.unlocked.iter()
+            .map(|(_achievement, _time)| (_achievement.id().to_string(), json!(_time)))
+            .collect();
+
+        serde_json::to_string_pretty(&json!({ "unlocked": unlocked })).unwrap_or_default()
+    }
+
+    /// When an achievement was unlocked, in seconds since the Unix epoch, None while locked
+    pub fn unlocked_at(&self, _achievement: Achievement) -> Option<u64> {
+        self.unlocked.iter().find(|(_unlocked, _)| *_unlocked == _achievement).map(|(_, _time)| *_time)
+    }
+
+    /// ## `check`
+    /// Looks at the moves played since the last check and at the end of the game, unlocking what they earned
+    /// ### Parameters
+    /// - `_controller: &GameController`: The game being played
+    /// ### Return
+    /// The achievements unlocked by this check, to be shown as toasts and saved
+    pub fn check(&mut self, _controller: &GameController) -> Vec<Achievement> {
+        let mut earned = vec![];
+
+        // Fewer moves than before means a new game or an undo
+        self.checked_moves = self.checked_moves.min(_controller.history.len());
+        if !_controller.is_over() {
+            self.checked_end = false;
+        }
+
+        for _move in &_controller.history[self.checked_moves..] {
+            let colour = *get_piece_colour(&_move.piece);
+            let castled_long = matches!(_move.piece, Piece::King(_))
+                && ((_move.from == "e1" && _move.to == "c1") || (_move.from == "e8" && _move.to == "c8"));
+
+            if castled_long && !_controller.bots.contains_key(&colour) {
+                earned.push(Achievement::QueensideCastle);
+            }
+        }
+        self.checked_moves = _controller.history.len();
+
+        if _controller.game.status() == GameStatus::CheckMate && !self.checked_end {
+            self.checked_end = true;
+            let winner = if _controller.game.active_colour() == Colour::White { Colour::Black } else { Colour::White };
+
+            if !_controller.bots.contains_key(&winner) {
+                earned.push(Achievement::FirstCheckmate);
+
+                let winner_moves = _controller.history.iter().filter(|_move| *get_piece_colour(&_move.piece) == winner).count();
+                if winner_moves < 20 {
+                    earned.push(Achievement::QuickWin);
+                }
+
+                if Achievements::only_pawns_promoted(_controller, winner) {
+                    earned.push(Achievement::PawnPower);
+                }
+            }
+        }
+
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.as_secs());
+        earned.retain(|_achievement| self.unlocked_at(*_achievement).is_none());
+        earned.dedup();
+        for _achievement in &earned {
+            self.unlocked.push((*_achievement, now));
+        }
+
+        earned
+    }
+
+    /// If every piece of a side besides the king is a pawn or could be one of its promoted pawns, after at least one promotion
+    fn only_pawns_promoted(_controller: &GameController, _colour: Colour) -> bool {
+        let promotions = _controller.history.iter()
+            .filter(|_move| _move.promotion.is_some() && *get_piece_colour(&_move.piece) == _colour)
+            .count();
+
+        let mut officers = 0;
+        for _col in 0..8 {
+            for _row in 0..8 {
+                match _controller.game.piece_at((_col, _row)) {
+                    Some(Piece::King(_)) | Some(Piece::Pawn(_)) | None => {},
+                    Some(piece) if *get_piece_colour(&piece) == _colour => officers += 1,
+                    Some(_) => {}
+                }
+            }
+        }
+
+        promotions > 0 && officers <= promotions
+    }
+}
@@ -0,0 +1,67 @@
+use eliasfl_chess::Color as Colour;
+use serde::{Deserialize, Serialize};
+use super::controller::Adjudication;
+
+/// Score of a forced mate in centipawns, whatever the distance to it
+pub const MATE_SCORE: i32 = 100_000;
+
+/// When engine matches (both sides played by bots) are ended early from the scores the engines give their moves,
+/// so a tournament does not wait for the mate of a lost position or the 50 move rule in a dead one. Kept in the
+/// configuration file, scores are in centipawns.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdjudicationRules {
+    pub enabled: bool,
+    pub resign_score: i32, // A side resigns once both engines see it this far behind...
+    pub resign_moves: usize, // ...for this many moves of each side in a row
+    pub draw_score: i32, // A draw is declared once both engines see the game within this of equal...
+    pub draw_moves: usize, // ...for this many moves of each side in a row...
+    pub draw_after: usize // ...but not before this move number
+}
+
+impl Default for AdjudicationRules {
+    fn default() -> AdjudicationRules {
+        AdjudicationRules { enabled: true, resign_score: 1000, resign_moves: 3, draw_score: 10, draw_moves: 10, draw_after: 40 }
+    }
+}
+
+/// ## `verdict`
+/// Adjudicates an engine match from the scores of its moves
+/// ### Parameters
+/// - `_rules: &AdjudicationRules`: Thresholds of the adjudication
+/// - `_scores: &[Option<i32>]`: Score of every move of the game from White's side, None for moves without one
+/// ### Return
+/// The result the scores call for, None to play on
+pub fn verdict(_rules: &AdjudicationRules, _scores: &[Option<i32>]) -> Option<Adjudication> {
+    if !_rules.enabled {
+        return None;
+    }
+
+    // The last moves of both sides, None when one of them has no score or there are not enough moves yet
+    let last = |_moves: usize| -> Option<Vec<i32>> {
+        let plies = _moves * 2;
+        if plies == 0 || _scores.len() < plies {
+            return None;
+        }
+        _scores[_scores.len() - plies..].iter().copied().collect()
+    };
+
+    if let Some(scores) = last(_rules.resign_moves) {
+        if scores.iter().all(|_score| *_score >= _rules.resign_score) {
+            return Some(Adjudication::Win(Colour::White));
+        }
+        if scores.iter().all(|_score| *_score <= -_rules.resign_score) {
+            return Some(Adjudication::Win(Colour::Black));
+        }
+    }
+
+    if _scores.len() >= _rules.draw_after * 2 {
+        if let Some(scores) = last(_rules.draw_moves) {
+            if scores.iter().all(|_score| _score.abs() <= _rules.draw_score) {
+                return Some(Adjudication::Draw);
+            }
+        }
+    }
+
+    None
+}
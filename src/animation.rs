@@ -0,0 +1,297 @@
+use std::{rc::Rc, cell::Cell};
+use super::graphics_funcs::renderer::Rgba;
+
+/// Value that animations write to and drawing code reads from. Cloning it shares the same value.
+pub type Animated<T> = Rc<Cell<T>>;
+
+/// Creates a new animated value starting at `_value`
+pub fn animated<T: Copy>(_value: T) -> Animated<T> {
+    Rc::new(Cell::new(_value))
+}
+
+/// Values that can be interpolated between
+pub trait Lerp: Copy {
+    /// Interpolates between `self` (at 0.0) and `_other` (at 1.0)
+    fn lerp(self, _other: Self, _t: f32) -> Self;
+}
+
+impl Lerp for f32 {
+    fn lerp(self, _other: f32, _t: f32) -> f32 {
+        self + (_other - self) * _t
+    }
+}
+
+impl Lerp for (f32, f32) {
+    fn lerp(self, _other: (f32, f32), _t: f32) -> (f32, f32) {
+        (self.0.lerp(_other.0, _t), self.1.lerp(_other.1, _t))
+    }
+}
+
+impl Lerp for Rgba {
+    fn lerp(self, _other: Rgba, _t: f32) -> Rgba {
+        [
+            self[0].lerp(_other[0], _t),
+            self[1].lerp(_other[1], _t),
+            self[2].lerp(_other[2], _t),
+            self[3].lerp(_other[3], _t)
+        ]
+    }
+}
+
+/// How the progress of a tween is shaped over time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut
+}
+
+impl Easing {
+    /// Maps linear progress (0.0 to 1.0) to eased progress (cubic curves)
+    pub fn apply(&self, _t: f32) -> f32 {
+        let t = _t.max(0.0).min(1.0);
+
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut =>
+                if t < 0.5 { 4.0 * t * t * t }
+                else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 }
+        }
+    }
+}
+
+/// Anything that plays over time
+pub trait Animate {
+    /// Moves the animation `_dt` seconds forward. Returns the part of `_dt` left over if the animation finished during this step.
+    fn advance(&mut self, _dt: f32) -> f32;
+
+    /// If the animation has played to its end
+    fn is_finished(&self) -> bool;
+
+    /// Jumps to the end of the animation, leaving every value at its final state
+    fn finish(&mut self) {
+        while !self.is_finished() {
+            self.advance(f32::MAX);
+        }
+    }
+}
+
+/// Moves an animated value from one value to another
+pub struct Tween<T: Lerp> {
+    target: Animated<T>,
+    from: T,
+    to: T,
+    duration: f32,
+    elapsed: f32,
+    easing: Easing
+}
+
+impl<T: Lerp> Tween<T> {
+    /// Creates a tween moving `_target` from `_from` to `_to` during `_duration` seconds
+    pub fn new(_target: &Animated<T>, _from: T, _to: T, _duration: f32, _easing: Easing) -> Tween<T> {
+        _target.set(_from);
+
+        Tween {
+            target: _target.clone(),
+            from: _from,
+            to: _to,
+            duration: _duration,
+            elapsed: 0.0,
+            easing: _easing
+        }
+    }
+}
+
+impl<T: Lerp> Animate for Tween<T> {
+    fn advance(&mut self, _dt: f32) -> f32 {
+        let left = self.duration - self.elapsed;
+        self.elapsed = (self.elapsed + _dt).min(self.duration);
+
+        let progress = if self.duration <= 0.0 { 1.0 } else { self.elapsed / self.duration };
+        self.target.set(self.from.lerp(self.to, self.easing.apply(progress)));
+
+        (_dt - left).max(0.0)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Waits without changing anything, used to space out animations in a sequence
+pub struct Delay {
+    duration: f32,
+    elapsed: f32
+}
+
+impl Delay {
+    /// Creates a delay of `_duration` seconds
+    pub fn new(_duration: f32) -> Delay {
+        Delay { duration: _duration, elapsed: 0.0 }
+    }
+}
+
+impl Animate for Delay {
+    fn advance(&mut self, _dt: f32) -> f32 {
+        let left = self.duration - self.elapsed;
+        self.elapsed = (self.elapsed + _dt).min(self.duration);
+
+        (_dt - left).max(0.0)
+    }
+
+    fn is_finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+}
+
+/// Plays animations one after the other
+pub struct Sequence {
+    steps: Vec<Box<dyn Animate>>,
+    current: usize
+}
+
+impl Sequence {
+    /// Creates a sequence playing `_steps` in order
+    pub fn new(_steps: Vec<Box<dyn Animate>>) -> Sequence {
+        Sequence { steps: _steps, current: 0 }
+    }
+}
+
+impl Animate for Sequence {
+    fn advance(&mut self, _dt: f32) -> f32 {
+        let mut dt = _dt;
+
+        // Time left over from a finished step carries over to the next one
+        while self.current < self.steps.len() {
+            dt = self.steps[self.current].advance(dt);
+
+            if !self.steps[self.current].is_finished() {
+                return 0.0;
+            }
+
+            self.current += 1;
+        }
+
+        dt
+    }
+
+    fn is_finished(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+}
+
+/// Plays animations at the same time, finishes when all of them are
+pub struct Parallel {
+    parts: Vec<Box<dyn Animate>>
+}
+
+impl Parallel {
+    /// Creates a group playing all `_parts` at once
+    pub fn new(_parts: Vec<Box<dyn Animate>>) -> Parallel {
+        Parallel { parts: _parts }
+    }
+}
+
+impl Animate for Parallel {
+    fn advance(&mut self, _dt: f32) -> f32 {
+        let mut left = _dt;
+
+        for _part in self.parts.iter_mut().filter(|_part| !_part.is_finished()) {
+            left = left.min(_part.advance(_dt));
+        }
+
+        left
+    }
+
+    fn is_finished(&self) -> bool {
+        self.parts.iter().all(|_part| _part.is_finished())
+    }
+}
+
+/// An animation being played by the `Animator` and what to do when it is done
+struct Running {
+    animation: Box<dyn Animate>,
+    on_complete: Option<Box<dyn FnOnce()>>
+}
+
+/// Plays animations, driven by `update` with the time since the last frame
+pub struct Animator {
+    running: Vec<Running>,
+    reduced_motion: bool // Every animation jumps to its end as soon as it is played
+}
+
+impl Animator {
+    /// Creates an animator with nothing playing
+    pub fn new() -> Animator {
+        Animator { running: vec![], reduced_motion: false }
+    }
+
+    /// Plays no animation from now on, the ones playing and the ones played later jump to their end.
+    /// For players who are bothered by movement on screen.
+    pub fn set_reduced_motion(&mut self, _reduced: bool) {
+        self.reduced_motion = _reduced;
+        if _reduced {
+            self.finish_all();
+        }
+    }
+
+    /// If animations jump to their end instead of playing
+    pub fn reduced_motion(&self) -> bool {
+        self.reduced_motion
+    }
+
+    /// Starts playing an animation
+    pub fn play(&mut self, _animation: impl Animate + 'static) {
+        self.running.push(Running { animation: Box::new(_animation), on_complete: None });
+        if self.reduced_motion {
+            self.finish_all();
+        }
+    }
+
+    /// Starts playing an animation and calls `_on_complete` once it has finished
+    pub fn play_then(&mut self, _animation: impl Animate + 'static, _on_complete: impl FnOnce() + 'static) {
+        self.running.push(Running { animation: Box::new(_animation), on_complete: Some(Box::new(_on_complete)) });
+        if self.reduced_motion {
+            self.finish_all();
+        }
+    }
+
+    /// Moves all animations `_dt` seconds forward, removing finished ones and calling their callbacks
+    pub fn update(&mut self, _dt: f32) {
+        for _running in self.running.iter_mut() {
+            _running.animation.advance(_dt);
+        }
+
+        self.collect_finished();
+    }
+
+    /// Jumps every animation to its end
+    pub fn finish_all(&mut self) {
+        for _running in self.running.iter_mut() {
+            _running.animation.finish();
+        }
+
+        self.collect_finished();
+    }
+
+    /// If nothing is playing
+    pub fn is_idle(&self) -> bool {
+        self.running.is_empty()
+    }
+
+    /// Removes finished animations and calls their callbacks
+    fn collect_finished(&mut self) {
+        let (finished, running): (Vec<Running>, Vec<Running>) = self.running.drain(..)
+            .partition(|_running| _running.animation.is_finished());
+        self.running = running;
+
+        for _finished in finished {
+            if let Some(callback) = _finished.on_complete {
+                callback();
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use ggez::{audio::{self, SoundSource}, Context};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use super::controller::GameController;
+use super::backend::GameStatus;
+
+/// Sound packs in `resources/sounds/`, each a directory with its own move, capture and game end effects.
+/// The first is played when the configured pack is unknown.
+pub const SOUND_PACKS: [&str; 3] = ["standard", "wood", "futuristic"];
+
+/// The pack to play, the standard one for names that are not a pack
+pub fn sound_pack(_name: &str) -> &'static str {
+    SOUND_PACKS.iter().find(|_pack| **_pack == _name).copied().unwrap_or(SOUND_PACKS[0])
+}
+
+/// What a sound is played as, each with its own volume
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    /// Moves, captures and the end of the game
+    Effects,
+    /// Spoken announcements
+    Voice
+}
+
+/// Volumes from 0 to 1. A sound plays at the master volume times the volume of its channel.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Volumes {
+    pub master: f32,
+    pub effects: f32,
+    pub voice: f32
+}
+
+impl Default for Volumes {
+    fn default() -> Volumes {
+        Volumes { master: 0.8, effects: 1.0, voice: 1.0 }
+    }
+}
+
+impl Volumes {
+    /// The volume a sound of a channel plays at
+    pub fn of(&self, _channel: Channel) -> f32 {
+        let channel = match _channel {
+            Channel::Effects => self.effects,
+            Channel::Voice => self.voice
+        };
+
+        (self.master * channel).max(0.0).min(1.0)
+    }
+}
+
+/// Every sound the interface plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Sound {
+    Move,
+    Capture,
+    GameEnd,
+    Check, // Spoken
+    Checkmate // Spoken
+}
+
+impl Sound {
+    /// The channel the sound plays on
+    pub fn channel(&self) -> Channel {
+        match self {
+            Sound::Move | Sound::Capture | Sound::GameEnd => Channel::Effects,
+            Sound::Check | Sound::Checkmate => Channel::Voice
+        }
+    }
+
+    /// Path of the sound in the resources. Effects come from the sound pack, the voice is the same for every pack.
+    pub fn path(&self, _pack: &str) -> String {
+        match self {
+            Sound::Move => format!("/sounds/{}/move.wav", sound_pack(_pack)),
+            Sound::Capture => format!("/sounds/{}/capture.wav", sound_pack(_pack)),
+            Sound::GameEnd => format!("/sounds/{}/game_end.wav", sound_pack(_pack)),
+            Sound::Check => "/sounds/voice/check.wav".to_string(),
+            Sound::Checkmate => "/sounds/voice/checkmate.wav".to_string()
+        }
+    }
+}
+
+/// ## `sounds_for`
+/// The sounds announcing what happened in a game since it was last heard
+/// ### Parameters
+/// - `_controller: &GameController`: The game
+/// - `_heard: usize`: Moves of the game already announced
+pub fn sounds_for(_controller: &GameController, _heard: usize) -> Vec<Sound> {
+    let last = match _controller.history.last() {
+        Some(last) if _controller.history.len() > _heard => last,
+        _ => return vec![]
+    };
+
+    let mut sounds = vec![if last.captured.is_some() { Sound::Capture } else { Sound::Move }];
+    match _controller.game.status() {
+        GameStatus::Check => sounds.push(Sound::Check),
+        GameStatus::CheckMate => sounds.extend([Sound::GameEnd, Sound::Checkmate].iter()),
+        GameStatus::Draw(_) => sounds.push(Sound::GameEnd),
+        GameStatus::InProgress if _controller.variant_win.is_some() => sounds.push(Sound::GameEnd),
+        GameStatus::InProgress => {}
+    }
+
+    sounds
+}
+
+/// Plays every sound of the interface, at the volumes of the settings. Sounds are loaded the first time they play,
+/// so only the packs that are actually heard are loaded.
+pub struct AudioManager {
+    sources: HashMap<(&'static str, Sound), Option<audio::Source>>, // By pack, None for sounds that are missing, i.e. voice clips not installed
+    heard: usize // Moves of the game already announced
+}
+
+impl AudioManager {
+    /// Nothing loaded yet
+    pub fn new() -> AudioManager {
+        AudioManager { sources: HashMap::new(), heard: 0 }
+    }
+
+    /// Plays a sound of a pack at the volume of its channel. Missing sounds are skipped without complaint.
+    pub fn play(&mut self, ctx: &mut Context, _sound: Sound, _volumes: &Volumes, _pack: &str) {
+        let volume = _volumes.of(_sound.channel());
+        if volume <= 0.0 {
+            return;
+        }
+
+        let pack = sound_pack(_pack);
+        let source = self.sources.entry((pack, _sound)).or_insert_with(|| audio::Source::new(ctx, _sound.path(pack)).ok());
+        if let Some(source) = source {
+            source.set_volume(volume);
+            source.play_detached(ctx).ok();
+        }
+    }
+
+    /// Plays the sounds of the moves made since the last call
+    pub fn update(&mut self, ctx: &mut Context, _controller: &GameController, _volumes: &Volumes, _pack: &str) {
+        for _sound in sounds_for(_controller, self.heard) {
+            self.play(ctx, _sound, _volumes, _pack);
+        }
+
+        self.heard = _controller.history.len();
+    }
+}
+
+impl Default for AudioManager {
+    fn default() -> AudioManager {
+        AudioManager::new()
+    }
+}
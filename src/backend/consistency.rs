@@ -0,0 +1,85 @@
+use super::{ChessBackend, PromotionKind};
+use super::super::help_funcs::num_to_filerank;
+use super::super::history::{self, MoveRecord};
+
+/// Debug helper that plays every move on a second backend too and compares the two afterwards.
+/// Catches bugs in one of the engines (i.e. a missing en passant) the moment they show up.
+pub struct ConsistencyChecker {
+    reference: Box<dyn ChessBackend>
+}
+
+impl ConsistencyChecker {
+    /// Creates a checker comparing against `_reference`, which must be at the same position as the checked backend
+    pub fn new(_reference: Box<dyn ChessBackend>) -> ConsistencyChecker {
+        ConsistencyChecker { reference: _reference }
+    }
+
+    /// Name of the backend compared against
+    pub fn reference_name(&self) -> &'static str {
+        self.reference.name()
+    }
+
+    /// Plays a move the checked backend has just made on the reference and compares the positions.
+    /// ### Return
+    /// `Err` with a description of the first discrepancy found
+    pub fn check_move(&mut self, _checked: &dyn ChessBackend, _from: &str, _to: &str, _promotion: Option<PromotionKind>) -> Result<(), String> {
+        self.reference.make_move(_from, _to, _promotion)
+            .map_err(|_error| format!("{} refused the move. {}", self.reference.name(), _error))?;
+
+        self.compare(_checked)
+    }
+
+    /// Compares the checked backend against the reference: side to move, status, pieces and legal moves of every square
+    pub fn compare(&self, _checked: &dyn ChessBackend) -> Result<(), String> {
+        let (checked, reference) = (_checked.name(), self.reference.name());
+
+        if _checked.active_colour() != self.reference.active_colour() {
+            return Err(format!("{} and {} disagree on the side to move", checked, reference));
+        }
+
+        if _checked.status() != self.reference.status() {
+            return Err(format!("{} says {:?}, {} says {:?}", checked, _checked.status(), reference, self.reference.status()));
+        }
+
+        for _row in 0..8 {
+            for _col in 0..8 {
+                let square = (_col, _row);
+                let filerank = num_to_filerank(&square);
+
+                if _checked.piece_at(square) != self.reference.piece_at(square) {
+                    return Err(format!("{} and {} disagree on the piece at {}", checked, reference, filerank));
+                }
+
+                let mut checked_moves = _checked.legal_moves(&filerank);
+                let mut reference_moves = self.reference.legal_moves(&filerank);
+                checked_moves.sort();
+                reference_moves.sort();
+
+                if checked_moves != reference_moves {
+                    return Err(format!("Moves from {}: {} has [{}], {} has [{}]",
+                        filerank, checked, checked_moves.join(" "), reference, reference_moves.join(" ")));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Puts the reference back at the starting position
+    pub fn reset(&mut self) {
+        self.reference.reset();
+    }
+
+    /// Puts the reference at another position, i.e. after the checked backend loaded a FEN or took a move back
+    /// ### Parameters
+    /// - `_fen: Option<&str>`: The position the game started from, None for the starting position
+    /// - `_moves: &[MoveRecord]`: The moves played since
+    pub fn rewind(&mut self, _fen: Option<&str>, _moves: &[MoveRecord]) -> Result<(), String> {
+        match _fen {
+            Some(fen) => self.reference.load_fen(fen)?,
+            None => self.reference.reset()
+        }
+
+        history::replay(self.reference.as_mut(), _moves)
+    }
+}
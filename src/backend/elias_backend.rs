@@ -0,0 +1,66 @@
+use eliasfl_chess::{Game, Color as Colour, Piece, GameState};
+use super::{ChessBackend, GameStatus, PromotionKind};
+use super::super::help_funcs::{fen_to_board, to_engine_coords};
+
+/// `ChessBackend` wrapping the Elias Engine
+pub struct EliasBackend {
+    pub game: Game
+}
+
+impl EliasBackend {
+    /// Creates a backend holding a new game
+    pub fn new() -> EliasBackend {
+        EliasBackend { game: Game::new() }
+    }
+}
+
+impl ChessBackend for EliasBackend {
+    fn name(&self) -> &'static str {
+        "elias"
+    }
+
+    fn piece_at(&self, _square: (u8, u8)) -> Option<Piece> {
+        self.game.board.get(&to_engine_coords(&_square)).copied()
+    }
+
+    fn active_colour(&self) -> Colour {
+        self.game.active_color
+    }
+
+    fn status(&self) -> GameStatus {
+        match self.game.get_game_state() {
+            GameState::InProgress => GameStatus::InProgress,
+            GameState::Check => GameStatus::Check,
+            GameState::CheckMate => GameStatus::CheckMate
+        }
+    }
+
+    fn legal_moves(&self, _from: &str) -> Vec<String> {
+        // As per Elias' Engine; no legal moves return None
+        self.game.get_possible_moves(_from.to_string()).unwrap_or_default()
+    }
+
+    fn make_move(&mut self, _from: &str, _to: &str, _promotion: Option<PromotionKind>) -> Result<(), String> {
+        if let Some(kind) = _promotion {
+            self.game.set_promotion(kind.as_engine_str().to_string()).map_err(|_error| _error.to_string())?;
+        }
+
+        self.game.make_move(_from.to_string(), _to.to_string())
+            .map(|_| ())
+            .map_err(|_error| _error.to_string())
+    }
+
+    fn reset(&mut self) {
+        self.game = Game::new();
+    }
+
+    fn new_game(&self) -> Box<dyn ChessBackend> {
+        Box::new(EliasBackend::new())
+    }
+
+    // The engine cannot be told castling rights or the en passant square, only the pieces and the side to move are set up
+    fn load_fen(&mut self, _fen: &str) -> Result<(), String> {
+        self.game = fen_to_board(_fen)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,61 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use super::{ChessBackend, GameStatus, PromotionKind};
+use super::super::{fen, validator};
+use super::super::help_funcs::num_to_filerank;
+
+/// A backend standing at a position read from FEN, for unit tests of what only reads positions: FEN, notation and
+/// the validator. Its legal moves are those of `validator`, it tells check but not mate, and no move can be played on it.
+pub struct FenBoard {
+    pieces: Vec<((u8, u8), Piece)>,
+    active: Colour,
+    en_passant: Option<String>
+}
+
+impl FenBoard {
+    /// The position of a FEN, which has to be readable
+    pub fn new(_fen: &str) -> FenBoard {
+        FenBoard {
+            pieces: fen::placement(_fen).expect("The placement could not be read."),
+            active: if _fen.split_whitespace().nth(1) == Some("b") { Colour::Black } else { Colour::White },
+            en_passant: fen::en_passant(_fen)
+        }
+    }
+}
+
+impl ChessBackend for FenBoard {
+    fn name(&self) -> &'static str {
+        "fen board"
+    }
+
+    fn piece_at(&self, _square: (u8, u8)) -> Option<Piece> {
+        self.pieces.iter().find(|(_at, _)| *_at == _square).map(|(_, _piece)| *_piece)
+    }
+
+    fn active_colour(&self) -> Colour {
+        self.active
+    }
+
+    fn status(&self) -> GameStatus {
+        let board = validator::snapshot(self);
+        match validator::find_king(&board, self.active) {
+            Some(king) if validator::is_attacked(&board, king, !self.active) => GameStatus::Check,
+            _ => GameStatus::InProgress
+        }
+    }
+
+    fn legal_moves(&self, _from: &str) -> Vec<String> {
+        (0..8).flat_map(|_col| (0..8).map(move |_row| num_to_filerank(&(_col, _row))))
+            .filter(|_to| validator::why_illegal(self, _from, _to, self.en_passant.as_deref()).is_ok())
+            .collect()
+    }
+
+    fn make_move(&mut self, _from: &str, _to: &str, _promotion: Option<PromotionKind>) -> Result<(), String> {
+        Err("a FEN board stays at its position".to_string())
+    }
+
+    fn reset(&mut self) {}
+
+    fn new_game(&self) -> Box<dyn ChessBackend> {
+        Box::new(FenBoard::new("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"))
+    }
+}
@@ -0,0 +1,109 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use super::{ChessBackend, GameStatus, PromotionKind};
+use super::super::fen;
+use super::super::help_funcs::filerank_to_num;
+use super::super::validator;
+
+/// Engine wordings and what they mean to a player. The engines word their errors for programmers, if at all.
+const ENGINE_WORDINGS: [(&str, &str); 6] = [
+    ("check", "leaves the king in check"),
+    ("turn", "it is the other side's turn"),
+    ("promot", "choose what the pawn becomes first"),
+    ("game over", "the game is over"),
+    ("ended", "the game is over"),
+    ("invalid position", "that is no square on the board")
+];
+
+/// ## `explain`
+/// Turns a refused move into a message for the player, with the squares involved, i.e. "Cannot move e2–e5: blocked".
+/// The GUI's own validator is asked first, as it knows the squares and pieces, then the engine's error is translated.
+/// ### Parameters
+/// - `_game: &dyn ChessBackend`: The game, at the position the move was tried in
+/// - `_from: &str`: The square moved from as "\<file\>\<rank\>"
+/// - `_to: &str`: The square moved to as "\<file\>\<rank\>"
+/// - `_en_passant: Option<&str>`: The en passant square of the position, see `validator::validate_move`
+/// - `_engine_error: &str`: What the engine said, empty when it said nothing
+pub fn explain(_game: &dyn ChessBackend, _from: &str, _to: &str, _en_passant: Option<&str>, _engine_error: &str) -> String {
+    let reason = match validator::why_illegal(_game, _from, _to, _en_passant) {
+        Err(reason) => reason,
+        // Castling rights are only known to the engine
+        Ok(()) => {
+            let lower = _engine_error.to_lowercase();
+            match ENGINE_WORDINGS.iter().find(|(_wording, _)| lower.contains(_wording)) {
+                Some((_, meaning)) => meaning.to_string(),
+                None if lower.is_empty() => "the engine refused it".to_string(),
+                None => lower
+            }
+        }
+    };
+
+    format!("Cannot move {}–{}: {}", _from, _to, reason)
+}
+
+/// Thin adapter every backend is created behind, see `BackendKind::create`. Passes everything through and words
+/// the errors of the engine for the player, with the squares involved, instead of in the engine's terms.
+pub struct FriendlyBackend {
+    inner: Box<dyn ChessBackend>,
+    en_passant: Option<String> // Square passed over by a pawn that just moved two squares, kept as the engines do not tell it
+}
+
+impl FriendlyBackend {
+    /// Puts a backend behind the adapter
+    pub fn new(_inner: Box<dyn ChessBackend>) -> FriendlyBackend {
+        FriendlyBackend { inner: _inner, en_passant: None }
+    }
+}
+
+impl ChessBackend for FriendlyBackend {
+    fn name(&self) -> &'static str {
+        self.inner.name()
+    }
+
+    fn piece_at(&self, _square: (u8, u8)) -> Option<Piece> {
+        self.inner.piece_at(_square)
+    }
+
+    fn active_colour(&self) -> Colour {
+        self.inner.active_colour()
+    }
+
+    fn status(&self) -> GameStatus {
+        self.inner.status()
+    }
+
+    fn legal_moves(&self, _from: &str) -> Vec<String> {
+        self.inner.legal_moves(_from)
+    }
+
+    fn make_move(&mut self, _from: &str, _to: &str, _promotion: Option<PromotionKind>) -> Result<(), String> {
+        let double_push = _from.len() == 2 && _to.len() == 2
+            && matches!(self.inner.piece_at(filerank_to_num(&_from.to_string())), Some(Piece::Pawn(_)))
+            && (_from.as_bytes()[1] as i8 - _to.as_bytes()[1] as i8).abs() == 2;
+
+        // A refused move leaves the position as it was, so it is explained from there
+        self.inner.make_move(_from, _to, _promotion)
+            .map_err(|_error| explain(self.inner.as_ref(), _from, _to, self.en_passant.as_deref(), &_error))?;
+
+        self.en_passant = if double_push { Some(format!("{}{}", &_from[0..1], ((_from.as_bytes()[1] + _to.as_bytes()[1]) / 2) as char)) } else { None };
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        self.en_passant = None;
+        self.inner.reset()
+    }
+
+    fn new_game(&self) -> Box<dyn ChessBackend> {
+        Box::new(FriendlyBackend::new(self.inner.new_game()))
+    }
+
+    fn load_fen(&mut self, _fen: &str) -> Result<(), String> {
+        self.inner.load_fen(_fen)?;
+        self.en_passant = fen::en_passant(_fen);
+        Ok(())
+    }
+
+    fn chess960(&self) -> bool {
+        self.inner.chess960()
+    }
+}
@@ -0,0 +1,127 @@
+use eliasfl_chess::{Color as Colour, Piece};
+
+pub mod elias_backend;
+pub mod consistency;
+pub mod friendly;
+#[cfg(feature = "shakmaty")]
+pub mod shakmaty_backend;
+#[cfg(test)]
+pub mod fen_board;
+
+/// Status of a game as shown by the GUI, independent of which backend plays the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameStatus {
+    InProgress,
+    Check,
+    CheckMate,
+    Draw(DrawReason)
+}
+
+impl GameStatus {
+    /// If no more moves can be made
+    pub fn is_over(&self) -> bool {
+        match self {
+            GameStatus::CheckMate | GameStatus::Draw(_) => true,
+            _ => false
+        }
+    }
+}
+
+/// Why a game ended in a draw
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawReason {
+    Stalemate,
+    InsufficientMaterial,
+    ThreefoldRepetition,
+    FiftyMoveRule
+}
+
+/// The piece types a pawn can be promoted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromotionKind {
+    Queen,
+    Knight,
+    Rook,
+    Bishop
+}
+
+impl PromotionKind {
+    /// The name of the piece type as expected by `Game::set_promotion` in the Elias Engine
+    pub fn as_engine_str(&self) -> &'static str {
+        match self {
+            PromotionKind::Queen => "queen",
+            PromotionKind::Knight => "knight",
+            PromotionKind::Rook => "rook",
+            PromotionKind::Bishop => "bishop"
+        }
+    }
+}
+
+/// The rules of chess as used by the GUI. Squares are given as "\<file\>\<rank\>" strings or GUI (u8, u8) coords.
+pub trait ChessBackend {
+    /// Short name shown in the interface and logs
+    fn name(&self) -> &'static str;
+
+    /// The piece on a square given in GUI coords, if any
+    fn piece_at(&self, _square: (u8, u8)) -> Option<Piece>;
+
+    /// The colour to move
+    fn active_colour(&self) -> Colour;
+
+    /// The current status of the game
+    fn status(&self) -> GameStatus;
+
+    /// The squares the piece on `_from` can legally move to
+    fn legal_moves(&self, _from: &str) -> Vec<String>;
+
+    /// Plays a move. `_promotion` is the piece a pawn reaching the last rank becomes.
+    fn make_move(&mut self, _from: &str, _to: &str, _promotion: Option<PromotionKind>) -> Result<(), String>;
+
+    /// Goes back to the starting position
+    fn reset(&mut self);
+
+    /// A new backend of the same kind at the starting position, i.e. to replay a game on without touching this one
+    fn new_game(&self) -> Box<dyn ChessBackend>;
+
+    /// Sets up the position described by a FEN string. Backends that cannot set up arbitrary positions return `Err`.
+    fn load_fen(&mut self, _fen: &str) -> Result<(), String> {
+        Err(format!("{} cannot load positions", self.name()))
+    }
+
+    /// If the backend plays Chess960 castling: from positions loaded with Shredder-FEN castling rights (i.e. "HAha"),
+    /// the king castles by moving onto the rook, wherever both stand
+    fn chess960(&self) -> bool {
+        false
+    }
+}
+
+/// The backends that can be chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackendKind {
+    Elias,
+    #[cfg(feature = "shakmaty")]
+    Shakmaty
+}
+
+impl BackendKind {
+    /// Parses a backend name as given on the command line
+    pub fn from_name(_name: &str) -> Option<BackendKind> {
+        match _name {
+            "elias" => Some(BackendKind::Elias),
+            #[cfg(feature = "shakmaty")]
+            "shakmaty" => Some(BackendKind::Shakmaty),
+            _ => None
+        }
+    }
+
+    /// Creates a backend of this kind at the starting position, behind the adapter wording its errors for the player
+    pub fn create(&self) -> Box<dyn ChessBackend> {
+        let backend: Box<dyn ChessBackend> = match self {
+            BackendKind::Elias => Box::new(elias_backend::EliasBackend::new()),
+            #[cfg(feature = "shakmaty")]
+            BackendKind::Shakmaty => Box::new(shakmaty_backend::ShakmatyBackend::new())
+        };
+
+        Box::new(friendly::FriendlyBackend::new(backend))
+    }
+}
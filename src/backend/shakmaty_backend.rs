@@ -0,0 +1,154 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use shakmaty::{Chess, Position, Role, Square, Color, EnPassantMode, CastlingMode, fen::Fen, uci::UciMove, zobrist::Zobrist64};
+use super::{ChessBackend, GameStatus, DrawReason, PromotionKind};
+use super::super::help_funcs::num_to_filerank;
+
+/// `ChessBackend` built on the shakmaty crate. Knows the full rules, including en passant, threefold repetition and the
+/// fifty-move rule, and castles the Chess960 way from positions loaded with Shredder-FEN castling rights.
+pub struct ShakmatyBackend {
+    position: Chess,
+    history: Vec<Zobrist64>, // Hash of every position reached, for threefold repetition
+    mode: CastlingMode // Chess960 once a position with Shredder-FEN castling rights was loaded, castling is then the king taking its rook
+}
+
+impl ShakmatyBackend {
+    /// Creates a backend holding a new game
+    pub fn new() -> ShakmatyBackend {
+        let position = Chess::default();
+        let history = vec![position.zobrist_hash(EnPassantMode::Legal)];
+
+        ShakmatyBackend { position, history, mode: CastlingMode::Standard }
+    }
+
+    /// How the castling rights of a FEN are written: Shredder-FEN names the files of the rooks (i.e. "HAha") and is
+    /// read as Chess960, "KQkq" and "-" as standard chess unless the king and rooks stand elsewhere (see `load_fen`)
+    fn castling_mode(_fen: &str) -> CastlingMode {
+        match _fen.split_whitespace().nth(2) {
+            Some(castling) if castling.chars().any(|_char| matches!(_char.to_ascii_lowercase(), 'a'..='h')) => CastlingMode::Chess960,
+            _ => CastlingMode::Standard
+        }
+    }
+
+    /// Converts a shakmaty colour to an Elias Engine colour
+    fn to_colour(_colour: Color) -> Colour {
+        match _colour {
+            Color::White => Colour::White,
+            Color::Black => Colour::Black
+        }
+    }
+
+    /// All legal moves in the position written as UCI moves, castling as the king's move (e1g1) or, in Chess960, as the
+    /// king taking its rook (b1a1)
+    fn uci_moves(&self) -> Vec<UciMove> {
+        let mode = self.mode;
+        self.position.legal_moves().into_iter().map(|_move| UciMove::from_move(_move, mode)).collect()
+    }
+}
+
+impl ChessBackend for ShakmatyBackend {
+    fn name(&self) -> &'static str {
+        "shakmaty"
+    }
+
+    fn piece_at(&self, _square: (u8, u8)) -> Option<Piece> {
+        let square: Square = num_to_filerank(&_square).parse().ok()?;
+
+        self.position.board().piece_at(square).map(|_piece| {
+            let colour = ShakmatyBackend::to_colour(_piece.color);
+
+            match _piece.role {
+                Role::Pawn => Piece::Pawn(colour),
+                Role::Knight => Piece::Knight(colour),
+                Role::Bishop => Piece::Bishop(colour),
+                Role::Rook => Piece::Rook(colour),
+                Role::Queen => Piece::Queen(colour),
+                Role::King => Piece::King(colour)
+            }
+        })
+    }
+
+    fn active_colour(&self) -> Colour {
+        ShakmatyBackend::to_colour(self.position.turn())
+    }
+
+    fn status(&self) -> GameStatus {
+        let current = self.history.last().copied();
+        let repetitions = self.history.iter().filter(|_hash| Some(**_hash) == current).count();
+
+        if self.position.is_checkmate() { GameStatus::CheckMate }
+        else if self.position.is_stalemate() { GameStatus::Draw(DrawReason::Stalemate) }
+        else if self.position.is_insufficient_material() { GameStatus::Draw(DrawReason::InsufficientMaterial) }
+        else if repetitions >= 3 { GameStatus::Draw(DrawReason::ThreefoldRepetition) }
+        else if self.position.halfmoves() >= 100 { GameStatus::Draw(DrawReason::FiftyMoveRule) }
+        else if self.position.is_check() { GameStatus::Check }
+        else { GameStatus::InProgress }
+    }
+
+    fn legal_moves(&self, _from: &str) -> Vec<String> {
+        let mut moves = vec![];
+
+        for _move in self.uci_moves() {
+            if let UciMove::Normal { from, to, .. } = _move {
+                let to = to.to_string();
+
+                // Promotions show up once per piece type, the GUI only needs the square once
+                if from.to_string() == _from && !moves.contains(&to) {
+                    moves.push(to);
+                }
+            }
+        }
+
+        moves
+    }
+
+    fn make_move(&mut self, _from: &str, _to: &str, _promotion: Option<PromotionKind>) -> Result<(), String> {
+        let promotion = _promotion.map(|_kind| match _kind {
+            PromotionKind::Queen => "q",
+            PromotionKind::Knight => "n",
+            PromotionKind::Rook => "r",
+            PromotionKind::Bishop => "b"
+        });
+
+        // Only add the promotion letter if the move really is a promotion
+        let plain = format!("{}{}", _from, _to);
+        let with_promotion = format!("{}{}", plain, promotion.unwrap_or(""));
+
+        let legal = self.uci_moves();
+        let uci = legal.iter()
+            .find(|_move| _move.to_string() == with_promotion)
+            .or_else(|| legal.iter().find(|_move| _move.to_string() == plain))
+            .ok_or(format!("Illegal move {}-{}", _from, _to))?;
+
+        let chess_move = uci.to_move(&self.position).map_err(|_error| _error.to_string())?;
+        self.position.play_unchecked(chess_move);
+        self.history.push(self.position.zobrist_hash(EnPassantMode::Legal));
+
+        Ok(())
+    }
+
+    fn reset(&mut self) {
+        *self = ShakmatyBackend::new();
+    }
+
+    fn new_game(&self) -> Box<dyn ChessBackend> {
+        Box::new(ShakmatyBackend::new())
+    }
+
+    fn load_fen(&mut self, _fen: &str) -> Result<(), String> {
+        let fen: Fen = _fen.parse().map_err(|_error: shakmaty::fen::ParseFenError| _error.to_string())?;
+        // X-FEN writes Chess960 rights as "KQkq" too, which only Chess960 allows with the king off the e-file
+        let (position, mode) = match (ShakmatyBackend::castling_mode(_fen), fen.clone().into_position::<Chess>(CastlingMode::Standard)) {
+            (CastlingMode::Standard, Ok(position)) => (position, CastlingMode::Standard),
+            _ => (fen.into_position::<Chess>(CastlingMode::Chess960).map_err(|_error| _error.to_string())?, CastlingMode::Chess960)
+        };
+        self.position = position;
+        self.history = vec![self.position.zobrist_hash(EnPassantMode::Legal)];
+        self.mode = mode;
+
+        Ok(())
+    }
+
+    fn chess960(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,94 @@
+use eliasfl_chess::Color as Colour;
+use std::collections::{HashMap, HashSet};
+use super::backend::BackendKind;
+use super::fen;
+use super::help_funcs::get_piece_colour;
+use super::history::MoveRecord;
+use super::pgn::{self, PgnGame};
+use super::polyglot::{self, Book, BookEntry};
+
+/// Moves of each game that go into a book, the opening only
+pub const BOOK_DEPTH: usize = 16;
+
+/// How building a book went
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BookReport {
+    pub games: usize, // Games whose moves went into the book
+    pub skipped: usize, // Games of other players, or from another starting position
+    pub broken: usize, // Games with a move that could not be played, only the moves before it went in
+    pub positions: usize // Different positions the book knows moves for
+}
+
+/// The side a player had in a game, matched on the White and Black headers without regard to case
+fn side_of(_game: &PgnGame, _player: &str) -> Option<Colour> {
+    let plays = |_tag: &str| _game.tag(_tag).map_or(false, |_name| _name.trim().eq_ignore_ascii_case(_player.trim()));
+
+    if plays("White") {
+        Some(Colour::White)
+    }
+    else if plays("Black") {
+        Some(Colour::Black)
+    }
+    else {
+        None
+    }
+}
+
+/// ## `build_book`
+/// Builds an opening book from the games of a PGN file, weighing each move by how often it was played. With a
+/// player, only the games of that player count and only their moves go in, so a bot with the book opens like they do.
+/// ### Parameters
+/// - `_pgn: &str`: The games, i.e. collected from `F7` exports
+/// - `_player: Option<&str>`: Name of the player as in the White and Black headers, None for every move of every game
+/// - `_backend: BackendKind`: Plays the moves, to know the positions they lead to
+pub fn build_book(_pgn: &str, _player: Option<&str>, _backend: BackendKind) -> (Book, BookReport) {
+    let mut report = BookReport::default();
+    let mut counts: HashMap<(u64, u16), u32> = HashMap::new();
+
+    for _game in pgn::read_games(_pgn) {
+        let side = match _player.map(|_player| side_of(&_game, _player)) {
+            Some(None) => {
+                report.skipped += 1;
+                continue;
+            },
+            Some(side) => side,
+            None => None
+        };
+        // Books are keyed by position, but only games from the usual start are replayed here
+        if _game.tag("SetUp") == Some("1") || _game.tag("FEN").is_some() {
+            report.skipped += 1;
+            continue;
+        }
+
+        let mut board = _backend.create();
+        let mut history: Vec<MoveRecord> = vec![];
+        report.games += 1;
+
+        for _san in _game.moves.iter().take(BOOK_DEPTH) {
+            let fen = fen::to_fen(board.as_ref(), &history, None);
+            let played = pgn::parse_san(board.as_ref(), _san).and_then(|_move| {
+                board.make_move(&_move.from, &_move.to, _move.promotion).map(|_| _move)
+            });
+            let record = match (played, polyglot::key(&fen)) {
+                (Ok(record), Ok(key)) => {
+                    if side.map_or(true, |_side| *get_piece_colour(&record.piece) == _side) {
+                        *counts.entry((key, polyglot::encode_move(&record.uci(), &fen))).or_insert(0) += 1;
+                    }
+                    record
+                },
+                _ => {
+                    report.broken += 1;
+                    break;
+                }
+            };
+            history.push(record);
+        }
+    }
+
+    report.positions = counts.keys().map(|(_key, _)| *_key).collect::<HashSet<u64>>().len();
+    let entries = counts.into_iter()
+        .map(|((_key, _move), _count)| BookEntry { key: _key, raw_move: _move, weight: _count.min(u16::MAX as u32) as u16, learn: 0 })
+        .collect();
+
+    (Book::from_entries(entries), report)
+}
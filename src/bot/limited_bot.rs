@@ -0,0 +1,49 @@
+use super::{Bot, Position, MIN_ELO, MAX_ELO};
+use super::random_bot::RandomBot;
+use super::super::speedrun::Difficulty;
+
+/// Share of random moves at the lowest Elo, it falls in a straight line to none at the highest
+const MAX_BLUNDER_CHANCE: f32 = 0.6;
+
+/// A bot held back to about an Elo by blundering: now and then it plays a random legal move instead of its own.
+/// For bots that cannot limit their strength themselves, i.e. scripts, the built-in random mover and UCI engines
+/// without `UCI_Elo`. The ratings are rough, a blunder rate does not make a rating.
+pub struct LimitedBot {
+    inner: Box<dyn Bot>,
+    blunders: RandomBot, // Picks the random moves, and when to play one
+    chance: f32 // Of a random move, from 0 to 1
+}
+
+impl LimitedBot {
+    /// Holds a bot back to about an Elo
+    pub fn new(_inner: Box<dyn Bot>, _elo: u32) -> LimitedBot {
+        LimitedBot { inner: _inner, blunders: RandomBot::new(Difficulty::Easy), chance: LimitedBot::blunder_chance(_elo) }
+    }
+
+    /// ## `blunder_chance`
+    /// Share of random moves played at an Elo, from 0 at `MAX_ELO` up to `MAX_BLUNDER_CHANCE` at `MIN_ELO`
+    pub fn blunder_chance(_elo: u32) -> f32 {
+        let below = MAX_ELO.saturating_sub(_elo.max(MIN_ELO)) as f32;
+        MAX_BLUNDER_CHANCE * below / (MAX_ELO - MIN_ELO) as f32
+    }
+}
+
+impl Bot for LimitedBot {
+    // Stats and the setup screen know the bot by its own name
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn choose_move(&mut self, _position: &Position) -> Result<String, String> {
+        if self.blunders.roll() < self.chance {
+            return self.blunders.choose_move(_position);
+        }
+
+        self.inner.choose_move(_position)
+    }
+
+    // Blunders are not scored, the last score of the bot is the best guess
+    fn evaluation(&self) -> Option<i32> {
+        self.inner.evaluation()
+    }
+}
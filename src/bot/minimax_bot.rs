@@ -0,0 +1,152 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use super::{Bot, Position, parse_uci};
+use super::random_bot::RandomBot;
+use super::super::help_funcs::{filerank_to_num, get_piece_colour, piece_value};
+use super::super::speedrun::Difficulty;
+use super::super::validator::{self, BoardSnapshot};
+
+/// Score of a checkmate in centipawns, more than all the material on the board
+const MATE_SCORE: i32 = 100_000;
+
+/// The built-in opponent for players without an engine: a minimax search with alpha-beta pruning that counts material.
+/// It looks at every legal move of the game and then further ahead on the board alone, deeper the harder it is.
+pub struct MinimaxBot {
+    difficulty: Difficulty,
+    random: RandomBot // Picks between moves that score the same, so games differ
+}
+
+impl MinimaxBot {
+    /// A minimax searcher at a difficulty
+    pub fn new(_difficulty: Difficulty) -> MinimaxBot {
+        MinimaxBot { difficulty: _difficulty, random: RandomBot::new(Difficulty::Easy) }
+    }
+
+    /// Name of the bot at a difficulty, which its stats are kept under
+    pub fn name_for(_difficulty: Difficulty) -> String {
+        format!("minimax ({})", _difficulty.label().to_lowercase())
+    }
+
+    /// Moves of each side the bot looks ahead, its own move included
+    fn depth(&self) -> u32 {
+        match self.difficulty {
+            Difficulty::Easy => 1,
+            Difficulty::Normal => 2,
+            Difficulty::Hard => 3
+        }
+    }
+}
+
+/// Material of a side minus that of the other, in centipawns
+fn evaluate(_board: &BoardSnapshot, _colour: Colour) -> i32 {
+    _board.iter().flatten().flatten().map(|_piece| {
+        let value = piece_value(_piece) as i32 * 100;
+        if *get_piece_colour(_piece) == _colour { value } else { -value }
+    }).sum()
+}
+
+/// Plays a move on a board. Pawns reaching the last rank become queens.
+fn play(_board: &BoardSnapshot, _from: (u8, u8), _to: (u8, u8)) -> BoardSnapshot {
+    let mut after = *_board;
+    let piece = after[_from.0 as usize][_from.1 as usize].take();
+
+    after[_to.0 as usize][_to.1 as usize] = match piece {
+        Some(Piece::Pawn(colour)) if _to.1 == 0 || _to.1 == 7 => Some(Piece::Queen(colour)),
+        _ => piece
+    };
+    after
+}
+
+/// ## `play_uci`
+/// Plays a legal move of the game on a board, which unlike the moves of the search may castle or take en passant
+/// ### Parameters
+/// - `_board: &BoardSnapshot`: The position
+/// - `_move: &str`: The move, as in `Position::moves`
+fn play_uci(_board: &BoardSnapshot, _move: &str) -> BoardSnapshot {
+    let (from, to) = (filerank_to_num(&_move[0..2].to_string()), filerank_to_num(&_move[2..4].to_string()));
+    let mut after = play(_board, from, to);
+
+    match _board[from.0 as usize][from.1 as usize] {
+        // The rook jumps over the king
+        Some(Piece::King(_)) if (to.0 as i8 - from.0 as i8).abs() == 2 => {
+            let (rook_from, rook_to) = if to.0 > from.0 { (7, 5) } else { (0, 3) };
+            after = play(&after, (rook_from, from.1), (rook_to, from.1));
+        },
+        // A pawn moving diagonally onto an empty square takes the pawn beside it
+        Some(Piece::Pawn(_)) if from.0 != to.0 && _board[to.0 as usize][to.1 as usize].is_none() => {
+            after[to.0 as usize][from.1 as usize] = None;
+        },
+        _ => {}
+    }
+    after
+}
+
+/// ## `search`
+/// Negamax with alpha-beta pruning
+/// ### Parameters
+/// - `_board: &BoardSnapshot`: The position
+/// - `_mover: Colour`: The side to move
+/// - `_depth: u32`: Moves left to look ahead
+/// - `_alpha: i32`, `_beta: i32`: The window of scores still worth telling apart
+/// ### Return
+/// Score of the position for the side to move, in centipawns. Mates found sooner score higher.
+fn search(_board: &BoardSnapshot, _mover: Colour, _depth: u32, _alpha: i32, _beta: i32) -> i32 {
+    if _depth == 0 {
+        return evaluate(_board, _mover);
+    }
+
+    let mut moves = validator::search_moves(_board, _mover);
+    if moves.is_empty() {
+        let in_check = validator::find_king(_board, _mover).map_or(false, |_king| validator::is_attacked(_board, _king, !_mover));
+        return if in_check { -MATE_SCORE - _depth as i32 } else { 0 };
+    }
+
+    // Captures of the biggest pieces first, they cut off the most
+    moves.sort_by_key(|(_, _to)| std::cmp::Reverse(_board[_to.0 as usize][_to.1 as usize].as_ref().map_or(0, piece_value)));
+
+    let mut alpha = _alpha;
+    for (_from, _to) in moves {
+        let score = -search(&play(_board, _from, _to), !_mover, _depth - 1, -_beta, -alpha);
+        if score >= _beta {
+            return score;
+        }
+        alpha = alpha.max(score);
+    }
+    alpha
+}
+
+impl Bot for MinimaxBot {
+    fn name(&self) -> String {
+        MinimaxBot::name_for(self.difficulty)
+    }
+
+    fn choose_move(&mut self, _position: &Position) -> Result<String, String> {
+        let mut board: BoardSnapshot = [[None; 8]; 8];
+        for ((_col, _row), _piece) in &_position.pieces {
+            board[*_col as usize][*_row as usize] = Some(*_piece);
+        }
+
+        // Promotions are written once per piece type, the search always takes a queen
+        let moves = _position.moves.iter()
+            .filter(|_move| parse_uci(_move).map_or(false, |(_, _, _promotion)| _promotion.is_none() || _move.ends_with('q')));
+
+        // Every move is searched in full rather than pruned at the root, to choose between the equally good ones
+        let mut best: Vec<&String> = vec![];
+        let mut best_score = -MATE_SCORE * 2;
+        for _move in moves {
+            let score = -search(&play_uci(&board, _move), !_position.turn, self.depth() - 1, -MATE_SCORE * 2, -best_score + 1);
+            if score > best_score {
+                best_score = score;
+                best.clear();
+            }
+            if score == best_score {
+                best.push(_move);
+            }
+        }
+        if best.is_empty() {
+            return Err("there is no legal move".to_string());
+        }
+
+        let index = ((self.random.roll() * best.len() as f32) as usize).min(best.len() - 1);
+        Ok(best[index].clone())
+    }
+}
@@ -0,0 +1,220 @@
+use std::{fs, path::{Path, PathBuf}, sync::{Arc, Mutex}};
+use eliasfl_chess::{Color as Colour, Piece};
+use super::backend::{ChessBackend, PromotionKind};
+use super::help_funcs::{num_to_filerank, filerank_to_num, get_piece_colour};
+
+pub mod limited_bot;
+pub mod minimax_bot;
+pub mod random_bot;
+#[cfg(feature = "scripting")]
+pub mod script_bot;
+pub mod uci_bot;
+
+/// Range of the strength slider, in rough Elo. Above the top bots play at full strength.
+pub const MIN_ELO: u32 = 400;
+pub const MAX_ELO: u32 = 2800;
+
+/// A computer opponent. Asked for a move by the controller whenever it is its side's turn,
+/// on a background thread so a long search does not freeze the window.
+pub trait Bot: Send {
+    /// Name shown in the settings and in errors
+    fn name(&self) -> String;
+
+    /// Picks a move in the position, as a UCI move (i.e. "e2e4" or "e7e8q")
+    /// ### Parameters
+    /// - `_position: &Position`: The position, with the bot's side to move
+    fn choose_move(&mut self, _position: &Position) -> Result<String, String>;
+
+    /// Score the bot gave the move it chose last, in centipawns from its own side. None for bots that do not say,
+    /// only engines do (see `UciBot`).
+    fn evaluation(&self) -> Option<i32> {
+        None
+    }
+}
+
+/// What a bot gets to see of the game. A copy, so it can be sent to the thread the bot searches on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Position {
+    pub fen: String,
+    pub turn: Colour,
+    pub moves: Vec<String>, // Every legal move, see `legal_uci_moves`
+    pub pieces: Vec<((u8, u8), Piece)> // Every piece on the board with its square
+}
+
+impl Position {
+    /// Copies the position of a game
+    /// ### Parameters
+    /// - `_game: &dyn ChessBackend`: The game
+    /// - `_fen: &str`: The position as FEN, which the controller knows best
+    pub fn of(_game: &dyn ChessBackend, _fen: &str) -> Position {
+        let mut pieces = vec![];
+        for _col in 0..8 {
+            for _row in 0..8 {
+                if let Some(piece) = _game.piece_at((_col, _row)) {
+                    pieces.push(((_col, _row), piece));
+                }
+            }
+        }
+
+        Position {
+            fen: _fen.to_string(),
+            turn: _game.active_colour(),
+            moves: legal_uci_moves(_game),
+            pieces
+        }
+    }
+}
+
+/// A bot in charge of a side, shared with the thread its moves are searched on
+#[derive(Clone)]
+pub struct BotHandle {
+    name: String, // Kept aside, the bot itself is locked while it searches
+    bot: Arc<Mutex<Box<dyn Bot>>>
+}
+
+impl BotHandle {
+    /// Wraps a bot to be searched on background threads
+    pub fn new(_bot: Box<dyn Bot>) -> BotHandle {
+        BotHandle { name: _bot.name(), bot: Arc::new(Mutex::new(_bot)) }
+    }
+
+    /// Name shown in the settings and in errors
+    pub fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    /// Lets the bot pick a move, waiting for an earlier search of the same bot to finish first. Meant for a background thread.
+    pub fn choose_move(&self, _position: &Position) -> Result<String, String> {
+        let mut bot = self.bot.lock().map_err(|_| "the bot crashed during an earlier move".to_string())?;
+        bot.choose_move(_position)
+    }
+
+    /// Score the bot gave its last move, see `Bot::evaluation`. None while it searches.
+    pub fn evaluation(&self) -> Option<i32> {
+        self.bot.try_lock().ok().and_then(|_bot| _bot.evaluation())
+    }
+}
+
+/// All legal moves of the side to move as UCI moves, promotions written once per piece type
+pub fn legal_uci_moves(_game: &dyn ChessBackend) -> Vec<String> {
+    let mut moves = vec![];
+
+    for _row in 0..8 {
+        for _col in 0..8 {
+            let piece = match _game.piece_at((_col, _row)) {
+                Some(piece) if *get_piece_colour(&piece) == _game.active_colour() => piece,
+                _ => continue
+            };
+
+            let from = num_to_filerank(&(_col, _row));
+            for _to in _game.legal_moves(&from) {
+                let target_row = filerank_to_num(&_to).1;
+
+                if let (Piece::Pawn(_), 0) | (Piece::Pawn(_), 7) = (piece, target_row) {
+                    for _letter in ["q", "n", "r", "b"].iter() {
+                        moves.push(format!("{}{}{}", from, _to, _letter));
+                    }
+                }
+                else {
+                    moves.push(format!("{}{}", from, _to));
+                }
+            }
+        }
+    }
+
+    moves
+}
+
+/// ## `parse_uci`
+/// Splits a UCI move ("e2e4", "e7e8q") into its squares and promotion
+/// ### Return
+/// None if the text is no UCI move
+pub fn parse_uci(_move: &str) -> Option<(String, String, Option<PromotionKind>)> {
+    let is_square = |_square: &str| {
+        let bytes = _square.as_bytes();
+        bytes.len() == 2 && (b'a'..=b'h').contains(&bytes[0]) && (b'1'..=b'8').contains(&bytes[1])
+    };
+
+    if !_move.is_ascii() || (_move.len() != 4 && _move.len() != 5) || !is_square(&_move[0..2]) || !is_square(&_move[2..4]) {
+        return None;
+    }
+
+    let promotion = match _move.get(4..5) {
+        Some("q") => Some(PromotionKind::Queen),
+        Some("n") => Some(PromotionKind::Knight),
+        Some("r") => Some(PromotionKind::Rook),
+        Some("b") => Some(PromotionKind::Bishop),
+        Some(_) => return None,
+        None => None
+    };
+
+    Some((_move[0..2].to_string(), _move[2..4].to_string(), promotion))
+}
+
+/// Finds the bots in a directory, sorted by name: scripts (`*.rhai` files) and UCI engines (any other executable file).
+/// A missing directory has no bots.
+pub fn discover_bots(_directory: &Path) -> Vec<PathBuf> {
+    let mut bots: Vec<PathBuf> = match fs::read_dir(_directory) {
+        Ok(entries) => entries
+            .filter_map(|_entry| _entry.ok().map(|_entry| _entry.path()))
+            .filter(|_path| _path.extension().map_or(false, |_extension| _extension == "rhai") || is_executable(_path))
+            .collect(),
+        Err(_) => vec![]
+    };
+
+    bots.sort();
+    bots
+}
+
+/// If a file can be run, which is how engines are told apart from the other files in `bots/`
+#[cfg(unix)]
+fn is_executable(_path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(_path).map_or(false, |_metadata| _metadata.is_file() && _metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    _path.is_file() && _path.extension().map_or(false, |_extension| _extension == "exe")
+}
+
+/// ## `load_bot`
+/// Loads a bot found by `discover_bots`: a script, or a UCI engine started in the background
+/// ### Parameters
+/// - `_path: &Path`: The script or engine
+/// - `_elo: Option<u32>`: Rough Elo to hold the bot back to, None for full strength. Engines with `UCI_Elo`
+/// limit themselves, the others blunder now and then (see `LimitedBot`).
+pub fn load_bot(_path: &Path, _elo: Option<u32>) -> Result<Box<dyn Bot>, String> {
+    if _path.extension().map_or(false, |_extension| _extension == "rhai") {
+        return load_script(_path).map(|_bot| limit(_bot, _elo));
+    }
+
+    let mut engine = uci_bot::UciBot::start(_path)?;
+    match _elo {
+        Some(elo) if engine.limit_strength(elo)?.is_none() => Ok(Box::new(limited_bot::LimitedBot::new(Box::new(engine), elo))),
+        _ => Ok(Box::new(engine))
+    }
+}
+
+/// ## `limit`
+/// Holds a bot back to a rough Elo by blundering, or leaves it at full strength
+pub fn limit(_bot: Box<dyn Bot>, _elo: Option<u32>) -> Box<dyn Bot> {
+    match _elo {
+        Some(elo) => Box::new(limited_bot::LimitedBot::new(_bot, elo)),
+        None => _bot
+    }
+}
+
+/// ## `load_script`
+/// Loads a bot script, if the GUI was built with scripting support (`--features scripting`)
+pub fn load_script(_path: &Path) -> Result<Box<dyn Bot>, String> {
+    #[cfg(feature = "scripting")]
+    {
+        script_bot::ScriptBot::load(_path).map(|_bot| Box::new(_bot) as Box<dyn Bot>)
+    }
+
+    #[cfg(not(feature = "scripting"))]
+    {
+        Err(format!("{} needs a build with --features scripting", _path.display()))
+    }
+}
@@ -0,0 +1,134 @@
+use eliasfl_chess::Piece;
+use std::{sync::Arc, time::{SystemTime, UNIX_EPOCH}};
+use super::{Bot, Position, parse_uci};
+use super::super::help_funcs::{filerank_to_num, get_piece_colour, piece_value};
+use super::super::polyglot::Book;
+use super::super::speedrun::Difficulty;
+use super::super::validator::{self, BoardSnapshot};
+
+/// The random mover, built in so speedruns work without scripts. How much it looks at the board before picking
+/// a move at random depends on the difficulty. With an opening book it plays the book's moves for as long as it knows the position.
+pub struct RandomBot {
+    difficulty: Difficulty,
+    book: Option<Arc<Book>>,
+    state: u64 // Of the xorshift generator, never 0
+}
+
+impl RandomBot {
+    /// A random mover seeded from the clock
+    pub fn new(_difficulty: Difficulty) -> RandomBot {
+        let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.as_nanos() as u64);
+        RandomBot { difficulty: _difficulty, book: None, state: seed | 1 }
+    }
+
+    /// The random mover playing its first moves from an opening book, picked by their weights
+    pub fn with_book(mut self, _book: Option<Arc<Book>>) -> RandomBot {
+        self.book = _book;
+        self
+    }
+
+    /// Name of the bot at a difficulty, which its stats are kept under
+    pub fn name_for(_difficulty: Difficulty) -> String {
+        format!("random mover ({})", _difficulty.label().to_lowercase())
+    }
+
+    /// Next number of the xorshift generator
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+
+    /// ## `roll`
+    /// A random number, for the choices of bots built on the random mover
+    /// ### Return
+    /// A number from 0 up to but not including 1
+    pub fn roll(&mut self) -> f32 {
+        (self.next() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// One of the moves, at random
+    fn pick(&mut self, _moves: &[String]) -> String {
+        let index = (self.next() % _moves.len() as u64) as usize;
+        _moves[index].clone()
+    }
+}
+
+/// The board of a position, to look at moves on
+fn board_of(_position: &Position) -> BoardSnapshot {
+    let mut board = [[None; 8]; 8];
+    for ((_col, _row), _piece) in &_position.pieces {
+        board[*_col as usize][*_row as usize] = Some(*_piece);
+    }
+
+    board
+}
+
+/// Value of the piece a move takes, 0 when it takes nothing
+fn capture_value(_board: &BoardSnapshot, _move: &str) -> u32 {
+    let to = filerank_to_num(&_move[2..4].to_string());
+    _board[to.0 as usize][to.1 as usize].as_ref().map_or(0, piece_value)
+}
+
+/// If the piece a move puts on its square can be taken there right away
+fn lands_en_prise(_board: &BoardSnapshot, _move: &str) -> bool {
+    let (from, to) = (filerank_to_num(&_move[0..2].to_string()), filerank_to_num(&_move[2..4].to_string()));
+    let piece: Option<Piece> = _board[from.0 as usize][from.1 as usize];
+
+    match piece {
+        Some(piece) => {
+            let mut after = *_board;
+            after[to.0 as usize][to.1 as usize] = Some(piece);
+            after[from.0 as usize][from.1 as usize] = None;
+            validator::is_attacked(&after, (to.0 as i8, to.1 as i8), !*get_piece_colour(&piece))
+        },
+        None => false
+    }
+}
+
+impl Bot for RandomBot {
+    fn name(&self) -> String {
+        RandomBot::name_for(self.difficulty)
+    }
+
+    fn choose_move(&mut self, _position: &Position) -> Result<String, String> {
+        // A book move is only played when it is legal, books are written for standard chess
+        let random = self.next();
+        if let Some(book_move) = self.book.as_ref().and_then(|_book| _book.pick(&_position.fen, random)) {
+            if _position.moves.contains(&book_move) {
+                return Ok(book_move);
+            }
+        }
+
+        // Promotions are written once per piece type, the random mover always takes a queen
+        let moves: Vec<String> = _position.moves.iter()
+            .filter(|_move| parse_uci(_move).map_or(false, |(_, _, _promotion)| _promotion.is_none() || _move.ends_with('q')))
+            .cloned()
+            .collect();
+        if moves.is_empty() {
+            return Err("there is no legal move".to_string());
+        }
+
+        let board = board_of(_position);
+
+        // Normal and hard take the biggest piece they can
+        if self.difficulty != Difficulty::Easy {
+            let best = moves.iter().map(|_move| capture_value(&board, _move)).max().unwrap_or(0);
+            if best > 0 {
+                let captures: Vec<String> = moves.iter().filter(|_move| capture_value(&board, _move) == best).cloned().collect();
+                return Ok(self.pick(&captures));
+            }
+        }
+
+        // Hard keeps its pieces out of reach when it can
+        if self.difficulty == Difficulty::Hard {
+            let safe: Vec<String> = moves.iter().filter(|_move| !lands_en_prise(&board, _move)).cloned().collect();
+            if !safe.is_empty() {
+                return Ok(self.pick(&safe));
+            }
+        }
+
+        Ok(self.pick(&moves))
+    }
+}
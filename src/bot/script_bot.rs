@@ -0,0 +1,68 @@
+use std::path::Path;
+use rhai::{Engine, AST, Scope, Map, Array, Dynamic};
+use eliasfl_chess::Color as Colour;
+use super::{Bot, Position};
+use super::super::fen::piece_char;
+use super::super::help_funcs::num_to_filerank;
+
+/// Most operations a script may run to choose one move, so a runaway loop cannot freeze the GUI
+const MAX_OPERATIONS: u64 = 5_000_000;
+
+/// `Bot` written as a rhai script. The script defines `fn choose_move(position)` and returns a UCI move string.
+///
+/// `position` is a map with:
+/// - `fen`: the position as FEN
+/// - `turn`: "white" or "black"
+/// - `moves`: array of every legal move as UCI strings ("e2e4", "e7e8q")
+/// - `pieces`: map from square ("e2") to FEN letter ("P" for a white pawn, "k" for the black king)
+pub struct ScriptBot {
+    name: String,
+    engine: Engine,
+    ast: AST
+}
+
+impl ScriptBot {
+    /// Compiles the script at `_path`, named after the file
+    pub fn load(_path: &Path) -> Result<ScriptBot, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+
+        let ast = engine.compile_file(_path.to_path_buf()).map_err(|_error| _error.to_string())?;
+        let name = _path.file_stem().map_or("script".to_string(), |_stem| _stem.to_string_lossy().to_string());
+
+        Ok(ScriptBot { name, engine, ast })
+    }
+
+    /// The position handed to the script
+    fn position(_position: &Position) -> Map {
+        let mut pieces = Map::new();
+        for (_square, _piece) in &_position.pieces {
+            pieces.insert(num_to_filerank(_square).into(), Dynamic::from(piece_char(_piece).to_string()));
+        }
+
+        let moves: Array = _position.moves.iter().cloned().map(Dynamic::from).collect();
+
+        let mut position = Map::new();
+        position.insert("fen".into(), Dynamic::from(_position.fen.clone()));
+        position.insert("turn".into(), Dynamic::from(if _position.turn == Colour::White { "white" } else { "black" }.to_string()));
+        position.insert("moves".into(), Dynamic::from(moves));
+        position.insert("pieces".into(), Dynamic::from(pieces));
+
+        position
+    }
+}
+
+impl Bot for ScriptBot {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn choose_move(&mut self, _position: &Position) -> Result<String, String> {
+        let position = ScriptBot::position(_position);
+
+        let chosen: Dynamic = self.engine.call_fn(&mut Scope::new(), &self.ast, "choose_move", (position,))
+            .map_err(|_error| _error.to_string())?;
+
+        chosen.into_string().map_err(|_type| format!("choose_move returned a {} instead of a move string", _type))
+    }
+}
@@ -0,0 +1,156 @@
+use std::{io::{BufRead, BufReader, Write}, path::Path, process::{Child, ChildStdin, ChildStdout, Command, Stdio}};
+use super::{Bot, Position};
+use super::super::adjudicator::MATE_SCORE;
+
+/// Milliseconds an engine thinks about each move
+const MOVE_TIME: u32 = 1000;
+
+/// `Bot` played by a chess engine speaking UCI (i.e. Stockfish), run as a child process. The engine is asked for one
+/// move at a time from the FEN of the position, so it never has to follow the game.
+pub struct UciBot {
+    name: String,
+    engine: Child,
+    input: ChildStdin,
+    output: BufReader<ChildStdout>,
+    elo_range: Option<(u32, u32)>, // Lowest and highest `UCI_Elo`, None when the engine cannot limit its strength
+    score: Option<i32> // Of the last search, in centipawns from the side that moved
+}
+
+impl UciBot {
+    /// ## `start`
+    /// Starts an engine and waits for it to be ready, named after the file
+    /// ### Return
+    /// `Err` if the engine could not be run or does not answer in UCI
+    pub fn start(_path: &Path) -> Result<UciBot, String> {
+        let mut engine = Command::new(_path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|_error| format!("{}: {}", _path.display(), _error))?;
+        let (input, output) = match (engine.stdin.take(), engine.stdout.take()) {
+            (Some(input), Some(output)) => (input, BufReader::new(output)),
+            _ => return Err(format!("{} could not be talked to", _path.display()))
+        };
+
+        let name = _path.file_stem().map_or("engine".to_string(), |_stem| _stem.to_string_lossy().to_string());
+        let mut bot = UciBot { name, engine, input, output, elo_range: None, score: None };
+
+        bot.send("uci")?;
+        let mut limits = false;
+        for _line in bot.read_until("uciok")? {
+            if _line.starts_with("option name UCI_LimitStrength ") {
+                limits = true;
+            }
+            else if _line.starts_with("option name UCI_Elo ") {
+                let value = |_key: &str| _line.split_whitespace().skip_while(|_word| *_word != _key).nth(1).and_then(|_value| _value.parse().ok());
+                if let (Some(min), Some(max)) = (value("min"), value("max")) {
+                    bot.elo_range = Some((min, max));
+                }
+            }
+        }
+        if !limits {
+            bot.elo_range = None;
+        }
+
+        bot.send("isready")?;
+        bot.read_until("readyok")?;
+        Ok(bot)
+    }
+
+    /// ## `limit_strength`
+    /// Asks the engine to play at about an Elo, through the `UCI_LimitStrength` and `UCI_Elo` options. Engines only
+    /// go down to a point, lower ratings are played at the lowest one.
+    /// ### Return
+    /// The Elo the engine was set to, None if it cannot limit its strength
+    pub fn limit_strength(&mut self, _elo: u32) -> Result<Option<u32>, String> {
+        let elo = match self.elo_range {
+            Some((min, max)) => _elo.max(min).min(max),
+            None => return Ok(None)
+        };
+
+        self.send("setoption name UCI_LimitStrength value true")?;
+        self.send(&format!("setoption name UCI_Elo value {}", elo))?;
+        self.send("isready")?;
+        self.read_until("readyok")?;
+        Ok(Some(elo))
+    }
+
+    /// ## `parse_score`
+    /// Reads the score off an info line of a search, i.e. "info depth 12 score cp -35 nodes ...". Mates count as `MATE_SCORE`.
+    /// ### Return
+    /// The score in centipawns from the side to move, None if the line has none
+    fn parse_score(_line: &str) -> Option<i32> {
+        let mut words = _line.split_whitespace().skip_while(|_word| *_word != "score").skip(1);
+        match (words.next(), words.next().and_then(|_value| _value.parse::<i32>().ok())) {
+            (Some("cp"), Some(centipawns)) => Some(centipawns),
+            (Some("mate"), Some(moves)) => Some(if moves > 0 { MATE_SCORE } else { -MATE_SCORE }),
+            _ => None
+        }
+    }
+
+    /// Writes a command to the engine
+    fn send(&mut self, _command: &str) -> Result<(), String> {
+        writeln!(self.input, "{}", _command).and_then(|_| self.input.flush())
+            .map_err(|_error| format!("{} stopped: {}", self.name, _error))
+    }
+
+    /// Reads what the engine says up to the line starting with a word, which is not included
+    fn read_until(&mut self, _word: &str) -> Result<Vec<String>, String> {
+        let mut lines = vec![];
+        loop {
+            let mut line = String::new();
+            match self.output.read_line(&mut line) {
+                Ok(0) | Err(_) => return Err(format!("{} stopped before saying {}", self.name, _word)),
+                Ok(_) if line.split_whitespace().next() == Some(_word) => return Ok(lines),
+                Ok(_) => lines.push(line.trim_end().to_string())
+            }
+        }
+    }
+}
+
+impl Bot for UciBot {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn choose_move(&mut self, _position: &Position) -> Result<String, String> {
+        self.send(&format!("position fen {}", _position.fen))?;
+        self.send(&format!("go movetime {}", MOVE_TIME))?;
+        self.score = None;
+
+        // Info lines while it searches, the last score is the one of its move. Then "bestmove <move> [ponder <move>]".
+        loop {
+            let mut line = String::new();
+            match self.output.read_line(&mut line) {
+                Ok(0) | Err(_) => return Err(format!("{} stopped while thinking", self.name)),
+                Ok(_) => {
+                    let mut words = line.split_whitespace();
+                    match words.next() {
+                        Some("info") => self.score = UciBot::parse_score(&line).or(self.score),
+                        Some("bestmove") => {
+                            return match words.next() {
+                                Some(best) if best != "(none)" => Ok(best.to_string()),
+                                _ => Err(format!("{} found no move", self.name))
+                            };
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+    }
+
+    fn evaluation(&self) -> Option<i32> {
+        self.score
+    }
+}
+
+impl Drop for UciBot {
+    /// Engines are not left running once their side is taken from them
+    fn drop(&mut self) {
+        self.send("quit").ok();
+        self.engine.kill().ok();
+        self.engine.wait().ok();
+    }
+}
@@ -0,0 +1,13 @@
+use copypasta::{ClipboardContext, ClipboardProvider};
+
+/// Puts text on the system clipboard
+pub fn copy(_text: &str) -> Result<(), String> {
+    let mut clipboard = ClipboardContext::new().map_err(|_error| _error.to_string())?;
+    clipboard.set_contents(_text.to_string()).map_err(|_error| _error.to_string())
+}
+
+/// The text on the system clipboard
+pub fn paste() -> Result<String, String> {
+    let mut clipboard = ClipboardContext::new().map_err(|_error| _error.to_string())?;
+    clipboard.get_contents().map_err(|_error| _error.to_string())
+}
@@ -0,0 +1,91 @@
+use super::consts::{CLOCK_SNAP, CLOCK_EASE};
+
+/// Starting time and increment of a chess clock, in seconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeControl {
+    pub initial: f32,
+    pub increment: f32
+}
+
+impl TimeControl {
+    /// Time control written as minutes + increment seconds, i.e. `TimeControl::minutes(5.0, 3.0)` for 5+3
+    pub fn minutes(_minutes: f32, _increment: f32) -> TimeControl {
+        TimeControl { initial: _minutes * 60.0, increment: _increment }
+    }
+
+    /// The time control as minutes + increment seconds, i.e. "5+3"
+    pub fn label(&self) -> String {
+        format!("{}+{}", self.initial / 60.0, self.increment)
+    }
+}
+
+/// The clock of one player
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clock {
+    remaining: f32,
+    increment: f32
+}
+
+impl Clock {
+    /// Creates a clock set to the starting time of the time control
+    pub fn new(_time_control: TimeControl) -> Clock {
+        Clock { remaining: _time_control.initial, increment: _time_control.increment }
+    }
+
+    /// Takes `_dt` seconds off the clock, never going below zero
+    pub fn tick(&mut self, _dt: f32) {
+        self.remaining = (self.remaining - _dt).max(0.0);
+    }
+
+    /// Called when the owner has made their move, adds the increment
+    pub fn press(&mut self) {
+        self.remaining += self.increment;
+    }
+
+    /// Sets the time left, i.e. when carrying on a saved game
+    pub fn set_remaining(&mut self, _remaining: f32) {
+        self.remaining = _remaining.max(0.0);
+    }
+
+    /// Seconds left
+    pub fn remaining(&self) -> f32 {
+        self.remaining
+    }
+
+    /// Seconds added after every move
+    pub fn increment(&self) -> f32 {
+        self.increment
+    }
+
+    /// If the clock has run out
+    pub fn is_flagged(&self) -> bool {
+        self.remaining <= 0.0
+    }
+
+    /// The remaining time as "mm:ss", with tenths of seconds under 10 seconds
+    pub fn format(&self) -> String {
+        if self.remaining < 10.0 {
+            format!("00:{:04.1}", self.remaining)
+        }
+        else {
+            let seconds = self.remaining.ceil() as u32;
+            format!("{:02}:{:02}", seconds / 60, seconds % 60)
+        }
+    }
+}
+
+/// ## `synced`
+/// Where a clock following the clock of a host, which keeps the time of the game, should be set to
+/// ### Parameters
+/// - `_local: f32`: Seconds the clock shows here
+/// - `_host: f32`: Seconds the host's clock showed when it answered
+/// - `_delay: f32`: Seconds the answer was on its way, half the round trip
+/// - `_running: bool`: If the clock was running meanwhile
+/// ### Return
+/// The seconds to set: the host's time, less the delay when running. Drifts up to `CLOCK_SNAP` are only corrected by `CLOCK_EASE` of the way.
+pub fn synced(_local: f32, _host: f32, _delay: f32, _running: bool) -> f32 {
+    let host = if _running { (_host - _delay).max(0.0) } else { _host };
+    let drift = host - _local;
+
+    if drift.abs() > CLOCK_SNAP { host } else { _local + drift * CLOCK_EASE }
+}
@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+use super::adjudicator::AdjudicationRules;
+use super::audio::Volumes;
+
+/// File the configuration is kept in, in the GGEZ user data directory
+pub const CONFIG_FILE: &str = "/config.toml";
+
+/// Settings kept between sessions
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub audio: Volumes,
+    pub sound_pack: String, // Name of the pack the effects are played from, see `SOUND_PACKS`
+    pub messages: String, // Name of the message pack for the state of the game, see `MessagePack`
+    pub square_labels: bool, // Teaching: the name of the square under the cursor in the corner of the board
+    pub run_unfocused: bool, // Keep clocks and bots going while the window is not focused, instead of pausing the game
+    pub low_spec: bool, // Old laptops: no animations, shapes drawn without building meshes every frame and at most 30 frames a second
+    pub text_scale: f32, // Low vision: all text drawn this many times larger, 0 or 1 for the normal size
+    pub piece_margin: f32, // Pixels between a piece and each edge of its tile, 0 to fill the tile
+    pub reduced_motion: bool, // No fades, slides, spinning or fading out, highlights stay as they are
+    pub arbiter: bool, // Arbiter controls (A on the board) to adjudicate, adjust clocks and force takebacks
+    pub opening_book: bool, // The built-in bot plays its first moves from the Polyglot book in the user data directory
+    pub bot_elo: Option<u32>, // Rough Elo bots are held back to, None for full strength
+    pub engine_adjudication: AdjudicationRules, // When games between two bots are ended early from their scores
+    pub online_name: String, // Name online opponents see
+    pub matchmaker: String, // Address of the matchmaking server as "host:port", empty for `DEFAULT_MATCHMAKER`
+    pub matchmaker_certificate: String, // PEM file of the matchmaker's certificate when it is self-signed, empty to trust the public authorities
+    pub lan_address: String, // Of the LAN game to join as "host:port", hosting listens on its port (`DEFAULT_LAN_PORT` without one)
+    pub lan_fingerprint: String, // Key of the LAN host to join as its lobby shows it, see `tls::fingerprint`
+    pub lichess_token: String, // Personal API token of the Lichess account to play with, empty when not logged in
+    #[serde(skip)]
+    pub unsaved: bool // Changed since the file was last written
+}
+
+impl Config {
+    /// Reads the configuration file. Missing or unreadable settings keep their defaults.
+    pub fn from_toml(_text: &str) -> Config {
+        toml::from_str(_text).unwrap_or_default()
+    }
+
+    /// Writes the configuration file
+    pub fn to_toml(&self) -> String {
+        toml::to_string_pretty(self).unwrap_or_default()
+    }
+}
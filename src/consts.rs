@@ -1,4 +1,4 @@
-use ggez::{graphics};
+use super::graphics_funcs::renderer::Rgba;
 
 /// A chess board is 8x8 tiles.
 pub const GRID_SIZE: i16 = 8;
@@ -6,16 +6,92 @@ pub const GRID_SIZE: i16 = 8;
 /// Sutible size of each tile.
 pub const GRID_CELL_SIZE: (i16, i16) = (90, 90);
 
+/// Radius of the legal move indicators, as a part of the width of a tile.
+pub const LEGAL_INDICATOR_RADIUS: f32 = 0.28;
+
 /// Size of the application window.
 pub const SCREEN_SIZE: (f32, f32) = (
     GRID_SIZE as f32 * GRID_CELL_SIZE.0 as f32,
     GRID_SIZE as f32 * GRID_CELL_SIZE.1 as f32 + 150.0,
 );
 
+/// Size of the window in `--overlay` mode: the board and a strip for the clocks.
+pub const OVERLAY_SIZE: (f32, f32) = (
+    GRID_SIZE as f32 * GRID_CELL_SIZE.0 as f32,
+    GRID_SIZE as f32 * GRID_CELL_SIZE.1 as f32 + 70.0,
+);
+
+/// Fixed timestep updates per second for clocks, animations and polling.
+pub const UPDATE_RATE: u32 = 60;
+
+/// Most frames drawn per second in low-spec mode.
+pub const LOW_SPEC_FPS: u32 = 30;
+
+/// Least seconds a bot seems to think, so instant replies don't snap onto the board before the player looked up.
+pub const BOT_MIN_DELAY: f32 = 0.6;
+
+/// Seconds between two looks of a `--mirror` window at the game it mirrors.
+pub const MIRROR_POLL: f32 = 0.5;
+
+/// Seconds without a look at the game after which a mirror window is no longer counted as a spectator.
+pub const SPECTATOR_TIMEOUT: f32 = 5.0;
+
+/// Longest line of the spectator chat, in characters.
+pub const MAX_CHAT_LENGTH: usize = 200;
+
+/// Seconds a clock may drift from the host's before it is set to the host's time outright, instead of being eased towards it.
+pub const CLOCK_SNAP: f32 = 1.0;
+
+/// Part of a small drift from the host's clock corrected at each sync, so the time shown never jumps back and forth.
+pub const CLOCK_EASE: f32 = 0.5;
+
+/// The button at the end of the banner rebuilding the GUI from the engine's board, once the two disagree.
+pub const RESYNC_BUTTON: (f32, f32, f32, f32) = (600.0, 3.0, 110.0, 24.0);
+
+/// Where the queen, knight, rook and bishop of the promotion dialog are laid out, drawn and clicked
+pub const PROMOTION_CHOICES: [(f32, f32, f32, f32); 4] = [
+    (20.0, 740.0, 180.0, 110.0), (200.0, 740.0, 180.0, 110.0), (380.0, 740.0, 180.0, 110.0), (560.0, 740.0, 140.0, 110.0)
+];
+
 // GUI Color representations
 
 /// Very Dark Grey
-pub const BLACK: graphics::Color = graphics::Color::new(30.0/255.0, 30.0/255.0, 30.0/255.0, 1.0);
+pub const BLACK: Rgba = [30.0/255.0, 30.0/255.0, 30.0/255.0, 1.0];
 
 /// Less Darker than `BLACK`
-pub const WHITE: graphics::Color = graphics::Color::new(70.0/255.0, 70.0/255.0, 70.0/255.0, 1.0);
\ No newline at end of file
+pub const WHITE: Rgba = [70.0/255.0, 70.0/255.0, 70.0/255.0, 1.0];
+
+/// Behind the board and the panel, the same whoever's turn it is
+pub const BACKGROUND: Rgba = [0.6, 0.6, 0.6, 1.0];
+
+/// Pure green around the board in `--overlay` mode, to be keyed out by the streaming software
+pub const CHROMA_KEY: Rgba = [0.0, 1.0, 0.0, 1.0];
+// Scene transitions
+
+/// Seconds to fade to black, and again back from black, when switching between full screen scenes.
+pub const FADE_TIME: f32 = 0.2;
+
+/// Seconds for an overlay (promotion, game over) to slide in or out.
+pub const SLIDE_TIME: f32 = 0.25;
+
+/// How far below its place an overlay starts sliding in from, the height of the panel.
+pub const SLIDE_DISTANCE: f32 = 150.0;
+
+// Move list
+
+/// Height of a row (one white and one black move) of the move list.
+pub const MOVE_LIST_ROW_HEIGHT: f32 = 22.0;
+
+/// Width of the move numbers on the left of the move list.
+pub const MOVE_LIST_NUMBER_WIDTH: f32 = 50.0;
+
+/// Width of a move in the move list.
+pub const MOVE_LIST_MOVE_WIDTH: f32 = 150.0;
+
+// Toasts
+
+/// Seconds a toast stays on screen.
+pub const TOAST_TIME: f32 = 4.0;
+
+/// Seconds a toast takes to fade out at the end of its time.
+pub const TOAST_FADE: f32 = 0.5;
@@ -0,0 +1,1183 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use ggez::event::{KeyCode, KeyMods};
+use std::{collections::HashMap, rc::Rc, time::SystemTime};
+use super::consts::*;
+use super::help_funcs::*;
+use super::clock::{Clock, TimeControl};
+use super::backend::{ChessBackend, BackendKind, GameStatus, consistency::ConsistencyChecker, friendly};
+use super::validator;
+use super::history::{self, captured_by, MoveRecord};
+use super::fen::{en_passant, to_fen};
+use super::bot::{Bot, BotHandle, Position, parse_uci};
+use super::tasks::Task;
+use super::view::BoardView;
+use super::variant::{Variant, Standard};
+use super::saves::{SavedGame, SavedMove};
+use super::vote::Vote;
+use super::export::piece_name;
+use super::adjudicator::{self, AdjudicationRules};
+use super::graphics_funcs::{draw_funcs::promotion_dialog, widgets::Regions};
+pub use super::backend::PromotionKind;
+
+/// Moves to be made after a promotion
+pub struct PendingMove {
+    pub _from: String,
+    pub _to: String
+}
+
+impl PendingMove {
+    /// An empty pending move, used when no promotion is waiting
+    pub fn empty() -> PendingMove {
+        PendingMove{_from: "".to_string(), _to: "".to_string()}
+    }
+}
+
+/// Raw input fed to the controller. Comes either from the ggez event loop or from a test/script.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputEvent {
+    /// A left click (the button being released) at window coordinates
+    Click { x: f32, y: f32 },
+    /// The left button being pressed at window coordinates
+    Press { x: f32, y: f32 },
+    /// The mouse moving to window coordinates
+    Motion { x: f32, y: f32 },
+    /// The mouse wheel turning, positive `y` is away from the player (scrolling up)
+    Scroll { y: f32 },
+    /// The middle button being pressed at window coordinates
+    MiddlePress { x: f32, y: f32 },
+    /// The middle button being released at window coordinates
+    MiddleRelease { x: f32, y: f32 },
+    /// A right click (the button being released) at window coordinates, with the modifier keys held
+    RightClick { x: f32, y: f32, mods: KeyMods },
+    /// A character typed, for text fields
+    Text(char),
+    /// A key press
+    Key(KeyCode)
+}
+
+/// Everything the player can ask the game to do. Input events (and console lines) are translated into commands before they are executed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    /// Select a piece or, if a piece is already selected, move it to the square
+    SelectSquare((u8, u8)),
+    /// Choose which piece the pending promotion becomes
+    Promote(PromotionKind),
+    /// Select a piece of the side to move and pick it up to be dragged
+    BeginDrag((u8, u8)),
+    /// Move the dragged piece to board coordinates, the window coordinates of the board at its normal size
+    DragTo((f32, f32)),
+    /// Let go of the dragged piece over a square (None when off the board), moving it there if the move is legal
+    Drop(Option<(u8, u8)>),
+    /// Drop the current selection and its legal move indicators
+    ClearSelection,
+    /// Start a new game once the current one is over
+    Restart,
+    /// Play a move given as "\<file\>\<rank\>" squares, with the piece a promoting pawn becomes
+    PlayMove(String, String, Option<PromotionKind>),
+    /// Take the last move back
+    Undo,
+    /// Take moves back until the player who moved last is to move again, i.e. their move and the reply to it
+    TakeBack,
+    /// Play the moves taken back again, the last one taken back and the bot's reply to it if a bot is to move
+    Redo,
+    /// Start over from a position given as FEN
+    LoadFen(String),
+    /// Arbiter: end the game with a result, the winner or None for a draw
+    Adjudicate(Option<Colour>),
+    /// A side gives the game up
+    Resign(Colour),
+    /// A side offers a draw. A bot on the other side answers at once, a player with `AnswerDraw`.
+    OfferDraw(Colour),
+    /// Accept the draw offered with `OfferDraw`, or decline it with false
+    AnswerDraw(bool),
+    /// Arbiter: add seconds to the clock of a side, or take them off with a negative amount
+    AdjustClock(Colour, f32),
+    /// Vote chess: put a move forward for the voter whose turn it is
+    Propose(String, String, Option<PromotionKind>),
+    /// Vote chess: take a proposal off the list, by its index
+    Withdraw(usize),
+    /// Vote chess: play a proposal, by its index, and start over on the next move
+    Confirm(usize),
+    /// Take the engine's board as the truth after it fell out of sync with the moves, see `GameController::out_of_sync`
+    Resync,
+    /// Mark a square in a colour, or take the mark off when it has that colour already
+    Mark((u8, u8), MarkColour)
+}
+
+/// Colours the player marks squares with, picked by the modifier keys held while right clicking. Drawn in the colours
+/// of the theme, see `Theme::marks`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MarkColour {
+    Red,
+    Green,
+    Blue,
+    Yellow
+}
+
+impl MarkColour {
+    /// The colour picked by modifier keys: none for red, Shift for green, Ctrl for blue and Alt for yellow
+    pub fn from_mods(_mods: KeyMods) -> MarkColour {
+        if _mods.contains(KeyMods::SHIFT) { MarkColour::Green }
+        else if _mods.contains(KeyMods::CTRL) { MarkColour::Blue }
+        else if _mods.contains(KeyMods::ALT) { MarkColour::Yellow }
+        else { MarkColour::Red }
+    }
+
+    /// Where the colour is in `Theme::marks`
+    pub fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// A result declared rather than reached on the board: by the arbiter, a resignation or a draw agreed by both sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Adjudication {
+    Win(Colour),
+    Draw,
+    Resigned(Colour), // The side that gave up
+    DrawAgreed
+}
+
+/// A piece being dragged with the mouse
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drag {
+    pub from: (u8, u8), // The square the piece was picked up from
+    pub at: (f32, f32) // Where the cursor is, in board coordinates (see `BoardView::to_board`)
+}
+
+/// A bot working on its move. The search runs in the background and the move is held back
+/// until the bot has thought for at least `BOT_MIN_DELAY`.
+pub struct Thinking {
+    pub colour: Colour, // The side of the bot
+    pub elapsed: f32, // Seconds since it started, also drives the spinner
+    ply: usize, // Moves played when it started, any other number means the position changed under it
+    search: Task<Result<String, String>>, // The move as UCI, or why the bot could not find one
+    chosen: Option<Result<String, String>> // What the search came up with, once it finished
+}
+
+/// Snapshot of the controller returned after every input, used to inspect the game without a window
+pub struct ControllerState {
+    pub game_state: GameStatus,
+    pub active_colour: Colour,
+    pub selected: Option<(u8, u8)>,
+    pub legal: Vec<(u8, u8)>,
+    pub promoting: bool,
+    pub deaths: HashMap<Colour, Vec<Piece>>
+}
+
+/// Holds the game and all GUI-side game logic. Knows nothing about ggez windows or sprites so it can be driven headlessly.
+pub struct GameController {
+    pub game: Box<dyn ChessBackend>, // The rules, played by whichever backend was chosen
+    pub legal: Vec<(u8, u8)>, // When clicking on a piece, it saves the legal moves in this vec to display the indicators on the board
+    pub previous_click: Option<(u8, u8)>, // The previous square clicked by the player
+    pub promoting: bool, // If the player is currently promoting a piece (makes a small window pop up for the player to choose)
+    pub pending_promotion_move: PendingMove,
+    pub drag: Option<Drag>, // The piece being dragged, if any
+    pub deaths: HashMap<Colour, Vec<Piece>>,
+    pub history: Vec<MoveRecord>, // Every move played since the start of the game
+    pub redo: Vec<MoveRecord>, // Moves taken back, the last one taken back at the end, until another move is played
+    pub start_fen: Option<String>, // Position the game started from when loaded from FEN, None for the starting position
+    pub started: SystemTime, // When the game started, for exports
+    pub game_time: f32, // Seconds played since the start of the game, stands still once it is over
+    pub time_control: Option<TimeControl>, // None for untimed games
+    pub time_odds: Option<TimeControl>, // Black's clock when it differs from White's, i.e. for handicap games. None gives both the time control
+    pub clocks: HashMap<Colour, Clock>, // Empty for untimed games
+    pub bots: HashMap<Colour, BotHandle>, // Sides played by the computer, the rest is played by clicking
+    pub thinking: Option<Thinking>, // The bot to move, while it works on its move
+    pub checker: Option<ConsistencyChecker>, // Debug mode: second backend every move is compared against
+    pub validate_moves: bool, // Double check every move with the GUI's own validator before the backend plays it
+    pub casual_takebacks: bool, // Casual mode: the Left key takes the last full move back, no questions asked, and Right plays it again
+    pub variant: Rc<dyn Variant>, // Rules the next game is set up with
+    pub variant_win: Option<(Colour, String)>, // Side that won by a win condition of the variant, and how
+    pub adjudication: Option<Adjudication>, // Result declared by the arbiter or the players, it ends the game whatever the position
+    pub draw_offer: Option<Colour>, // Side that offered a draw, until the other side answers or a move is played
+    pub adjudication_rules: Option<AdjudicationRules>, // When games between two bots are ended from their scores, None to play them out
+    pub scores: Vec<Option<i32>>, // Score the bot gave every move from White's side, None for moves without one
+    pub vote: Option<Vote>, // Vote chess: moves of the human sides are proposed, and only played once confirmed
+    pub flipped: bool, // Board seen from Black's side, rank 1 at the top
+    pub view: BoardView, // Zoom and pan of the board, clicks are turned into squares through it
+    pub out_of_sync: Option<String>, // Where the engine's board differs from the moves the GUI recorded, until resynced
+    pub notice: Option<String>, // Why the last move or command was refused, shown in the status bar until the next move
+    pub hover: Option<(u8, u8)>, // The square under the cursor, None when it is off the board
+    pub pointer: Option<(f32, f32)>, // Where the cursor is on the screen, for the tooltips of the panel
+    pub banner: Option<String>, // Error shown above the board, i.e. a discrepancy found by the checker
+    pub marks: HashMap<(u8, u8), MarkColour> // Squares marked with a right click, until the next move
+}
+
+impl GameController {
+    /// Creates a controller holding a new game
+    pub fn new() -> GameController {
+        GameController::with_backend(BackendKind::Elias.create())
+    }
+
+    /// Creates a controller holding a new game played by the given backend
+    pub fn with_backend(_backend: Box<dyn ChessBackend>) -> GameController {
+        let mut controller = GameController {
+            game: _backend,
+            legal: vec![],
+            previous_click: None,
+            promoting: false,
+            pending_promotion_move: PendingMove::empty(),
+            drag: None,
+            deaths: HashMap::new(),
+            history: vec![],
+            redo: vec![],
+            start_fen: None,
+            started: SystemTime::now(),
+            game_time: 0.0,
+            time_control: None,
+            time_odds: None,
+            clocks: HashMap::new(),
+            bots: HashMap::new(),
+            thinking: None,
+            checker: None,
+            validate_moves: true,
+            casual_takebacks: false,
+            variant: Rc::new(Standard),
+            variant_win: None,
+            adjudication: None,
+            draw_offer: None,
+            adjudication_rules: None,
+            scores: vec![],
+            vote: None,
+            flipped: false,
+            view: BoardView::new(),
+            out_of_sync: None,
+            notice: None,
+            hover: None,
+            pointer: None,
+            banner: None,
+            marks: HashMap::new()
+        };
+
+        controller.deaths.insert(Colour::Black, vec![]);
+        controller.deaths.insert(Colour::White, vec![]);
+        controller
+    }
+
+    /// Creates a controller holding a new game played with clocks
+    pub fn with_time_control(_time_control: TimeControl) -> GameController {
+        let mut controller = GameController::new();
+        controller.time_control = Some(_time_control);
+        controller.reset_clocks();
+        controller
+    }
+
+    /// Turns on the consistency checker, comparing the backend against `_reference` after every move
+    pub fn check_against(&mut self, _reference: Box<dyn ChessBackend>) {
+        self.checker = Some(ConsistencyChecker::new(_reference));
+    }
+
+    /// Advances everything that runs on time by one fixed step of `_dt` seconds. Called from the fixed timestep loop.
+    pub fn tick(&mut self, _dt: f32) {
+        // Clocks stand still once the game is over
+        if self.is_over() {
+            return;
+        }
+
+        self.game_time += _dt;
+
+        if let Some(clock) = self.clocks.get_mut(&self.game.active_colour()) {
+            clock.tick(_dt);
+        }
+
+        self.play_bot_move(_dt);
+    }
+
+    /// Puts a bot in charge of a side, or gives the side back to a human with None
+    pub fn set_bot(&mut self, _colour: Colour, _bot: Option<Box<dyn Bot>>) {
+        match _bot {
+            Some(bot) => { self.bots.insert(_colour, BotHandle::new(bot)); },
+            None => { self.bots.remove(&_colour); }
+        }
+    }
+
+    /// Lets the bot of the side to move, if there is one, play its move once it has thought for long enough
+    fn play_bot_move(&mut self, _dt: f32) {
+        let colour = self.game.active_colour();
+        if self.promoting || !self.bots.contains_key(&colour) {
+            self.thinking = None;
+            return;
+        }
+
+        // An undo or a new game since the bot started makes it start over, the old search is left to finish unheard
+        let ply = self.history.len();
+        if !matches!(&self.thinking, Some(thinking) if thinking.colour == colour && thinking.ply == ply) {
+            let position = Position::of(self.game.as_ref(), &self.fen());
+            let bot = self.bots[&colour].clone();
+            let search = Task::spawn(move || bot.choose_move(&position));
+            self.thinking = Some(Thinking { colour, elapsed: 0.0, ply, search, chosen: None });
+        }
+
+        let thinking = self.thinking.as_mut().unwrap();
+        thinking.elapsed += _dt;
+        if thinking.chosen.is_none() {
+            thinking.chosen = thinking.search.poll().map(|_result| _result.and_then(|_chosen| _chosen));
+        }
+        if thinking.chosen.is_none() || thinking.elapsed < BOT_MIN_DELAY {
+            return;
+        }
+
+        let chosen = self.thinking.take().and_then(|_thinking| _thinking.chosen).unwrap()
+            .and_then(|_move| parse_uci(&_move).ok_or(format!("{} is no UCI move", _move)));
+
+        let result = chosen.and_then(|(from, to, promotion)| self.try_execute(Command::PlayMove(from, to, promotion)));
+
+        // A broken bot would fail again every tick, the side goes back to the player instead
+        match result {
+            Ok(()) => self.adjudicate(colour, ply),
+            Err(error) => {
+                let bot = self.bots.remove(&colour).unwrap();
+                self.banner = Some(format!("Bot {} gave up: {}", bot.name(), error));
+            }
+        }
+    }
+
+    /// ## `adjudicate`
+    /// Engine matches: keeps the score a bot gave the move it just played, and ends the game once the scores call
+    /// for it, see `adjudicator::verdict`
+    /// ### Parameters
+    /// - `_colour: Colour`: The side of the bot
+    /// - `_ply: usize`: Moves played before the bot's move
+    fn adjudicate(&mut self, _colour: Colour, _ply: usize) {
+        let rules = match &self.adjudication_rules {
+            Some(rules) if self.bots.len() == 2 => rules,
+            _ => return
+        };
+
+        // Moves taken back since are forgotten, moves without a score leave a gap
+        let score = self.bots.get(&_colour).and_then(|_bot| _bot.evaluation());
+        self.scores.resize(_ply, None);
+        self.scores.push(score.map(|_score| if _colour == Colour::White { _score } else { -_score }));
+
+        if !self.is_over() {
+            if let Some(verdict) = adjudicator::verdict(rules, &self.scores) {
+                self.adjudication = Some(verdict);
+                self.thinking = None;
+            }
+        }
+    }
+
+    /// The time controls of White and Black: the variant's when it brings its own clocks, else the time control with
+    /// Black's own under time odds. None for untimed games.
+    pub fn time_controls(&self) -> Option<(TimeControl, TimeControl)> {
+        self.variant.time_controls().or(self.time_control.map(|_time_control| (_time_control, self.time_odds.unwrap_or(_time_control))))
+    }
+
+    /// Sets both clocks back to the starting time of their time control
+    fn reset_clocks(&mut self) {
+        self.clocks.clear();
+
+        if let Some((white, black)) = self.time_controls() {
+            self.clocks.insert(Colour::White, Clock::new(white));
+            self.clocks.insert(Colour::Black, Clock::new(black));
+        }
+    }
+
+    /// Makes a move in the engine, adds a captured piece to the deaths and presses the mover's clock if the engine accepted it.
+    /// Returns if the move was played.
+    fn play_move(&mut self, _from: String, _to: String, _promotion: Option<PromotionKind>) -> bool {
+        // The backend offered this move, if it breaks the rules the backend has a bug. Refuse it instead of corrupting the game.
+        let en_passant = en_passant(&self.fen());
+        if self.validate_moves {
+            if let Err(reason) = validator::validate_move(self.game.as_ref(), &_from, &_to, en_passant.as_deref()) {
+                self.banner = Some(format!("The {} backend allowed an illegal move: {}", self.game.name(), reason));
+                return false;
+            }
+        }
+
+        let mover = self.game.active_colour();
+        let piece = self.game.piece_at(filerank_to_num(&_from));
+
+        // The piece taken, en passant beside the target square, is added to the death vector for display
+        let captured = captured_by(self.game.as_ref(), filerank_to_num(&_from), filerank_to_num(&_to));
+
+        // A refused move should leave the engine as it was, if it did not the GUI no longer shows the engine's game
+        let made = self.game.make_move(&_from, &_to, _promotion);
+        if made.is_err() || self.game.active_colour() == mover {
+            self.notice = Some(made.err().unwrap_or_else(|| friendly::explain(self.game.as_ref(), &_from, &_to, en_passant.as_deref(), "")));
+
+            if let Err(difference) = self.check_sync() {
+                self.banner = Some(format!("The board is out of sync with the engine, {}", difference));
+                self.out_of_sync = Some(difference);
+            }
+            return false;
+        }
+
+        self.notice = None;
+
+        if let Some(_piece) = captured {
+            self.deaths.get_mut(&!mover).unwrap().push(_piece);
+        }
+
+        if let Some(clock) = self.clocks.get_mut(&mover) {
+            clock.press();
+        }
+
+        if let Some(piece) = piece {
+            self.history.push(MoveRecord {
+                from: _from.clone(),
+                to: _to.clone(),
+                promotion: _promotion,
+                piece,
+                captured,
+                elapsed: self.game_time,
+                clock: self.clocks.get(&mover).map(|_clock| _clock.remaining())
+            });
+        }
+
+        // Playing on declines a draw offered, and the marks were about the position before
+        self.draw_offer = None;
+        self.marks.clear();
+
+        // Playing the move taken back last keeps the rest to redo, any other move starts a new line
+        match self.redo.last() {
+            Some(next) if next.from == _from && next.to == _to && next.promotion == _promotion => { self.redo.pop(); },
+            _ => self.redo.clear()
+        }
+
+        self.variant_win = self.variant.winner(self.game.as_ref(), &self.history);
+
+        // Only the first discrepancy is shown, the positions are off from there on anyway
+        if let Some(checker) = self.checker.as_mut() {
+            if let Err(discrepancy) = checker.check_move(self.game.as_ref(), &_from, &_to, _promotion) {
+                if self.banner.is_none() {
+                    self.banner = Some(discrepancy);
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Ends the game with a result declared rather than reached on the board
+    fn declare(&mut self, _result: Adjudication) -> Result<(), String> {
+        if self.is_over() {
+            return Err("the game is already over".to_string());
+        }
+
+        self.adjudication = Some(_result);
+        self.draw_offer = None;
+        self.legal.clear();
+        self.previous_click = None;
+        self.drag = None;
+        self.thinking = None;
+
+        Ok(())
+    }
+
+    /// ## `offer_draw`
+    /// Offers a draw to the other side. A bot takes it when it is not ahead in material, a player answers with `Command::AnswerDraw`.
+    /// ### Parameters
+    /// - `_colour: Colour`: The side offering
+    /// ### Return
+    /// Err when the game is over or a bot declined
+    fn offer_draw(&mut self, _colour: Colour) -> Result<(), String> {
+        if self.is_over() {
+            return Err("the game is already over".to_string());
+        }
+
+        match self.bots.get(&!_colour) {
+            Some(bot) if material(self.game.as_ref(), !_colour) > material(self.game.as_ref(), _colour) =>
+                Err(format!("{} declines the draw", bot.name())),
+            Some(_) => self.declare(Adjudication::DrawAgreed),
+            None => {
+                self.draw_offer = Some(_colour);
+                Ok(())
+            }
+        }
+    }
+
+    /// The side the player at the board resigns and offers draws for: the only side not played by a bot, else the side to move.
+    /// None when bots play both sides.
+    pub fn player_side(&self) -> Option<Colour> {
+        match (self.bots.contains_key(&Colour::White), self.bots.contains_key(&Colour::Black)) {
+            (true, true) => None,
+            (true, false) => Some(Colour::Black),
+            (false, true) => Some(Colour::White),
+            (false, false) => Some(self.game.active_colour())
+        }
+    }
+
+    /// If the game is over, by the rules of chess, a clock running out, a win condition of the variant or the arbiter
+    pub fn is_over(&self) -> bool {
+        self.game.status().is_over() || self.flagged().is_some() || self.variant_win.is_some() || self.adjudication.is_some()
+    }
+
+    /// The side whose clock ran out, None while both have time left or the game is untimed
+    pub fn flagged(&self) -> Option<Colour> {
+        [Colour::White, Colour::Black].iter().copied().find(|_colour| self.clocks.get(_colour).map_or(false, |_clock| _clock.is_flagged()))
+    }
+
+    /// Rebuilds the position after the first `_ply` moves of the game on a new backend, leaving the game itself alone
+    pub fn position_at(&self, _ply: usize) -> Result<Box<dyn ChessBackend>, String> {
+        let mut position = self.game.new_game();
+        if let Some(fen) = &self.start_fen {
+            position.load_fen(fen)?;
+        }
+        history::replay(position.as_mut(), &self.history[.._ply.min(self.history.len())])?;
+
+        Ok(position)
+    }
+
+    /// ## `check_sync`
+    /// Compares the engine's board with the position the recorded moves lead to
+    /// ### Return
+    /// `Err` with the first square that differs, i.e. "e4 should hold a white pawn but is empty", or why the moves could not be replayed
+    pub fn check_sync(&self) -> Result<(), String> {
+        let expected = self.position_at(self.history.len())
+            .map_err(|_error| format!("the moves cannot be replayed: {}", _error))?;
+
+        let describe = |_piece: Option<Piece>| match _piece {
+            Some(piece) => format!("{} {}", if *get_piece_colour(&piece) == Colour::White { "a white" } else { "a black" }, piece_name(&piece)),
+            None => "empty".to_string()
+        };
+
+        for _row in 0..8 {
+            for _col in 0..8 {
+                let (should, is) = (expected.piece_at((_col, _row)), self.game.piece_at((_col, _row)));
+                if should != is {
+                    let should = if should.is_some() { format!("hold {}", describe(should)) } else { "be empty".to_string() };
+                    let is = if is.is_some() { format!("holds {}", describe(is)) } else { "is empty".to_string() };
+                    return Err(format!("{} should {} but {}", num_to_filerank(&(_col, _row)), should, is));
+                }
+            }
+        }
+
+        if expected.active_colour() != self.game.active_colour() {
+            return Err("it is the other side's turn on the engine's board".to_string());
+        }
+
+        Ok(())
+    }
+
+    /// Rebuilds everything the GUI keeps about the game from the engine's board, which becomes the new starting position.
+    /// The moves before it are dropped, and the captured pieces are counted from what is missing off the board.
+    fn resync(&mut self) {
+        let fen = to_fen(self.game.as_ref(), &self.history, self.start_fen.as_deref());
+        let (clocks, game_time, started) = (self.clocks.clone(), self.game_time, self.started);
+
+        self.restart_gui_state();
+        self.start_fen = Some(fen.clone());
+        // The game goes on, only its record starts over
+        self.clocks = clocks;
+        self.game_time = game_time;
+        self.started = started;
+        self.out_of_sync = None;
+
+        for _colour in [Colour::White, Colour::Black].iter() {
+            *self.deaths.get_mut(_colour).unwrap() = missing_pieces(self.game.as_ref(), *_colour);
+        }
+        self.variant_win = self.variant.winner(self.game.as_ref(), &self.history);
+
+        // A reference that cannot set the position up has nothing left to compare against
+        if let Some(checker) = self.checker.as_mut() {
+            if checker.rewind(Some(&fen), &[]).is_err() {
+                self.checker = None;
+            }
+        }
+    }
+
+    /// Returns a snapshot of the current state
+    pub fn snapshot(&self) -> ControllerState {
+        ControllerState {
+            game_state: self.game.status(),
+            active_colour: self.game.active_colour(),
+            selected: self.previous_click,
+            legal: self.legal.clone(),
+            promoting: self.promoting,
+            deaths: self.deaths.clone()
+        }
+    }
+
+    /// Feeds an input event to the controller and returns the resulting state
+    pub fn handle_input(&mut self, _event: InputEvent) -> ControllerState {
+        if let InputEvent::Motion { x, y } = _event {
+            self.hover = self.square_at(x, y);
+            self.pointer = Some((x, y));
+        }
+
+        // Zooming and panning only change how the board is seen, they never reach the game
+        if self.view.input(_event) {
+            return self.snapshot();
+        }
+
+        // Refused commands are told in the status bar
+        if let Some(command) = self.command_for(_event) {
+            if let Err(error) = self.try_execute(command) {
+                self.notice = Some(error);
+            }
+        }
+
+        self.snapshot()
+    }
+
+    /// Shorthand for a left click at window coordinates
+    pub fn click(&mut self, _x: f32, _y: f32) -> ControllerState {
+        self.handle_input(InputEvent::Click { x: _x, y: _y })
+    }
+
+    /// Shorthand for clicking in the middle of a board square given as "\<file\>\<rank\>"
+    pub fn click_square(&mut self, _filerank: &str) -> ControllerState {
+        let centre = self.view.to_window(square_centre(flip_square(filerank_to_num(&_filerank.to_string()), self.flipped)));
+        self.click(centre.0, centre.1)
+    }
+
+    /// Shorthand for dragging a piece from one square to another, both given as "\<file\>\<rank\>"
+    pub fn drag_square(&mut self, _from: &str, _to: &str) -> ControllerState {
+        let from = self.view.to_window(square_centre(flip_square(filerank_to_num(&_from.to_string()), self.flipped)));
+        let to = self.view.to_window(square_centre(flip_square(filerank_to_num(&_to.to_string()), self.flipped)));
+
+        self.handle_input(InputEvent::Press { x: from.0, y: from.1 });
+        self.handle_input(InputEvent::Motion { x: to.0, y: to.1 });
+        self.handle_input(InputEvent::Click { x: to.0, y: to.1 })
+    }
+
+    /// Shorthand for a key press
+    pub fn key_press(&mut self, _key: KeyCode) -> ControllerState {
+        self.handle_input(InputEvent::Key(_key))
+    }
+
+    /// The board square under window coordinates, seen the way the board is drawn. The panel covers a zoomed board.
+    fn square_at(&self, _x: f32, _y: f32) -> Option<(u8, u8)> {
+        if _y >= (GRID_SIZE * GRID_CELL_SIZE.1) as f32 {
+            return None;
+        }
+
+        let (x, y) = self.view.to_board((_x, _y));
+        board_square(x, y).map(|_square| flip_square(_square, self.flipped))
+    }
+
+    /// ## `regions`
+    /// Where the buttons of the game are clicked and what they do: the promotion choices, the proposals of vote chess,
+    /// the panel starting a new game once the game is over and the resync button on the banner. Laid out here for
+    /// clicks and drawn at the same places, so they stay where they are drawn.
+    /// ### Return
+    /// The regions, topmost last
+    pub fn regions(&self) -> Regions<Command> {
+        let mut regions = Regions::new();
+        let board_height = (GRID_SIZE * GRID_CELL_SIZE.1) as f32;
+
+        // Releasing a dragged piece drops it, wherever that is
+        if self.drag.is_none() {
+            if self.is_over() {
+                regions.register((0.0, board_height, SCREEN_SIZE.0, SCREEN_SIZE.1 - board_height), Command::Restart);
+            }
+            else if self.promoting {
+                regions.extend(promotion_dialog(self.game.active_colour()).regions(), Command::Promote);
+            }
+            // Vote chess: a proposal is played by clicking it, or withdrawn with the cross at its end
+            else if let Some(vote) = &self.vote {
+                for _index in 0..vote.proposals.len() {
+                    regions.register(Vote::row(_index), Command::Confirm(_index));
+                    regions.register(Vote::withdraw_button(_index), Command::Withdraw(_index));
+                }
+            }
+        }
+
+        // The button on the banner, once the board fell out of sync
+        if self.out_of_sync.is_some() {
+            regions.register(RESYNC_BUTTON, Command::Resync);
+        }
+
+        regions
+    }
+
+    /// Translates a raw input event into a command, if the input means anything in the current state
+    pub fn command_for(&self, _event: InputEvent) -> Option<Command> {
+        match _event {
+            // Buttons under the board and on the banner
+            InputEvent::Click { x, y } => if let Some(command) = self.regions().hit(x, y) {
+                return Some(command);
+            },
+            // Shortcuts for the promotion choices, shown beside them
+            InputEvent::Key(_) if self.promoting => if let Some(kind) = promotion_dialog(self.game.active_colour()).input(_event) {
+                return Some(Command::Promote(kind));
+            },
+            _ => {}
+        }
+
+        match _event {
+            // Releasing a dragged piece drops it, wherever that is
+            InputEvent::Click { x, y } if self.drag.is_some() => Some(Command::Drop(self.square_at(x, y))),
+            // Pressing on a piece of the side to move picks it up
+            InputEvent::Press { x, y } => {
+                let square = self.square_at(x, y)?;
+                let piece = self.game.piece_at(square)?;
+
+                if self.promoting || self.is_over() || *get_piece_colour(&piece) != self.game.active_colour() {
+                    return None;
+                }
+
+                Some(Command::BeginDrag(square))
+            },
+            InputEvent::Motion { x, y } if self.drag.is_some() => Some(Command::DragTo(self.view.to_board((x, y)))),
+            InputEvent::Motion { .. } | InputEvent::Scroll { .. } | InputEvent::Text(_) => None,
+            // Right clicks mark squares, in the colour of the modifier keys held
+            InputEvent::RightClick { x, y, mods } => Some(Command::Mark(self.square_at(x, y)?, MarkColour::from_mods(mods))),
+            InputEvent::MiddlePress { .. } | InputEvent::MiddleRelease { .. } => None,
+            // Clicks within the board grid, the panel below only has the regions. `square_at` knows where the grid ends.
+            InputEvent::Click { x, y } if !self.promoting => {
+                // The square clicked on by the player represented as a (u8, u8) coordinate
+                Some(Command::SelectSquare(self.square_at(x, y)?))
+            },
+            InputEvent::Click { .. } => None,
+            InputEvent::Key(KeyCode::Escape) if !self.promoting => Some(Command::ClearSelection),
+            // Enter plays the proposal with the most voters
+            InputEvent::Key(KeyCode::Return) if !self.promoting && !self.is_over() =>
+                self.vote.as_ref().and_then(|_vote| _vote.leading()).map(Command::Confirm),
+            InputEvent::Key(KeyCode::Left) if self.casual_takebacks && !self.is_over() => Some(Command::TakeBack),
+            InputEvent::Key(KeyCode::Right) if self.casual_takebacks && !self.is_over() => Some(Command::Redo),
+            InputEvent::Key(_) => None
+        }
+    }
+
+    /// Executes a command against the game. Commands that cannot be carried out are ignored, see `try_execute`.
+    pub fn execute(&mut self, _command: Command) {
+        self.try_execute(_command).ok();
+    }
+
+    /// Executes a command against the game, telling why if it could not be carried out
+    pub fn try_execute(&mut self, _command: Command) -> Result<(), String> {
+        match _command {
+            Command::SelectSquare(square) => self.select_square(square),
+            Command::Promote(kind) => self.promote(kind),
+            Command::BeginDrag(square) => {
+                self.select_square(square);
+                self.drag = Some(Drag { from: square, at: square_centre(flip_square(square, self.flipped)) });
+            },
+            Command::DragTo(at) => {
+                if let Some(drag) = self.drag.as_mut() {
+                    drag.at = at;
+                }
+            },
+            Command::Drop(square) => {
+                // Dropping back on the origin or on an illegal square keeps the piece selected, click to move still works
+                if let Some(square) = square.filter(|_square| self.legal.contains(_square)) {
+                    self.select_square(square);
+                }
+                self.drag = None;
+            },
+            Command::ClearSelection => {
+                self.legal.clear();
+                self.previous_click = None;
+                self.drag = None;
+            },
+            Command::Restart => self.restart(),
+            Command::PlayMove(from, to, promotion) => return self.play_command_move(from, to, promotion),
+            Command::Undo => return self.undo(),
+            Command::TakeBack => return self.take_back(),
+            Command::Redo => return self.redo_moves(),
+            Command::LoadFen(fen) => return self.load_fen(&fen),
+            Command::Adjudicate(winner) => return self.declare(winner.map_or(Adjudication::Draw, Adjudication::Win)),
+            Command::Resign(colour) => return self.declare(Adjudication::Resigned(colour)),
+            Command::OfferDraw(colour) => return self.offer_draw(colour),
+            Command::AnswerDraw(accepted) => {
+                self.draw_offer.take().ok_or("no draw was offered")?;
+                if accepted {
+                    return self.declare(Adjudication::DrawAgreed);
+                }
+            },
+            Command::AdjustClock(colour, seconds) => {
+                let clock = self.clocks.get_mut(&colour).ok_or("the game is untimed")?;
+                clock.set_remaining(clock.remaining() + seconds);
+            },
+            Command::Propose(from, to, promotion) => {
+                if self.vote.is_none() {
+                    return Err("vote chess is off".to_string());
+                }
+                if self.is_over() {
+                    return Err("the game is over".to_string());
+                }
+                if !self.game.legal_moves(&from).contains(&to) {
+                    return Err(friendly::explain(self.game.as_ref(), &from, &to, en_passant(&self.fen()).as_deref(), ""));
+                }
+
+                self.vote.as_mut().unwrap().propose(from, to, promotion);
+                self.legal.clear();
+                self.previous_click = None;
+            },
+            Command::Withdraw(index) => {
+                let vote = self.vote.as_mut().ok_or("vote chess is off")?;
+                if index >= vote.proposals.len() {
+                    return Err("there is no such proposal".to_string());
+                }
+                vote.proposals.remove(index);
+            },
+            Command::Confirm(index) => {
+                let proposal = self.vote.as_ref().and_then(|_vote| _vote.proposals.get(index)).cloned().ok_or("there is no such proposal")?;
+                if !self.play_move(proposal.from, proposal.to, proposal.promotion) {
+                    return Err(self.notice.clone().unwrap_or_else(|| "the move was refused".to_string()));
+                }
+
+                if let Some(vote) = self.vote.as_mut() {
+                    vote.clear();
+                }
+                self.legal.clear();
+                self.previous_click = None;
+            },
+            Command::Resync => self.resync(),
+            Command::Mark(square, colour) => {
+                if self.marks.get(&square) == Some(&colour) {
+                    self.marks.remove(&square);
+                }
+                else {
+                    self.marks.insert(square, colour);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Plays a move given by squares instead of clicks, checking it against the legal moves first
+    fn play_command_move(&mut self, _from: String, _to: String, _promotion: Option<PromotionKind>) -> Result<(), String> {
+        if self.promoting {
+            return Err("choose the promotion first".to_string());
+        }
+        if !self.game.legal_moves(&_from).contains(&_to) {
+            return Err(friendly::explain(self.game.as_ref(), &_from, &_to, en_passant(&self.fen()).as_deref(), ""));
+        }
+
+        // Pawns reaching the last rank become a queen unless told otherwise
+        let promotion = match self.game.piece_at(filerank_to_num(&_from)) {
+            Some(Piece::Pawn(_)) if _to.ends_with('1') || _to.ends_with('8') => Some(_promotion.unwrap_or(PromotionKind::Queen)),
+            _ => None
+        };
+
+        let played = self.play_move(_from, _to, promotion);
+        self.legal.clear();
+        self.previous_click = None;
+
+        if played { Ok(()) } else { Err(self.notice.clone().unwrap_or_else(|| "the move was refused".to_string())) }
+    }
+
+    /// Takes the last move back by replaying the game without it, giving back the captured piece
+    fn undo(&mut self) -> Result<(), String> {
+        let last = self.history.last().cloned().ok_or("there is no move to take back")?;
+
+        self.game = self.position_at(self.history.len() - 1)?;
+        self.redo.extend(self.history.pop());
+        self.variant_win = self.variant.winner(self.game.as_ref(), &self.history);
+        // Taking a move back reopens a game the arbiter or the players had ended
+        self.adjudication = None;
+        if let Some(vote) = self.vote.as_mut() {
+            vote.clear();
+        }
+
+        if let Some(captured) = last.captured {
+            self.deaths.get_mut(get_piece_colour(&captured)).unwrap().pop();
+        }
+
+        // The mover gets back the time they had before the move, so taking it back and playing it again earns no increment
+        let mover = *get_piece_colour(&last.piece);
+        if let Some(remaining) = self.clock_before(mover) {
+            if let Some(clock) = self.clocks.get_mut(&mover) {
+                clock.set_remaining(remaining);
+            }
+        }
+
+        self.legal.clear();
+        self.previous_click = None;
+        self.promoting = false;
+        self.pending_promotion_move = PendingMove::empty();
+        self.drag = None;
+
+        if let Some(checker) = self.checker.as_mut() {
+            checker.rewind(self.start_fen.as_deref(), &self.history)?;
+        }
+
+        Ok(())
+    }
+
+    /// ## `clock_before`
+    /// The time a side had before its next move: what was left on its clock after its last move in the history, or
+    /// the starting time of its time control before its first
+    /// ### Parameters
+    /// - `_colour: Colour`: The side
+    /// ### Return
+    /// Seconds, None for untimed games
+    fn clock_before(&self, _colour: Colour) -> Option<f32> {
+        match self.history.iter().rev().find(|_move| *get_piece_colour(&_move.piece) == _colour) {
+            Some(previous) => previous.clock,
+            None => self.time_controls().map(|(white, black)| if _colour == Colour::White { white.initial } else { black.initial })
+        }
+    }
+
+    /// Plays a move of a saved game, or of another window, at the time and with the clock it was played with
+    pub fn play_saved_move(&mut self, _move: &SavedMove) -> Result<(), String> {
+        let (from, to, promotion) = parse_uci(&_move.uci).ok_or(format!("{} is not a move", _move.uci))?;
+
+        self.game_time = _move.elapsed;
+        if !self.play_move(from, to, promotion) {
+            return Err(format!("{} could not be played", _move.uci));
+        }
+
+        if let Some(record) = self.history.last_mut() {
+            record.clock = _move.clock;
+        }
+
+        Ok(())
+    }
+
+    /// Carries on a saved game: sets up its starting position, replays its moves with their times and puts the clocks back
+    pub fn load_saved(&mut self, _save: &SavedGame) -> Result<(), String> {
+        self.time_control = _save.time_control.map(|(initial, increment)| TimeControl { initial, increment });
+        self.time_odds = _save.time_odds.map(|(initial, increment)| TimeControl { initial, increment });
+
+        match &_save.start_fen {
+            Some(fen) => self.load_fen(fen)?,
+            None => {
+                self.game.reset();
+                self.start_fen = None;
+                self.restart_gui_state();
+
+                if let Some(checker) = self.checker.as_mut() {
+                    checker.reset();
+                }
+            }
+        }
+
+        for _move in &_save.moves {
+            self.play_saved_move(_move)?;
+        }
+
+        self.game_time = _save.game_time;
+        if let Some((white, black)) = _save.clocks {
+            for (_colour, _remaining) in [(Colour::White, white), (Colour::Black, black)].iter() {
+                if let Some(clock) = self.clocks.get_mut(_colour) {
+                    clock.set_remaining(*_remaining);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Takes back a full move: plies are undone until a human side is to move again, the same side as before when it is human.
+    /// Against a bot that is the player's last move and the bot's reply, in hot-seat games one move of each side.
+    fn take_back(&mut self) -> Result<(), String> {
+        let mover = self.game.active_colour();
+        let target = if self.bots.contains_key(&mover) {
+            if mover == Colour::White { Colour::Black } else { Colour::White }
+        } else {
+            mover
+        };
+
+        self.undo()?;
+        while !self.history.is_empty() && self.game.active_colour() != target {
+            self.undo()?;
+        }
+
+        Ok(())
+    }
+
+    /// Plays the last move taken back again, then the moves taken back after it for as long as a bot is to move,
+    /// so redoing a takeback against a bot brings its reply back too
+    fn redo_moves(&mut self) -> Result<(), String> {
+        if self.is_over() {
+            return Err("the game is over".to_string());
+        }
+
+        self.redo_move()?;
+        while !self.redo.is_empty() && self.bots.contains_key(&self.game.active_colour()) && !self.is_over() {
+            self.redo_move()?;
+        }
+
+        Ok(())
+    }
+
+    /// Plays the last move taken back again
+    fn redo_move(&mut self) -> Result<(), String> {
+        let next = self.redo.last().cloned().ok_or("there is no move to redo")?;
+
+        if !self.play_move(next.from.clone(), next.to.clone(), next.promotion) {
+            return Err(self.notice.clone().unwrap_or_else(|| format!("{} could not be played again", next.notation())));
+        }
+
+        // The mover's clock shows what it did after the move the first time, instead of being pressed once more
+        if let Some(remaining) = next.clock {
+            if let Some(clock) = self.clocks.get_mut(get_piece_colour(&next.piece)) {
+                clock.set_remaining(remaining);
+            }
+            if let Some(record) = self.history.last_mut() {
+                record.clock = next.clock;
+            }
+        }
+
+        self.legal.clear();
+        self.previous_click = None;
+        self.drag = None;
+        self.thinking = None;
+
+        Ok(())
+    }
+
+    /// Starts a new game from a position given as FEN, if the backend can set it up
+    fn load_fen(&mut self, _fen: &str) -> Result<(), String> {
+        self.game.load_fen(_fen)?;
+
+        self.restart_gui_state();
+        self.start_fen = Some(_fen.to_string());
+
+        if let Some(checker) = self.checker.as_mut() {
+            checker.rewind(Some(_fen), &[])?;
+        }
+
+        Ok(())
+    }
+
+    /// The current position as FEN
+    pub fn fen(&self) -> String {
+        to_fen(self.game.as_ref(), &self.history, self.start_fen.as_deref())
+    }
+
+    /// Handles a click on a board square: either a move/attack with the selected piece or selecting a new piece
+    fn select_square(&mut self, square_clicked: (u8, u8)) {
+        // If the square is something new then do it's either check new legal moves, attack or just pure none-sense
+        if self.previous_click == Some(square_clicked) {
+            return;
+        }
+
+        // If the square is a legal move then it must be a move/attack
+        if self.legal.contains(&square_clicked) && !self.previous_click.is_none() { // Previous click can be none at times when reseting previous clicks
+
+            // The piece currently moving or attacking
+            let piece = self.game.piece_at(self.previous_click.unwrap());
+
+            // Get from and to coords as "<file><rank>" format
+            let from = num_to_filerank(&self.previous_click.unwrap());
+            let to = num_to_filerank(&square_clicked);
+
+            // If the piece is a pawn then check if it reached the edges for promotion
+            if let Piece::Pawn(_colour) = piece.unwrap() {
+                if (_colour == Colour::Black && square_clicked.1 == 7) // Black reached bottom of board
+                || (_colour == Colour::White && square_clicked.1 == 0) { // White reached top of board
+                    self.promoting = true; // It's promoting time
+
+                    // Save the moves in the pending move struct to deploy them after the player's choice of piece type to promote to
+                    // NOTE: the move occurs in `promote`. It is marked by the (👌)
+                    self.pending_promotion_move = PendingMove {
+                        _from: from,
+                        _to: to
+                    }
+                }
+                else { // If piece was no at the edge then just do a normal move and move on
+                    self.human_move(from, to, None);
+                }
+            }
+            else { // If it was no pawn that is the piece just make the move and clear the legal moves stored
+
+                // Dead pieces are added to the death vector for display
+                self.human_move(from, to, None);
+            }
+        }
+        else { // If move is not legal
+            // Clear the legal moves because a new piece might calculate it's legal pieces and store it here
+            self.legal.clear();
+
+            // Get the legal moves of the piece
+            let square_as_filerank = num_to_filerank(&square_clicked); // Numerical coord to "<File><Rank>" conversion
+            let moves = self.game.legal_moves(&square_as_filerank); // Get the legal moves
+
+            // The previous clicked is now the one the player just clicked
+            self.previous_click = Some(square_clicked);
+
+            // Newly calculated legal moves are stored in the legal move vec for board indication and legal move checking
+            for _move in moves {
+                self.legal.push(filerank_to_num(&_move));
+            }
+        }
+    }
+
+    /// Plays a move made on the board, or in vote chess puts it forward as a proposal of the voter whose turn it is
+    fn human_move(&mut self, _from: String, _to: String, _promotion: Option<PromotionKind>) {
+        if self.vote.is_some() && !self.bots.contains_key(&self.game.active_colour()) {
+            self.execute(Command::Propose(_from, _to, _promotion));
+        }
+        else {
+            self.play_move(_from, _to, _promotion);
+        }
+
+        self.legal.clear();
+    }
+
+    /// Completes the pending promotion move with the chosen piece type
+    fn promote(&mut self, _kind: PromotionKind) {
+        if !self.promoting {
+            return;
+        }
+
+        // (👌) Pending move occurs here
+        self.human_move(self.pending_promotion_move._from.to_string(), self.pending_promotion_move._to.to_string(), Some(_kind));
+
+        // Clear the struct of legal moves that hold the legal moves of the piece
+        self.legal.clear();
+
+        // Reset Pending Move struct
+        self.pending_promotion_move = PendingMove::empty();
+
+        // No longer promoting
+        self.promoting = false;
+    }
+
+    /// Resets the game and all game storages
+    fn restart(&mut self) {
+        // Variants with their own starting position are loaded as FEN
+        let error = match self.variant.start_fen() {
+            Some(fen) => match self.load_fen(&fen) {
+                Ok(()) => return,
+                Err(error) => Some(error)
+            },
+            None => None
+        };
+
+        self.game.reset(); // New board
+        self.start_fen = None;
+        self.restart_gui_state();
+
+        if let Some(checker) = self.checker.as_mut() {
+            checker.reset();
+        }
+
+        // Fall back to the usual starting position, i.e. on a backend that cannot load positions
+        if let Some(error) = error {
+            self.banner = Some(format!("{} could not be set up: {}", self.variant.name(), error));
+        }
+    }
+
+    /// Clears everything the GUI keeps about the game, for a new game
+    fn restart_gui_state(&mut self) {
+        // Reset game storages
+        self.legal.clear();
+        self.previous_click = None;
+        self.promoting = false;
+        self.pending_promotion_move = PendingMove::empty();
+        self.drag = None;
+        self.history.clear();
+        self.redo.clear();
+        self.marks.clear();
+        self.started = SystemTime::now();
+        self.game_time = 0.0;
+        self.deaths.get_mut(&Colour::Black).unwrap().clear();
+        self.deaths.get_mut(&Colour::White).unwrap().clear();
+        self.reset_clocks();
+        self.variant_win = None;
+        self.adjudication = None;
+        self.draw_offer = None;
+        self.scores.clear();
+        if let Some(vote) = self.vote.as_mut() {
+            vote.clear();
+        }
+        self.thinking = None;
+        self.out_of_sync = None;
+        self.notice = None;
+        self.banner = None;
+    }
+
+    /// Calls the move functions from the engine, clears the board and updates the dear bar
+    pub fn make_move_full(&mut self, _from: String, _to: String) {
+        self.play_move(_from, _to, None);
+        self.legal.clear();
+    }
+}
@@ -0,0 +1,79 @@
+use super::backend::ChessBackend;
+use super::fen;
+use super::history::MoveRecord;
+use super::pgn::{self, PgnGame};
+use super::saves::{SavedGame, SavedMove};
+
+/// PGN file browsed as the game database, in the GGEZ user data directory
+pub const DATABASE_FILE: &str = "/database.pgn";
+
+/// Results the database can be filtered by, in the order the filter cycles through them
+pub const RESULTS: [&str; 3] = ["1-0", "0-1", "1/2-1/2"];
+
+/// What the games of the database are narrowed down to. Empty fields let every game through.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DatabaseFilter {
+    pub query: String, // Found in the names of the players or the event, without regard to case
+    pub result: Option<&'static str>, // One of `RESULTS`
+    pub eco: String // Start of the ECO code, i.e. "B" for every semi-open game or "C6" for the Ruy Lopez
+}
+
+impl DatabaseFilter {
+    /// If a game gets through the filter
+    pub fn matches(&self, _game: &PgnGame) -> bool {
+        let query = self.query.trim().to_lowercase();
+        let found = query.is_empty() || ["White", "Black", "Event"].iter()
+            .any(|_tag| _game.tag(_tag).map_or(false, |_value| _value.to_lowercase().contains(&query)));
+
+        let result = self.result.map_or(true, |_result| _game.tag("Result") == Some(_result));
+
+        let eco = self.eco.trim().to_uppercase();
+        let opening = eco.is_empty() || _game.tag("ECO").map_or(false, |_code| _code.to_uppercase().starts_with(&eco));
+
+        found && result && opening
+    }
+
+    /// Indices of the games that get through the filter, in the order of the file
+    pub fn search(&self, _games: &[PgnGame]) -> Vec<usize> {
+        _games.iter().enumerate().filter(|(_, _game)| self.matches(_game)).map(|(_index, _)| _index).collect()
+    }
+}
+
+/// Name of a game in the list, i.e. "Carlsen – Nepomniachtchi"
+pub fn title(_game: &PgnGame) -> String {
+    format!("{} – {}", _game.tag("White").unwrap_or("?"), _game.tag("Black").unwrap_or("?"))
+}
+
+/// ## `to_saved`
+/// Replays a game of the database into a save, which the controller can load like a save slot
+/// ### Parameters
+/// - `_game: &PgnGame`: The game
+/// - `_board: Box<dyn ChessBackend>`: A new game of the backend the moves are played with
+/// ### Return
+/// `Err` naming the first move that could not be played
+pub fn to_saved(_game: &PgnGame, mut _board: Box<dyn ChessBackend>) -> Result<SavedGame, String> {
+    let start_fen = _game.tag("FEN").map(|_fen| _fen.to_string());
+    if let Some(start) = &start_fen {
+        _board.load_fen(start)?;
+    }
+
+    let mut history: Vec<MoveRecord> = vec![];
+    for (_index, _san) in _game.moves.iter().enumerate() {
+        let record = pgn::parse_san(_board.as_ref(), _san)
+            .and_then(|_move| _board.make_move(&_move.from, &_move.to, _move.promotion).map(|_| _move))
+            .map_err(|_error| format!("move {}: {}", _index / 2 + 1, _error))?;
+        history.push(record);
+    }
+
+    Ok(SavedGame {
+        name: title(_game),
+        saved: 0,
+        start_fen,
+        fen: fen::to_fen(_board.as_ref(), &history, start_fen.as_deref()),
+        moves: history.iter().map(|_move| SavedMove { uci: _move.uci(), elapsed: 0.0, clock: None }).collect(),
+        game_time: 0.0,
+        time_control: None,
+        time_odds: None,
+        clocks: None
+    })
+}
@@ -0,0 +1,107 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use super::backend::ChessBackend;
+use super::controller::GameController;
+use super::history::MoveRecord;
+use super::graphics_funcs::renderer::Rgba;
+use super::help_funcs::{filerank_to_num, flip_square, get_piece_colour};
+use super::theme::Theme;
+
+/// File the diagram is exported to, in the GGEZ user data directory
+pub const DIAGRAM_FILE: &str = "/diagram.svg";
+
+/// Width and height of a square in the SVG, in pixels
+const SQUARE: u32 = 45;
+
+/// What goes on the diagram besides the tiles and pieces
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DiagramOptions {
+    pub coordinates: bool, // Files and ranks along the edges
+    pub arrow: bool // An arrow for the last move
+}
+
+/// Colour as an SVG hex code, the alpha is left out
+fn hex(_colour: Rgba) -> String {
+    let channel = |_value: f32| (_value.max(0.0).min(1.0) * 255.0).round() as u8;
+    format!("#{:02x}{:02x}{:02x}", channel(_colour[0]), channel(_colour[1]), channel(_colour[2]))
+}
+
+/// Chess symbol of a piece, drawn as text so the SVG needs no images
+fn glyph(_piece: &Piece) -> char {
+    let white = *get_piece_colour(_piece) == Colour::White;
+    match _piece {
+        Piece::King(_) => if white { '♔' } else { '♚' },
+        Piece::Queen(_) => if white { '♕' } else { '♛' },
+        Piece::Rook(_) => if white { '♖' } else { '♜' },
+        Piece::Bishop(_) => if white { '♗' } else { '♝' },
+        Piece::Knight(_) => if white { '♘' } else { '♞' },
+        Piece::Pawn(_) => if white { '♙' } else { '♟' }
+    }
+}
+
+/// Centre of a square in the SVG, the square given as "\<file\>\<rank\>"
+fn centre(_square: &str, _flipped: bool) -> (u32, u32) {
+    let (col, row) = flip_square(filerank_to_num(&_square.to_string()), _flipped);
+    (col as u32 * SQUARE + SQUARE / 2, row as u32 * SQUARE + SQUARE / 2)
+}
+
+/// ## `to_svg`
+/// Draws the current position as an SVG diagram, seen from the side the board is turned to, for documents and web pages
+/// ### Parameters
+/// - `_controller: &GameController`: The game, at the position to draw
+/// - `_theme: &Theme`: Colours of the tiles
+/// - `_options: DiagramOptions`: Coordinates and arrow
+pub fn to_svg(_controller: &GameController, _theme: &Theme, _options: DiagramOptions) -> String {
+    board_svg(_controller.game.as_ref(), _controller.history.last(), _theme, _options, _controller.flipped)
+}
+
+/// ## `board_svg`
+/// Draws any position as an SVG diagram
+/// ### Parameters
+/// - `_game: &dyn ChessBackend`: The position to draw
+/// - `_last: Option<&MoveRecord>`: The move that led to it, for the arrow
+/// - `_theme: &Theme`: Colours of the tiles
+/// - `_options: DiagramOptions`: Coordinates and arrow
+/// - `_flipped: bool`: If Black is at the bottom
+pub fn board_svg(_game: &dyn ChessBackend, _last: Option<&MoveRecord>, _theme: &Theme, _options: DiagramOptions, _flipped: bool) -> String {
+    let size = SQUARE * 8;
+    let flipped = _flipped;
+    let mut svg = format!("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n", size);
+
+    for _row in 0..8u8 {
+        for _col in 0..8u8 {
+            let (x, y) = (_col as u32 * SQUARE, _row as u32 * SQUARE);
+            let colour = if (_row + _col) % 2 == 0 { _theme.light_tile } else { _theme.dark_tile };
+            svg.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"{3}\"/>\n", x, y, SQUARE, hex(colour)));
+
+            // Written on the squares of the edges, in the colour of the other tiles
+            if _options.coordinates {
+                let other = hex(if (_row + _col) % 2 == 0 { _theme.dark_tile } else { _theme.light_tile });
+                let (file, rank) = flip_square((_col, _row), flipped);
+                if _row == 7 {
+                    svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"10\" font-family=\"sans-serif\" fill=\"{}\">{}</text>\n",
+                        x + SQUARE - 9, y + SQUARE - 3, other, (b'a' + file) as char));
+                }
+                if _col == 0 {
+                    svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"10\" font-family=\"sans-serif\" fill=\"{}\">{}</text>\n",
+                        x + 2, y + 11, other, 8 - rank));
+                }
+            }
+
+            if let Some(piece) = _game.piece_at(flip_square((_col, _row), flipped)) {
+                svg.push_str(&format!("<text x=\"{}\" y=\"{}\" font-size=\"38\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                    x + SQUARE / 2, y + SQUARE / 2, glyph(&piece)));
+            }
+        }
+    }
+
+    if let (true, Some(last)) = (_options.arrow, _last) {
+        let (from, to) = (centre(&last.from, flipped), centre(&last.to, flipped));
+        svg.push_str("<defs><marker id=\"head\" markerWidth=\"4\" markerHeight=\"4\" refX=\"2\" refY=\"2\" orient=\"auto\">");
+        svg.push_str("<path d=\"M0,0 L4,2 L0,4 z\" fill=\"#15781b\"/></marker></defs>\n");
+        svg.push_str(&format!("<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"#15781b\" stroke-width=\"8\" stroke-opacity=\"0.8\" marker-end=\"url(#head)\"/>\n",
+            from.0, from.1, to.0, to.1));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
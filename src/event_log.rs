@@ -0,0 +1,105 @@
+use eliasfl_chess::Color as Colour;
+use std::{fs::File, io::{self, Write}};
+use super::backend::GameStatus;
+use super::controller::GameController;
+use super::export;
+use super::help_funcs::get_piece_colour;
+use super::pgn;
+
+/// Name of a side as written in the log
+fn side_name(_colour: Colour) -> &'static str {
+    if _colour == Colour::White { "White" } else { "Black" }
+}
+
+/// A plain text log of what happens in the game, one line per event ("White plays Nf3", "Black is in check"),
+/// written as it happens so screen readers and other accessibility tools can follow the game
+pub struct EventLog {
+    output: Box<dyn Write>,
+    logged: Vec<String>, // Coordinate notation of the moves written so far, to tell new moves from takebacks
+    result_logged: bool // The end of the game was written, until a move is taken back or a new game starts
+}
+
+impl EventLog {
+    /// A log written to the standard output
+    pub fn stdout() -> EventLog {
+        EventLog { output: Box::new(io::stdout()), logged: vec![], result_logged: false }
+    }
+
+    /// A log written to a file, emptied first
+    pub fn file(_path: &str) -> io::Result<EventLog> {
+        Ok(EventLog { output: Box::new(File::create(_path)?), logged: vec![], result_logged: false })
+    }
+
+    /// Writes a line and flushes it, so readers see it at once. A log that cannot be written is not worth stopping the game for.
+    fn line(&mut self, _text: &str) {
+        let _ = writeln!(self.output, "{}", _text);
+        let _ = self.output.flush();
+    }
+
+    /// ## `update`
+    /// Writes what changed in the game since the last update: moves taken back, moves played, check and the result
+    /// ### Parameters
+    /// - `_controller: &GameController`: The game being played
+    pub fn update(&mut self, _controller: &GameController) {
+        let history = &_controller.history;
+        let unchanged = history.len() == self.logged.len()
+            && history.last().map(|_move| _move.notation()) == self.logged.last().cloned();
+        if unchanged {
+            self.log_result(_controller);
+            return;
+        }
+
+        // Moves the log has that the game no longer has were taken back, or belong to a game that was replaced
+        let kept = history.iter().zip(&self.logged).take_while(|(_move, _logged)| _move.notation() == **_logged).count();
+        if kept < self.logged.len() {
+            self.logged.truncate(kept);
+            self.result_logged = false;
+
+            let side = side_name(_controller.game.active_colour());
+            if kept == 0 {
+                self.line(&format!("New game, {} to move", side));
+            }
+            else {
+                self.line(&format!("Taken back to move {}, {} to move", kept / 2 + 1, side));
+            }
+        }
+
+        if history.len() > kept {
+            // Standard algebraic notation when the moves can be replayed, coordinates otherwise
+            let san = pgn::san_moves(_controller).ok();
+            for _index in kept..history.len() {
+                let notation = history[_index].notation();
+                let spoken = san.as_ref().and_then(|_san| _san.get(_index).cloned()).unwrap_or_else(|| notation.clone());
+
+                self.line(&format!("{} plays {}", side_name(*get_piece_colour(&history[_index].piece)), spoken));
+                self.logged.push(notation);
+            }
+
+            if _controller.game.status() == GameStatus::Check {
+                self.line(&format!("{} is in check", side_name(_controller.game.active_colour())));
+            }
+        }
+
+        self.log_result(_controller);
+    }
+
+    /// Writes the result once the game is over
+    fn log_result(&mut self, _controller: &GameController) {
+        let (result, reason) = export::result(_controller);
+        if result == "*" {
+            self.result_logged = false;
+            return;
+        }
+        if self.result_logged {
+            return;
+        }
+
+        let winner = match result {
+            "1-0" => "White wins",
+            "0-1" => "Black wins",
+            _ => "Draw"
+        };
+        self.line(&format!("Game over: {}{}, {}", winner, reason.map_or(String::new(), |_reason| format!(" by {}", _reason)), result));
+        self.result_logged = true;
+    }
+}
@@ -0,0 +1,146 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use serde_json::{json, Value};
+use std::time::UNIX_EPOCH;
+use super::backend::{GameStatus, DrawReason, PromotionKind};
+use super::controller::{GameController, Adjudication};
+use super::help_funcs::has_mating_material;
+
+/// Version of the exported format, raised whenever a field changes meaning or goes away
+pub const EXPORT_VERSION: u32 = 1;
+
+/// Lower case name of a piece type, i.e. "knight"
+pub fn piece_name(_piece: &Piece) -> &'static str {
+    match _piece {
+        Piece::Pawn(_) => "pawn",
+        Piece::Knight(_) => "knight",
+        Piece::Bishop(_) => "bishop",
+        Piece::Rook(_) => "rook",
+        Piece::Queen(_) => "queen",
+        Piece::King(_) => "king"
+    }
+}
+
+/// Lower case name of a colour
+pub fn colour_name(_colour: Colour) -> &'static str {
+    if _colour == Colour::White { "white" } else { "black" }
+}
+
+/// Lower case name of a promotion choice
+fn promotion_name(_kind: PromotionKind) -> &'static str {
+    _kind.as_engine_str()
+}
+
+/// ## `drawn`
+/// The result of a drawn game, which goes to a side instead when the variant gives it draw odds (see `Variant::resolve_draw`)
+/// ### Parameters
+/// - `_controller: &GameController`: The game
+/// - `_termination: &'static str`: How it was drawn, i.e. "stalemate"
+fn drawn(_controller: &GameController, _termination: &'static str) -> (&'static str, Option<&'static str>) {
+    match _controller.variant.resolve_draw() {
+        Some(winner) => (if winner == Colour::White { "1-0" } else { "0-1" }, Some("draw odds")),
+        None => ("1/2-1/2", Some(_termination))
+    }
+}
+
+/// ## `result`
+/// The result of the game as in PGN ("1-0", "0-1", "1/2-1/2" or "*" while playing) and how it ended
+/// ### Parameters
+/// - `_controller: &GameController`: The game
+/// ### Return
+/// The result and the termination, None while the game goes on
+pub fn result(_controller: &GameController) -> (&'static str, Option<&'static str>) {
+    // A side that ran out of time loses, unless the other side could never checkmate
+    if let Some(flagged) = _controller.flagged() {
+        if !has_mating_material(_controller.game.as_ref(), !flagged) {
+            return drawn(_controller, "timeout vs insufficient material");
+        }
+        return (if flagged == Colour::White { "0-1" } else { "1-0" }, Some("time"));
+    }
+
+    // The arbiter and the players have the last word
+    match _controller.adjudication {
+        Some(Adjudication::Win(winner)) => return (if winner == Colour::White { "1-0" } else { "0-1" }, Some("adjudication")),
+        Some(Adjudication::Draw) => return drawn(_controller, "adjudication"),
+        Some(Adjudication::Resigned(loser)) => return (if loser == Colour::White { "0-1" } else { "1-0" }, Some("resignation")),
+        Some(Adjudication::DrawAgreed) => return drawn(_controller, "agreement"),
+        None => {}
+    }
+
+    // So does a side that lost to a win condition of the variant
+    if let Some((winner, _)) = &_controller.variant_win {
+        return (if *winner == Colour::White { "1-0" } else { "0-1" }, Some("variant"));
+    }
+
+    match _controller.game.status() {
+        GameStatus::CheckMate =>
+            (if _controller.game.active_colour() == Colour::White { "0-1" } else { "1-0" }, Some("checkmate")),
+        GameStatus::Draw(reason) => drawn(_controller, match reason {
+            DrawReason::Stalemate => "stalemate",
+            DrawReason::InsufficientMaterial => "insufficient material",
+            DrawReason::ThreefoldRepetition => "threefold repetition",
+            DrawReason::FiftyMoveRule => "fifty-move rule"
+        }),
+        _ => ("*", None)
+    }
+}
+
+/// ## `game_to_json`
+/// Describes the game for external tools: metadata, every move with its timestamp, capture and clock, the clocks and the result
+/// ### Parameters
+/// - `_controller: &GameController`: The game to export
+pub fn game_to_json(_controller: &GameController) -> Value {
+    let (result, termination) = result(_controller);
+
+    let moves: Vec<Value> = _controller.history.iter().enumerate().map(|(_ply, _move)| json!({
+        "ply": _ply + 1,
+        "colour": colour_name(if _ply % 2 == 0 { Colour::White } else { Colour::Black }),
+        "from": _move.from,
+        "to": _move.to,
+        "notation": _move.notation(),
+        "piece": piece_name(&_move.piece),
+        "captured": _move.captured.as_ref().map(piece_name),
+        "promotion": _move.promotion.map(promotion_name),
+        "elapsed": _move.elapsed,
+        "clock": _move.clock
+    })).collect();
+
+    let captures = |_colour: Colour| -> Vec<&'static str> {
+        _controller.deaths.get(&_colour).map_or(vec![], |_pieces| _pieces.iter().map(piece_name).collect())
+    };
+
+    let clocks = if _controller.clocks.is_empty() { Value::Null } else {
+        json!({
+            "white": _controller.clocks.get(&Colour::White).map(|_clock| _clock.remaining()),
+            "black": _controller.clocks.get(&Colour::Black).map(|_clock| _clock.remaining())
+        })
+    };
+
+    json!({
+        "version": EXPORT_VERSION,
+        "backend": _controller.game.name(),
+        "started": _controller.started.duration_since(UNIX_EPOCH).map(|_since| _since.as_secs()).unwrap_or(0),
+        "duration": _controller.game_time,
+        "time_control": _controller.time_control.map(|_time_control| json!({
+            "initial": _time_control.initial,
+            "increment": _time_control.increment
+        })),
+        "time_odds": _controller.time_odds.filter(|_| _controller.time_control.is_some()).map(|_time_control| json!({
+            "initial": _time_control.initial,
+            "increment": _time_control.increment
+        })),
+        "result": result,
+        "termination": termination,
+        "to_move": colour_name(_controller.game.active_colour()),
+        "clocks": clocks,
+        "captured": {
+            "white": captures(Colour::White),
+            "black": captures(Colour::Black)
+        },
+        "moves": moves
+    })
+}
+
+/// The game as pretty printed JSON, see `game_to_json`
+pub fn game_to_json_string(_controller: &GameController) -> String {
+    serde_json::to_string_pretty(&game_to_json(_controller)).unwrap_or_default()
+}
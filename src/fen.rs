@@ -0,0 +1,352 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use super::backend::ChessBackend;
+use super::history::MoveRecord;
+use super::help_funcs::{get_piece_colour, num_to_filerank};
+
+/// FEN letter of a piece, upper case for white
+pub fn piece_char(_piece: &Piece) -> char {
+    let letter = match _piece {
+        Piece::Pawn(_) => 'p',
+        Piece::Knight(_) => 'n',
+        Piece::Bishop(_) => 'b',
+        Piece::Rook(_) => 'r',
+        Piece::Queen(_) => 'q',
+        Piece::King(_) => 'k'
+    };
+
+    if *get_piece_colour(_piece) == Colour::White { letter.to_ascii_uppercase() } else { letter }
+}
+
+/// ## `castling_rights`
+/// The castling rights as X-FEN. The rights of the starting position are only ever taken away: a king and a rook that
+/// have moved (or been captured) during the game can no longer castle. Written "KQkq" for the outermost rook on each
+/// side of the king, as in standard chess and Chess960 starting positions, and as the file of the rook otherwise (i.e. "Bkq").
+/// ### Parameters
+/// - `_game: &dyn ChessBackend`: The game at the position
+/// - `_history: &[MoveRecord]`: Every move played from the starting position to this one
+/// - `_start: Option<&str>`: The FEN the game started from, None for a game from the pieces' own starting squares
+fn castling_rights(_game: &dyn ChessBackend, _history: &[MoveRecord], _start: Option<&str>) -> String {
+    let untouched = |_square: (u8, u8)| {
+        let square = num_to_filerank(&_square);
+        !_history.iter().any(|_move| _move.from == square || _move.to == square)
+    };
+    let seeded = match _start {
+        Some(fen) => start_rights(fen),
+        // Every king and rook on its back rank could castle at the start
+        None => back_rank_rights(|_square| _game.piece_at(_square))
+    };
+
+    let mut castling = String::new();
+
+    for (_colour, _row) in [(Colour::White, 7u8), (Colour::Black, 0u8)].iter() {
+        let king = match (0..8).find(|_col| _game.piece_at((*_col, *_row)) == Some(Piece::King(*_colour))) {
+            Some(king) if untouched((king, *_row)) => king,
+            _ => continue
+        };
+        let rooks: Vec<u8> = (0..8).filter(|_col| _game.piece_at((*_col, *_row)) == Some(Piece::Rook(*_colour))).collect();
+        let kept = |_rook: u8| untouched((_rook, *_row))
+            && seeded.iter().any(|(_right, _king, _col)| _right == _colour && *_king == king && *_col == _rook);
+
+        // The king's side first, its rooks from the edge in
+        let sides: [(Vec<u8>, char); 2] = [
+            (rooks.iter().copied().filter(|_col| *_col > king).rev().collect(), 'k'),
+            (rooks.iter().copied().filter(|_col| *_col < king).collect(), 'q')
+        ];
+        for (_rooks, _letter) in sides.iter() {
+            if let Some(rook) = _rooks.iter().copied().find(|_col| kept(*_col)) {
+                let letter = if Some(&rook) == _rooks.first() { *_letter } else { (b'a' + rook) as char };
+                castling.push(if *_colour == Colour::White { letter.to_ascii_uppercase() } else { letter });
+            }
+        }
+    }
+
+    if castling.is_empty() { "-".to_string() } else { castling }
+}
+
+/// Every pair of a king and a rook of the same side on their back rank, as the side and the files of the king and the rook
+fn back_rank_rights(_piece_at: impl Fn((u8, u8)) -> Option<Piece>) -> Vec<(Colour, u8, u8)> {
+    let mut rights = vec![];
+
+    for (_colour, _row) in [(Colour::White, 7u8), (Colour::Black, 0u8)].iter() {
+        if let Some(king) = (0..8).find(|_col| _piece_at((*_col, *_row)) == Some(Piece::King(*_colour))) {
+            for _rook in (0..8).filter(|_col| _piece_at((*_col, *_row)) == Some(Piece::Rook(*_colour))) {
+                rights.push((*_colour, king, _rook));
+            }
+        }
+    }
+
+    rights
+}
+
+/// ## `start_rights`
+/// The castling rights a FEN gives, as the side and the files of the king and the rook. Reads "KQkq" as the outermost
+/// rook on that side of the king and Shredder-FEN letters (i.e. "HAha") as the file of the rook.
+/// ### Parameters
+/// - `_fen: &str`: The position, a missing castling field gives no rights
+fn start_rights(_fen: &str) -> Vec<(Colour, u8, u8)> {
+    let pieces = placement(_fen).unwrap_or_default();
+    let possible = back_rank_rights(|_square| pieces.iter().find(|(_at, _)| *_at == _square).map(|(_, _piece)| *_piece));
+
+    _fen.split_whitespace().nth(2).unwrap_or("-").chars().filter_map(|_letter| {
+        let colour = if _letter.is_ascii_uppercase() { Colour::White } else { Colour::Black };
+        let mut candidates = possible.iter().copied().filter(|(_colour, _, _)| *_colour == colour);
+
+        match _letter.to_ascii_lowercase() {
+            'k' => candidates.filter(|(_, _king, _rook)| _rook > _king).max_by_key(|(_, _, _rook)| *_rook),
+            'q' => candidates.filter(|(_, _king, _rook)| _rook < _king).min_by_key(|(_, _, _rook)| *_rook),
+            file @ 'a'..='h' => candidates.find(|(_, _, _rook)| *_rook == file as u8 - b'a'),
+            _ => None
+        }
+    }).collect()
+}
+
+/// The placement field of a FEN, rank 8 first, from what stands on each square in GUI coordinates
+fn write_placement(_piece_at: impl Fn((u8, u8)) -> Option<Piece>) -> String {
+    let mut placement = String::new();
+
+    for _row in 0..8 {
+        let mut empty = 0;
+
+        for _col in 0..8 {
+            match _piece_at((_col, _row)) {
+                Some(piece) => {
+                    if empty > 0 {
+                        placement.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    placement.push(piece_char(&piece));
+                },
+                None => empty += 1
+            }
+        }
+
+        if empty > 0 {
+            placement.push_str(&empty.to_string());
+        }
+        if _row < 7 {
+            placement.push('/');
+        }
+    }
+
+    placement
+}
+
+/// ## `to_fen`
+/// Writes a position as FEN. Backends do not tell castling rights, the en passant square or the move counters,
+/// so they are worked out from the starting position and the moves played since.
+/// ### Parameters
+/// - `_game: &dyn ChessBackend`: The game at the position
+/// - `_history: &[MoveRecord]`: Every move played from the starting position to this one
+/// - `_start: Option<&str>`: The FEN the game started from, None for the usual starting position
+pub fn to_fen(_game: &dyn ChessBackend, _history: &[MoveRecord], _start: Option<&str>) -> String {
+    let placement = write_placement(|_square| _game.piece_at(_square));
+
+    let active = if _game.active_colour() == Colour::White { "w" } else { "b" };
+
+    let castling = castling_rights(_game, _history, _start);
+
+    // The square passed over by a pawn that just moved two squares, or the starting position's before any move
+    let en_passant = match _history.last() {
+        Some(last) if matches!(last.piece, Piece::Pawn(_)) && (last.from.as_bytes()[1] as i8 - last.to.as_bytes()[1] as i8).abs() == 2 =>
+            format!("{}{}", &last.from[0..1], ((last.from.as_bytes()[1] + last.to.as_bytes()[1]) / 2) as char),
+        Some(_) => "-".to_string(),
+        None => _start.and_then(en_passant).unwrap_or_else(|| "-".to_string())
+    };
+    let start_counters = _start.map_or((0, 1), counters);
+
+    // Moves since the last capture or pawn move, counted on from the starting position's if there was none since
+    let quiet = _history.iter().rev()
+        .take_while(|_move| _move.captured.is_none() && !matches!(_move.piece, Piece::Pawn(_)))
+        .count();
+    let halfmoves = if quiet == _history.len() { start_counters.0 + quiet } else { quiet };
+
+    // The fullmove number goes up after every move of Black, the first of which is the first move when Black started
+    let black_started = (_game.active_colour() == Colour::Black) != (_history.len() % 2 == 1);
+    let fullmoves = start_counters.1 + (_history.len() + black_started as usize) / 2;
+
+    format!("{} {} {} {} {} {}", placement, active, castling, en_passant, halfmoves, fullmoves)
+}
+
+/// Piece of a FEN letter, upper case for white
+pub fn char_piece(_letter: char) -> Option<Piece> {
+    let colour = if _letter.is_ascii_uppercase() { Colour::White } else { Colour::Black };
+
+    match _letter.to_ascii_lowercase() {
+        'p' => Some(Piece::Pawn(colour)),
+        'n' => Some(Piece::Knight(colour)),
+        'b' => Some(Piece::Bishop(colour)),
+        'r' => Some(Piece::Rook(colour)),
+        'q' => Some(Piece::Queen(colour)),
+        'k' => Some(Piece::King(colour)),
+        _ => None
+    }
+}
+
+/// ## `placement`
+/// Reads the pieces of a FEN, without a backend, i.e. to draw a position that is not being played
+/// ### Parameters
+/// - `_fen: &str`: The position, only the placement field is read
+/// ### Return
+/// Every piece with its square in GUI coordinates, `Err` for a rank longer than 8 squares
+pub fn placement(_fen: &str) -> Result<Vec<((u8, u8), Piece)>, String> {
+    let mut pieces = vec![];
+
+    for (_row, _rank) in _fen.split_whitespace().next().unwrap_or("").split('/').take(8).enumerate() {
+        let mut col = 0u8;
+
+        for _letter in _rank.chars() {
+            if let Some(empty) = _letter.to_digit(10) {
+                col += empty as u8;
+            }
+            else {
+                if let Some(piece) = char_piece(_letter) {
+                    pieces.push(((col, _row as u8), piece));
+                }
+                col += 1;
+            }
+
+            if col > 8 {
+                return Err(format!("rank {} has more than 8 squares", 8 - _row));
+            }
+        }
+    }
+
+    Ok(pieces)
+}
+
+/// ## `from_placement`
+/// Writes a position set up piece by piece as FEN, i.e. from the practice editor. Castling is allowed wherever
+/// a king and a rook still stand on their starting squares.
+/// ### Parameters
+/// - `_pieces: &[((u8, u8), Piece)]`: The pieces and their squares in GUI coordinates
+/// - `_active: Colour`: The side to move
+pub fn from_placement(_pieces: &[((u8, u8), Piece)], _active: Colour) -> String {
+    let piece_at = |_square: (u8, u8)| _pieces.iter().find(|(_at, _)| *_at == _square).map(|(_, _piece)| *_piece);
+    let placement = write_placement(piece_at);
+
+    let mut castling = String::new();
+    for (_letter, _colour, _rook_col) in [('K', Colour::White, 7), ('Q', Colour::White, 0), ('k', Colour::Black, 7), ('q', Colour::Black, 0)].iter() {
+        let row = if *_colour == Colour::White { 7 } else { 0 };
+        if piece_at((4, row)) == Some(Piece::King(*_colour)) && piece_at((*_rook_col, row)) == Some(Piece::Rook(*_colour)) {
+            castling.push(*_letter);
+        }
+    }
+    if castling.is_empty() {
+        castling.push('-');
+    }
+
+    format!("{} {} {} - 0 1", placement, if _active == Colour::White { "w" } else { "b" }, castling)
+}
+
+/// The halfmove clock and fullmove number of a position, (0, 1) when its FEN leaves them out
+pub fn counters(_fen: &str) -> (usize, usize) {
+    let mut fields = _fen.split_whitespace().skip(4).map(|_field| _field.parse::<usize>().ok());
+    let halfmoves = fields.next().flatten().unwrap_or(0);
+    let fullmoves = fields.next().flatten().filter(|_number| *_number > 0).unwrap_or(1);
+
+    (halfmoves, fullmoves)
+}
+
+/// The en passant square of a position, i.e. "e3" after 1. e4, None when its FEN has "-" or no such field
+pub fn en_passant(_fen: &str) -> Option<String> {
+    _fen.split_whitespace().nth(3).filter(|_square| *_square != "-").map(str::to_string)
+}
+
+/// ## `position_hash`
+/// A short fingerprint of a position for two windows to check they agree on it, the same on every machine and build.
+/// Only the placement, side to move, castling rights and en passant square count, not the move counters.
+/// ### Parameters
+/// - `_fen: &str`: The position
+/// ### Return
+/// The 64-bit FNV-1a hash of the position, as 16 hex digits
+pub fn position_hash(_fen: &str) -> String {
+    let position = _fen.split_whitespace().take(4).collect::<Vec<&str>>().join(" ");
+
+    let hash = position.bytes().fold(0xcbf29ce484222325u64, |_hash, _byte| (_hash ^ _byte as u64).wrapping_mul(0x100000001b3));
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::backend::fen_board::FenBoard;
+
+    /// A move as the controller records it, untimed
+    fn record(_from: &str, _to: &str, _piece: Piece, _captured: Option<Piece>) -> MoveRecord {
+        MoveRecord { from: _from.to_string(), to: _to.to_string(), promotion: None, piece: _piece, captured: _captured, elapsed: 0.0, clock: None }
+    }
+
+    /// The FEN of a game loaded from `_start` after `_history`, the pieces standing as in `_after`
+    fn written(_start: &str, _history: &[MoveRecord], _after: &str) -> String {
+        to_fen(&FenBoard::new(_after), _history, Some(_start))
+    }
+
+    // A loaded position is written back as it was read, before any move
+    #[test]
+    fn loaded_positions_round_trip() {
+        let positions = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1",
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3",
+            "4k3/8/8/8/8/8/8/4K2R b K - 7 42"
+        ];
+        for _fen in positions.iter() {
+            assert_eq!(written(_fen, &[], _fen), *_fen);
+        }
+
+        // Shredder-FEN rights are written as X-FEN
+        let shredder = "rkrbbqnn/pppppppp/8/8/8/8/PPPPPPPP/RKRBBQNN w CAca - 0 1";
+        assert_eq!(written(shredder, &[], shredder), "rkrbbqnn/pppppppp/8/8/8/8/PPPPPPPP/RKRBBQNN w KQkq - 0 1");
+    }
+
+    // Rights the loaded position did not give are never granted, the ones it gave are lost as kings and rooks move
+    #[test]
+    fn castling_rights_come_from_the_loaded_position() {
+        let none = "r3k2r/8/8/8/8/8/8/R3K2R w - - 0 1";
+        let rook_moved = [record("a1", "a2", Piece::Rook(Colour::White), None)];
+        assert_eq!(written(none, &rook_moved, "r3k2r/8/8/8/8/8/R7/4K2R b - - 1 1"), "r3k2r/8/8/8/8/8/R7/4K2R b - - 1 1");
+
+        let some = "r3k2r/8/8/8/8/8/8/R3K2R w Kq - 0 1";
+        let rook_moved = [record("h1", "h2", Piece::Rook(Colour::White), None)];
+        assert_eq!(written(some, &rook_moved, "r3k2r/8/8/8/8/8/7R/R3K3 b q - 1 1"), "r3k2r/8/8/8/8/8/7R/R3K3 b q - 1 1");
+
+        let king_moved = [record("e1", "d1", Piece::King(Colour::White), None)];
+        assert_eq!(written(some, &king_moved, "r3k2r/8/8/8/8/8/8/R2K3R b q - 1 1"), "r3k2r/8/8/8/8/8/8/R2K3R b q - 1 1");
+    }
+
+    // The en passant square only stands right after a pawn moved two squares
+    #[test]
+    fn en_passant_square_follows_the_last_move() {
+        let start = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+        let pushed = [record("e2", "e4", Piece::Pawn(Colour::White), None)];
+        assert_eq!(written(start, &pushed, "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq - 0 1"),
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1");
+
+        let loaded = "rnbqkbnr/ppp1pppp/8/3pP3/8/8/PPPP1PPP/RNBQKBNR w KQkq d6 0 3";
+        let knight = [record("g1", "f3", Piece::Knight(Colour::White), None)];
+        assert_eq!(written(loaded, &knight, "rnbqkbnr/ppp1pppp/8/3pP3/8/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 3"),
+            "rnbqkbnr/ppp1pppp/8/3pP3/8/5N2/PPPP1PPP/RNBQKB1R b KQkq - 1 3");
+    }
+
+    // The move counters go on from the loaded position's, and the halfmove clock starts over at a pawn move
+    #[test]
+    fn counters_go_on_from_the_loaded_position() {
+        assert_eq!(counters("4k3/8/8/8/8/8/8/4K3 w - - 12 40"), (12, 40));
+        assert_eq!(counters("4k3/8/8/8/8/8/8/4K3 w - -"), (0, 1));
+        assert_eq!(counters("4k3/8/8/8/8/8/8/4K3 w - - x 0"), (0, 1));
+
+        let start = "4k3/4p3/8/8/8/8/8/4K3 w - - 12 40";
+        let mut history = vec![record("e1", "e2", Piece::King(Colour::White), None)];
+        assert_eq!(written(start, &history, "4k3/4p3/8/8/8/8/4K3/8 b - - 0 1"), "4k3/4p3/8/8/8/8/4K3/8 b - - 13 40");
+        history.push(record("e8", "d8", Piece::King(Colour::Black), None));
+        assert_eq!(written(start, &history, "3k4/4p3/8/8/8/8/4K3/8 w - - 0 1"), "3k4/4p3/8/8/8/8/4K3/8 w - - 14 41");
+        history.push(record("e2", "d2", Piece::King(Colour::White), None));
+        history.push(record("e7", "e5", Piece::Pawn(Colour::Black), None));
+        assert_eq!(written(start, &history, "3k4/8/8/4p3/8/8/3K4/8 w - - 0 1"), "3k4/8/8/4p3/8/8/3K4/8 w - e6 0 42");
+
+        // A game where Black moves first counts its first full move up with Black's reply
+        let black_first = "4k3/8/8/8/8/8/8/4K3 b - - 0 40";
+        let history = [record("e8", "e7", Piece::King(Colour::Black), None)];
+        assert_eq!(written(black_first, &history, "8/4k3/8/8/8/8/8/4K3 w - - 0 1"), "8/4k3/8/8/8/8/8/4K3 w - - 1 41");
+    }
+}
@@ -1,147 +1,1175 @@
-use eliasfl_chess::{Color as Colour, GameState, Game, Piece as Piece};
-use ggez::{graphics, Context};
-use super::super::{consts, AppState, help_funcs};
+use eliasfl_chess::{Color as Colour, Piece as Piece};
+use super::super::{consts, fen, export, controller::{GameController, Drag, Adjudication, MarkColour}, backend::{ChessBackend, GameStatus, PromotionKind}, history::{self, MoveRecord}, theme::Theme, pawn_structure::PawnFlag, profile::{self, Identity, Profiles}, help_funcs::{self, flip_square, get_piece_colour, num_to_filerank}, vote::Vote, messages::MessagePack, overlay_server::ChatLine};
+use super::renderer::{Renderer, Rgba};
+use super::layers::{Layer, Layers};
+use super::widgets::{Button, Dialog};
+use ggez::event::KeyCode;
+use super::text_layout::{TextLayout, Align, VAlign};
+use std::collections::HashMap;
 
 /// ## `pepare_text`
-/// Returns an instance of the `ggez::graphics::Text` holding a text that informs the state of the game.
+/// Returns the text that informs the state of the game.
 /// ### Parameters
-/// - `_game: Game`: The `Game` instance from The Elias Engine.
+/// - `_game: &dyn ChessBackend`: The backend playing the game.
 /// - `_current_colour: &Colour`: The current colour (turn colour) in the game
+/// - `_messages: &MessagePack`: The texts to choose from
 /// ### Return
-/// Returns an `ggez::graphics::Text` with the game state info (comedically humorous)
-/// 
+/// Returns a `String` with the game state info, in the words of the message pack
+///
 /// ### The different ouputs:
-/// - `InProgress`: (USES RUST `format!()`)
-/// - - Haskellers: `"Haskellers's Turn"`
-///   - Rustaceans: `"Rustaceans's Turn"`
-/// - `Check`: `"It's Check!!!"`
-/// - `CheckMate`: 
-/// - - Haskellers: `"Farewell Haskell!"`
-/// - - Rustaceans: `"Rust lost? PANIC!"`
-pub fn prepare_text(_game: &mut Game, _current_colour: &Colour) -> graphics::Text {
-        let state_text = graphics::Text::new(
-            graphics::TextFragment::from(
-                match _game.get_game_state() {
-                    GameState::InProgress => format!("{}'s turn!", if *_current_colour == Colour::Black {"Haskeller"} else {"Rustacean"}),
-                    GameState::Check => "It's Check!!!".to_string(),
-                    GameState::CheckMate => (if *_current_colour == Colour::Black {"Farewell Haskell!"} else {"Rust lost? PANIC!"}).to_string()
-                }
-            
-        )
-        .scale(graphics::PxScale { x: 30.0, y: 30.0 }));
-
-        state_text
-}
-
-/// ## `draw_text`
-/// Takes a `ggez:graphics::Text` and draws it on the screen using an offset from the center
-/// ### Parameters
-/// - `_ctx: &mut Context`: Instace of GGEZ context
-/// - `_text: &ggez::graphics::Text`: GGEZ Text instance
-/// - `_offset: (f32, f32)`: Offset from the center, i.e (50.0, 40.0), moves the text 50 units to right and 40 units down
-pub fn draw_text(_ctx: &mut Context, _text: &graphics::Text, _offset: (f32, f32)) {
-    let text_dimensions = _text.dimensions(_ctx);
-
-    // draw text with dark gray colouring and center position
-    graphics::draw(_ctx, _text, graphics::DrawParam::default().color([0.0, 0.0, 0.0, 1.0].into())
-        .dest(ggez::mint::Point2 {
-            x: (consts::SCREEN_SIZE.0 - text_dimensions.w as f32) / 2f32 as f32 + _offset.0,
-            y: (consts::SCREEN_SIZE.0 - text_dimensions.h as f32) / 2f32 as f32 + _offset.1,
-        })).expect("Failed to draw text.");
+/// - `InProgress`: `white_to_move` or `black_to_move`
+/// - `Check`: `check`
+/// - `CheckMate`: `white_mated` or `black_mated`, by the side that was mated
+/// - `Draw`: `draw`
+pub fn prepare_text(_game: &dyn ChessBackend, _current_colour: &Colour, _messages: &MessagePack) -> String {
+        match _game.status() {
+            GameStatus::InProgress => (if *_current_colour == Colour::Black { &_messages.black_to_move } else { &_messages.white_to_move }).clone(),
+            GameStatus::Check => _messages.check.clone(),
+            GameStatus::CheckMate => (if *_current_colour == Colour::Black { &_messages.black_mated } else { &_messages.white_mated }).clone(),
+            GameStatus::Draw(_) => _messages.draw.clone()
+        }
 }
 
-/// ## `draw_tile`
-/// Takes a row and a column and draws a coloured tile (grey or greyer).
+/// ## `prepare_status_text`
+/// Same as `prepare_text`, except when a clock ran out or a win condition of the variant or the arbiter ended the game, i.e. `"Rustaceans win: reached e4"`
 /// ### Parameters
-/// - `_ctx: &mut Context`: Instace of GGEZ context
+/// - `_controller: &GameController`: Reference to the GameController instance
+/// - `_messages: &MessagePack`: The texts to choose from
+pub fn prepare_status_text(_controller: &GameController, _messages: &MessagePack) -> String {
+    // Every kind of draw is a win for the side with draw odds, see `Variant::resolve_draw`
+    let drawn = |_draw: &String| match _controller.variant.resolve_draw() {
+        Some(winner) => {
+            let side = if winner == Colour::White { "White" } else { "Black" };
+            _messages.fill(&_messages.variant_win, winner == Colour::White, &format!("a draw, and {} had draw odds", side))
+        },
+        None => _draw.clone()
+    };
+
+    // A flag falls before anything else can end the game
+    if let Some(flagged) = _controller.flagged() {
+        if help_funcs::has_mating_material(_controller.game.as_ref(), !flagged) {
+            return _messages.fill(&_messages.time_win, flagged == Colour::Black, "");
+        }
+        return drawn(&_messages.time_draw);
+    }
+
+    match (&_controller.adjudication, &_controller.variant_win) {
+        (Some(Adjudication::Win(winner)), _) => _messages.fill(&_messages.adjudicated_win, *winner == Colour::White, ""),
+        (Some(Adjudication::Draw), _) => drawn(&_messages.adjudicated_draw),
+        (Some(Adjudication::Resigned(loser)), _) => _messages.fill(&_messages.resigned, *loser == Colour::Black, ""),
+        (Some(Adjudication::DrawAgreed), _) => drawn(&_messages.draw_agreed),
+        (None, Some((winner, reason))) => _messages.fill(&_messages.variant_win, *winner == Colour::White, reason),
+        (None, None) if matches!(_controller.game.status(), GameStatus::Draw(_)) => drawn(&_messages.draw),
+        (None, None) => prepare_text(_controller.game.as_ref(), &_controller.game.active_colour(), _messages)
+    }
+}
+
+/// ## `prepare_window_title`
+/// Title of the window while a game is shown, with the Chess960 position, the clocks, the move and the side to move,
+/// i.e. `"Schack — 5+3 — Move 12, Black to move"`. Finished games show their result instead of the side to move.
+/// ### Parameters
+/// - `_controller: &GameController`: Reference to the GameController instance
+/// - `_base: &str`: Name of the window, i.e. `"Schack"`
+pub fn prepare_window_title(_controller: &GameController, _base: &str) -> String {
+    let mut parts = vec![_base.to_string()];
+
+    if let Some(position) = _controller.variant.chess960().and_then(|_chess960| _chess960.current()) {
+        parts.push(format!("Chess960 #{}", position));
+    }
+
+    match (_controller.variant.time_controls(), _controller.time_controls()) {
+        (Some((white, black)), _) => parts.push(format!("{} vs {}", white.initial / 60.0, black.initial / 60.0)),
+        (None, Some((white, black))) if white != black => parts.push(format!("{} vs {}", white.label(), black.label())),
+        (None, Some((time_control, _))) => parts.push(time_control.label()),
+        (None, None) => {}
+    }
+
+    let move_number = _controller.history.len() / 2 + 1;
+    let (result, _) = export::result(_controller);
+    if result != "*" {
+        parts.push(format!("Move {}, {}", move_number, result));
+    }
+    else {
+        let side = if _controller.game.active_colour() == Colour::Black { "Black" } else { "White" };
+        parts.push(format!("Move {}, {} to move", move_number, side));
+    }
+
+    parts.join(" — ")
+}
+
+/// ## `draw_title`
+/// Draws the title of a full screen scene, centred across the window
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_title: &str`: The title
+/// - `_top: f32`: Height on the screen of the top of the title
+/// - `_size: f32`: Height of the text in pixels
+/// - `_colour: Rgba`: Colour of the text
+pub fn draw_title(_renderer: &mut dyn Renderer, _title: &str, _top: f32, _size: f32, _colour: Rgba) {
+    TextLayout::new().fragment(_title, _size, _colour).align(Align::Centre)
+        .draw(_renderer, (0.0, _top, consts::SCREEN_SIZE.0, _size));
+}
+
+/// ## `tile_colour`
+/// Colour of the tile on a row and a column, light or dark from the theme
+/// ### Parameters
+/// - `_theme: &Theme`: The colours to draw with
 /// - `_row: i32` The number of the row, 0 indexed
 /// - `_col: i32` The number of the column, 0 indexed
-pub fn draw_tile(_ctx: &mut Context, _row: i32, _col: i32) {
-    let rectangle = graphics::Mesh::new_rectangle(_ctx, 
-        graphics::DrawMode::fill(), 
-        graphics::Rect::new_i32(
-            _col * consts::GRID_CELL_SIZE.0 as i32,
-            _row * consts::GRID_CELL_SIZE.1 as i32,
-            consts::GRID_CELL_SIZE.0 as i32,
-            consts::GRID_CELL_SIZE.1 as i32,
-        ), match _col % 2 {
-            0 => 
-                if _row % 2 == 0 { consts::WHITE } 
-                else { consts::BLACK },
-            _ => 
-                if _row % 2 == 0 { consts::BLACK } 
-                else { consts::WHITE },
-        }).expect("Failed to create tile.");
-
-    graphics::draw(_ctx, &rectangle, graphics::DrawParam::default()).expect("Failed to draw tiles.");
+fn tile_colour(_theme: &Theme, _row: i32, _col: i32) -> Rgba {
+    match _col % 2 {
+        0 =>
+            if _row % 2 == 0 { _theme.light_tile }
+            else { _theme.dark_tile },
+        _ =>
+            if _row % 2 == 0 { _theme.dark_tile }
+            else { _theme.light_tile },
+    }
 }
 
-/// ## `draw_piece`
-/// Takes the AppState instance (that includes the Chess Engine board) and a row and a column and draws the piece on the row and column (if there are any)
+/// ## `draw_tile`
+/// Takes a row and a column and draws a coloured tile (light or dark, from the theme).
 /// ### Parameters
-/// - `_ctx: &mut Context`: Instace of GGEZ context
-/// - `_appstate: &AppState`: Reference to the AppState instance
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with
 /// - `_row: i32` The number of the row, 0 indexed
 /// - `_col: i32` The number of the column, 0 indexed
-pub fn draw_piece(_ctx: &mut Context, _appstate: &AppState, _row: i32, _col: i32) {
-    if let Some(_piece) = _appstate.game.board.get(&help_funcs::to_engine_coords(&(_col as u8, _row as u8))) {
-        graphics::draw(_ctx, _appstate.sprites.get(&_piece).unwrap(), graphics::DrawParam::default()
-            .dest(
-                [_col as f32 * consts::GRID_CELL_SIZE.0 as f32, _row as f32 * consts::GRID_CELL_SIZE.1 as f32],
-            )
-        ).expect("Failed to draw piece.");
+pub fn draw_tile(_renderer: &mut dyn Renderer, _theme: &Theme, _row: i32, _col: i32) {
+    _renderer.draw_rect((
+            (_col * consts::GRID_CELL_SIZE.0 as i32) as f32,
+            (_row * consts::GRID_CELL_SIZE.1 as i32) as f32,
+            consts::GRID_CELL_SIZE.0 as f32,
+            consts::GRID_CELL_SIZE.1 as f32,
+        ), tile_colour(_theme, _row, _col));
+}
+
+/// ## `draw_ring`
+/// Draws a ring of the theme's outline colour on a tile, if the theme has outlines
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with
+/// - `_centre: (f32, f32)`: Centre of the ring
+/// - `_radius: f32`: Outer radius of the ring
+/// - `_inside: Rgba`: Colour within the ring, the colour of the tile
+fn draw_ring(_renderer: &mut dyn Renderer, _theme: &Theme, _centre: (f32, f32), _radius: f32, _inside: Rgba) {
+    if _theme.outline > 0.0 {
+        _renderer.draw_circle(_centre, _radius, _theme.outline_colour);
+        _renderer.draw_circle(_centre, _radius - _theme.outline, _inside);
+    }
+}
+
+/// ## `piece_placement`
+/// Where a piece is drawn on a tile and at what scale, leaving a margin to each edge of the tile
+/// ### Parameters
+/// - `_tile: (f32, f32)`: Top left corner of the tile
+/// - `_margin: f32`: Pixels between the piece and each edge of the tile, 0 fills the tile
+/// ### Return
+/// The top left corner of the piece and the scale to draw its sprite at
+fn piece_placement(_tile: (f32, f32), _margin: f32) -> ((f32, f32), f32) {
+    let margin = _margin.max(0.0).min(consts::GRID_CELL_SIZE.0 as f32 / 4.0);
+    ((_tile.0 + margin, _tile.1 + margin), 1.0 - 2.0 * margin / consts::GRID_CELL_SIZE.0 as f32)
+}
+
+/// The pieces the player is handling, which are drawn differently from the others
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Handling {
+    pub selected: Option<(u8, u8)>, // Square of the piece whose legal moves are shown
+    pub drag: Option<Drag> // The piece being dragged, which only leaves a ghost on its square
+}
+
+impl Handling {
+    /// What the player is handling in a game
+    pub fn of(_controller: &GameController) -> Handling {
+        Handling { selected: _controller.previous_click, drag: _controller.drag }
+    }
+}
+
+/// How a piece is drawn compared to the pieces at rest
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PieceModifier {
+    scale: f32, // Of the piece's usual size
+    lift: f32, // Pixels the piece is raised above its square
+    alpha: f32,
+    glow: bool // An outline around the piece
+}
+
+impl PieceModifier {
+    /// Lifting and enlarging the selected piece a little, so it is clear whose legal moves are shown
+    const SELECTED: PieceModifier = PieceModifier { scale: 1.1, lift: 6.0, alpha: 1.0, glow: true };
+    /// The ghost a dragged piece leaves on its square
+    const GHOST: PieceModifier = PieceModifier { scale: 1.0, lift: 0.0, alpha: 0.35, glow: false };
+    const AT_REST: PieceModifier = PieceModifier { scale: 1.0, lift: 0.0, alpha: 1.0, glow: false };
+
+    /// The modifier of the piece on a square
+    fn of(_handling: Handling, _square: (u8, u8)) -> PieceModifier {
+        if _handling.drag.map_or(false, |_drag| _drag.from == _square) { PieceModifier::GHOST }
+        else if _handling.selected == Some(_square) { PieceModifier::SELECTED }
+        else { PieceModifier::AT_REST }
     }
 }
 
 /// ## `draw_piece`
-/// Takes the AppState intance (that includes the Chess Engine board) and a row and a column
-/// and draws a small blue circle on that square if the piece click on has that square as its legal moves
+/// Takes a game and a tile and draws the piece on it (if there is any). With a margin the piece is drawn smaller
+/// than the tile, over a drop shadow. The selected piece is lifted and outlined.
 /// ### Parameters
-/// - `_ctx: &mut Context`: Instace of GGEZ context
-/// - `_appstate: &AppState`: Reference to the AppState instance
-/// - `_row: i32` The number of the row, 0 indexed
-/// - `_col: i32` The number of the column, 0 indexed
-pub fn draw_legal_indicator(_ctx: &mut Context, _appstate: &AppState, _row: i32, _col: i32) {
-    if _appstate.legal.contains(&(_col as u8, _row as u8)) {
-        let circle = graphics::Mesh::new_circle(_ctx, 
-            graphics::DrawMode::fill(), ggez::mint::Point2{
-            x: (_col as i16 * consts::GRID_CELL_SIZE.0 + consts::GRID_CELL_SIZE.0 / 2) as f32,
-            y: (_row as i16 * consts::GRID_CELL_SIZE.1 + consts::GRID_CELL_SIZE.1 / 2) as f32
-        }, 25.0, 1.0, graphics::Color::new(0.6, 1.0, 0.6, 0.5)).unwrap();
-        
-        graphics::draw(_ctx,
-            &circle,
-            graphics::DrawParam::default()).expect("Failed to draw legal move indictator");
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with, outlined themes ring the piece
+/// - `_game: &dyn ChessBackend`: The game holding the pieces
+/// - `_handling: Handling`: The pieces selected and dragged by the player
+/// - `_tile: (i32, i32)` The row and the column on screen, 0 indexed
+/// - `_flipped: bool`: If the board is seen from Black's side
+/// - `_margin: f32`: Pixels between the piece and each edge of its tile
+pub fn draw_piece(_renderer: &mut dyn Renderer, _theme: &Theme, _game: &dyn ChessBackend, _handling: Handling, _tile: (i32, i32), _flipped: bool, _margin: f32) {
+    let (row, col) = _tile;
+    let square = flip_square((col as u8, row as u8), _flipped);
+
+    if let Some(_piece) = _game.piece_at(square) {
+        let size = consts::GRID_CELL_SIZE.0 as f32;
+        let tile = (col as f32 * size, row as f32 * consts::GRID_CELL_SIZE.1 as f32);
+        let centre = (tile.0 + size / 2.0, tile.1 + consts::GRID_CELL_SIZE.1 as f32 / 2.0);
+        draw_ring(_renderer, _theme, centre, size / 2.0 - 2.0, tile_colour(_theme, row, col));
+
+        let modifier = PieceModifier::of(_handling, square);
+        let (at, scale) = piece_placement(tile, _margin);
+
+        // The shadow falls down and to the right, into the margin, and stays on the square when the piece is lifted
+        if (scale < 1.0 || modifier.lift > 0.0) && modifier.alpha >= 1.0 {
+            let offset = (1.0 - scale) * size / 4.0 + modifier.lift / 2.0;
+            _renderer.draw_circle((centre.0 + offset, centre.1 + offset), 0.4 * scale * size, [0.0, 0.0, 0.0, 0.25]);
+        }
+        if modifier.glow {
+            _renderer.draw_circle((centre.0, centre.1 - modifier.lift), size / 2.0 - 2.0, [0.97, 0.3, 0.0, 0.45]);
+        }
+
+        // Enlarged around the centre of the piece, then raised
+        let grown = scale * modifier.scale;
+        let at = (at.0 - (grown - scale) * size / 2.0, at.1 - (grown - scale) * size / 2.0 - modifier.lift);
+        if modifier.alpha < 1.0 {
+            _renderer.draw_sprite_alpha(&_piece, at, grown, modifier.alpha);
+        }
+        else {
+            _renderer.draw_sprite(&_piece, at, grown);
+        }
+    }
+}
+
+/// ## `draw_dragged_piece`
+/// Draws the piece being dragged centered under the cursor, on top of everything on the board
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_game: &dyn ChessBackend`: The game holding the pieces
+/// - `_drag: Option<Drag>`: The piece being dragged, if any
+/// - `_margin: f32`: Pixels between a piece and each edge of its tile, the dragged piece keeps its size
+pub fn draw_dragged_piece(_renderer: &mut dyn Renderer, _game: &dyn ChessBackend, _drag: Option<Drag>, _margin: f32) {
+    if let Some(drag) = _drag {
+        if let Some(_piece) = _game.piece_at(drag.from) {
+            let (at, scale) = piece_placement((
+                drag.at.0 - consts::GRID_CELL_SIZE.0 as f32 / 2.0,
+                drag.at.1 - consts::GRID_CELL_SIZE.1 as f32 / 2.0
+            ), _margin);
+            _renderer.draw_sprite(&_piece, at, scale);
+        }
+    }
+}
+
+/// ## `draw_drop_target`
+/// Frames the square under a dragged piece when the piece can move there, so it is clear where letting go plays the move
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with, the frame is the colour of the legal indicators without their see-through
+/// - `_legal: &[(u8, u8)]`: The squares the dragged piece can move to
+/// - `_drag: Option<Drag>`: The piece being dragged, if any
+/// - `_flipped: bool`: If the board is seen from Black's side
+pub fn draw_drop_target(_renderer: &mut dyn Renderer, _theme: &Theme, _legal: &[(u8, u8)], _drag: Option<Drag>, _flipped: bool) {
+    let target = _drag.and_then(|_drag| help_funcs::board_square(_drag.at.0, _drag.at.1));
+    if let Some((col, row)) = target.filter(|_square| _legal.contains(&flip_square(*_square, _flipped))) {
+        let (width, height) = (consts::GRID_CELL_SIZE.0 as f32, consts::GRID_CELL_SIZE.1 as f32);
+        let (x, y) = (col as f32 * width, row as f32 * height);
+        let (thickness, colour) = (5.0, [_theme.legal_indicator[0], _theme.legal_indicator[1], _theme.legal_indicator[2], 1.0]);
+
+        _renderer.draw_rect((x, y, width, thickness), colour);
+        _renderer.draw_rect((x, y + height - thickness, width, thickness), colour);
+        _renderer.draw_rect((x, y, thickness, height), colour);
+        _renderer.draw_rect((x + width - thickness, y, thickness, height), colour);
+    }
+}
+
+/// ## `draw_legal_indicator`
+/// Takes the legal moves of the selected piece and a row and a column
+/// and draws a smooth circle, sized to the tile, on that square if the piece click on has that square as its legal moves
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with
+/// - `_legal: &[(u8, u8)]`: The squares the selected piece can move to
+/// - `_row: i32` The number of the row on screen, 0 indexed
+/// - `_col: i32` The number of the column on screen, 0 indexed
+/// - `_flipped: bool`: If the board is seen from Black's side
+pub fn draw_legal_indicator(_renderer: &mut dyn Renderer, _theme: &Theme, _legal: &[(u8, u8)], _row: i32, _col: i32, _flipped: bool) {
+    if _legal.contains(&flip_square((_col as u8, _row as u8), _flipped)) {
+        let cell = consts::GRID_CELL_SIZE.0 as f32;
+        let centre = (
+            (_col as i16 * consts::GRID_CELL_SIZE.0 + consts::GRID_CELL_SIZE.0 / 2) as f32,
+            (_row as i16 * consts::GRID_CELL_SIZE.1 + consts::GRID_CELL_SIZE.1 / 2) as f32
+        );
+        draw_ring(_renderer, _theme, centre, cell * consts::LEGAL_INDICATOR_RADIUS + _theme.outline, tile_colour(_theme, _row, _col));
+        _renderer.draw_indicator(centre, cell, _theme.legal_indicator);
+    }
+}
+
+/// Corners of the rounded end of an arrow's shaft, a half circle around its tail
+const ARROW_CAP_POINTS: usize = 8;
+
+/// ## `draw_arrow`
+/// Draws an arrow from the middle of one square to the middle of another: a shaft with a rounded tail and a triangle head.
+/// For last moves, hints, engine lines and annotations alike.
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_from: (u8, u8)`: Square the arrow starts on, in GUI coords
+/// - `_to: (u8, u8)`: Square the arrow points at, in GUI coords
+/// - `_colour: Rgba`: Colour of the arrow, the shaft and the head do not overlap so see-through colours stay even
+/// - `_thickness: f32`: Width of the shaft in pixels, the head is sized to it
+/// - `_flipped: bool`: If the board is seen from Black's side
+pub fn draw_arrow(_renderer: &mut dyn Renderer, _from: (u8, u8), _to: (u8, u8), _colour: Rgba, _thickness: f32, _flipped: bool) {
+    let from = help_funcs::square_centre(flip_square(_from, _flipped));
+    let to = help_funcs::square_centre(flip_square(_to, _flipped));
+
+    let length = (to.0 - from.0).hypot(to.1 - from.1);
+    if length < 1.0 {
+        return;
+    }
+    let along = ((to.0 - from.0) / length, (to.1 - from.1) / length);
+    let across = (-along.1, along.0);
+    let at = |_origin: (f32, f32), _along: f32, _across: f32| (
+        _origin.0 + along.0 * _along + across.0 * _across,
+        _origin.1 + along.1 * _along + across.1 * _across
+    );
+
+    // The head never takes more than half of a short arrow
+    let head_length = (_thickness * 2.5).min(length / 2.0);
+    let head_width = _thickness * 1.4;
+    let base = at(to, -head_length, 0.0);
+    let half = _thickness / 2.0;
+
+    // Shaft: from one side of the base round the tail to the other side
+    let mut shaft = vec![at(base, 0.0, half)];
+    for _point in 0..=ARROW_CAP_POINTS {
+        let angle = std::f32::consts::FRAC_PI_2 + std::f32::consts::PI * _point as f32 / ARROW_CAP_POINTS as f32;
+        shaft.push(at(from, angle.cos() * half, angle.sin() * half));
     }
+    shaft.push(at(base, 0.0, -half));
+
+    _renderer.draw_polygon(&shaft, _colour);
+    _renderer.draw_polygon(&[at(base, 0.0, head_width), to, at(base, 0.0, -head_width)], _colour);
+}
+
+/// ## `draw_last_move_arrow`
+/// Draws a green arrow along a move, the same as on exported diagrams
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_move: &MoveRecord`: The move to point out
+/// - `_flipped: bool`: If the board is seen from Black's side
+pub fn draw_last_move_arrow(_renderer: &mut dyn Renderer, _move: &MoveRecord, _flipped: bool) {
+    let thickness = consts::GRID_CELL_SIZE.0 as f32 * 0.18;
+    draw_arrow(_renderer, help_funcs::filerank_to_num(&_move.from), help_funcs::filerank_to_num(&_move.to), [0.08, 0.47, 0.11, 0.8], thickness, _flipped);
 }
 
 /// ## `draw_rectangle`
 /// Takes a geometry representation of a rectangle and draws a grey rectangle using that geometry given
 /// ### Parameters
-/// - `_ctx: &mut Context`: Instace of GGEZ context
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
 /// - `_geometry: (f32, f32, f32, f32)`: Rectangle in this form (top_left_corner_x, top_left_corner_y, width, height)
-pub fn draw_rectangle(_ctx: &mut Context, _geometry: (f32, f32, f32, f32))
+pub fn draw_rectangle(_renderer: &mut dyn Renderer, _geometry: (f32, f32, f32, f32))
 {
-    let background = graphics::Mesh::new_rectangle(_ctx, graphics::DrawMode::fill(),
-                        graphics::Rect::new(_geometry.0, _geometry.1, _geometry.2, _geometry.3),
-                                [0.2, 0.2, 0.2, 1.0].into());
-
-    graphics::draw(_ctx, &background.unwrap(), graphics::DrawParam::default()).expect("Failed to draw background.");
+    _renderer.draw_rect(_geometry, [0.2, 0.2, 0.2, 1.0]);
 }
 
 /// ## `draw_icon`
-/// Takes AppState (to get sprites) and a position and piece and draws the piece at the given postion.
+/// Takes a position and piece and draws the piece at the given postion.
 /// Also takes scale of the art
 /// ### Parameters
-/// - `_ctx: &mut Context`: Instace of GGEZ context
-/// - `_appstate: &AppState`: Reference to the AppState instance
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
 /// - `_at: (f32, f32)`: The destination of the icon (top left anchored)
 /// - `_piece: &Piece`: Reference to a piece from the Elias Engine
 /// - `_scale: f32`: Scales the sprite
-pub fn draw_icon(_ctx: &mut Context, _appstate: &AppState, _at: (f32, f32), _piece: &Piece, _scale: f32) {
-    graphics::draw(_ctx, _appstate.sprites.get(&_piece).unwrap(), graphics::DrawParam::default()
-                .scale([_scale, _scale])
-                .dest(
-                    [_at.0, _at.1],
-                )).expect("Failed to draw piece.");
-}
\ No newline at end of file
+pub fn draw_icon(_renderer: &mut dyn Renderer, _at: (f32, f32), _piece: &Piece, _scale: f32) {
+    _renderer.draw_sprite(_piece, _at, _scale);
+}
+
+/// ## `draw_background`
+/// Fills the whole screen with a colour, used to clear the interface before drawing
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_colour: Rgba`: The background colour
+pub fn draw_background(_renderer: &mut dyn Renderer, _colour: Rgba) {
+    _renderer.draw_rect((0.0, 0.0, consts::SCREEN_SIZE.0, consts::SCREEN_SIZE.1), _colour);
+}
+
+/// ## `draw_banner`
+/// Draws a red banner with a message across the top of the board, used for errors
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_message: &str`: The message to show
+pub fn draw_banner(_renderer: &mut dyn Renderer, _message: &str) {
+    _renderer.draw_rect((0.0, 0.0, consts::SCREEN_SIZE.0, 30.0), [0.8, 0.1, 0.1, 0.9]);
+    _renderer.draw_text(_message, (10.0, 7.0), 16.0, [1.0, 1.0, 1.0, 1.0]);
+}
+
+/// ## `draw_resync_button`
+/// Draws the button at the end of the banner that rebuilds the GUI from the engine's board, see `Command::Resync`
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+pub fn draw_resync_button(_renderer: &mut dyn Renderer) {
+    _renderer.draw_rect(consts::RESYNC_BUTTON, [1.0, 1.0, 1.0, 0.9]);
+    TextLayout::centred("Resync", 16.0, [0.8, 0.1, 0.1, 1.0]).draw(_renderer, consts::RESYNC_BUTTON);
+}
+
+/// ## `draw_toast`
+/// Draws a toast in the top right corner, stacked below the toasts shown before it, fading out at the end of its time
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_title: &str`: First line, in bold colours
+/// - `_message: &str`: Second line
+/// - `_slot: usize`: Position in the stack, 0 is the top
+/// - `_remaining: f32`: Seconds the toast has left on screen
+pub fn draw_toast(_renderer: &mut dyn Renderer, _title: &str, _message: &str, _slot: usize, _remaining: f32) {
+    let alpha = (_remaining / consts::TOAST_FADE).min(1.0).max(0.0);
+    let geometry = (consts::SCREEN_SIZE.0 - 430.0, 40.0 + 70.0 * _slot as f32, 420.0, 60.0);
+
+    _renderer.draw_rect(geometry, [0.1, 0.1, 0.1, 0.9 * alpha]);
+    _renderer.draw_rect((geometry.0, geometry.1, 6.0, geometry.3), [0.97, 0.3, 0.0, alpha]);
+    TextLayout::new()
+        .fragment(&format!("{}\n", _title), 18.0, [0.97, 0.3, 0.0, alpha])
+        .fragment(_message, 14.0, [1.0, 1.0, 1.0, alpha])
+        .valign(VAlign::Middle)
+        .draw(_renderer, (geometry.0 + 16.0, geometry.1, geometry.2 - 24.0, geometry.3));
+}
+
+/// How the interface shows whose turn it is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnIndicator {
+    /// A badge with the name of the side to move, on its side of the panel
+    Badge,
+    /// A glow around the clock of the side to move
+    Clock,
+    /// A small flag in the colour of the side to move
+    Flag
+}
+
+impl TurnIndicator {
+    /// Every indicator, in the order the settings cycle through them
+    pub const ALL: [TurnIndicator; 3] = [TurnIndicator::Badge, TurnIndicator::Clock, TurnIndicator::Flag];
+
+    /// Name shown in the settings
+    pub fn label(&self) -> &'static str {
+        match self {
+            TurnIndicator::Badge => "Badge",
+            TurnIndicator::Clock => "Glowing clock",
+            TurnIndicator::Flag => "Flag"
+        }
+    }
+}
+
+/// ## `side_colour`
+/// The colour representing a side in turn indicators, orange for the Rustaceans and purple for the Haskellers
+/// ### Parameters
+/// - `_colour: Colour`: The side
+pub fn side_colour(_colour: Colour) -> Rgba {
+    match _colour {
+        Colour::Black => [0.37, 0.31, 0.53, 1.0],
+        _ => [0.97, 0.3, 0.0, 1.0]
+    }
+}
+
+/// ## `side_slot`
+/// Left edge of the area in the panel belonging to a side, white on the left
+/// ### Parameters
+/// - `_colour: Colour`: The side
+fn side_slot(_colour: Colour) -> f32 {
+    if _colour == Colour::White { 20.0 } else { 570.0 }
+}
+
+/// ## `draw_clocks`
+/// Draws the clock of both sides in their slot of the panel. Untimed games show dashes.
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_controller: &GameController`: Reference to the GameController instance
+/// - `_top: f32`: Height on the screen of the top of the clocks
+/// - `_glow: bool`: If the clock of the side to move glows
+/// - `_reduced_motion: bool`: If the spinner of a thinking bot stands still
+pub fn draw_clocks(_renderer: &mut dyn Renderer, _controller: &GameController, _top: f32, _glow: bool, _reduced_motion: bool) {
+    for _colour in [Colour::White, Colour::Black].iter() {
+        let geometry = (side_slot(*_colour), _top, 130.0, 44.0);
+
+        if _glow && _controller.game.active_colour() == *_colour && !_controller.is_over() {
+            let mut glow = side_colour(*_colour);
+            glow[3] = 0.6;
+            _renderer.draw_rect((geometry.0 - 6.0, geometry.1 - 6.0, geometry.2 + 12.0, geometry.3 + 12.0), glow);
+        }
+
+        let time = match _controller.clocks.get(_colour) {
+            Some(clock) => clock.format(),
+            None => "--:--".to_string()
+        };
+        draw_button(_renderer, geometry, &time);
+
+        // The increment in the corner, for the players to count on it
+        if let Some(increment) = _controller.clocks.get(_colour).map(|_clock| _clock.increment()).filter(|_increment| *_increment > 0.0) {
+            _renderer.draw_text(&format!("+{}", increment), (geometry.0 + 5.0, geometry.1 + geometry.3 - 15.0), 12.0, [0.8, 0.8, 0.8, 1.0]);
+        }
+
+        // A bot working on its move spins in the corner of its clock
+        if let Some(thinking) = _controller.thinking.as_ref().filter(|_thinking| _thinking.colour == *_colour && !_controller.is_over()) {
+            let time = if _reduced_motion { 0.0 } else { thinking.elapsed };
+            draw_spinner(_renderer, (geometry.0 + geometry.2 - 12.0, geometry.1 + 12.0), time);
+        }
+    }
+}
+
+/// ## `draw_identity`
+/// Draws the avatar of a player with their flag in its corner, or the flag alone. Images that are not loaded
+/// leave a grey square for the avatar and the country code for the flag.
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_identity: &Identity`: The avatar and flag to draw
+/// - `_geometry: (f32, f32, f32, f32)`: Area of the avatar as (top_left_corner_x, top_left_corner_y, width, height)
+pub fn draw_identity(_renderer: &mut dyn Renderer, _identity: &Identity, _geometry: (f32, f32, f32, f32)) {
+    let (x, y, width, height) = _geometry;
+
+    if let Some(avatar) = &_identity.avatar {
+        if !_renderer.draw_image(avatar, _geometry) {
+            _renderer.draw_rect(_geometry, [0.3, 0.3, 0.3, 1.0]);
+        }
+    }
+
+    let flag_geometry = if _identity.avatar.is_some() {
+        (x + width * 0.5, y + height * 0.65, width * 0.5, height * 0.35)
+    } else {
+        (x, y + height * 0.17, width, height * 0.66)
+    };
+    if let (Some(code), Some(image)) = (&_identity.flag, _identity.flag_image()) {
+        if !_renderer.draw_image(&image, flag_geometry) {
+            _renderer.draw_rect(flag_geometry, [0.1, 0.1, 0.1, 1.0]);
+            TextLayout::centred(&code.to_uppercase(), flag_geometry.3 * 0.7, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, flag_geometry);
+        }
+    }
+}
+
+/// ## `draw_player_identities`
+/// Draws the avatar and flag of both players beside their clocks, on the side facing the middle of the panel
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_profiles: &Profiles`: The profiles the identities are kept in
+/// - `_controller: &GameController`: Reference to the GameController instance
+/// - `_top: f32`: Height on the screen of the top of the clocks
+pub fn draw_player_identities(_renderer: &mut dyn Renderer, _profiles: &Profiles, _controller: &GameController, _top: f32) {
+    for _colour in [Colour::White, Colour::Black].iter() {
+        if let Some(identity) = _profiles.identity(&profile::player_name(_controller, *_colour)) {
+            let x = if *_colour == Colour::White { side_slot(*_colour) + 136.0 } else { side_slot(*_colour) - 50.0 };
+            draw_identity(_renderer, identity, (x, _top, 44.0, 44.0));
+        }
+    }
+}
+
+/// ## `draw_spinner`
+/// Draws a ring of dots with a bright one going round, for something that is being waited on
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_centre: (f32, f32)`: The middle of the ring
+/// - `_time: f32`: Seconds since the wait started, sets where the bright dot is
+pub fn draw_spinner(_renderer: &mut dyn Renderer, _centre: (f32, f32), _time: f32) {
+    const DOTS: usize = 8;
+    let lead = (_time * 12.0) as usize % DOTS; // One and a half turns per second
+
+    for _dot in 0..DOTS {
+        let angle = _dot as f32 / DOTS as f32 * std::f32::consts::TAU;
+        // The dots behind the bright one fade out like a tail
+        let behind = (lead + DOTS - _dot) % DOTS;
+        let alpha = 1.0 - behind as f32 / DOTS as f32;
+
+        _renderer.draw_circle((_centre.0 + 7.0 * angle.sin(), _centre.1 - 7.0 * angle.cos()), 2.0, [1.0, 1.0, 1.0, alpha]);
+    }
+}
+
+/// ## `draw_turn_indicator`
+/// Shows whose turn it is on the mover's side of the panel
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_controller: &GameController`: Reference to the GameController instance
+/// - `_indicator: TurnIndicator`: How to show it
+pub fn draw_turn_indicator(_renderer: &mut dyn Renderer, _controller: &GameController, _indicator: TurnIndicator) {
+    let mover = _controller.game.active_colour();
+    let slot = side_slot(mover);
+
+    match _indicator {
+        TurnIndicator::Badge => {
+            let name = if mover == Colour::Black { "Haskellers" } else { "Rustaceans" };
+
+            _renderer.draw_rect((slot, 830.0, 130.0, 30.0), side_colour(mover));
+            TextLayout::centred(name, 20.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, (slot, 830.0, 130.0, 30.0));
+        },
+        TurnIndicator::Clock => {}, // The glow is drawn with the clocks
+        TurnIndicator::Flag => {
+            // Next to the clock, on the side facing the middle of the panel
+            let pole = if mover == Colour::White { slot + 140.0 } else { slot - 34.0 };
+
+            _renderer.draw_rect((pole, 780.0, 3.0, 40.0), [0.1, 0.1, 0.1, 1.0]);
+            _renderer.draw_rect((pole + 3.0, 780.0, 24.0, 16.0), side_colour(mover));
+        }
+    }
+}
+
+/// ## `draw_square_label`
+/// Draws the name of a square in the top left corner of the board, for learning the coordinates
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_square: (u8, u8)`: The square to name, in GUI coords
+pub fn draw_square_label(_renderer: &mut dyn Renderer, _square: (u8, u8)) {
+    let geometry = (10.0, 40.0, 80.0, 50.0);
+
+    _renderer.draw_rect(geometry, [0.1, 0.1, 0.1, 0.8]);
+    TextLayout::centred(&num_to_filerank(&_square), 32.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, geometry);
+}
+
+/// ## `draw_speedrun_timer`
+/// Draws the time of a speedrun in the top right corner of the board
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_seconds: f32`: The time so far
+pub fn draw_speedrun_timer(_renderer: &mut dyn Renderer, _seconds: f32) {
+    let geometry = (consts::SCREEN_SIZE.0 - 130.0, 40.0, 120.0, 50.0);
+
+    _renderer.draw_rect(geometry, [0.1, 0.1, 0.1, 0.8]);
+    TextLayout::centred(&format!("{:.1} s", _seconds), 28.0, [0.97, 0.3, 0.0, 1.0]).draw(_renderer, geometry);
+}
+
+/// How the board is drawn, apart from its colours
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BoardStyle {
+    pub margin: f32, // Pixels between a piece and each edge of its tile
+    pub layers: Layers // Which of the board, the pieces and the indicators are drawn
+}
+
+/// ## `draw_board`
+/// Clears the interface and draws the board of the game being played with its pieces and legal move indicators
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with
+/// - `_controller: &GameController`: Reference to the GameController instance
+/// - `_style: BoardStyle`: The margin of the pieces and the layers drawn
+pub fn draw_board(_renderer: &mut dyn Renderer, _theme: &Theme, _controller: &GameController, _style: BoardStyle) {
+    draw_position(_renderer, _theme, _controller.game.as_ref(), &_controller.legal, Handling::of(_controller), _controller.flipped, _style);
+}
+
+/// ## `draw_position`
+/// Clears the interface and draws any position in layers: the tiles, then the pieces, then the legal move indicators
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with
+/// - `_game: &dyn ChessBackend`: The game at the position to draw
+/// - `_legal: &[(u8, u8)]`: The squares to mark as legal moves
+/// - `_handling: Handling`: The pieces selected and dragged by the player
+/// - `_flipped: bool`: If the board is seen from Black's side, rank 1 at the top
+/// - `_style: BoardStyle`: The margin of the pieces and the layers drawn
+pub fn draw_position(_renderer: &mut dyn Renderer, _theme: &Theme, _game: &dyn ChessBackend, _legal: &[(u8, u8)], _handling: Handling, _flipped: bool, _style: BoardStyle) {
+    // clear interface with the background colour, the same for both sides, whatever layers are hidden
+    draw_background(_renderer, _theme.background);
+
+    let layers = _style.layers;
+    layers.draw(Layer::Board, || {
+        for _row in 0..8 {
+            for _col in 0..8 {
+                draw_tile(_renderer, _theme, _row, _col);
+            }
+        }
+    });
+
+    layers.draw(Layer::Pieces, || {
+        for _row in 0..8 {
+            for _col in 0..8 {
+                draw_piece(_renderer, _theme, _game, _handling, (_row, _col), _flipped, _style.margin);
+            }
+        }
+    });
+
+    // Draw an indicator (white circle) on legal moves for the piece clicked
+    layers.draw(Layer::Indicators, || {
+        for _row in 0..8 {
+            for _col in 0..8 {
+                draw_legal_indicator(_renderer, _theme, _legal, _row, _col, _flipped);
+            }
+        }
+        draw_drop_target(_renderer, _theme, _legal, _handling.drag, _flipped);
+    });
+
+    // The piece in hand goes with the pieces, but above the indicators
+    layers.draw(Layer::Pieces, || draw_dragged_piece(_renderer, _game, _handling.drag, _style.margin));
+}
+
+/// ## `draw_panel_background`
+/// Clears the panel below the board, so another panel can be drawn over it
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with
+pub fn draw_panel_background(_renderer: &mut dyn Renderer, _theme: &Theme) {
+    let board_height = (consts::GRID_SIZE * consts::GRID_CELL_SIZE.1) as f32;
+    _renderer.draw_rect((0.0, board_height, consts::SCREEN_SIZE.0, consts::SCREEN_SIZE.1 - board_height), _theme.background);
+}
+
+/// ## `draw_status_panel`
+/// Draws the panel below the board: the turn and the state of the game, the clocks and the capture timeline
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with
+/// - `_controller: &GameController`: Reference to the GameController instance
+/// - `_indicator: TurnIndicator`: How to show whose turn it is
+/// - `_messages: &MessagePack`: The texts of the state of the game
+/// - `_reduced_motion: bool`: If nothing on the panel moves
+pub fn draw_status_panel(_renderer: &mut dyn Renderer, _theme: &Theme, _controller: &GameController, _indicator: TurnIndicator, _messages: &MessagePack, _reduced_motion: bool) {
+    // create text representation
+    let state_text = prepare_status_text(_controller, _messages);
+    TextLayout::centred(&state_text, 30.0, _theme.text).draw(_renderer, (160.0, 770.0, 400.0, 60.0));
+
+    // Why the last move was refused
+    if let Some(notice) = &_controller.notice {
+        TextLayout::centred(notice, 16.0, _theme.text).draw(_renderer, (160.0, 830.0, 400.0, 30.0));
+    }
+
+    let timeline = (5.0, 725.0, 710.0, 40.0);
+    draw_capture_timeline(_renderer, &_controller.history, timeline);
+
+    draw_clocks(_renderer, _controller, 778.0, _indicator == TurnIndicator::Clock, _reduced_motion);
+    if !_controller.is_over() {
+        draw_turn_indicator(_renderer, _controller, _indicator);
+    }
+
+    // Last, as it reaches over the board
+    if let Some(pointer) = _controller.pointer {
+        draw_capture_tooltip(_renderer, &_controller.history, timeline, pointer);
+    }
+}
+
+/// ## `draw_vote_panel`
+/// Draws the panel below the board in vote chess: the clocks, who proposes next and the proposals for the move,
+/// each with its voters and a cross to withdraw it
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours to draw with
+/// - `_controller: &GameController`: Reference to the GameController instance, with a vote
+/// - `_indicator: TurnIndicator`: How to show whose turn it is
+/// - `_messages: &MessagePack`: The texts of the state of the game
+/// - `_reduced_motion: bool`: If nothing on the panel moves
+pub fn draw_vote_panel(_renderer: &mut dyn Renderer, _theme: &Theme, _controller: &GameController, _indicator: TurnIndicator, _messages: &MessagePack, _reduced_motion: bool) {
+    let vote = match &_controller.vote {
+        Some(vote) => vote,
+        None => return
+    };
+
+    draw_clocks(_renderer, _controller, 778.0, _indicator == TurnIndicator::Clock, _reduced_motion);
+
+    // While a bot moves, or once the game is over, there is nothing to vote on
+    let voting = !_controller.is_over() && !_controller.bots.contains_key(&_controller.game.active_colour());
+    let header = if voting {
+        format!("Voter {} of {} proposes", vote.turn + 1, vote.voters)
+    } else {
+        prepare_status_text(_controller, _messages)
+    };
+    TextLayout::centred(&header, 20.0, _theme.text).draw(_renderer, (160.0, 725.0, 400.0, 24.0));
+
+    if voting && vote.proposals.is_empty() {
+        TextLayout::centred("Move a piece to propose a move", 16.0, _theme.text).draw(_renderer, Vote::row(0));
+    }
+
+    let leading = vote.leading();
+    for (_index, _proposal) in vote.proposals.iter().enumerate() {
+        let row = Vote::row(_index);
+        _renderer.draw_rect(row, if Some(_index) == leading { [0.35, 0.35, 0.35, 1.0] } else { [0.25, 0.25, 0.25, 1.0] });
+
+        let voters: Vec<String> = _proposal.voters.iter().map(|_voter| (_voter + 1).to_string()).collect();
+        TextLayout::new()
+            .fragment(&format!("{}  ", _proposal.notation()), 16.0, [1.0, 1.0, 1.0, 1.0])
+            .fragment(&format!("voters {}", voters.join(", ")), 14.0, [0.75, 0.75, 0.75, 1.0])
+            .valign(VAlign::Middle)
+            .draw(_renderer, (row.0 + 8.0, row.1, row.2 - row.3 - 8.0, row.3));
+
+        TextLayout::centred("x", 16.0, [1.0, 0.5, 0.5, 1.0]).draw(_renderer, Vote::withdraw_button(_index));
+    }
+}
+
+/// Width of a capture on the timeline
+const CAPTURE_SLOT_WIDTH: f32 = 24.0;
+
+/// ## `timeline_slots`
+/// The captures that fit on the timeline, with the rectangle of each. When they no longer fit, the oldest
+/// captures scroll off to the left.
+/// ### Parameters
+/// - `_moves: &[MoveRecord]`: The moves of the game
+/// - `_geometry: (f32, f32, f32, f32)`: Area of the strip as (top_left_corner_x, top_left_corner_y, width, height)
+fn timeline_slots(_moves: &[MoveRecord], _geometry: (f32, f32, f32, f32)) -> Vec<((f32, f32, f32, f32), history::Capture)> {
+    let captures = history::captures(_moves);
+    let fitting = ((_geometry.2 - 10.0) / CAPTURE_SLOT_WIDTH) as usize;
+
+    captures.iter().skip(captures.len().saturating_sub(fitting)).enumerate()
+        .map(|(_slot, _capture)| {
+            let x = _geometry.0 + 5.0 + _slot as f32 * CAPTURE_SLOT_WIDTH;
+            ((x, _geometry.1 + 2.0, CAPTURE_SLOT_WIDTH - 2.0, _geometry.3 - 4.0), *_capture)
+        })
+        .collect()
+}
+
+/// ## `draw_capture_timeline`
+/// Draws the captured pieces left to right in the order they were taken, with the move number under each.
+/// When they no longer fit, the oldest captures scroll off to the left.
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_moves: &[MoveRecord]`: The moves of the game
+/// - `_geometry: (f32, f32, f32, f32)`: Area of the strip as (top_left_corner_x, top_left_corner_y, width, height)
+pub fn draw_capture_timeline(_renderer: &mut dyn Renderer, _moves: &[MoveRecord], _geometry: (f32, f32, f32, f32)) {
+    draw_rectangle(_renderer, _geometry);
+
+    for (_slot, _capture) in timeline_slots(_moves, _geometry) {
+        // Lost white pieces on a light backing, lost black ones on a dark one
+        let backing = if *get_piece_colour(&_capture.piece) == Colour::White { [0.35, 0.35, 0.35, 1.0] } else { [0.25, 0.25, 0.25, 1.0] };
+        _renderer.draw_rect(_slot, backing);
+
+        draw_icon(_renderer, (_slot.0, _slot.1), &_capture.piece, 0.24);
+
+        TextLayout::new().fragment(&_capture.move_number.to_string(), 11.0, [0.85, 0.85, 0.85, 1.0]).align(Align::Centre)
+            .draw(_renderer, (_slot.0, _geometry.1 + _geometry.3 - 14.0, _slot.2, 11.0));
+    }
+}
+
+/// ## `draw_capture_tooltip`
+/// Draws, above the capture timeline, when and by which move the capture under the cursor was taken,
+/// i.e. "Move 12: knight f3xe5 took the bishop". Nothing is drawn when the cursor is not on a capture.
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_moves: &[MoveRecord]`: The moves of the game
+/// - `_geometry: (f32, f32, f32, f32)`: Area of the strip as (top_left_corner_x, top_left_corner_y, width, height)
+/// - `_pointer: (f32, f32)`: Where the cursor is on the screen
+pub fn draw_capture_tooltip(_renderer: &mut dyn Renderer, _moves: &[MoveRecord], _geometry: (f32, f32, f32, f32), _pointer: (f32, f32)) {
+    const TOOLTIP_SIZE: (f32, f32) = (300.0, 30.0);
+
+    let (slot, capture) = match timeline_slots(_moves, _geometry).into_iter().find(|(_slot, _)| help_funcs::in_rect(*_slot, _pointer.0, _pointer.1)) {
+        Some(found) => found,
+        None => return
+    };
+    let text = format!("Move {}: {} {} took the {}", capture.move_number, export::piece_name(&capture.by),
+        _moves[capture.ply].notation(), export::piece_name(&capture.piece));
+
+    // Centred over the capture, kept inside the window
+    let x = (slot.0 + slot.2 / 2.0 - TOOLTIP_SIZE.0 / 2.0).max(5.0).min(consts::SCREEN_SIZE.0 - TOOLTIP_SIZE.0 - 5.0);
+    let geometry = (x, _geometry.1 - TOOLTIP_SIZE.1 - 4.0, TOOLTIP_SIZE.0, TOOLTIP_SIZE.1);
+
+    _renderer.draw_rect(geometry, [0.1, 0.1, 0.1, 0.9]);
+    TextLayout::centred(&text, 16.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, geometry);
+}
+
+/// ## `promotion_dialog`
+/// The choices of piece to promote to, over the panel below the board, each with its key
+/// ### Parameters
+/// - `_colour: Colour`: The colour of the promoting player
+/// ### Return
+/// The dialog, whose buttons are at `consts::PROMOTION_CHOICES` where the controller takes clicks on them too
+pub fn promotion_dialog(_colour: Colour) -> Dialog<PromotionKind> {
+    let choices = [
+        (Piece::Queen(_colour), PromotionKind::Queen, "Q", KeyCode::Q),
+        (Piece::Knight(_colour), PromotionKind::Knight, "N", KeyCode::N),
+        (Piece::Rook(_colour), PromotionKind::Rook, "R", KeyCode::R),
+        (Piece::Bishop(_colour), PromotionKind::Bishop, "B", KeyCode::B)
+    ];
+
+    choices.iter().zip(consts::PROMOTION_CHOICES.iter()).fold(Dialog::new(None), |_dialog, ((_piece, _kind, _hint, _key), _geometry)| {
+        _dialog.button(Button::new(*_geometry, _hint, *_kind).with_icon(*_piece).with_key(*_key))
+    })
+}
+
+/// ## `draw_promotion_panel`
+/// Draws the choices of piece to promote to in the panel below the board
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_colour: Colour`: The colour of the promoting player
+pub fn draw_promotion_panel(_renderer: &mut dyn Renderer, _colour: Colour) {
+    promotion_dialog(_colour).draw(_renderer);
+}
+
+/// ## `draw_button`
+/// Draws a grey button with a centered label
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_geometry: (f32, f32, f32, f32)`: Rectangle in this form (top_left_corner_x, top_left_corner_y, width, height)
+/// - `_label: &str`: The text on the button
+pub fn draw_button(_renderer: &mut dyn Renderer, _geometry: (f32, f32, f32, f32), _label: &str) {
+    draw_rectangle(_renderer, _geometry);
+
+    TextLayout::centred(_label, 24.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, _geometry);
+}
+
+/// ## `draw_focus`
+/// Draws the outline of the element the keyboard focus is on
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_geometry: (f32, f32, f32, f32)`: Rectangle of the element in this form (top_left_corner_x, top_left_corner_y, width, height)
+pub fn draw_focus(_renderer: &mut dyn Renderer, _geometry: (f32, f32, f32, f32)) {
+    let (x, y, width, height) = _geometry;
+    let (thickness, colour) = (3.0, [0.97, 0.3, 0.0, 1.0]);
+
+    _renderer.draw_rect((x - thickness, y - thickness, width + 2.0 * thickness, thickness), colour);
+    _renderer.draw_rect((x - thickness, y + height, width + 2.0 * thickness, thickness), colour);
+    _renderer.draw_rect((x - thickness, y, thickness, height), colour);
+    _renderer.draw_rect((x + width, y, thickness, height), colour);
+}
+
+/// ## `draw_slider`
+/// Draws a horizontal slider with its label and value above the track
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_geometry: (f32, f32, f32, f32)`: Rectangle of the track in this form (top_left_corner_x, top_left_corner_y, width, height)
+/// - `_label: &str`: What the slider sets
+/// - `_value: f32`: From 0 to 1, shown as a percentage
+pub fn draw_slider(_renderer: &mut dyn Renderer, _geometry: (f32, f32, f32, f32), _label: &str, _value: f32) {
+    let value = _value.max(0.0).min(1.0);
+    draw_labelled_slider(_renderer, _geometry, &format!("{}: {:.0}%", _label, value * 100.0), value);
+}
+
+/// ## `draw_labelled_slider`
+/// Draws a horizontal slider with a text of its own above the track, for values that are no percentage
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_geometry: (f32, f32, f32, f32)`: Rectangle of the track in this form (top_left_corner_x, top_left_corner_y, width, height)
+/// - `_text: &str`: Shown above the track, i.e. "Strength: ~1200 Elo"
+/// - `_value: f32`: From 0 to 1, how far the track is filled
+pub fn draw_labelled_slider(_renderer: &mut dyn Renderer, _geometry: (f32, f32, f32, f32), _text: &str, _value: f32) {
+    let value = _value.max(0.0).min(1.0);
+
+    _renderer.draw_text(_text, (_geometry.0, _geometry.1 - 30.0), 22.0, [1.0, 1.0, 1.0, 1.0]);
+    _renderer.draw_rect(_geometry, [0.3, 0.3, 0.3, 1.0]);
+    _renderer.draw_rect((_geometry.0, _geometry.1, _geometry.2 * value, _geometry.3), [0.97, 0.3, 0.0, 1.0]);
+    _renderer.draw_circle((_geometry.0 + _geometry.2 * value, _geometry.1 + _geometry.3 / 2.0), _geometry.3, [1.0, 1.0, 1.0, 1.0]);
+}
+
+/// ## `draw_thumbnail`
+/// Draws a position as a small board, i.e. a preview of a saved game. Drawn from a texture when the renderer keeps thumbnails.
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: Colours of the tiles
+/// - `_fen: &str`: The position, only the placement field is read
+/// - `_at: (f32, f32)`: Top left corner of the board
+/// - `_size: f32`: Width and height of the board
+pub fn draw_thumbnail(_renderer: &mut dyn Renderer, _theme: &Theme, _fen: &str, _at: (f32, f32), _size: f32) {
+    if !_renderer.draw_cached_thumbnail(_fen, _at, _size) {
+        draw_small_board(_renderer, _theme, &fen::placement(_fen).unwrap_or_default(), _at, _size);
+    }
+}
+
+/// ## `draw_small_board`
+/// Draws a small board with pieces tile by tile and piece by piece, what thumbnails are rendered with
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: Colours of the tiles
+/// - `_pieces: &[((u8, u8), Piece)]`: The pieces and their squares in GUI coordinates, see `fen::placement`
+/// - `_at: (f32, f32)`: Top left corner of the board
+/// - `_size: f32`: Width and height of the board
+pub fn draw_small_board(_renderer: &mut dyn Renderer, _theme: &Theme, _pieces: &[((u8, u8), Piece)], _at: (f32, f32), _size: f32) {
+    let cell = _size / consts::GRID_SIZE as f32;
+
+    for _row in 0..consts::GRID_SIZE {
+        for _col in 0..consts::GRID_SIZE {
+            let colour = if (_row + _col) % 2 == 0 { _theme.light_tile } else { _theme.dark_tile };
+            _renderer.draw_rect((_at.0 + _col as f32 * cell, _at.1 + _row as f32 * cell, cell, cell), colour);
+        }
+    }
+
+    for ((_col, _row), _piece) in _pieces {
+        _renderer.draw_sprite(_piece, (_at.0 + *_col as f32 * cell, _at.1 + *_row as f32 * cell), cell / consts::GRID_CELL_SIZE.0 as f32);
+    }
+}
+
+/// ## `draw_pawn_marks`
+/// Draws small icons on the bottom of the squares of pawns: D for doubled, I for isolated and P for passed
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_marks: &[((u8, u8), Vec<PawnFlag>)]`: The flagged pawns, see `pawn_structure::analyse`
+/// - `_flipped: bool`: If the board is seen from Black's side
+pub fn draw_pawn_marks(_renderer: &mut dyn Renderer, _marks: &[((u8, u8), Vec<PawnFlag>)], _flipped: bool) {
+    for (_square, _flags) in _marks {
+        let (col, row) = flip_square(*_square, _flipped);
+        let corner = (col as f32 * consts::GRID_CELL_SIZE.0 as f32, row as f32 * consts::GRID_CELL_SIZE.1 as f32);
+
+        for (_index, _flag) in _flags.iter().enumerate() {
+            let colour = match _flag {
+                PawnFlag::Doubled => [0.85, 0.55, 0.1, 0.95],
+                PawnFlag::Isolated => [0.8, 0.15, 0.15, 0.95],
+                PawnFlag::Passed => [0.15, 0.6, 0.25, 0.95]
+            };
+            let centre = (corner.0 + 12.0 + _index as f32 * 22.0, corner.1 + consts::GRID_CELL_SIZE.1 as f32 - 12.0);
+
+            _renderer.draw_circle(centre, 10.0, colour);
+            TextLayout::centred(_flag.letter(), 14.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, (centre.0 - 10.0, centre.1 - 10.0, 20.0, 20.0));
+        }
+    }
+}
+
+/// ## `draw_square_marks`
+/// Tints the squares the player marked with a right click
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_theme: &Theme`: The colours of the marks
+/// - `_marks: &HashMap<(u8, u8), MarkColour>`: The marked squares, see `GameController::marks`
+/// - `_flipped: bool`: If the board is seen from Black's side
+pub fn draw_square_marks(_renderer: &mut dyn Renderer, _theme: &Theme, _marks: &HashMap<(u8, u8), MarkColour>, _flipped: bool) {
+    for (_square, _colour) in _marks {
+        let (col, row) = flip_square(*_square, _flipped);
+        _renderer.draw_rect((
+            col as f32 * consts::GRID_CELL_SIZE.0 as f32,
+            row as f32 * consts::GRID_CELL_SIZE.1 as f32,
+            consts::GRID_CELL_SIZE.0 as f32,
+            consts::GRID_CELL_SIZE.1 as f32
+        ), _theme.marks[_colour.index()]);
+    }
+}
+
+/// ## `draw_heatmap`
+/// Tints the squares of the board, the more visits the stronger
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_visits: &[[u32; 8]; 8]`: Visits by square, indexed `[col][row]`, see `stats::heatmap`
+/// - `_tint: Rgba`: Colour of the most visited squares, its alpha is ignored
+/// - `_flipped: bool`: If the board is seen from Black's side
+pub fn draw_heatmap(_renderer: &mut dyn Renderer, _visits: &[[u32; 8]; 8], _tint: Rgba, _flipped: bool) {
+    let most = _visits.iter().flatten().copied().max().unwrap_or(0);
+    if most == 0 {
+        return;
+    }
+
+    for (_col, _column) in _visits.iter().enumerate() {
+        for (_row, _count) in _column.iter().enumerate().filter(|(_, _count)| **_count > 0) {
+            let alpha = 0.15 + 0.6 * *_count as f32 / most as f32;
+            let (col, row) = flip_square((_col as u8, _row as u8), _flipped);
+            _renderer.draw_rect((
+                col as f32 * consts::GRID_CELL_SIZE.0 as f32,
+                row as f32 * consts::GRID_CELL_SIZE.1 as f32,
+                consts::GRID_CELL_SIZE.0 as f32,
+                consts::GRID_CELL_SIZE.1 as f32
+            ), [_tint[0], _tint[1], _tint[2], alpha]);
+        }
+    }
+}
+
+/// ## `draw_line`
+/// Draws a straight line between two points
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_from: (f32, f32)`, `_to: (f32, f32)`: The ends of the line
+/// - `_thickness: f32`: Width of the line in pixels
+/// - `_colour: Rgba`: Colour of the line
+pub fn draw_line(_renderer: &mut dyn Renderer, _from: (f32, f32), _to: (f32, f32), _thickness: f32, _colour: Rgba) {
+    let length = (_to.0 - _from.0).hypot(_to.1 - _from.1);
+    if length < 0.01 {
+        return;
+    }
+    let half = _thickness / 2.0;
+    let across = (-(_to.1 - _from.1) / length * half, (_to.0 - _from.0) / length * half);
+
+    _renderer.draw_polygon(&[
+        (_from.0 + across.0, _from.1 + across.1), (_to.0 + across.0, _to.1 + across.1),
+        (_to.0 - across.0, _to.1 - across.1), (_from.0 - across.0, _from.1 - across.1)
+    ], _colour);
+}
+
+/// ## `draw_clock_graph`
+/// Plots the time both sides had left over the game, with the time trouble zone shaded red along the bottom
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_white: &[(usize, f32)]`, `_black: &[(usize, f32)]`: Points of (ply, seconds left) of each side, see `stats::clock_history`
+/// - `_trouble: f32`: Seconds left under which a side is in time trouble
+/// - `_geometry: (f32, f32, f32, f32)`: Area of the graph as (top_left_corner_x, top_left_corner_y, width, height)
+pub fn draw_clock_graph(_renderer: &mut dyn Renderer, _white: &[(usize, f32)], _black: &[(usize, f32)], _trouble: f32, _geometry: (f32, f32, f32, f32)) {
+    let (x, y, width, height) = _geometry;
+    _renderer.draw_rect(_geometry, [0.1, 0.1, 0.1, 0.9]);
+
+    // The plot leaves room for the times on the left and the move numbers below
+    let plot = (x + 60.0, y + 40.0, width - 80.0, height - 80.0);
+    let points = _white.iter().chain(_black);
+    let plies = points.clone().map(|(_ply, _)| *_ply).max().unwrap_or(0).max(1);
+    let most = points.map(|(_, _seconds)| *_seconds).fold(1.0, f32::max);
+    let at = |_ply: usize, _seconds: f32| (plot.0 + plot.2 * _ply as f32 / plies as f32, plot.1 + plot.3 * (1.0 - _seconds.min(most) / most));
+
+    TextLayout::centred("Time left", 22.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, (x, y + 8.0, width, 26.0));
+
+    let trouble_top = at(0, _trouble).1;
+    _renderer.draw_rect((plot.0, trouble_top, plot.2, plot.1 + plot.3 - trouble_top), [0.8, 0.1, 0.1, 0.3]);
+    _renderer.draw_text("time trouble", (plot.0 + 5.0, trouble_top + 2.0), 12.0, [1.0, 0.6, 0.6, 1.0]);
+
+    // Axes, with the longest time at the top and the number of moves at the end
+    let grey = [0.6, 0.6, 0.6, 1.0];
+    draw_line(_renderer, (plot.0, plot.1), (plot.0, plot.1 + plot.3), 1.0, grey);
+    draw_line(_renderer, (plot.0, plot.1 + plot.3), (plot.0 + plot.2, plot.1 + plot.3), 1.0, grey);
+    let seconds = most.ceil() as u32;
+    _renderer.draw_text(&format!("{}:{:02}", seconds / 60, seconds % 60), (x + 8.0, plot.1 - 6.0), 14.0, grey);
+    _renderer.draw_text("0:00", (x + 8.0, plot.1 + plot.3 - 8.0), 14.0, grey);
+    _renderer.draw_text("move 1", (plot.0, plot.1 + plot.3 + 8.0), 14.0, grey);
+    _renderer.draw_text(&format!("move {}", (plies + 1) / 2), (plot.0 + plot.2 - 60.0, plot.1 + plot.3 + 8.0), 14.0, grey);
+
+    for (_points, _colour) in [(_white, Colour::White), (_black, Colour::Black)].iter() {
+        for _pair in _points.windows(2) {
+            draw_line(_renderer, at(_pair[0].0, _pair[0].1), at(_pair[1].0, _pair[1].1), 3.0, side_colour(*_colour));
+        }
+    }
+}
+
+/// ## `draw_move_list`
+/// Draws the moves of a game as numbered rows, white's move then black's, with one move highlighted
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_moves: &[MoveRecord]`: The moves of the game
+/// - `_geometry: (f32, f32, f32, f32)`: Area of the list as (top_left_corner_x, top_left_corner_y, width, height)
+/// - `_scroll: usize`: How many rows are scrolled past
+/// - `_current: Option<usize>`: Index of the move to highlight
+pub fn draw_move_list(_renderer: &mut dyn Renderer, _moves: &[MoveRecord], _geometry: (f32, f32, f32, f32), _scroll: usize, _current: Option<usize>) {
+    draw_rectangle(_renderer, _geometry);
+
+    let visible_rows = (_geometry.3 / consts::MOVE_LIST_ROW_HEIGHT) as usize;
+
+    for (_row, _pair) in _moves.chunks(2).enumerate().skip(_scroll).take(visible_rows) {
+        let y = _geometry.1 + (_row - _scroll) as f32 * consts::MOVE_LIST_ROW_HEIGHT;
+        _renderer.draw_text(&format!("{}.", _row + 1), (_geometry.0 + 6.0, y + 3.0), 18.0, [0.7, 0.7, 0.7, 1.0]);
+
+        for (_side, _move) in _pair.iter().enumerate() {
+            let x = _geometry.0 + consts::MOVE_LIST_NUMBER_WIDTH + _side as f32 * consts::MOVE_LIST_MOVE_WIDTH;
+
+            if _current == Some(_row * 2 + _side) {
+                _renderer.draw_rect((x - 4.0, y, consts::MOVE_LIST_MOVE_WIDTH - 8.0, consts::MOVE_LIST_ROW_HEIGHT), [0.97, 0.3, 0.0, 1.0]);
+            }
+            _renderer.draw_text(&_move.notation(), (x, y + 3.0), 18.0, [1.0, 1.0, 1.0, 1.0]);
+        }
+    }
+}
+
+/// ## `draw_chat`
+/// Draws the last lines of the spectator chat as "name: text" on a dark box, newest at the bottom, with the line
+/// being typed below them
+/// ### Parameters
+/// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+/// - `_lines: &[ChatLine]`: The chat, oldest first
+/// - `_typing: Option<&str>`: The line being typed, if any
+/// - `_geometry: (f32, f32, f32, f32)`: Area of the chat as (top_left_corner_x, top_left_corner_y, width, height)
+pub fn draw_chat(_renderer: &mut dyn Renderer, _lines: &[ChatLine], _typing: Option<&str>, _geometry: (f32, f32, f32, f32)) {
+    const LINE_HEIGHT: f32 = 22.0;
+
+    _renderer.draw_rect(_geometry, [0.0, 0.0, 0.0, 0.7]);
+
+    let rows = ((_geometry.3 / LINE_HEIGHT) as usize).saturating_sub(if _typing.is_some() { 1 } else { 0 });
+    for (_row, _line) in _lines.iter().skip(_lines.len().saturating_sub(rows)).enumerate() {
+        let y = _geometry.1 + 4.0 + _row as f32 * LINE_HEIGHT;
+        let name = format!("{}:", _line.name);
+        _renderer.draw_text(&name, (_geometry.0 + 8.0, y), 16.0, [0.97, 0.6, 0.3, 1.0]);
+
+        let indent = _renderer.text_size(&name, 16.0).0 + 14.0;
+        _renderer.draw_text(&_line.text, (_geometry.0 + indent, y), 16.0, [1.0, 1.0, 1.0, 1.0]);
+    }
+
+    if let Some(typing) = _typing {
+        let y = _geometry.1 + _geometry.3 - LINE_HEIGHT;
+        _renderer.draw_text(&format!("> {}_", typing), (_geometry.0 + 8.0, y), 16.0, [0.6, 1.0, 0.6, 1.0]);
+    }
+}
@@ -0,0 +1,61 @@
+use ggez::{graphics, Context, GameResult};
+
+/// The font all text is drawn with, bundled in `resources`. DejaVu Sans, which has the chess symbols (♔ to ♟) too.
+pub const FONT_FILE: &str = "/fonts/DejaVuSans.ttf";
+
+/// The font of the interface and the scale factor of the display it is drawn on.
+///
+/// Text sizes in the interface are in window coordinates. On a high DPI display a window coordinate is several
+/// pixels, so text is rasterised at its size in pixels and drawn scaled back down, keeping it sharp.
+pub struct FontManager {
+    font: graphics::Font,
+    scale_factor: f32, // Pixels per window coordinate
+    text_scale: f32 // Every text is drawn this many times larger, for low vision
+}
+
+impl FontManager {
+    /// Loads the bundled font
+    pub fn load(ctx: &mut Context) -> GameResult<FontManager> {
+        let font = graphics::Font::new(ctx, FONT_FILE)?;
+        let mut fonts = FontManager { font, scale_factor: 1.0, text_scale: 1.0 };
+        fonts.update_scale_factor(ctx);
+
+        Ok(fonts)
+    }
+
+    /// The built-in font of GGEZ, for when the bundled one cannot be loaded
+    pub fn fallback() -> FontManager {
+        FontManager { font: graphics::Font::default(), scale_factor: 1.0, text_scale: 1.0 }
+    }
+
+    /// Picks up the scale factor of the display the window is on, which changes when it is moved to another screen
+    pub fn update_scale_factor(&mut self, ctx: &Context) {
+        self.scale_factor = graphics::window(ctx).scale_factor().max(1.0) as f32;
+    }
+
+    /// Draws every text larger, never smaller than its size
+    pub fn set_text_scale(&mut self, _scale: f32) {
+        self.text_scale = _scale.max(1.0);
+    }
+
+    /// Pixels per window coordinate
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// ## `text`
+    /// Builds a GGEZ text rasterised for the display
+    /// ### Parameters
+    /// - `_text: &str`: The text
+    /// - `_size: f32`: Height in window coordinates, before the larger text setting
+    /// ### Return
+    /// The text, to be drawn scaled by `1.0 / scale_factor()`
+    pub fn text(&self, _text: &str, _size: f32) -> graphics::Text {
+        let pixels = _size * self.text_scale * self.scale_factor;
+
+        graphics::Text::new(
+            graphics::TextFragment::from(_text)
+            .font(self.font)
+            .scale(graphics::PxScale { x: pixels, y: pixels }))
+    }
+}
@@ -0,0 +1,69 @@
+/// What is drawn on the screen, in layers from the bottom up. Each layer can be hidden on its own, i.e. to take a clean
+/// picture of the board for a stream or to see what the indicators hide.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    Board, // The tiles
+    Pieces, // The pieces at rest and the one in hand
+    Indicators, // Legal moves of the selected piece
+    Annotations, // Arrows and marks drawn over the board, i.e. the pawn structure
+    Hud, // Status panel, clocks, buttons and toasts
+    Modals // Overlays over the scene below them, i.e. the promotion choice or the game over screen
+}
+
+impl Layer {
+    /// Every layer, in the order they are drawn
+    pub const ALL: [Layer; 6] = [Layer::Board, Layer::Pieces, Layer::Indicators, Layer::Annotations, Layer::Hud, Layer::Modals];
+
+    /// Name of the layer, as typed in the console
+    pub fn name(&self) -> &'static str {
+        match self {
+            Layer::Board => "board",
+            Layer::Pieces => "pieces",
+            Layer::Indicators => "indicators",
+            Layer::Annotations => "annotations",
+            Layer::Hud => "hud",
+            Layer::Modals => "modals"
+        }
+    }
+
+    /// The layer with a name, None if there is no such layer
+    pub fn from_name(_name: &str) -> Option<Layer> {
+        Layer::ALL.iter().copied().find(|_layer| _layer.name() == _name)
+    }
+}
+
+/// Which layers are drawn, all of them by default
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Layers {
+    hidden: [bool; 6] // Indexed by the position of the layer in `Layer::ALL`
+}
+
+impl Layers {
+    /// If a layer is drawn
+    pub fn is_visible(&self, _layer: Layer) -> bool {
+        !self.hidden[_layer as usize]
+    }
+
+    /// Shows or hides a layer
+    pub fn set_visible(&mut self, _layer: Layer, _visible: bool) {
+        self.hidden[_layer as usize] = !_visible;
+    }
+
+    /// Hides a shown layer or shows a hidden one. Returns if it is now drawn.
+    pub fn toggle(&mut self, _layer: Layer) -> bool {
+        let visible = !self.is_visible(_layer);
+        self.set_visible(_layer, visible);
+        visible
+    }
+
+    /// ## `draw`
+    /// Draws a layer, if it is visible
+    /// ### Parameters
+    /// - `_layer: Layer`: The layer the drawing belongs to
+    /// - `_draw: impl FnOnce()`: Draws the layer
+    pub fn draw(&self, _layer: Layer, _draw: impl FnOnce()) {
+        if self.is_visible(_layer) {
+            _draw();
+        }
+    }
+}
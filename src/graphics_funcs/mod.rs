@@ -1 +1,8 @@
-pub mod draw_funcs;
\ No newline at end of file
+pub mod draw_funcs;
+pub mod fonts;
+pub mod layers;
+pub mod offscreen;
+pub mod renderer;
+pub mod text_layout;
+pub mod thumbnails;
+pub mod widgets;
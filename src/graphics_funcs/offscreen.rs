@@ -0,0 +1,97 @@
+use eliasfl_chess::Piece;
+use ggez::{conf, graphics, Context, ContextBuilder, GameResult, event::EventLoop};
+use std::{collections::HashMap, path};
+use super::super::{consts, fen, theme::Theme, AppState};
+use super::draw_funcs;
+use super::renderer::GgezRenderer;
+
+/// ## `headless_context`
+/// Builds a GGEZ context whose window is never shown. Used to render positions (image export, regression images) without a visible window.
+/// ### Parameters
+/// - `_resource_dir: path::PathBuf`: Directory holding the piece sprites
+/// ### Return
+/// The context and its event loop, same as `ContextBuilder::build`
+pub fn headless_context(_resource_dir: path::PathBuf) -> GameResult<(Context, EventLoop<()>)> {
+    ContextBuilder::new("schack", "viola")
+        .add_resource_path(_resource_dir)
+        .window_mode(
+            conf::WindowMode::default()
+                .dimensions(consts::SCREEN_SIZE.0, consts::SCREEN_SIZE.1)
+                .visible(false)                 // Never show the window
+        )
+        .build()
+}
+
+/// ## `render_to_image`
+/// Draws the current state of the AppState to an offscreen canvas instead of the window.
+/// ### Parameters
+/// - `_ctx: &mut Context`: Instace of GGEZ context
+/// - `_appstate: &mut AppState`: Reference to the AppState instance
+/// ### Return
+/// A `ggez::graphics::Image` the size of the window holding the rendered interface
+pub fn render_to_image(_ctx: &mut Context, _appstate: &mut AppState) -> GameResult<graphics::Image> {
+    let canvas = graphics::Canvas::with_window_size(_ctx)?;
+    let coordinates = graphics::screen_coordinates(_ctx);
+
+    draw_offscreen(_ctx, &canvas, coordinates, |_ctx| _appstate.draw_scene(_ctx))?;
+
+    Ok(canvas.image().clone())
+}
+
+/// ## `render_thumbnail`
+/// Draws a position as a small board on its own canvas, to be drawn in menus and lists without redrawing every piece
+/// ### Parameters
+/// - `_ctx: &mut Context`: Instace of GGEZ context
+/// - `_sprites: &HashMap<Piece, graphics::Image>`: The piece sprites
+/// - `_theme: &Theme`: Colours of the tiles
+/// - `_fen: &str`: The position, only the placement field is read
+/// - `_size: u16`: Width and height of the thumbnail in pixels
+/// ### Return
+/// The canvas holding the thumbnail
+pub fn render_thumbnail(_ctx: &mut Context, _sprites: &HashMap<Piece, graphics::Image>, _theme: &Theme, _fen: &str, _size: u16) -> GameResult<graphics::Canvas> {
+    let canvas = graphics::Canvas::new(_ctx, _size, _size, conf::NumSamples::One, graphics::get_window_color_format(_ctx))?;
+    let coordinates = graphics::Rect::new(0.0, 0.0, _size as f32, _size as f32);
+
+    draw_offscreen(_ctx, &canvas, coordinates, |_ctx| {
+        let mut renderer = GgezRenderer::new(_ctx, _sprites);
+        draw_funcs::draw_small_board(&mut renderer, _theme, &fen::placement(_fen).unwrap_or_default(), (0.0, 0.0), _size as f32);
+        Ok(())
+    })?;
+
+    Ok(canvas)
+}
+
+/// Redirects all drawing to a canvas, with its own screen coordinates, and then returns to the window
+fn draw_offscreen<F>(_ctx: &mut Context, _canvas: &graphics::Canvas, _coordinates: graphics::Rect, _draw: F) -> GameResult
+    where F: FnOnce(&mut Context) -> GameResult {
+    let window_coordinates = graphics::screen_coordinates(_ctx);
+
+    graphics::set_canvas(_ctx, Some(_canvas));
+    graphics::set_screen_coordinates(_ctx, _coordinates)?;
+    let result = _draw(_ctx);
+    graphics::set_canvas(_ctx, None);
+    graphics::set_screen_coordinates(_ctx, window_coordinates)?;
+
+    result
+}
+
+/// ## `render_to_rgba`
+/// Same as `render_to_image` but returns the raw pixels, useful when comparing against a reference image.
+/// ### Parameters
+/// - `_ctx: &mut Context`: Instace of GGEZ context
+/// - `_appstate: &mut AppState`: Reference to the AppState instance
+/// ### Return
+/// The pixels as RGBA bytes, row by row
+pub fn render_to_rgba(_ctx: &mut Context, _appstate: &mut AppState) -> GameResult<Vec<u8>> {
+    render_to_image(_ctx, _appstate)?.to_rgba8(_ctx)
+}
+
+/// ## `save_png`
+/// Renders the current state offscreen and saves it as a PNG.
+/// ### Parameters
+/// - `_ctx: &mut Context`: Instace of GGEZ context
+/// - `_appstate: &mut AppState`: Reference to the AppState instance
+/// - `_path: &str`: Path inside the GGEZ user directory, i.e "/position.png"
+pub fn save_png(_ctx: &mut Context, _appstate: &mut AppState, _path: &str) -> GameResult {
+    render_to_image(_ctx, _appstate)?.encode(_ctx, graphics::ImageFormat::Png, _path)
+}
@@ -0,0 +1,441 @@
+use eliasfl_chess::Piece;
+use ggez::{graphics, Context};
+use std::collections::HashMap;
+use super::thumbnails::ThumbnailCache;
+use super::fonts::FontManager;
+use super::super::consts::LEGAL_INDICATOR_RADIUS;
+
+/// A colour as red, green, blue and alpha between 0.0 and 1.0. Backend independent.
+pub type Rgba = [f32; 4];
+
+/// The drawing primitives the interface is built from. Implement this to draw the board with another backend than GGEZ.
+pub trait Renderer {
+    /// Draws the sprite of a piece with its top left corner at `_at`, scaled by `_scale` (1.0 fills one tile)
+    fn draw_sprite(&mut self, _piece: &Piece, _at: (f32, f32), _scale: f32);
+
+    /// Draws the sprite of a piece see-through, `_alpha` from 0.0 (invisible) to 1.0. Renderers without transparency draw it solid.
+    fn draw_sprite_alpha(&mut self, _piece: &Piece, _at: (f32, f32), _scale: f32, _alpha: f32) {
+        self.draw_sprite(_piece, _at, _scale);
+    }
+
+    /// Draws a filled rectangle given as (top_left_corner_x, top_left_corner_y, width, height)
+    fn draw_rect(&mut self, _geometry: (f32, f32, f32, f32), _colour: Rgba);
+
+    /// Draws a filled circle
+    fn draw_circle(&mut self, _centre: (f32, f32), _radius: f32, _colour: Rgba);
+
+    /// Draws a filled convex polygon through its corners, in order
+    fn draw_polygon(&mut self, _points: &[(f32, f32)], _colour: Rgba);
+
+    /// Draws the indicator of a legal move in the middle of a tile `_cell` wide, sized to the tile.
+    /// Renderers without smoother indicators draw a plain circle.
+    fn draw_indicator(&mut self, _centre: (f32, f32), _cell: f32, _colour: Rgba) {
+        self.draw_circle(_centre, _cell * LEGAL_INDICATOR_RADIUS, _colour);
+    }
+
+    /// Draws a line of text with its top left corner at `_at`, `_size` is the height in pixels
+    fn draw_text(&mut self, _text: &str, _at: (f32, f32), _size: f32, _colour: Rgba);
+
+    /// Width and height a text would take if drawn with `draw_text`
+    fn text_size(&mut self, _text: &str, _size: f32) -> (f32, f32);
+
+    /// Draws the thumbnail of a position (FEN) from a texture, if the renderer keeps them. Returns false when there is none to draw,
+    /// `draw_funcs::draw_thumbnail` then draws the board piece by piece.
+    fn draw_cached_thumbnail(&mut self, _fen: &str, _at: (f32, f32), _size: f32) -> bool {
+        false
+    }
+
+    /// Draws an image of the resources (i.e. an avatar) stretched over a rectangle, if the renderer has it loaded.
+    /// Returns false when it has not, the caller then draws something in its place.
+    fn draw_image(&mut self, _path: &str, _geometry: (f32, f32, f32, f32)) -> bool {
+        false
+    }
+}
+
+/// Radius of the prebuilt circle, scaled to the radius drawn
+const PREBUILT_RADIUS: f32 = 32.0;
+
+/// Shapes built once and scaled into place, so drawing a frame builds no meshes. Used in low-spec mode.
+pub struct PrebuiltShapes {
+    pixel: graphics::Image, // White, stretched and tinted into rectangles
+    circle: graphics::Mesh // White, of `PREBUILT_RADIUS` around (0, 0)
+}
+
+impl PrebuiltShapes {
+    /// Builds the shapes
+    pub fn new(_ctx: &mut Context) -> ggez::GameResult<PrebuiltShapes> {
+        Ok(PrebuiltShapes {
+            pixel: graphics::Image::solid(_ctx, 1, graphics::Color::WHITE)?,
+            circle: graphics::Mesh::new_circle(_ctx, graphics::DrawMode::fill(), ggez::mint::Point2 { x: 0.0, y: 0.0 },
+                PREBUILT_RADIUS, 0.5, graphics::Color::WHITE)?
+        })
+    }
+}
+
+/// Sizes of indicators kept at most, the board being zoomed in and out would otherwise keep adding sizes
+const INDICATOR_SIZES: usize = 16;
+
+/// Physical pixels over which the edge of an indicator fades out, smoothing it without multisampling
+const INDICATOR_FEATHER: f32 = 1.5;
+
+/// Legal move indicators built once per tile size, with an edge fading out so they are smooth at any size and on any display
+pub struct IndicatorCache {
+    meshes: HashMap<u32, graphics::Mesh> // White, around (0, 0), by the width of the tile in physical pixels
+}
+
+impl IndicatorCache {
+    /// Creates an empty cache, indicators are built the first time they are drawn at a size
+    pub fn new() -> IndicatorCache {
+        IndicatorCache { meshes: HashMap::new() }
+    }
+
+    /// ## `get`
+    /// The indicator for a tile size, built if there is none yet
+    /// ### Parameters
+    /// - `_ctx: &mut Context`: Instance of GGEZ context
+    /// - `_cell: f32`: Width of the tile in window coordinates
+    /// ### Return
+    /// The mesh, in window coordinates, to be drawn tinted at the centre of the tile
+    fn get(&mut self, _ctx: &mut Context, _cell: f32) -> ggez::GameResult<&graphics::Mesh> {
+        let scale_factor = graphics::window(_ctx).scale_factor().max(1.0) as f32;
+        let key = (_cell * scale_factor).round() as u32;
+
+        if !self.meshes.contains_key(&key) {
+            if self.meshes.len() >= INDICATOR_SIZES {
+                self.meshes.clear();
+            }
+            self.meshes.insert(key, IndicatorCache::build(_ctx, _cell, scale_factor)?);
+        }
+
+        Ok(&self.meshes[&key])
+    }
+
+    /// Builds an indicator: a solid disc and rings around it fading out over `INDICATOR_FEATHER` pixels
+    fn build(_ctx: &mut Context, _cell: f32, _scale_factor: f32) -> ggez::GameResult<graphics::Mesh> {
+        const RINGS: usize = 3;
+        let radius = _cell * LEGAL_INDICATOR_RADIUS;
+        let feather = INDICATOR_FEATHER / _scale_factor;
+        let tolerance = 0.1 / _scale_factor; // A tenth of a pixel off the true circle at most
+        let centre = ggez::mint::Point2 { x: 0.0, y: 0.0 };
+
+        let mut builder = graphics::MeshBuilder::new();
+        builder.circle(graphics::DrawMode::fill(), centre, radius - feather / 2.0, tolerance, graphics::Color::WHITE)?;
+        for _ring in 0..RINGS {
+            let part = (_ring as f32 + 0.5) / RINGS as f32;
+            builder.circle(graphics::DrawMode::stroke(feather / RINGS as f32), centre, radius - feather / 2.0 + feather * part,
+                tolerance, graphics::Color::new(1.0, 1.0, 1.0, 1.0 - part))?;
+        }
+
+        builder.build(_ctx)
+    }
+}
+
+impl Default for IndicatorCache {
+    fn default() -> IndicatorCache {
+        IndicatorCache::new()
+    }
+}
+
+/// `Renderer` drawing with GGEZ on whatever the current render target is (window or canvas)
+pub struct GgezRenderer<'a> {
+    ctx: &'a mut Context,
+    sprites: &'a HashMap<Piece, graphics::Image>,
+    thumbnails: Option<&'a mut ThumbnailCache>, // Without one, thumbnails are drawn piece by piece
+    fonts: Option<&'a FontManager>, // Without one, text is drawn in the default font of GGEZ
+    images: Option<&'a HashMap<String, Option<graphics::Image>>>, // Images by path, None for the ones that failed to load
+    shapes: Option<&'a PrebuiltShapes>, // Rectangles and circles are scaled from these instead of built, when given
+    indicators: Option<&'a mut IndicatorCache> // Without one, legal move indicators are plain circles
+}
+
+impl<'a> GgezRenderer<'a> {
+    /// Creates a renderer drawing pieces with the given sprites
+    pub fn new(_ctx: &'a mut Context, _sprites: &'a HashMap<Piece, graphics::Image>) -> GgezRenderer<'a> {
+        GgezRenderer { ctx: _ctx, sprites: _sprites, thumbnails: None, fonts: None, images: None, shapes: None, indicators: None }
+    }
+
+    /// Draws thumbnails from a cache of textures instead of piece by piece
+    pub fn with_thumbnails(mut self, _thumbnails: &'a mut ThumbnailCache) -> GgezRenderer<'a> {
+        self.thumbnails = Some(_thumbnails);
+        self
+    }
+
+    /// Draws text in the bundled font, sharp on high DPI displays
+    pub fn with_fonts(mut self, _fonts: &'a FontManager) -> GgezRenderer<'a> {
+        self.fonts = Some(_fonts);
+        self
+    }
+
+    /// Draws the avatars and flags loaded by the application
+    pub fn with_images(mut self, _images: &'a HashMap<String, Option<graphics::Image>>) -> GgezRenderer<'a> {
+        self.images = Some(_images);
+        self
+    }
+
+    /// Draws rectangles and circles by scaling prebuilt shapes rather than building a mesh for each
+    pub fn with_shapes(mut self, _shapes: &'a PrebuiltShapes) -> GgezRenderer<'a> {
+        self.shapes = Some(_shapes);
+        self
+    }
+
+    /// Draws legal move indicators smooth and sized to the tiles, from meshes built once per size
+    pub fn with_indicators(mut self, _indicators: &'a mut IndicatorCache) -> GgezRenderer<'a> {
+        self.indicators = Some(_indicators);
+        self
+    }
+
+    /// Builds a GGEZ text of the given size, with the scale to draw it at
+    fn text(&self, _text: &str, _size: f32) -> (graphics::Text, f32) {
+        match self.fonts {
+            Some(fonts) => (fonts.text(_text, _size), 1.0 / fonts.scale_factor()),
+            None => (graphics::Text::new(
+                graphics::TextFragment::from(_text)
+                .scale(graphics::PxScale { x: _size, y: _size })), 1.0)
+        }
+    }
+}
+
+impl<'a> Renderer for GgezRenderer<'a> {
+    fn draw_sprite(&mut self, _piece: &Piece, _at: (f32, f32), _scale: f32) {
+        graphics::draw(self.ctx, self.sprites.get(_piece).unwrap(), graphics::DrawParam::default()
+                    .scale([_scale, _scale])
+                    .dest(
+                        [_at.0, _at.1],
+                    )).expect("Failed to draw piece.");
+    }
+
+    fn draw_sprite_alpha(&mut self, _piece: &Piece, _at: (f32, f32), _scale: f32, _alpha: f32) {
+        graphics::draw(self.ctx, self.sprites.get(_piece).unwrap(), graphics::DrawParam::default()
+                    .scale([_scale, _scale])
+                    .color([1.0, 1.0, 1.0, _alpha].into())
+                    .dest([_at.0, _at.1])).expect("Failed to draw piece.");
+    }
+
+    fn draw_rect(&mut self, _geometry: (f32, f32, f32, f32), _colour: Rgba) {
+        if let Some(shapes) = self.shapes {
+            graphics::draw(self.ctx, &shapes.pixel, graphics::DrawParam::default()
+                .scale([_geometry.2, _geometry.3])
+                .color(_colour.into())
+                .dest([_geometry.0, _geometry.1])).expect("Failed to draw rectangle.");
+            return;
+        }
+
+        let rectangle = graphics::Mesh::new_rectangle(self.ctx, graphics::DrawMode::fill(),
+                            graphics::Rect::new(_geometry.0, _geometry.1, _geometry.2, _geometry.3),
+                            _colour.into()).expect("Failed to create rectangle.");
+
+        graphics::draw(self.ctx, &rectangle, graphics::DrawParam::default()).expect("Failed to draw rectangle.");
+    }
+
+    fn draw_circle(&mut self, _centre: (f32, f32), _radius: f32, _colour: Rgba) {
+        if let Some(shapes) = self.shapes {
+            let scale = _radius / PREBUILT_RADIUS;
+            graphics::draw(self.ctx, &shapes.circle, graphics::DrawParam::default()
+                .scale([scale, scale])
+                .color(_colour.into())
+                .dest([_centre.0, _centre.1])).expect("Failed to draw circle.");
+            return;
+        }
+
+        let circle = graphics::Mesh::new_circle(self.ctx,
+            graphics::DrawMode::fill(), ggez::mint::Point2{
+            x: _centre.0,
+            y: _centre.1
+        }, _radius, 1.0, _colour.into()).expect("Failed to create circle.");
+
+        graphics::draw(self.ctx, &circle, graphics::DrawParam::default()).expect("Failed to draw circle.");
+    }
+
+    // Polygons are few and differ every time (arrows), so they are built even in low-spec mode
+    fn draw_polygon(&mut self, _points: &[(f32, f32)], _colour: Rgba) {
+        let points: Vec<ggez::mint::Point2<f32>> = _points.iter().map(|_point| ggez::mint::Point2 { x: _point.0, y: _point.1 }).collect();
+        let polygon = graphics::Mesh::new_polygon(self.ctx, graphics::DrawMode::fill(), &points, _colour.into())
+            .expect("Failed to create polygon.");
+
+        graphics::draw(self.ctx, &polygon, graphics::DrawParam::default()).expect("Failed to draw polygon.");
+    }
+
+    fn draw_indicator(&mut self, _centre: (f32, f32), _cell: f32, _colour: Rgba) {
+        let mesh = match self.indicators.as_mut() {
+            Some(indicators) => indicators.get(self.ctx, _cell).expect("Failed to create indicator.").clone(),
+            None => return self.draw_circle(_centre, _cell * LEGAL_INDICATOR_RADIUS, _colour)
+        };
+
+        graphics::draw(self.ctx, &mesh, graphics::DrawParam::default()
+            .color(_colour.into())
+            .dest([_centre.0, _centre.1])).expect("Failed to draw indicator.");
+    }
+
+    fn draw_text(&mut self, _text: &str, _at: (f32, f32), _size: f32, _colour: Rgba) {
+        let (text, scale) = self.text(_text, _size);
+        graphics::draw(self.ctx, &text, graphics::DrawParam::default()
+            .color(_colour.into())
+            .scale([scale, scale])
+            .dest(ggez::mint::Point2 { x: _at.0, y: _at.1 })).expect("Failed to draw text.");
+    }
+
+    fn text_size(&mut self, _text: &str, _size: f32) -> (f32, f32) {
+        let (text, scale) = self.text(_text, _size);
+        let dimensions = text.dimensions(self.ctx);
+        (dimensions.w as f32 * scale, dimensions.h as f32 * scale)
+    }
+
+    fn draw_cached_thumbnail(&mut self, _fen: &str, _at: (f32, f32), _size: f32) -> bool {
+        match self.thumbnails.as_mut().and_then(|_thumbnails| _thumbnails.get(_fen, _size)) {
+            Some(canvas) => {
+                graphics::draw(self.ctx, canvas, graphics::DrawParam::default().dest([_at.0, _at.1])).expect("Failed to draw thumbnail.");
+                true
+            },
+            None => false
+        }
+    }
+
+    fn draw_image(&mut self, _path: &str, _geometry: (f32, f32, f32, f32)) -> bool {
+        match self.images.and_then(|_images| _images.get(_path)).and_then(|_image| _image.as_ref()) {
+            Some(image) => {
+                let scale = [_geometry.2 / image.width() as f32, _geometry.3 / image.height() as f32];
+                graphics::draw(self.ctx, image, graphics::DrawParam::default().scale(scale).dest([_geometry.0, _geometry.1]))
+                    .expect("Failed to draw image.");
+                true
+            },
+            None => false
+        }
+    }
+}
+
+/// `Renderer` moving everything drawn through it by an offset, i.e. to slide a part of the interface
+pub struct OffsetRenderer<'a> {
+    inner: &'a mut dyn Renderer,
+    offset: (f32, f32)
+}
+
+impl<'a> OffsetRenderer<'a> {
+    /// Creates a renderer drawing onto `_inner` moved by `_offset`
+    pub fn new(_inner: &'a mut dyn Renderer, _offset: (f32, f32)) -> OffsetRenderer<'a> {
+        OffsetRenderer { inner: _inner, offset: _offset }
+    }
+
+    fn moved(&self, _at: (f32, f32)) -> (f32, f32) {
+        (_at.0 + self.offset.0, _at.1 + self.offset.1)
+    }
+}
+
+impl<'a> Renderer for OffsetRenderer<'a> {
+    fn draw_sprite(&mut self, _piece: &Piece, _at: (f32, f32), _scale: f32) {
+        let at = self.moved(_at);
+        self.inner.draw_sprite(_piece, at, _scale);
+    }
+
+    fn draw_sprite_alpha(&mut self, _piece: &Piece, _at: (f32, f32), _scale: f32, _alpha: f32) {
+        let at = self.moved(_at);
+        self.inner.draw_sprite_alpha(_piece, at, _scale, _alpha);
+    }
+
+    fn draw_rect(&mut self, _geometry: (f32, f32, f32, f32), _colour: Rgba) {
+        let at = self.moved((_geometry.0, _geometry.1));
+        self.inner.draw_rect((at.0, at.1, _geometry.2, _geometry.3), _colour);
+    }
+
+    fn draw_circle(&mut self, _centre: (f32, f32), _radius: f32, _colour: Rgba) {
+        let centre = self.moved(_centre);
+        self.inner.draw_circle(centre, _radius, _colour);
+    }
+
+    fn draw_polygon(&mut self, _points: &[(f32, f32)], _colour: Rgba) {
+        let points: Vec<(f32, f32)> = _points.iter().map(|_point| self.moved(*_point)).collect();
+        self.inner.draw_polygon(&points, _colour);
+    }
+
+    fn draw_indicator(&mut self, _centre: (f32, f32), _cell: f32, _colour: Rgba) {
+        let centre = self.moved(_centre);
+        self.inner.draw_indicator(centre, _cell, _colour);
+    }
+
+    fn draw_text(&mut self, _text: &str, _at: (f32, f32), _size: f32, _colour: Rgba) {
+        let at = self.moved(_at);
+        self.inner.draw_text(_text, at, _size, _colour);
+    }
+
+    fn text_size(&mut self, _text: &str, _size: f32) -> (f32, f32) {
+        self.inner.text_size(_text, _size)
+    }
+
+    fn draw_cached_thumbnail(&mut self, _fen: &str, _at: (f32, f32), _size: f32) -> bool {
+        let at = self.moved(_at);
+        self.inner.draw_cached_thumbnail(_fen, at, _size)
+    }
+
+    fn draw_image(&mut self, _path: &str, _geometry: (f32, f32, f32, f32)) -> bool {
+        let at = self.moved((_geometry.0, _geometry.1));
+        self.inner.draw_image(_path, (at.0, at.1, _geometry.2, _geometry.3))
+    }
+}
+
+/// `Renderer` scaling everything drawn through it and then moving it, i.e. to draw the board zoomed in (see `BoardView`)
+pub struct ZoomRenderer<'a> {
+    inner: &'a mut dyn Renderer,
+    zoom: f32,
+    offset: (f32, f32)
+}
+
+impl<'a> ZoomRenderer<'a> {
+    /// Creates a renderer drawing onto `_inner` scaled by `_zoom`, with what would be drawn at (0, 0) at `_offset`
+    pub fn new(_inner: &'a mut dyn Renderer, _zoom: f32, _offset: (f32, f32)) -> ZoomRenderer<'a> {
+        ZoomRenderer { inner: _inner, zoom: _zoom, offset: _offset }
+    }
+
+    fn moved(&self, _at: (f32, f32)) -> (f32, f32) {
+        (_at.0 * self.zoom + self.offset.0, _at.1 * self.zoom + self.offset.1)
+    }
+}
+
+impl<'a> Renderer for ZoomRenderer<'a> {
+    fn draw_sprite(&mut self, _piece: &Piece, _at: (f32, f32), _scale: f32) {
+        let at = self.moved(_at);
+        self.inner.draw_sprite(_piece, at, _scale * self.zoom);
+    }
+
+    fn draw_sprite_alpha(&mut self, _piece: &Piece, _at: (f32, f32), _scale: f32, _alpha: f32) {
+        let at = self.moved(_at);
+        self.inner.draw_sprite_alpha(_piece, at, _scale * self.zoom, _alpha);
+    }
+
+    fn draw_rect(&mut self, _geometry: (f32, f32, f32, f32), _colour: Rgba) {
+        let at = self.moved((_geometry.0, _geometry.1));
+        self.inner.draw_rect((at.0, at.1, _geometry.2 * self.zoom, _geometry.3 * self.zoom), _colour);
+    }
+
+    fn draw_circle(&mut self, _centre: (f32, f32), _radius: f32, _colour: Rgba) {
+        let centre = self.moved(_centre);
+        self.inner.draw_circle(centre, _radius * self.zoom, _colour);
+    }
+
+    fn draw_polygon(&mut self, _points: &[(f32, f32)], _colour: Rgba) {
+        let points: Vec<(f32, f32)> = _points.iter().map(|_point| self.moved(*_point)).collect();
+        self.inner.draw_polygon(&points, _colour);
+    }
+
+    fn draw_indicator(&mut self, _centre: (f32, f32), _cell: f32, _colour: Rgba) {
+        let centre = self.moved(_centre);
+        self.inner.draw_indicator(centre, _cell * self.zoom, _colour);
+    }
+
+    fn draw_text(&mut self, _text: &str, _at: (f32, f32), _size: f32, _colour: Rgba) {
+        let at = self.moved(_at);
+        self.inner.draw_text(_text, at, _size * self.zoom, _colour);
+    }
+
+    fn text_size(&mut self, _text: &str, _size: f32) -> (f32, f32) {
+        let size = self.inner.text_size(_text, _size * self.zoom);
+        (size.0 / self.zoom, size.1 / self.zoom)
+    }
+
+    fn draw_cached_thumbnail(&mut self, _fen: &str, _at: (f32, f32), _size: f32) -> bool {
+        let at = self.moved(_at);
+        self.inner.draw_cached_thumbnail(_fen, at, _size * self.zoom)
+    }
+
+    fn draw_image(&mut self, _path: &str, _geometry: (f32, f32, f32, f32)) -> bool {
+        let at = self.moved((_geometry.0, _geometry.1));
+        self.inner.draw_image(_path, (at.0, at.1, _geometry.2 * self.zoom, _geometry.3 * self.zoom))
+    }
+}
@@ -0,0 +1,214 @@
+use super::renderer::{Renderer, Rgba};
+
+/// Where lines go across the rectangle of a layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Centre,
+    Right
+}
+
+/// Where the block of lines goes down the rectangle of a layout
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VAlign {
+    Top,
+    Middle,
+    Bottom
+}
+
+/// A run of text in one size and colour
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fragment {
+    pub text: String,
+    pub size: f32, // Height in pixels
+    pub colour: Rgba
+}
+
+/// A piece of a laid out line: text of one fragment, at an x relative to the start of the line
+#[derive(Debug, Clone, PartialEq)]
+struct Run {
+    text: String,
+    fragment: usize, // Index into the fragments, for the style
+    x: f32
+}
+
+/// A laid out line
+#[derive(Debug, Clone, PartialEq)]
+struct Line {
+    runs: Vec<Run>,
+    width: f32,
+    height: f32
+}
+
+/// Text laid out in a rectangle: aligned, wrapped at spaces to the width of the rectangle and made of
+/// fragments with their own size and colour. A `\n` in a fragment starts a new line.
+///
+/// ```ignore
+/// TextLayout::new()
+///     .fragment("Rustaceans ", 24.0, ORANGE)
+///     .fragment("win on time", 24.0, WHITE)
+///     .align(Align::Centre)
+///     .draw(_renderer, (0.0, 725.0, 720.0, 45.0));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextLayout {
+    fragments: Vec<Fragment>,
+    align: Align,
+    valign: VAlign,
+    line_spacing: f32 // Times the height of the largest text of a line
+}
+
+impl Default for TextLayout {
+    fn default() -> TextLayout {
+        TextLayout { fragments: vec![], align: Align::Left, valign: VAlign::Top, line_spacing: 1.15 }
+    }
+}
+
+impl TextLayout {
+    /// An empty layout, left and top aligned
+    pub fn new() -> TextLayout {
+        TextLayout::default()
+    }
+
+    /// A layout of a single fragment, centred both ways
+    pub fn centred(_text: &str, _size: f32, _colour: Rgba) -> TextLayout {
+        TextLayout::new().fragment(_text, _size, _colour).align(Align::Centre).valign(VAlign::Middle)
+    }
+
+    /// Adds text in a size and colour after what is already there
+    pub fn fragment(mut self, _text: &str, _size: f32, _colour: Rgba) -> TextLayout {
+        self.fragments.push(Fragment { text: _text.to_string(), size: _size, colour: _colour });
+        self
+    }
+
+    /// Where lines go across the rectangle
+    pub fn align(mut self, _align: Align) -> TextLayout {
+        self.align = _align;
+        self
+    }
+
+    /// Where the lines go down the rectangle
+    pub fn valign(mut self, _valign: VAlign) -> TextLayout {
+        self.valign = _valign;
+        self
+    }
+
+    /// Space between lines, in times the height of the largest text of a line
+    pub fn line_spacing(mut self, _spacing: f32) -> TextLayout {
+        self.line_spacing = _spacing;
+        self
+    }
+
+    /// ## `wrap`
+    /// Breaks the fragments into lines no wider than `_width`. A word wider than that gets a line of its own and sticks out.
+    fn wrap(&self, _renderer: &mut dyn Renderer, _width: f32) -> Vec<Line> {
+        let mut lines = vec![];
+        let mut line = Line { runs: vec![], width: 0.0, height: 0.0 };
+
+        for (_index, _fragment) in self.fragments.iter().enumerate() {
+            // A space is measured between two letters, on its own it may measure as nothing
+            let space = _renderer.text_size("x x", _fragment.size).0 - _renderer.text_size("xx", _fragment.size).0;
+
+            for (_part, _paragraph) in _fragment.text.split('\n').enumerate() {
+                if _part > 0 {
+                    line.height = line.height.max(_fragment.size);
+                    lines.push(std::mem::replace(&mut line, Line { runs: vec![], width: 0.0, height: 0.0 }));
+                }
+
+                // Spaces at the edges of a fragment join it to its neighbours
+                let leading = _paragraph.len() - _paragraph.trim_start().len();
+                let trailing = _paragraph.len() - _paragraph.trim_end().len();
+                if leading > 0 && !line.runs.is_empty() {
+                    line.width += space * leading as f32;
+                }
+
+                for _word in _paragraph.split_whitespace() {
+                    let width = _renderer.text_size(_word, _fragment.size).0;
+                    let gap = match line.runs.last() {
+                        Some(run) if run.fragment == _index => space,
+                        _ => 0.0
+                    };
+
+                    if !line.runs.is_empty() && line.width + gap + width > _width {
+                        lines.push(std::mem::replace(&mut line, Line { runs: vec![], width: 0.0, height: 0.0 }));
+                        line.runs.push(Run { text: _word.to_string(), fragment: _index, x: 0.0 });
+                        line.width = width;
+                    }
+                    else {
+                        // Words of the same fragment on a line are drawn as one run
+                        match line.runs.last_mut() {
+                            Some(run) if run.fragment == _index => {
+                                if gap > 0.0 {
+                                    run.text.push(' ');
+                                }
+                                run.text.push_str(_word);
+                            },
+                            _ => line.runs.push(Run { text: _word.to_string(), fragment: _index, x: line.width + gap })
+                        }
+                        line.width += gap + width;
+                    }
+                    line.height = line.height.max(_fragment.size);
+                }
+
+                if trailing > 0 && !_paragraph.trim().is_empty() {
+                    line.width += space * trailing as f32;
+                }
+            }
+        }
+
+        if !line.runs.is_empty() || lines.is_empty() {
+            lines.push(line);
+        }
+
+        lines
+    }
+
+    /// ## `size`
+    /// Width and height the text takes when wrapped to `_width`
+    pub fn size(&self, _renderer: &mut dyn Renderer, _width: f32) -> (f32, f32) {
+        let lines = self.wrap(_renderer, _width);
+        let width = lines.iter().map(|_line| _line.width).fold(0.0, f32::max);
+
+        (width, self.height(&lines))
+    }
+
+    /// Height of laid out lines, without the spacing under the last one
+    fn height(&self, _lines: &[Line]) -> f32 {
+        let spaced: f32 = _lines.iter().map(|_line| _line.height * self.line_spacing).sum();
+        spaced - _lines.last().map_or(0.0, |_last| _last.height * (self.line_spacing - 1.0))
+    }
+
+    /// ## `draw`
+    /// Lays the text out in a rectangle and draws it
+    /// ### Parameters
+    /// - `_renderer: &mut dyn Renderer`: The renderer to draw with
+    /// - `_geometry: (f32, f32, f32, f32)`: Rectangle in this form (top_left_corner_x, top_left_corner_y, width, height).
+    ///   Text taller than it overflows at the bottom, top or both depending on the vertical alignment.
+    pub fn draw(&self, _renderer: &mut dyn Renderer, _geometry: (f32, f32, f32, f32)) {
+        let lines = self.wrap(_renderer, _geometry.2);
+        let height = self.height(&lines);
+
+        let mut y = match self.valign {
+            VAlign::Top => _geometry.1,
+            VAlign::Middle => _geometry.1 + (_geometry.3 - height) / 2.0,
+            VAlign::Bottom => _geometry.1 + _geometry.3 - height
+        };
+
+        for _line in &lines {
+            let x = match self.align {
+                Align::Left => _geometry.0,
+                Align::Centre => _geometry.0 + (_geometry.2 - _line.width) / 2.0,
+                Align::Right => _geometry.0 + _geometry.2 - _line.width
+            };
+
+            for _run in &_line.runs {
+                let fragment = &self.fragments[_run.fragment];
+                // Smaller text sits on the same baseline as the largest of the line
+                let drop = _line.height - fragment.size;
+                _renderer.draw_text(&_run.text, (x + _run.x, y + drop), fragment.size, fragment.colour);
+            }
+
+            y += _line.height * self.line_spacing;
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use eliasfl_chess::Piece;
+use ggez::{graphics, Context, GameResult};
+use std::collections::HashMap;
+use super::super::theme::Theme;
+use super::offscreen;
+
+/// Thumbnails of positions rendered once and kept, by the placement field of their FEN and their size.
+/// Drawing asks for thumbnails with `get`, those missing are rendered on the next `render_wanted`, outside of drawing.
+#[derive(Default)]
+pub struct ThumbnailCache {
+    canvases: HashMap<(String, u16), graphics::Canvas>,
+    wanted: Vec<(String, u16)>, // Asked for but not rendered yet
+    theme: Option<Theme> // The theme the thumbnails were rendered with
+}
+
+impl ThumbnailCache {
+    pub fn new() -> ThumbnailCache {
+        ThumbnailCache::default()
+    }
+
+    /// Cache key of a position, the castling rights and counters do not change the picture
+    fn key(_fen: &str, _size: f32) -> (String, u16) {
+        (_fen.split_whitespace().next().unwrap_or("").to_string(), _size.round() as u16)
+    }
+
+    /// The thumbnail of a position if it was rendered already, otherwise it is rendered on the next `render_wanted`
+    pub fn get(&mut self, _fen: &str, _size: f32) -> Option<&graphics::Canvas> {
+        let key = ThumbnailCache::key(_fen, _size);
+
+        if !self.canvases.contains_key(&key) {
+            if !self.wanted.contains(&key) {
+                self.wanted.push(key);
+            }
+            return None;
+        }
+
+        self.canvases.get(&key)
+    }
+
+    /// Throws every thumbnail away, i.e. after the piece set changed
+    pub fn clear(&mut self) {
+        self.canvases.clear();
+    }
+
+    /// Renders the thumbnails asked for since the last call. Changing the theme renders everything again.
+    pub fn render_wanted(&mut self, ctx: &mut Context, _sprites: &HashMap<Piece, graphics::Image>, _theme: &Theme) -> GameResult {
+        if self.theme.as_ref() != Some(_theme) {
+            self.canvases.clear();
+            self.theme = Some(_theme.clone());
+        }
+
+        for (_placement, _size) in std::mem::take(&mut self.wanted) {
+            let canvas = offscreen::render_thumbnail(ctx, _sprites, _theme, &_placement, _size)?;
+            self.canvases.insert((_placement, _size), canvas);
+        }
+
+        Ok(())
+    }
+}
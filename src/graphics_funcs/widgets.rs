@@ -0,0 +1,206 @@
+use eliasfl_chess::Piece;
+use ggez::event::KeyCode;
+use super::super::controller::InputEvent;
+use super::super::help_funcs::in_rect;
+use super::draw_funcs;
+use super::renderer::{Renderer, Rgba};
+use super::text_layout::TextLayout;
+
+/// A rectangle given as (top_left_corner_x, top_left_corner_y, width, height)
+pub type Rect = (f32, f32, f32, f32);
+
+/// A grey rectangle with a label or the icon of a piece on it, standing for an action of its scene when clicked
+#[derive(Debug, Clone, PartialEq)]
+pub struct Button<A> {
+    pub geometry: Rect,
+    pub label: String,
+    pub icon: Option<Piece>, // Drawn in place of the label, which is then written small beside it
+    pub key: Option<KeyCode>, // Shortcut clicking the button
+    pub action: A
+}
+
+impl<A: Copy> Button<A> {
+    /// A button with a label
+    pub fn new(_geometry: Rect, _label: &str, _action: A) -> Button<A> {
+        Button { geometry: _geometry, label: _label.to_string(), icon: None, key: None, action: _action }
+    }
+
+    /// The same button showing a piece, its label becomes a hint beside it
+    pub fn with_icon(mut self, _piece: Piece) -> Button<A> {
+        self.icon = Some(_piece);
+        self
+    }
+
+    /// The same button clicked by a key as well
+    pub fn with_key(mut self, _key: KeyCode) -> Button<A> {
+        self.key = Some(_key);
+        self
+    }
+
+    /// Draws the button
+    pub fn draw(&self, _renderer: &mut dyn Renderer) {
+        let (x, y, _, height) = self.geometry;
+        match &self.icon {
+            // The hint goes right of the icon, which is one tile wide
+            Some(piece) => {
+                draw_funcs::draw_rectangle(_renderer, self.geometry);
+                draw_funcs::draw_icon(_renderer, (x + 30.0, y + 10.0), piece, 1.0);
+                _renderer.draw_text(&self.label, (x + 120.0, y + height - 35.0), 20.0, [0.7, 0.7, 0.7, 1.0]);
+            },
+            None => draw_funcs::draw_button(_renderer, self.geometry, &self.label)
+        }
+    }
+
+}
+
+/// Clickable regions of a screen, each with what clicking it does. Built by the code laying the screen out, the same
+/// the drawing goes by, so a button moved is clicked where it is drawn. Later regions are on top of earlier ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Regions<A> {
+    regions: Vec<(Rect, A)>
+}
+
+impl<A> Default for Regions<A> {
+    fn default() -> Regions<A> {
+        Regions { regions: vec![] }
+    }
+}
+
+impl<A: Clone> Regions<A> {
+    /// No regions, every click misses
+    pub fn new() -> Regions<A> {
+        Regions::default()
+    }
+
+    /// The regions of buttons, in their focus order
+    pub fn of(_buttons: &[Button<A>]) -> Regions<A> {
+        Regions { regions: _buttons.iter().map(|_button| (_button.geometry, _button.action.clone())).collect() }
+    }
+
+    /// Adds a region on top of the others
+    pub fn register(&mut self, _geometry: Rect, _action: A) {
+        self.regions.push((_geometry, _action));
+    }
+
+    /// Adds other regions on top of these, with their actions turned into actions of these
+    pub fn extend<B>(&mut self, _other: Regions<B>, _convert: impl Fn(B) -> A) {
+        self.regions.extend(_other.regions.into_iter().map(|(_geometry, _action)| (_geometry, _convert(_action))));
+    }
+
+    /// ## `hit`
+    /// The action of the region under a point
+    /// ### Parameters
+    /// - `_x: f32`, `_y: f32`: The point clicked
+    /// ### Return
+    /// The action of the topmost region containing the point, None if it missed them all
+    pub fn hit(&self, _x: f32, _y: f32) -> Option<A> {
+        self.regions.iter().rev().find(|(_geometry, _)| in_rect(*_geometry, _x, _y)).map(|(_, _action)| _action.clone())
+    }
+
+    /// Rectangles of the regions in the order they were added, for `Scene::focusables`
+    pub fn rects(&self) -> Vec<Rect> {
+        self.regions.iter().map(|(_geometry, _)| *_geometry).collect()
+    }
+}
+
+/// A line of text centred in a rectangle
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub geometry: Rect,
+    pub text: String,
+    pub size: f32,
+    pub colour: Rgba
+}
+
+impl Label {
+    /// A label of a size and colour
+    pub fn new(_geometry: Rect, _text: &str, _size: f32, _colour: Rgba) -> Label {
+        Label { geometry: _geometry, text: _text.to_string(), size: _size, colour: _colour }
+    }
+
+    /// Draws the label
+    pub fn draw(&self, _renderer: &mut dyn Renderer) {
+        TextLayout::centred(&self.text, self.size, self.colour).draw(_renderer, self.geometry);
+    }
+}
+
+/// A modal dialog: labels and buttons on a panel, which takes all input while it is open. Clicks outside it are swallowed,
+/// and as its buttons are the only focusables of the scene showing it, the keyboard focus stays within it too.
+///
+/// ```ignore
+/// let dialog = Dialog::new(Some((160.0, 300.0, 400.0, 200.0)))
+///     .label(Label::new((160.0, 320.0, 400.0, 40.0), "Accept the draw?", 24.0, WHITE))
+///     .button(Button::new((180.0, 420.0, 170.0, 50.0), "Accept", Answer::Yes).with_key(KeyCode::Y))
+///     .button(Button::new((370.0, 420.0, 170.0, 50.0), "Decline", Answer::No).with_key(KeyCode::N))
+///     .cancel(Answer::No);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Dialog<A> {
+    panel: Option<Rect>, // Background behind the dialog, None when the buttons cover it
+    labels: Vec<Label>,
+    buttons: Vec<Button<A>>,
+    cancel: Option<A> // Action of Escape, None when the dialog must be answered
+}
+
+impl<A: Copy> Dialog<A> {
+    /// An empty dialog on a panel
+    pub fn new(_panel: Option<Rect>) -> Dialog<A> {
+        Dialog { panel: _panel, labels: vec![], buttons: vec![], cancel: None }
+    }
+
+    /// The same dialog with one more label
+    pub fn label(mut self, _label: Label) -> Dialog<A> {
+        self.labels.push(_label);
+        self
+    }
+
+    /// The same dialog with one more button, after the others in the focus order
+    pub fn button(mut self, _button: Button<A>) -> Dialog<A> {
+        self.buttons.push(_button);
+        self
+    }
+
+    /// The same dialog closed by Escape with an action
+    pub fn cancel(mut self, _action: A) -> Dialog<A> {
+        self.cancel = Some(_action);
+        self
+    }
+
+    /// Draws the panel, then the labels and the buttons on it
+    pub fn draw(&self, _renderer: &mut dyn Renderer) {
+        if let Some(panel) = self.panel {
+            draw_funcs::draw_rectangle(_renderer, panel);
+        }
+        for _label in &self.labels {
+            _label.draw(_renderer);
+        }
+        for _button in &self.buttons {
+            _button.draw(_renderer);
+        }
+    }
+
+    /// ## `input`
+    /// Handles an input event while the dialog is open
+    /// ### Parameters
+    /// - `_event: InputEvent`: The event, which the scene below never gets
+    /// ### Return
+    /// The action of the button clicked or whose key was pressed, or the cancel action on Escape. None for anything else.
+    pub fn input(&self, _event: InputEvent) -> Option<A> {
+        match _event {
+            InputEvent::Click { x, y } => self.regions().hit(x, y),
+            InputEvent::Key(KeyCode::Escape) => self.cancel,
+            InputEvent::Key(key) => self.buttons.iter().find(|_button| _button.key == Some(key)).map(|_button| _button.action),
+            _ => None
+        }
+    }
+
+    /// Where the buttons are clicked
+    pub fn regions(&self) -> Regions<A> {
+        Regions::of(&self.buttons)
+    }
+
+    /// Rectangles of the buttons, the only places the keyboard focus goes while the dialog is open
+    pub fn focusables(&self) -> Vec<Rect> {
+        self.regions().rects()
+    }
+}
@@ -1,5 +1,7 @@
-use eliasfl_chess::{Position, Piece, Color as Colour};
-use super::{AppState};
+use eliasfl_chess::{Game, Position, Piece, Color as Colour};
+use super::consts::{GRID_SIZE, GRID_CELL_SIZE};
+use super::backend::ChessBackend;
+use super::fen;
 
 // The two functions below are the same as the ones used in my Engine assignment. They serve their purpose there so I copied them over here to do likewise.
 
@@ -52,18 +54,194 @@ pub fn get_piece_colour(_piece: &Piece) -> &Colour {
     }
 }
 
-impl AppState {
-    /// Calls the move functions from the engine, clears the board and updates the dear bar
-    pub fn make_move_full(&mut self, _clicked: (u8, u8), _from: String, _to: String) {
-        if let Some(_piece) = self.game.board.get(&to_engine_coords(&_clicked)) {
+/// Checks if a point is inside a rectangle given as (top_left_corner_x, top_left_corner_y, width, height)
+pub fn in_rect(_geometry: (f32, f32, f32, f32), _x: f32, _y: f32) -> bool {
+    _x >= _geometry.0 && _x <= _geometry.0 + _geometry.2 && _y >= _geometry.1 && _y <= _geometry.1 + _geometry.3
+}
+
+/// The board square under window coordinates, None when outside the board
+pub fn board_square(_x: f32, _y: f32) -> Option<(u8, u8)> {
+    let board_size = (GRID_SIZE * GRID_CELL_SIZE.0) as f32;
+
+    if _x < 0.0 || _y < 0.0 || _x >= board_size || _y >= board_size {
+        return None;
+    }
+
+    Some(((_x as i16 / GRID_CELL_SIZE.0) as u8, (_y as i16 / GRID_CELL_SIZE.1) as u8))
+}
+
+/// The square drawn at a board square's place, the same one unless the board is flipped to be seen from Black's side.
+/// Flipping twice gives the square back, so it turns window squares into board squares too.
+pub fn flip_square(_square: (u8, u8), _flipped: bool) -> (u8, u8) {
+    if _flipped { (7 - _square.0, 7 - _square.1) } else { _square }
+}
+
+/// Window coordinates of the middle of a board square
+pub fn square_centre(_square: (u8, u8)) -> (f32, f32) {
+    (
+        (_square.0 as i16 * GRID_CELL_SIZE.0 + GRID_CELL_SIZE.0 / 2) as f32,
+        (_square.1 as i16 * GRID_CELL_SIZE.1 + GRID_CELL_SIZE.1 / 2) as f32
+    )
+}
+
+/// Usual material value of a piece in pawns
+pub fn piece_value(_piece: &Piece) -> u32 {
+    match _piece {
+        Piece::Pawn(_) => 1,
+        Piece::Knight(_) | Piece::Bishop(_) => 3,
+        Piece::Rook(_) => 5,
+        Piece::Queen(_) => 9,
+        Piece::King(_) => 0
+    }
+}
+
+/// Total material of a side on the board, in pawns
+pub fn material(_game: &dyn ChessBackend, _colour: Colour) -> u32 {
+    let mut total = 0;
+
+    for _col in 0..8 {
+        for _row in 0..8 {
+            if let Some(piece) = _game.piece_at((_col, _row)) {
+                if *get_piece_colour(&piece) == _colour {
+                    total += piece_value(&piece);
+                }
+            }
+        }
+    }
+
+    total
+}
+
+/// ## `has_mating_material`
+/// If a side has the pieces to ever checkmate, i.e. to win on time when the other side's clock runs out.
+/// A lone king, or a king with a single bishop or knight, cannot.
+/// ### Parameters
+/// - `_game: &dyn ChessBackend`: The game at the position
+/// - `_colour: Colour`: The side
+pub fn has_mating_material(_game: &dyn ChessBackend, _colour: Colour) -> bool {
+    let pieces: Vec<Piece> = (0..8u8).flat_map(|_col| (0..8u8).map(move |_row| (_col, _row)))
+        .filter_map(|_square| _game.piece_at(_square))
+        .filter(|_piece| *get_piece_colour(_piece) == _colour && !matches!(_piece, Piece::King(_)))
+        .collect();
+
+    match pieces.as_slice() {
+        [] | [Piece::Bishop(_)] | [Piece::Knight(_)] => false,
+        _ => true
+    }
+}
+
+/// Pieces of a side missing from the board compared to a full set, the pieces it lost as far as the board tells.
+/// Promoted pieces make up for the pawns they came from.
+pub fn missing_pieces(_game: &dyn ChessBackend, _colour: Colour) -> Vec<Piece> {
+    let mut counts = [(Piece::Pawn(_colour), 8), (Piece::Knight(_colour), 2), (Piece::Bishop(_colour), 2), (Piece::Rook(_colour), 2), (Piece::Queen(_colour), 1)];
+
+    for _col in 0..8 {
+        for _row in 0..8 {
+            if let Some(piece) = _game.piece_at((_col, _row)) {
+                // A piece beyond the full set was a pawn
+                let index = counts.iter().position(|(_piece, _count)| *_piece == piece && *_count > 0).or_else(|| {
+                    if *get_piece_colour(&piece) == _colour && !matches!(piece, Piece::King(_)) { Some(0) } else { None }
+                });
 
-            // Get colour of the piece
+                if let Some(index) = index {
+                    counts[index].1 = (counts[index].1 - 1).max(0);
+                }
+            }
+        }
+    }
+
+    counts.iter().flat_map(|(_piece, _count)| std::iter::repeat(*_piece).take(*_count as usize)).collect()
+}
+
+/// A time in seconds since the Unix epoch as "YYYY-MM-DD HH:MM" in UTC
+pub fn format_timestamp(_seconds: u64) -> String {
+    let days = (_seconds / 86400) as i64;
+    let minutes = (_seconds % 86400) / 60;
+
+    // Days to a civil date, from Howard Hinnant's `civil_from_days`
+    let shifted = days + 719468;
+    let era = shifted.div_euclid(146097);
+    let day_of_era = shifted - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, minutes / 60, minutes % 60)
+}
+
+/// ## `board_to_fen`
+/// Writes the board of an Elias Engine game as FEN. The engine keeps no history, so castling is written wherever a king
+/// and a rook still stand on their starting squares, and there is no en passant square.
+/// ### Parameters
+/// - `_game: &Game`: The game at the position
+pub fn board_to_fen(_game: &Game) -> String {
+    let pieces: Vec<((u8, u8), Piece)> = _game.board.iter()
+        .map(|(_position, _piece)| ((_position.file - 1, 8 - _position.rank), *_piece))
+        .collect();
+
+    fen::from_placement(&pieces, _game.active_color)
+}
+
+/// ## `fen_to_board`
+/// Sets up an Elias Engine game at a position given as FEN. Only the placement and the side to move are read,
+/// as the engine has no say in castling rights, the en passant square or the move counters.
+/// ### Parameters
+/// - `_fen: &str`: The position
+/// ### Return
+/// The game at the position, or why the FEN was refused: it must have 8 ranks of 8 squares, `w` or `b` to move and one king per side
+pub fn fen_to_board(_fen: &str) -> Result<Game, String> {
+    let mut fields = _fen.split_whitespace();
+    let ranks: Vec<&str> = fields.next().ok_or("the FEN is empty")?.split('/').collect();
+    if ranks.len() != 8 {
+        return Err(format!("{} ranks instead of 8", ranks.len()));
+    }
 
-            if self.game.active_color != *get_piece_colour(_piece) {
-                self.deaths.get_mut(&!self.game.active_color).unwrap().push(*_piece);
+    let active = match fields.next().unwrap_or("w") {
+        "w" => Colour::White,
+        "b" => Colour::Black,
+        side => return Err(format!("\"{}\" to move, not w or b", side))
+    };
+
+    let mut pieces = vec![];
+    for (_row, _rank) in ranks.iter().enumerate() {
+        let mut col = 0u8;
+
+        for _letter in _rank.chars() {
+            match (_letter.to_digit(10), fen::char_piece(_letter)) {
+                (Some(empty), _) if (1..=8).contains(&empty) => col += empty as u8,
+                (None, Some(piece)) => {
+                    pieces.push(((col, _row as u8), piece));
+                    col += 1;
+                },
+                _ => return Err(format!("'{}' is not a piece", _letter))
+            }
+
+            if col > 8 {
+                return Err(format!("rank {} has more than 8 squares", 8 - _row));
             }
         }
-        self.game.make_move(_from, _to).ok();
-        self.legal.clear();
+
+        if col != 8 {
+            return Err(format!("rank {} has {} squares instead of 8", 8 - _row, col));
+        }
     }
-}
\ No newline at end of file
+
+    for _colour in [Colour::White, Colour::Black].iter() {
+        let kings = pieces.iter().filter(|(_, _piece)| *_piece == Piece::King(*_colour)).count();
+        if kings != 1 {
+            return Err(format!("{} {} kings instead of one", kings, if *_colour == Colour::White { "white" } else { "black" }));
+        }
+    }
+
+    let mut game = Game::new();
+    game.board.clear();
+    for (_square, _piece) in pieces {
+        game.board.insert(to_engine_coords(&_square), _piece);
+    }
+    game.active_color = active;
+
+    Ok(game)
+}
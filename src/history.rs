@@ -0,0 +1,91 @@
+use eliasfl_chess::Piece;
+use super::backend::{ChessBackend, PromotionKind};
+use super::help_funcs::get_piece_colour;
+
+/// A move played in the game, as recorded by the controller
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveRecord {
+    pub from: String, // "<file><rank>"
+    pub to: String, // "<file><rank>"
+    pub promotion: Option<PromotionKind>,
+    pub piece: Piece, // The piece that moved
+    pub captured: Option<Piece>,
+    pub elapsed: f32, // Seconds into the game the move was played at
+    pub clock: Option<f32> // Seconds left on the mover's clock after the move, None for untimed games
+}
+
+impl MoveRecord {
+    /// The move in coordinate notation, i.e. "e2-e4", "d5xe6" or "b7-b8=N"
+    pub fn notation(&self) -> String {
+        let promotion = match self.promotion {
+            Some(PromotionKind::Queen) => "=Q",
+            Some(PromotionKind::Knight) => "=N",
+            Some(PromotionKind::Rook) => "=R",
+            Some(PromotionKind::Bishop) => "=B",
+            None => ""
+        };
+
+        format!("{}{}{}{}", self.from, if self.captured.is_some() { "x" } else { "-" }, self.to, promotion)
+    }
+
+    /// The move in UCI notation, i.e. "e2e4" or "b7b8n"
+    pub fn uci(&self) -> String {
+        let promotion = match self.promotion {
+            Some(PromotionKind::Queen) => "q",
+            Some(PromotionKind::Knight) => "n",
+            Some(PromotionKind::Rook) => "r",
+            Some(PromotionKind::Bishop) => "b",
+            None => ""
+        };
+
+        format!("{}{}{}", self.from, self.to, promotion)
+    }
+}
+
+/// A piece taken during the game, as shown on the capture timeline
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Capture {
+    pub piece: Piece, // The piece that was taken
+    pub by: Piece, // The piece that took it
+    pub ply: usize, // Index of the capturing move in the moves of the game
+    pub move_number: usize // Full move it was taken on, starting at 1
+}
+
+/// Every capture of a game in the order they happened
+pub fn captures(_moves: &[MoveRecord]) -> Vec<Capture> {
+    _moves.iter().enumerate()
+        .filter_map(|(_ply, _move)| _move.captured.map(|_piece| Capture { piece: _piece, by: _move.piece, ply: _ply, move_number: _ply / 2 + 1 }))
+        .collect()
+}
+
+/// ## `captured_by`
+/// The piece a move takes: the one on the square moved to, or the pawn beside it when a pawn moves diagonally onto an
+/// empty square, en passant. Castling onto the mover's own rook, as in Chess960, takes nothing.
+/// ### Parameters
+/// - `_game: &dyn ChessBackend`: The position the move is played in
+/// - `_from: (u8, u8)`, `_to: (u8, u8)`: The squares of the move
+pub fn captured_by(_game: &dyn ChessBackend, _from: (u8, u8), _to: (u8, u8)) -> Option<Piece> {
+    let piece = _game.piece_at(_from)?;
+    let square = match (piece, _game.piece_at(_to)) {
+        (Piece::Pawn(_), None) if _from.0 != _to.0 => (_to.0, _from.1),
+        _ => _to
+    };
+
+    _game.piece_at(square).filter(|_captured| get_piece_colour(_captured) != get_piece_colour(&piece))
+}
+
+/// ## `replay`
+/// Plays moves on a backend, i.e. to rebuild the position at some point of a game
+/// ### Parameters
+/// - `_backend: &mut dyn ChessBackend`: The backend to play on, usually at the starting position
+/// - `_moves: &[MoveRecord]`: The moves to play, in order
+/// ### Return
+/// `Err` with the reason if the backend refused a move
+pub fn replay(_backend: &mut dyn ChessBackend, _moves: &[MoveRecord]) -> Result<(), String> {
+    for _move in _moves {
+        _backend.make_move(&_move.from, &_move.to, _move.promotion)
+            .map_err(|_error| format!("{}: {}", _move.notation(), _error))?;
+    }
+
+    Ok(())
+}
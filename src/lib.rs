@@ -0,0 +1,613 @@
+//! # schack_gui
+//! A chess board GUI built on GGEZ and the Elias Engine.
+//!
+//! The crate is split into:
+//! - `controller`: the game logic (`GameController`), driven by `InputEvent`s and usable without a window
+//! - `backend`: the `ChessBackend` trait playing the rules, with the Elias Engine (and shakmaty behind the `shakmaty` feature), behind an adapter wording their errors for the player
+//! - `perft`: move generation counts for validating a backend
+//! - `history`: the record of the moves played, and replaying them to rebuild earlier positions
+//! - `export`: the game as JSON (moves, timestamps, captures, clocks and result) for external tools
+//! - `pgn`: the game as PGN, with headers edited before exporting, and reading the games of PGN files
+//! - `fen`: writing positions as FEN
+//! - `clipboard`: copying text to and pasting it from the system clipboard, i.e. positions as FEN
+//! - `diagram`: the position as an SVG diagram, with coordinates and the last move as an arrow
+//! - `scoresheet`: a finished game as a printable HTML scoresheet, with a diagram every ten moves
+//! - `pawn_structure`: doubled, isolated and passed pawns, marked on the board as a learning aid
+//! - `saves`: named save slots, the quick save and the game left going when the window closed, kept in the user data directory
+//! - `recent`: the last games saved, loaded, imported or finished, reopened from the main menu
+//! - `event_log`: a plain text log of the moves, checks and results for screen readers, written as the game goes
+//! - `overlay_server`: optional local HTTP server mirroring the game as JSON for stream overlays and mirror windows, with a chat for the spectators
+//! - `net`: the client of the matchmaking server (the `schack_matchmaker` binary), which pairs players and relays their moves over TLS, LAN games hosted and joined over TLS with a key the guest checks, and games on Lichess through its Board API
+//! - `validator`: the GUI's own move legality check, run before the backend plays a move
+//! - `scenes`: the scene stack (menu, setup, game, promotion, hand over, settings, game over, analysis, overlay, mirror, online, arbiter, simul, practice, mate patterns, game menu, draw offer, FEN entry, PGN headers, diagram, spectators, paused, console, achievements, save, load), each with its own update, draw and input
+//! - `graphics_funcs`: drawing of the board, pieces and panels in layers that can be hidden, plus widgets for buttons and dialogs, text layout, offscreen rendering and cached board thumbnails
+//! - `help_funcs`: coordinate conversions between the GUI and the engine, and engine boards to and from FEN
+//! - `view`: zoom and pan of the board, which clicks are turned into squares through
+//! - `animation`: tweens, sequences and parallel groups played by an `Animator`
+//! - `timestep` and `clock`: the fixed timestep loop and the chess clocks it ticks
+//! - `bot`: computer opponents, the built-in random mover and minimax bot, UCI engines and rhai scripts from the `bots/` directory (behind the `scripting` feature), held back to an Elo on request
+//! - `adjudicator`: ends engine matches early from the scores of the engines, once one side is lost or the game is dead equal
+//! - `polyglot`: opening books in the Polyglot format, which the built-in bot plays its first moves from
+//! - `book_builder`: builds a Polyglot book from the games of a PGN file, i.e. to have the bot open like a player
+//! - `database`: search and filters over the games of a PGN database, and replaying them onto the board
+//! - `speedrun`: timed checkmates of the random mover, with the best times per difficulty kept with the profiles
+//! - `mate_patterns`: named checkmates (back-rank, smothered, Anastasia's, Boden's) set up to practise, with their lines and hints
+//! - `vote`: vote chess, where a group proposes moves for its side and confirms one of them
+//! - `simul`: one human against a bot on several boards at once, the bot's replies searched on the task runner
+//! - `tasks`: background threads for bot searches and IO, polled from the update loop so nothing blocks drawing
+//! - `audio` and `config`: sound effects and spoken announcements at the volumes of the settings, which are kept in the user data directory
+//! - `theme`: the colours of the interface, switchable at runtime
+//! - `messages`: the texts about the state of the game, in built-in or plugin message packs
+//! - `achievements`: achievements unlocked by playing, kept in the user data directory
+//! - `stats` and `profile`: accuracy and think times of a game, summed up per player in the user data directory
+//! - `plugins` and `variant`: themes, piece sets and variants loaded from `plugins/` at startup, without recompiling
+//! - `consts`: sizes and colours
+//!
+//! `AppState` bundles a controller with the piece sprites and implements `ggez::event::EventHandler`,
+//! so it can be run as is or embedded in another GGEZ application by forwarding its events and calling `draw_scene`.
+
+pub mod graphics_funcs;
+pub mod consts;
+pub mod help_funcs;
+pub mod controller;
+pub mod backend;
+pub mod perft;
+pub mod validator;
+pub mod history;
+pub mod export;
+pub mod pgn;
+pub mod diagram;
+pub mod scoresheet;
+pub mod fen;
+pub mod clipboard;
+pub mod pawn_structure;
+pub mod saves;
+pub mod recent;
+pub mod polyglot;
+pub mod book_builder;
+pub mod database;
+pub mod net;
+pub mod theme;
+pub mod audio;
+pub mod config;
+pub mod achievements;
+pub mod stats;
+pub mod profile;
+pub mod variant;
+pub mod plugins;
+pub mod bot;
+pub mod adjudicator;
+pub mod overlay_server;
+pub mod event_log;
+pub mod tasks;
+pub mod simul;
+pub mod vote;
+pub mod messages;
+pub mod speedrun;
+pub mod mate_patterns;
+pub mod view;
+pub mod scenes;
+pub mod animation;
+pub mod timestep;
+pub mod clock;
+
+use ggez::{event, filesystem, graphics, input::keyboard, timer, Context, GameError, GameResult};
+use std::{collections::HashMap, io::{Read, Write}, path::Path, sync::Arc, time::{Duration, Instant}};
+use eliasfl_chess::{Color as Colour, Piece};
+use graphics_funcs::*;
+use graphics_funcs::renderer::{GgezRenderer, IndicatorCache, PrebuiltShapes};
+use graphics_funcs::thumbnails::ThumbnailCache;
+use graphics_funcs::fonts::FontManager;
+use graphics_funcs::layers::Layer;
+use controller::{GameController, InputEvent};
+use animation::Animator;
+use timestep::FixedTimestep;
+use scenes::{Scene, Shared, SceneStack, Transition, main_menu::MainMenuScene, console::ConsoleScene, paused::PausedScene};
+use consts::{UPDATE_RATE, LOW_SPEC_FPS};
+use overlay_server::OverlayServer;
+use event_log::EventLog;
+use achievements::{Achievements, ACHIEVEMENTS_FILE};
+use profile::{Profiles, PROFILES_FILE};
+use config::{Config, CONFIG_FILE};
+use polyglot::{Book, BOOK_FILE};
+use database::DATABASE_FILE;
+use recent::{RecentGames, RECENT_FILE};
+use audio::AudioManager;
+use saves::{SaveSlots, SavedGame, SAVES_DIR, AUTO_SAVE};
+
+
+/// GUI logic and event implementation structure. The chess board widget.
+pub struct AppState {
+    sprites: HashMap<Piece, graphics::Image>,
+    images: HashMap<String, Option<graphics::Image>>, // Avatars and flags of the profiles by path, None for the ones that failed to load
+    loaded_piece_set: Option<usize>, // Piece set the sprites were loaded from, see `Shared::piece_set`
+    thumbnails: ThumbnailCache, // Small boards shown in menus, rendered once
+    fonts: FontManager, // Font of all text, sized for the display
+    shapes: PrebuiltShapes, // Rectangles and circles drawn without building meshes, in low-spec mode
+    indicators: IndicatorCache, // Legal move indicators, built once per size of the tiles
+    last_frame: Instant, // When the last frame was shown, to hold frames back in low-spec mode
+    shared: Shared, // Game logic and everything else the scenes share, kept apart from ggez so it can be driven without a window
+    scenes: SceneStack, // Menu, game, promotion... the top one gets the input
+    animator: Animator, // Plays all interface animations
+    audio: AudioManager, // Plays the sounds of the game
+    timestep: FixedTimestep, // Turns frame times into fixed steps for `tick`
+    quit_requested: bool, // Set when a scene asks to close the application
+    achievements_unsaved: bool, // Achievements were unlocked since the file was last written
+    window_title: String, // Last title given to the window, it is only set again when the game changes it
+    overlay: Option<OverlayServer>, // Serves the game to stream overlays, when turned on
+    event_log: Option<EventLog> // Follows the game in plain text for accessibility tools, when turned on
+}
+
+impl AppState {
+    /// Initialise new application, i.e. initialise new game and load resources.
+    pub fn new(ctx: &mut Context) -> GameResult<AppState> {
+        AppState::with_controller(ctx, GameController::new())
+    }
+
+    /// Initialise new application around an already set up controller (i.e. another backend or a time control) and load resources.
+    pub fn with_controller(ctx: &mut Context, _controller: GameController) -> GameResult<AppState> {
+        AppState::with_scene(ctx, _controller, Box::new(MainMenuScene))
+    }
+
+    /// Initialise new application starting at another scene than the main menu, i.e. `OverlayScene`, and load resources.
+    pub fn with_scene(ctx: &mut Context, _controller: GameController, _first: Box<dyn Scene>) -> GameResult<AppState> {
+
+        // Piece sets of plugins are loaded through the GGEZ filesystem like the ones in `resources`
+        filesystem::mount(ctx, Path::new(plugins::PLUGIN_DIR), true);
+
+        // Without the bundled font the text is still readable in the one of GGEZ
+        let (fonts, font_error) = match FontManager::load(ctx) {
+            Ok(fonts) => (fonts, None),
+            Err(error) => (FontManager::fallback(), Some(format!("Font not loaded: {}", error)))
+        };
+
+        let mut state = AppState {
+            sprites: AppState::load_sprites(ctx, "").expect("Failed to load the pieces."),
+            images: HashMap::new(),
+            loaded_piece_set: None,
+            thumbnails: ThumbnailCache::new(),
+            fonts,
+            shapes: PrebuiltShapes::new(ctx)?,
+            indicators: IndicatorCache::new(),
+            last_frame: Instant::now(),
+            shared: Shared::new(_controller),
+            scenes: SceneStack::new(_first),
+            animator: Animator::new(),
+            audio: AudioManager::new(),
+            timestep: FixedTimestep::new(UPDATE_RATE),
+            quit_requested: false,
+            achievements_unsaved: false,
+            window_title: String::new(),
+            overlay: None,
+            event_log: None
+        };
+
+        state.shared.achievements = Achievements::from_json(&AppState::read_user_file(ctx, ACHIEVEMENTS_FILE));
+        state.shared.profiles = Profiles::from_json(&AppState::read_user_file(ctx, PROFILES_FILE));
+        state.shared.config = Config::from_toml(&AppState::read_user_file(ctx, CONFIG_FILE));
+        state.shared.saves = AppState::read_saves(ctx);
+        state.shared.recent = RecentGames::from_json(&AppState::read_user_file(ctx, RECENT_FILE));
+        state.shared.database = pgn::read_games(&AppState::read_user_file(ctx, DATABASE_FILE));
+        state.shared.controller.banner = font_error;
+
+        // Without a book the bot makes up its openings as it does the rest
+        if let Ok(mut file) = filesystem::open(ctx, BOOK_FILE) {
+            let mut bytes = vec![];
+            match file.read_to_end(&mut bytes).map_err(|_error| _error.to_string()).and_then(|_| Book::from_bytes(&bytes)) {
+                Ok(book) => state.shared.book = Some(Arc::new(book)),
+                Err(error) => state.shared.controller.banner = Some(format!("Opening book not loaded: {}", error))
+            }
+        }
+
+        Ok(state)
+    }
+
+    /// The game logic behind the board
+    pub fn controller(&self) -> &GameController {
+        &self.shared.controller
+    }
+
+    /// Mutable access to the game logic, i.e. to feed it input from an embedding application
+    pub fn controller_mut(&mut self) -> &mut GameController {
+        &mut self.shared.controller
+    }
+
+    /// Makes the application only show a game played elsewhere, i.e. in a mirror window, see `Shared::read_only`
+    pub fn set_read_only(&mut self, _read_only: bool) {
+        self.shared.read_only = _read_only;
+    }
+
+    /// The scenes of the application, the last one is active
+    pub fn scenes(&self) -> &SceneStack {
+        &self.scenes
+    }
+
+    /// The animations playing on the interface
+    pub fn animator_mut(&mut self) -> &mut Animator {
+        &mut self.animator
+    }
+
+    /// Offers a UCI engine or bot script kept outside `bots/` (i.e. an installed Stockfish) as the first opponent of the
+    /// new game screen and the engine of practice
+    pub fn add_engine(&mut self, _path: &Path) {
+        self.shared.bot_scripts.retain(|_known| _known != _path);
+        self.shared.bot_scripts.insert(0, _path.to_path_buf());
+    }
+
+    /// Writes every move, check and result to a plain text log as they happen, see `EventLog`
+    pub fn log_events(&mut self, _log: EventLog) {
+        self.event_log = Some(_log);
+    }
+
+    /// Starts serving the game to stream overlays on a local port, see `OverlayServer`
+    pub fn serve_overlay(&mut self, _port: u16) -> std::io::Result<()> {
+        let server = OverlayServer::start(_port)?;
+        server.publish(&self.shared.controller);
+        self.shared.spectators = Some(server.spectators());
+        self.overlay = Some(server);
+
+        Ok(())
+    }
+
+    /// Advances clocks, scenes and animations by one fixed step of `_dt` seconds
+    pub fn tick(&mut self, _dt: f32) {
+        let reduced_motion = self.shared.config.reduced_motion;
+        self.scenes.set_reduced_motion(reduced_motion);
+        self.animator.set_reduced_motion(reduced_motion);
+
+        if !self.shared.paused {
+            self.shared.controller.tick(_dt);
+        }
+        self.quit_requested |= self.scenes.update(&mut self.shared, _dt);
+        self.animator.update(_dt);
+
+        // Low-spec mode skips straight to the end of every animation
+        if self.shared.config.low_spec {
+            self.quit_requested |= self.scenes.finish_transition();
+            self.animator.finish_all();
+        }
+        self.achievements_unsaved |= self.shared.check_achievements();
+        self.shared.update_toasts(_dt);
+
+        if let Some(overlay) = &mut self.overlay {
+            overlay.update(&self.shared.controller, _dt);
+        }
+        if let Some(log) = &mut self.event_log {
+            log.update(&self.shared.controller);
+        }
+    }
+
+    /// Pauses the clocks and bots of the game being played, under a "paused — click to resume" screen. Menus and
+    /// finished games have nothing to pause.
+    pub fn pause(&mut self) {
+        let playing = matches!(self.scenes.top_name(), "game" | "simul" | "practice");
+        if playing && !self.shared.paused && !self.shared.read_only {
+            self.shared.paused = true;
+            self.scenes.apply(Transition::Push(Box::new(PausedScene)));
+        }
+    }
+
+    /// Shows the state of the game being played in the title of the window, just the name in the menus
+    fn update_window_title(&mut self, ctx: &mut Context) {
+        let base = if self.shared.read_only { "Schack (mirror)" } else { "Schack" };
+        let names = self.scenes.names();
+
+        let title = match &self.shared.simul {
+            Some(simul) if names.contains(&"simul") => draw_funcs::prepare_window_title(simul.active_board(), base),
+            _ if names.contains(&"game") || names.contains(&"practice") || names.contains(&"mirror") => draw_funcs::prepare_window_title(&self.shared.controller, base),
+            _ => base.to_string()
+        };
+
+        if title != self.window_title {
+            graphics::set_window_title(ctx, &title);
+            self.window_title = title;
+        }
+    }
+
+    /// Gives an input event to the active scene
+    pub fn input(&mut self, _event: InputEvent) {
+        self.quit_requested |= self.scenes.input(&mut self.shared, _event);
+    }
+
+    /// Loads chess piese images into vector, from the root of the resources or from a piece set directory in it.
+    fn load_sprites(ctx: &mut Context, _directory: &str) -> GameResult<HashMap<Piece, graphics::Image>> {
+
+        [
+            ((Piece::King(Colour::Black)), "black_king.png"),
+            ((Piece::Queen(Colour::Black)), "black_queen.png"),
+            ((Piece::Rook(Colour::Black)), "black_rook.png"),
+            ((Piece::Pawn(Colour::Black)), "black_pawn.png"),
+            ((Piece::Bishop(Colour::Black)), "black_bishop.png"),
+            ((Piece::Knight(Colour::Black)), "black_knight.png"),
+            ((Piece::King(Colour::White)), "white_king.png"),
+            ((Piece::Queen(Colour::White)), "white_queen.png"),
+            ((Piece::Rook(Colour::White)), "white_rook.png"),
+            ((Piece::Pawn(Colour::White)), "white_pawn.png"),
+            ((Piece::Bishop(Colour::White)), "white_bishop.png"),
+            ((Piece::Knight(Colour::White)), "white_knight.png")
+        ]
+            .iter()
+            .map(|(_piece, _file)| {
+                Ok((*_piece, graphics::Image::new(ctx, format!("{}/{}", _directory, _file))?))
+            })
+            .collect::<GameResult<HashMap<Piece, graphics::Image>>>()
+    }
+
+    /// Swaps the sprites when another piece set was chosen. A set that fails to load keeps the pieces shown before.
+    fn reload_sprites(&mut self, ctx: &mut Context) {
+        if self.loaded_piece_set == self.shared.piece_set {
+            return;
+        }
+
+        let directory = self.shared.piece_set
+            .and_then(|_index| self.shared.piece_sets.get(_index))
+            .map_or(String::new(), |_set| _set.resource_dir.clone());
+
+        match AppState::load_sprites(ctx, &directory) {
+            Ok(sprites) => {
+                self.sprites = sprites;
+                self.thumbnails.clear();
+            },
+            Err(error) => {
+                self.shared.controller.banner = Some(format!("Piece set not loaded: {}", error));
+                self.shared.piece_set = self.loaded_piece_set;
+            }
+        }
+
+        self.loaded_piece_set = self.shared.piece_set;
+    }
+
+    /// Loads the avatars and flags of the players when they are first shown. Images that fail are not tried again.
+    fn load_images(&mut self, ctx: &mut Context) {
+        for _path in self.shared.player_images() {
+            if !self.images.contains_key(&_path) {
+                let image = graphics::Image::new(ctx, &_path).ok();
+                self.images.insert(_path, image);
+            }
+        }
+    }
+
+    /// Reads a file kept from earlier sessions, empty if there is none yet
+    fn read_user_file(ctx: &mut Context, _path: &str) -> String {
+        let mut text = String::new();
+
+        if let Ok(mut file) = filesystem::open(ctx, _path) {
+            file.read_to_string(&mut text).ok();
+        }
+
+        text
+    }
+
+    /// Reads every save slot, skipping files that are not saved games
+    fn read_saves(ctx: &mut Context) -> SaveSlots {
+        let paths: Vec<String> = match filesystem::read_dir(ctx, SAVES_DIR) {
+            Ok(entries) => entries.filter_map(|_path| _path.to_str().map(|_path| _path.to_string())).collect(),
+            Err(_) => vec![]
+        };
+
+        SaveSlots::new(paths.iter()
+            .filter(|_path| _path.ends_with(".json"))
+            .filter_map(|_path| SavedGame::from_json(&AppState::read_user_file(ctx, _path)).ok())
+            .collect())
+    }
+
+    /// Writes a file to the user data directory of GGEZ
+    fn write_user_file(ctx: &mut Context, _path: &str, _text: &str) -> GameResult {
+        let mut file = filesystem::create(ctx, _path)?;
+        file.write_all(_text.as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Writes the game as JSON (see `export::game_to_json`) to a file in the user data directory of GGEZ
+    pub fn save_json(&self, ctx: &mut Context, _path: &str) -> GameResult {
+        let mut file = filesystem::create(ctx, _path)?;
+        file.write_all(export::game_to_json_string(&self.shared.controller).as_bytes())?;
+
+        Ok(())
+    }
+
+    /// Keeps the game being played in the autosave slot, for Continue on the main menu to carry on after a restart.
+    /// Only games of the game scene that have begun and are not over are kept, a mirror window keeps nothing.
+    fn autosave(&mut self, ctx: &mut Context) {
+        let controller = &self.shared.controller;
+        let playing = self.scenes.names().contains(&"game") && !controller.history.is_empty() && !controller.is_over();
+        if !playing || self.shared.read_only {
+            return;
+        }
+
+        let game = SavedGame::from_controller(AUTO_SAVE, controller);
+        filesystem::create_dir(ctx, SAVES_DIR).ok();
+        AppState::write_user_file(ctx, &game.file_name(), &game.to_json()).ok();
+    }
+
+    /// Draws the whole interface (the active scenes and the error banner) onto the current render target.
+    /// Shared by the window and the offscreen renderer, so it must not present.
+    pub fn draw_scene(&mut self, ctx: &mut Context) -> GameResult {
+        let mut renderer = GgezRenderer::new(ctx, &self.sprites)
+            .with_thumbnails(&mut self.thumbnails)
+            .with_fonts(&self.fonts)
+            .with_images(&self.images)
+            .with_indicators(&mut self.indicators);
+        if self.shared.config.low_spec {
+            renderer = renderer.with_shapes(&self.shapes);
+        }
+        self.scenes.draw(&mut renderer, &self.shared);
+
+        let shared = &self.shared;
+        shared.layers.draw(Layer::Hud, || {
+            for (_slot, _toast) in shared.toasts.iter().enumerate() {
+                // With reduced motion a toast stays solid until it goes
+                let remaining = if shared.config.reduced_motion { consts::TOAST_FADE } else { _toast.remaining };
+                draw_funcs::draw_toast(&mut renderer, &_toast.title, &_toast.message, _slot, remaining);
+            }
+        });
+
+        // Errors go on top of everything
+        if let Some(message) = &self.shared.controller.banner {
+            draw_funcs::draw_banner(&mut renderer, message);
+        }
+        if self.shared.controller.out_of_sync.is_some() && !self.shared.read_only {
+            draw_funcs::draw_resync_button(&mut renderer);
+        }
+
+        Ok(())
+    }
+}
+
+impl event::EventHandler<GameError> for AppState {
+
+    /// For updating game logic, which front-end doesn't handle. Runs as many fixed steps as the frame time covers.
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let steps = self.timestep.advance(timer::delta(ctx).as_secs_f32());
+        let step = self.timestep.step();
+
+        for _ in 0..steps {
+            self.tick(step);
+        }
+
+        // Results of background work are handled here, on the main thread. Their callbacks may start more of it.
+        let mut tasks = std::mem::take(&mut self.shared.tasks);
+        tasks.poll(&mut self.shared);
+        tasks.append(&mut self.shared.tasks);
+        self.shared.tasks = tasks;
+
+        self.update_window_title(ctx);
+        self.reload_sprites(ctx);
+        self.load_images(ctx);
+        self.fonts.update_scale_factor(ctx);
+        self.fonts.set_text_scale(self.shared.config.text_scale);
+        self.thumbnails.render_wanted(ctx, &self.sprites, &self.shared.theme)?;
+        let (volumes, pack) = (&self.shared.config.audio, &self.shared.config.sound_pack);
+        self.audio.update(ctx, &self.shared.controller, volumes, pack);
+        if let Some(sound) = self.shared.preview {
+            self.audio.play(ctx, sound, volumes, pack);
+        }
+        self.shared.preview = None;
+
+        if self.achievements_unsaved {
+            self.achievements_unsaved = AppState::write_user_file(ctx, ACHIEVEMENTS_FILE, &self.shared.achievements.to_json()).is_err();
+        }
+        if self.shared.profiles.unsaved {
+            self.shared.profiles.unsaved = AppState::write_user_file(ctx, PROFILES_FILE, &self.shared.profiles.to_json()).is_err();
+        }
+        if self.shared.recent.unsaved {
+            self.shared.recent.unsaved = AppState::write_user_file(ctx, RECENT_FILE, &self.shared.recent.to_json()).is_err();
+        }
+        for _name in std::mem::take(&mut self.shared.saves.unsaved) {
+            if let Some(game) = self.shared.saves.get(&_name) {
+                filesystem::create_dir(ctx, SAVES_DIR).ok();
+                if let Err(error) = AppState::write_user_file(ctx, &game.file_name(), &game.to_json()) {
+                    self.shared.controller.banner = Some(format!("{} could not be saved: {}", _name, error));
+                }
+            }
+        }
+        for _file in std::mem::take(&mut self.shared.saves.deleted) {
+            filesystem::delete(ctx, &_file).ok();
+        }
+        for (_path, _text) in std::mem::take(&mut self.shared.exports) {
+            if let Err(error) = AppState::write_user_file(ctx, &_path, &_text) {
+                self.shared.controller.banner = Some(format!("{} could not be written: {}", _path, error));
+            }
+        }
+        if self.shared.config.unsaved {
+            self.shared.config.unsaved = AppState::write_user_file(ctx, CONFIG_FILE, &self.shared.config.to_toml()).is_err();
+        }
+
+        if self.quit_requested {
+            self.autosave(ctx);
+            event::quit(ctx);
+        }
+
+        Ok(())
+    }
+
+    /// Draw interface, i.e. draw game board
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        self.draw_scene(ctx)?;
+
+        // render updated graphics
+        graphics::present(ctx).expect("Failed to update graphics.");
+
+        // Low-spec mode holds frames back to keep the laptop cool
+        if self.shared.config.low_spec {
+            let frame = Duration::from_secs_f32(1.0 / LOW_SPEC_FPS as f32);
+            if let Some(rest) = frame.checked_sub(self.last_frame.elapsed()) {
+                timer::sleep(rest);
+            }
+        }
+        self.last_frame = Instant::now();
+
+        Ok(())
+    }
+
+    /// Update game on mouse click
+    fn mouse_button_up_event(&mut self, ctx: &mut Context, button: event::MouseButton, x: f32, y: f32) {
+        match button {
+            event::MouseButton::Left => self.input(InputEvent::Click { x, y }),
+            event::MouseButton::Middle => self.input(InputEvent::MiddleRelease { x, y }),
+            event::MouseButton::Right => self.input(InputEvent::RightClick { x, y, mods: keyboard::active_mods(ctx) }),
+            _ => {}
+        }
+    }
+
+    /// Pick up pieces on mouse press, and start panning the board with the middle button
+    fn mouse_button_down_event(&mut self, _ctx: &mut Context, button: event::MouseButton, x: f32, y: f32) {
+        match button {
+            event::MouseButton::Left => self.input(InputEvent::Press { x, y }),
+            event::MouseButton::Middle => self.input(InputEvent::MiddlePress { x, y }),
+            _ => {}
+        }
+    }
+
+    /// Move the dragged piece with the mouse
+    fn mouse_motion_event(&mut self, _ctx: &mut Context, x: f32, y: f32, _dx: f32, _dy: f32) {
+        self.input(InputEvent::Motion { x, y });
+    }
+
+    /// Typed characters, for the text fields of scenes
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) {
+        self.input(InputEvent::Text(character));
+    }
+
+    /// Scroll lists and zoom the board with the mouse wheel
+    fn mouse_wheel_event(&mut self, _ctx: &mut Context, _x: f32, y: f32) {
+        self.input(InputEvent::Scroll { y });
+    }
+
+    /// Keep the game going on for the next start when the window is closed
+    fn quit_event(&mut self, ctx: &mut Context) -> bool {
+        self.autosave(ctx);
+        false
+    }
+
+    /// Pause the game when the window loses focus, unless the settings keep it going
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) {
+        if !gained && !self.shared.config.run_unfocused {
+            self.pause();
+        }
+    }
+
+    /// Update game on key press
+    fn key_down_event(&mut self, ctx: &mut Context, keycode: event::KeyCode, _keymods: event::KeyMods, _repeat: bool) {
+        // F12 saves the current position as an image
+        if keycode == event::KeyCode::F12 {
+            offscreen::save_png(ctx, self, "/position.png").ok();
+            return;
+        }
+
+        // The tilde key opens the developer console over any scene
+        if keycode == event::KeyCode::Grave && self.scenes.top_name() != "console" {
+            self.scenes.apply(Transition::Push(Box::new(ConsoleScene::new())));
+            return;
+        }
+
+        // F11 exports the game as JSON
+        if keycode == event::KeyCode::F11 {
+            self.save_json(ctx, "/game.json").ok();
+            return;
+        }
+
+        self.input(InputEvent::Key(keycode));
+    }
+}
@@ -0,0 +1,74 @@
+/// A named checkmate to practise: a position where White mates in a few moves the way the pattern goes. Black's
+/// replies are part of the line, so the player only finds White's moves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MatePattern {
+    pub name: &'static str,
+    pub description: &'static str, // What the pattern is, shown in the catalog
+    pub fen: &'static str, // The position, White to move
+    pub line: &'static [&'static str], // The moves in UCI, White's and Black's in turn, ending with White's mate
+    pub hints: &'static [&'static str] // One for every move of White
+}
+
+impl MatePattern {
+    /// Move of the line at a ply from the start of the position, None past its end
+    pub fn expected(&self, _ply: usize) -> Option<&'static str> {
+        self.line.get(_ply).copied()
+    }
+
+    /// The hint for the player's move at a ply, their moves are the even ones
+    pub fn hint(&self, _ply: usize) -> &'static str {
+        self.hints.get(_ply / 2).copied().unwrap_or("")
+    }
+
+    /// Moves the player makes to mate
+    pub fn moves(&self) -> usize {
+        (self.line.len() + 1) / 2
+    }
+}
+
+/// The patterns of the catalog, from the quickest mate to the longest
+pub const PATTERNS: [MatePattern; 4] = [
+    MatePattern {
+        name: "Back-rank mate",
+        description: "A rook or queen mates on the last rank, the king shut in by its own pawns",
+        fen: "3r2k1/5ppp/8/1Q6/8/8/5PPP/4R1K1 w - - 0 1",
+        line: &["e1e8", "d8e8", "b5e8"],
+        hints: &[
+            "The rook on d8 is all that guards the back rank. Offer it your rook with check.",
+            "The back rank is open again, and the queen reaches e8 along the diagonal."
+        ]
+    },
+    MatePattern {
+        name: "Smothered mate",
+        description: "A knight mates a king that its own pieces leave no square to",
+        fen: "4r2k/6pp/8/4N3/2Q5/B7/6PP/7K w - - 0 1",
+        line: &["e5f7", "h8g8", "f7h6", "g8h8", "c4g8", "e8g8", "h6f7"],
+        hints: &[
+            "Check with the knight from f7, the king only has g8.",
+            "A double check: the knight checks from h6 and uncovers the queen on the diagonal.",
+            "Give the queen up on g8, only the rook can take it.",
+            "The king is walled in by its own rook and pawns. The knight mates from f7."
+        ]
+    },
+    MatePattern {
+        name: "Anastasia's mate",
+        description: "A knight takes the squares beside a king on the edge, a rook mates along the edge",
+        fen: "5rk1/5ppp/8/3N3Q/8/3R4/5PPP/6K1 w - - 0 1",
+        line: &["d5e7", "g8h8", "h5h7", "h8h7", "d3h3"],
+        hints: &[
+            "Check from e7 with the knight, which then covers g8 and g6.",
+            "Give the queen up on h7 to open the h-file, the king has to take it.",
+            "The rook swings over to the h-file and mates."
+        ]
+    },
+    MatePattern {
+        name: "Boden's mate",
+        description: "Two bishops on crossing diagonals mate a king castled queenside",
+        fen: "2kr4/pp1n4/2n5/8/5B2/5Q2/4BPPP/6K1 w - - 0 1",
+        line: &["f3c6", "b7c6", "e2a6"],
+        hints: &[
+            "The bishop on f4 already covers b8 and c7. Give the queen up on c6 to pull the b-pawn away.",
+            "The b7 square is empty now, the other bishop mates from a6."
+        ]
+    }
+];
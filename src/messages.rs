@@ -0,0 +1,148 @@
+use serde::Deserialize;
+
+/// The texts the game shows about its state: whose turn it is, check and how it ended.
+///
+/// `{winner}` in the texts of wins is replaced by the name of the winning team (in `draw_offered`, of the team asked),
+/// and `{reason}` by how a variant was won.
+/// Plugins can add packs in a `[messages]` section, texts left out are taken from the taunts.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct MessagePack {
+    pub name: String,
+    pub white_team: String, // The players of White, i.e. "Rustaceans"
+    pub black_team: String,
+    pub white_to_move: String,
+    pub black_to_move: String,
+    pub check: String,
+    pub white_mated: String, // White lost by checkmate
+    pub black_mated: String,
+    pub draw: String,
+    pub variant_win: String, // A win condition of the variant was reached
+    pub time_win: String, // The loser's clock ran out
+    pub time_draw: String, // A clock ran out, but the other side could never have mated
+    pub adjudicated_win: String,
+    pub adjudicated_draw: String,
+    pub resigned: String, // The loser gave up
+    pub draw_agreed: String,
+    pub draw_offered: String // Asks the other side to accept a draw, `{winner}` is the side asked
+}
+
+impl Default for MessagePack {
+    fn default() -> MessagePack {
+        MessagePack::taunts()
+    }
+}
+
+impl MessagePack {
+    /// The Rustaceans against the Haskellers, as the GUI always had it
+    pub fn taunts() -> MessagePack {
+        MessagePack {
+            name: "taunts".to_string(),
+            white_team: "Rustaceans".to_string(),
+            black_team: "Haskellers".to_string(),
+            white_to_move: "Rustacean's turn!".to_string(),
+            black_to_move: "Haskeller's turn!".to_string(),
+            check: "It's Check!!!".to_string(),
+            white_mated: "Rust lost? PANIC!".to_string(),
+            black_mated: "Farewell Haskell!".to_string(),
+            draw: "Draw! Nobody panics.".to_string(),
+            variant_win: "{winner} win: {reason}".to_string(),
+            time_win: "{winner} win on time".to_string(),
+            time_draw: "Out of time, but nobody can mate. Draw!".to_string(),
+            adjudicated_win: "{winner} win by adjudication".to_string(),
+            adjudicated_draw: "Draw by adjudication".to_string(),
+            resigned: "{winner} win, the others resigned".to_string(),
+            draw_agreed: "Draw agreed. Nobody panics.".to_string(),
+            draw_offered: "{winner}, take the draw?".to_string()
+        }
+    }
+
+    /// Plain English, for tournaments and lessons
+    pub fn plain() -> MessagePack {
+        MessagePack {
+            name: "plain".to_string(),
+            white_team: "White".to_string(),
+            black_team: "Black".to_string(),
+            white_to_move: "White to move".to_string(),
+            black_to_move: "Black to move".to_string(),
+            check: "Check".to_string(),
+            white_mated: "Checkmate, Black wins".to_string(),
+            black_mated: "Checkmate, White wins".to_string(),
+            draw: "Draw".to_string(),
+            variant_win: "{winner} wins: {reason}".to_string(),
+            time_win: "{winner} wins on time".to_string(),
+            time_draw: "Draw, out of time against insufficient material".to_string(),
+            adjudicated_win: "{winner} wins by adjudication".to_string(),
+            adjudicated_draw: "Draw by adjudication".to_string(),
+            resigned: "{winner} wins by resignation".to_string(),
+            draw_agreed: "Draw agreed".to_string(),
+            draw_offered: "{winner}, accept a draw?".to_string()
+        }
+    }
+
+    /// Swedish
+    pub fn svenska() -> MessagePack {
+        MessagePack {
+            name: "svenska".to_string(),
+            white_team: "Vit".to_string(),
+            black_team: "Svart".to_string(),
+            white_to_move: "Vit vid draget".to_string(),
+            black_to_move: "Svart vid draget".to_string(),
+            check: "Schack!".to_string(),
+            white_mated: "Schack matt, svart vinner".to_string(),
+            black_mated: "Schack matt, vit vinner".to_string(),
+            draw: "Remi".to_string(),
+            variant_win: "{winner} vinner: {reason}".to_string(),
+            time_win: "{winner} vinner på tid".to_string(),
+            time_draw: "Remi, tiden slut men otillräckligt material".to_string(),
+            adjudicated_win: "{winner} vinner efter domslut".to_string(),
+            adjudicated_draw: "Remi efter domslut".to_string(),
+            resigned: "{winner} vinner, motståndaren gav upp".to_string(),
+            draw_agreed: "Remi efter överenskommelse".to_string(),
+            draw_offered: "{winner}, remi?".to_string()
+        }
+    }
+
+    /// The crabs talk back
+    pub fn pirate() -> MessagePack {
+        MessagePack {
+            name: "pirate".to_string(),
+            white_team: "The crabs".to_string(),
+            black_team: "The lambdas".to_string(),
+            white_to_move: "Crabs, man the guns!".to_string(),
+            black_to_move: "Lambdas, hoist the sails!".to_string(),
+            check: "Yer king be in peril!".to_string(),
+            white_mated: "The crabs walk the plank!".to_string(),
+            black_mated: "The lambdas sleep with the fishes!".to_string(),
+            draw: "A truce, ye scallywags".to_string(),
+            variant_win: "{winner} plunder the win: {reason}".to_string(),
+            time_win: "{winner} win as the sand runs out".to_string(),
+            time_draw: "The sand ran out, but nobody can sink a ship. A truce".to_string(),
+            adjudicated_win: "The captain hands {winner} the win".to_string(),
+            adjudicated_draw: "The captain calls a truce".to_string(),
+            resigned: "{winner} win, the foe strikes its colours".to_string(),
+            draw_agreed: "Both crews agree to a truce".to_string(),
+            draw_offered: "{winner}, will ye take a truce?".to_string()
+        }
+    }
+
+    /// Names of the packs built into the GUI
+    pub const BUILTIN: [&'static str; 4] = ["taunts", "plain", "svenska", "pirate"];
+
+    /// A built-in pack by name
+    pub fn builtin(_name: &str) -> Option<MessagePack> {
+        match _name {
+            "taunts" => Some(MessagePack::taunts()),
+            "plain" => Some(MessagePack::plain()),
+            "svenska" => Some(MessagePack::svenska()),
+            "pirate" => Some(MessagePack::pirate()),
+            _ => None
+        }
+    }
+
+    /// A text of the pack with the winning team and the reason filled in
+    pub fn fill(&self, _text: &str, _white_won: bool, _reason: &str) -> String {
+        let winner = if _white_won { &self.white_team } else { &self.black_team };
+        _text.replace("{winner}", winner).replace("{reason}", _reason)
+    }
+}
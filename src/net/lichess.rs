@@ -0,0 +1,297 @@
+use eliasfl_chess::Color as Colour;
+use serde_json::Value;
+use std::{io::{BufRead, BufReader, Read, Write}, net::{Shutdown, TcpStream}, sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError}, thread};
+use super::{Connection, Message, POLL_INTERVAL, tls::{self, TlsStream}};
+use super::super::clock::TimeControl;
+
+/// Where the Lichess API is
+const LICHESS: &str = "lichess.org:443";
+
+/// Days a move may take in untimed games, which Lichess plays as correspondence
+const CORRESPONDENCE_DAYS: u32 = 3;
+
+/// Which request a line of the Lichess API came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    Events, // The account's events, i.e. a game starting
+    Seek, // The seek, open until someone takes it
+    Game // The game being played
+}
+
+/// A response of the Lichess API, read up to its body
+struct Response {
+    status: u16,
+    chunked: bool, // The body comes in chunks, as the streams do
+    reader: BufReader<TlsStream>
+}
+
+impl Response {
+    /// A copy of the socket, to close the response from another thread
+    fn socket(&self) -> Result<TcpStream, String> {
+        self.reader.get_ref().sock.try_clone().map_err(|_error| _error.to_string())
+    }
+
+    /// ## `lines`
+    /// Hands the lines of the body to `_line` until it returns false or the body ends. Blocks.
+    /// Streams are newline-delimited JSON, with empty lines now and then to keep the connection alive.
+    fn lines(mut self, mut _line: impl FnMut(&str) -> bool) {
+        let mut partial = vec![];
+        loop {
+            let mut data = vec![];
+            if self.chunked {
+                let mut size = String::new();
+                if self.reader.read_line(&mut size).unwrap_or(0) == 0 {
+                    break;
+                }
+                let size = usize::from_str_radix(size.trim(), 16).unwrap_or(0);
+                data.resize(size, 0);
+                let mut end = [0; 2];
+                if size == 0 || self.reader.read_exact(&mut data).and_then(|_| self.reader.read_exact(&mut end)).is_err() {
+                    break;
+                }
+            }
+            else if self.reader.read_until(b'\n', &mut data).unwrap_or(0) == 0 {
+                break;
+            }
+
+            partial.extend_from_slice(&data);
+            while let Some(end) = partial.iter().position(|_byte| *_byte == b'\n') {
+                let line: Vec<u8> = partial.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                if !line.trim().is_empty() && !_line(line.trim()) {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// The whole body as JSON, `Err` with what Lichess says when the request failed
+    fn json(self) -> Result<Value, String> {
+        let status = self.status;
+        let mut body = String::new();
+        self.lines(|_line| {
+            body.push_str(_line);
+            true
+        });
+
+        let json: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+        match (status, json["error"].as_str()) {
+            (200..=299, None) => Ok(json),
+            (_, Some(error)) => Err(error.to_string()),
+            _ => Err(format!("Lichess answered {}", status))
+        }
+    }
+}
+
+/// ## `request`
+/// Sends a request to the Lichess API and reads the head of the response. Blocks.
+/// ### Parameters
+/// - `_method: &str`, `_path: &str`: The request, i.e. "GET" and "/api/account"
+/// - `_token: &str`: The player's API token
+/// - `_body: &str`: Form fields, empty for none
+fn request(_method: &str, _path: &str, _token: &str, _body: &str) -> Result<Response, String> {
+    let mut stream = tls::connect(LICHESS, "")?;
+    write!(stream, "{} {} HTTP/1.1\r\nHost: lichess.org\r\nAuthorization: Bearer {}\r\nAccept: application/x-ndjson\r\n\
+        Content-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        _method, _path, _token.trim(), _body.len(), _body)
+        .and_then(|_| stream.flush())
+        .map_err(|_error| _error.to_string())?;
+
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).map_err(|_error| _error.to_string())?;
+    let status = line.split_whitespace().nth(1).and_then(|_status| _status.parse().ok()).ok_or("Lichess did not answer in HTTP")?;
+
+    let mut chunked = false;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).map_err(|_error| _error.to_string())? == 0 || line.trim().is_empty() {
+            break;
+        }
+        let header = line.to_ascii_lowercase();
+        chunked |= header.starts_with("transfer-encoding:") && header.contains("chunked");
+    }
+
+    Ok(Response { status, chunked, reader })
+}
+
+/// ## `stream`
+/// Opens a stream of the Lichess API and hands its lines as JSON to `_lines` from a thread of its own, Null once it ended
+/// ### Return
+/// A copy of the socket to close the stream with, or `Err` if Lichess refused the request
+fn stream(_method: &str, _path: &str, _token: &str, _body: &str, _source: Source, _lines: &Sender<(Source, Value)>) -> Result<TcpStream, String> {
+    let response = request(_method, _path, _token, _body)?;
+    if !(200..=299).contains(&response.status) {
+        return Err(response.json().err().unwrap_or_default());
+    }
+
+    let socket = response.socket()?;
+    let lines = _lines.clone();
+    thread::spawn(move || {
+        response.lines(|_line| lines.send((_source, serde_json::from_str(_line).unwrap_or(Value::Null))).is_ok());
+        lines.send((_source, Value::Null)).ok();
+    });
+    Ok(socket)
+}
+
+/// The game being played on Lichess
+struct LichessGame {
+    id: String,
+    colour: Colour, // The player's side
+    known: usize // Moves already passed on or played here
+}
+
+impl LichessGame {
+    /// ## `update`
+    /// Passes on the opponent's moves of a game state that are new, and the end of the game
+    /// ### Parameters
+    /// - `_state: &Value`: The state, with the moves in UCI, the clocks in milliseconds and the status
+    /// - `_sender: &Sender<Message>`: Where the moves go
+    fn update(&mut self, _state: &Value, _sender: &Sender<Message>) {
+        let moves: Vec<&str> = _state["moves"].as_str().unwrap_or("").split_whitespace().collect();
+
+        for (_index, _move) in moves.iter().enumerate().skip(self.known) {
+            let mover = if _index % 2 == 0 { Colour::White } else { Colour::Black };
+            if mover == self.colour {
+                continue;
+            }
+
+            // Only the clock after the last move is known
+            let time = if mover == Colour::White { &_state["wtime"] } else { &_state["btime"] };
+            let clock = time.as_f64().filter(|_| _index + 1 == moves.len()).map(|_time| (_time / 1000.0) as f32);
+            _sender.send(Message::Move { uci: _move.to_string(), clock, hash: None }).ok();
+        }
+        self.known = self.known.max(moves.len());
+
+        let status = _state["status"].as_str().unwrap_or("started");
+        if status != "created" && status != "started" {
+            let winner = match _state["winner"].as_str() {
+                Some("white") => Some(Colour::White),
+                Some("black") => Some(Colour::Black),
+                _ => None
+            };
+            _sender.send(Message::Ended { winner, reason: status.to_string() }).ok();
+        }
+    }
+}
+
+/// ## `run`
+/// Seeks a game for the player and plays it: the opponent's moves go to `_sender` and the player's moves handed to
+/// the connection are sent to Lichess. Runs on the connection's thread until the game ends or the connection is
+/// dropped, which resigns a game that is going on (or aborts it before both sides moved).
+fn run(_token: String, _account: String, _time_control: Option<TimeControl>, _outgoing: Receiver<String>, _sender: Sender<Message>) {
+    let (to_lines, lines) = mpsc::channel();
+    let body = match _time_control {
+        Some(control) => format!("rated=false&time={}&increment={}", control.initial / 60.0, control.increment),
+        None => format!("rated=false&days={}", CORRESPONDENCE_DAYS)
+    };
+
+    let mut sockets = vec![];
+    for (_method, _path, _body, _source) in [("GET", "/api/stream/event", "", Source::Events), ("POST", "/api/board/seek", body.as_str(), Source::Seek)].iter() {
+        match stream(_method, _path, &_token, _body, *_source, &to_lines) {
+            Ok(socket) => sockets.push(socket),
+            Err(error) => {
+                _sender.send(Message::Error(error)).ok();
+                sockets.iter().for_each(|_socket: &TcpStream| { _socket.shutdown(Shutdown::Both).ok(); });
+                return;
+            }
+        }
+    }
+
+    let mut game: Option<LichessGame> = None;
+    loop {
+        match _outgoing.try_recv() {
+            Ok(line) => {
+                // "MOVE <uci> <clock> <hash>", Lichess keeps the clocks and the position itself. Resyncs are between
+                // two windows of this GUI only.
+                let words: Vec<&str> = line.split_whitespace().collect();
+                if let (Some(game), ["MOVE", uci, ..]) = (game.as_mut(), words.as_slice()) {
+                    game.known += 1;
+                    if let Err(error) = request("POST", &format!("/api/board/game/{}/move/{}", game.id, uci), &_token, "").and_then(Response::json) {
+                        _sender.send(Message::Error(format!("Lichess refused {}: {}", uci, error))).ok();
+                    }
+                }
+            },
+            Err(TryRecvError::Empty) => {},
+            Err(TryRecvError::Disconnected) => {
+                if let Some(game) = &game {
+                    let action = if game.known < 2 { "abort" } else { "resign" };
+                    request("POST", &format!("/api/board/game/{}/{}", game.id, action), &_token, "").and_then(Response::json).ok();
+                }
+                break;
+            }
+        }
+
+        match lines.recv_timeout(POLL_INTERVAL) {
+            Ok((Source::Events, event)) if event["type"] == "gameStart" && game.is_none() => {
+                let id = event["game"]["gameId"].as_str().or_else(|| event["game"]["id"].as_str()).unwrap_or("").to_string();
+                match stream("GET", &format!("/api/board/game/stream/{}", id), &_token, "", Source::Game, &to_lines) {
+                    Ok(socket) => sockets.push(socket),
+                    Err(error) => {
+                        _sender.send(Message::Error(error)).ok();
+                        break;
+                    }
+                }
+                game = Some(LichessGame { id, colour: Colour::White, known: 0 });
+            },
+            Ok((Source::Game, Value::Null)) => {
+                _sender.send(Message::Left).ok();
+                break;
+            },
+            Ok((Source::Game, event)) => {
+                let game = match game.as_mut() {
+                    Some(game) => game,
+                    None => continue
+                };
+
+                if event["type"] == "gameFull" {
+                    game.colour = if event["white"]["id"].as_str() == Some(_account.to_lowercase().as_str()) { Colour::White } else { Colour::Black };
+                    let opponent = if game.colour == Colour::White { &event["black"] } else { &event["white"] };
+                    let name = opponent["name"].as_str().map(str::to_string)
+                        .or_else(|| opponent["aiLevel"].as_u64().map(|_level| format!("Stockfish_level_{}", _level)))
+                        .unwrap_or_else(|| "Anonymous".to_string());
+                    let started = event["createdAt"].as_u64().map_or(0, |_created| _created / 1000);
+
+                    _sender.send(Message::Paired { colour: game.colour, opponent: name, started }).ok();
+                    game.update(&event["state"], &_sender);
+                }
+                else if event["type"] == "gameState" {
+                    game.update(&event, &_sender);
+                }
+            },
+            // The seek ends once it is taken, the game starts on the events
+            Ok(_) | Err(RecvTimeoutError::Timeout) => {},
+            Err(RecvTimeoutError::Disconnected) => break
+        }
+    }
+
+    for _socket in &sockets {
+        _socket.shutdown(Shutdown::Both).ok();
+    }
+}
+
+impl Connection {
+    /// ## `lichess`
+    /// Logs in to Lichess with an API token and seeks a casual game with the clock of the settings, played through
+    /// the Board API. The account's name arrives as `Message::LoggedIn`, then the game as from the matchmaker. Blocks until
+    /// logged in, so it is meant for a `Task`.
+    /// ### Parameters
+    /// - `_token: &str`: A personal API token of the player, with the `board:play` scope
+    /// - `_time_control: Option<TimeControl>`: The clock to play with, untimed games are played by correspondence
+    pub fn lichess(_token: &str, _time_control: Option<TimeControl>) -> Result<Connection, String> {
+        if _token.trim().is_empty() {
+            return Err("no Lichess token, make one on lichess.org with the board:play scope".to_string());
+        }
+        let account = request("GET", "/api/account", _token, "")?.json()?;
+        let name = account["username"].as_str().ok_or("Lichess did not say whose token it is")?.to_string();
+
+        let (to_thread, outgoing) = mpsc::channel::<String>();
+        let (sender, incoming) = mpsc::channel();
+        sender.send(Message::LoggedIn(name.clone())).ok();
+
+        let token = _token.trim().to_string();
+        thread::spawn(move || run(token, name, _time_control, outgoing, sender));
+
+        Ok(Connection { outgoing: to_thread, incoming, fingerprint: None })
+    }
+}
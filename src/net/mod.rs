@@ -0,0 +1,451 @@
+use eliasfl_chess::Color as Colour;
+use std::{io::{self, Read, Write}, net::{Shutdown, TcpListener}, sync::mpsc::{self, Receiver, Sender, TryRecvError}, thread, time::{Duration, SystemTime, UNIX_EPOCH}};
+use super::clock::TimeControl;
+use super::saves::SavedMove;
+
+pub mod lichess;
+pub mod tls;
+
+/// Matchmaking server asked when the configuration names none, see the `schack_matchmaker` binary
+pub const DEFAULT_MATCHMAKER: &str = "127.0.0.1:7878";
+
+/// Port LAN games are hosted on when the address names none
+pub const DEFAULT_LAN_PORT: u16 = 7879;
+
+/// How long the connection's thread waits for lines before it looks for lines to send again
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Longest line the opponent or the matchmaker may send, whole move lists included, the same as the matchmaker takes.
+/// Whoever sends more without a line break is dropped.
+pub(crate) const MAX_LINE_LENGTH: usize = 16 * 1024;
+
+/// How long a host waits for the greeting of a player who connected, before it listens for the next one
+const GREETING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the matchmaker or the opponent says, one line each
+#[derive(Debug, Clone, PartialEq)]
+pub enum Message {
+    /// No opponent with the same time control yet
+    Waiting,
+    /// An opponent was found, the game starts with this side for the player. `started` is when, in seconds since
+    /// the Unix epoch by the matchmaker's clock, the same for both players.
+    Paired { colour: Colour, opponent: String, started: u64 },
+    /// The opponent played a move, with what was left on their clock after it and the hash of the position it led to
+    /// (see `fen::position_hash`), None from opponents that send none, i.e. on Lichess
+    Move { uci: String, clock: Option<f32>, hash: Option<String> },
+    /// The opponent's position differs from theirs after a move, they ask for the moves of the game to rebuild it from
+    Resync,
+    /// The moves of the opponent's game with when they were played and the clocks after them, answering `Resync`
+    Moves(Vec<SavedMove>),
+    /// The opponent disconnected, or the connection to the matchmaker was lost
+    Left,
+    /// The matchmaker refused the greeting
+    Error(String),
+    /// Lichess took the token of this account, see `Connection::lichess`
+    LoggedIn(String),
+    /// The game ended without the board showing it, i.e. by resignation or on time on Lichess. `reason` is as
+    /// Lichess words it, i.e. "resign".
+    Ended { winner: Option<Colour>, reason: String }
+}
+
+impl Message {
+    /// Reads a line of the protocol, None for lines this version does not know
+    pub fn parse(_line: &str) -> Option<Message> {
+        let mut words = _line.split_whitespace();
+
+        match words.next()? {
+            "WAITING" => Some(Message::Waiting),
+            "PAIRED" => {
+                let colour = match words.next()? {
+                    "white" => Colour::White,
+                    "black" => Colour::Black,
+                    _ => return None
+                };
+                Some(Message::Paired { colour, opponent: words.next()?.to_string(), started: words.next()?.parse().ok()? })
+            },
+            "MOVE" => Some(Message::Move {
+                uci: words.next()?.to_string(),
+                // "-" for untimed games
+                clock: words.next().and_then(|_clock| _clock.parse().ok()),
+                hash: words.next().map(str::to_string)
+            }),
+            "RESYNC" => Some(Message::Resync),
+            "MOVES" => words.map(parse_move_token).collect::<Option<Vec<SavedMove>>>().map(Message::Moves),
+            "LEFT" => Some(Message::Left),
+            "ERROR" => Some(Message::Error(words.collect::<Vec<&str>>().join(" "))),
+            _ => None
+        }
+    }
+}
+
+/// ## `move_token`
+/// A move of `MOVES` as one word of the protocol: "<uci>,<elapsed>,<clock>", i.e. "e2e4,3.5,297.25", the clock "-" for
+/// untimed games. The times are written in full, so the rebuilt game exports the same as the one it was rebuilt from.
+pub fn move_token(_move: &SavedMove) -> String {
+    let clock = _move.clock.map_or("-".to_string(), |_clock| _clock.to_string());
+    format!("{},{},{}", _move.uci, _move.elapsed, clock)
+}
+
+/// Reads a move of `MOVES` written by `move_token`, None if it is not one
+fn parse_move_token(_token: &str) -> Option<SavedMove> {
+    let mut parts = _token.split(',');
+    let uci = parts.next()?.to_string();
+    let elapsed = parts.next()?.parse().ok()?;
+    let clock = match parts.next()? {
+        "-" => None,
+        clock => Some(clock.parse().ok()?)
+    };
+
+    if parts.next().is_some() {
+        return None;
+    }
+    Some(SavedMove { uci, elapsed, clock })
+}
+
+/// ## `time_control_key`
+/// The time control as the matchmaker pairs players on, i.e. "300+3", or "-" for untimed games
+pub fn time_control_key(_time_control: Option<TimeControl>) -> String {
+    match _time_control {
+        Some(control) => format!("{}+{}", control.initial, control.increment),
+        None => "-".to_string()
+    }
+}
+
+/// ## `name_token`
+/// A player name as one word of the protocol: spaces become `_`, and an empty name is "Anonymous"
+pub fn name_token(_name: &str) -> String {
+    let name: String = _name.trim().chars().map(|_char| if _char.is_whitespace() { '_' } else { _char }).take(24).collect();
+    if name.is_empty() { "Anonymous".to_string() } else { name }
+}
+
+/// ## `read_lines`
+/// The lines that arrived on a stream with a read timeout, none when the timeout passed first. What arrived of a
+/// line that is not complete yet is kept for the next read.
+/// ### Parameters
+/// - `_stream: &mut impl Read`: The stream, with a read timeout
+/// - `_partial: &mut Vec<u8>`: What arrived of the line being read
+/// ### Return
+/// The complete lines, or an error once the other end is gone or a line is longer than `MAX_LINE_LENGTH`
+pub fn read_lines(_stream: &mut impl Read, _partial: &mut Vec<u8>) -> io::Result<Vec<String>> {
+    let mut buffer = [0; 1024];
+    match _stream.read(&mut buffer) {
+        Ok(0) => return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "the connection was closed")),
+        Ok(read) => _partial.extend_from_slice(&buffer[..read]),
+        Err(error) if error.kind() == io::ErrorKind::WouldBlock || error.kind() == io::ErrorKind::TimedOut => {},
+        Err(error) => return Err(error)
+    }
+
+    let mut lines = vec![];
+    while let Some(end) = _partial.iter().position(|_byte| *_byte == b'\n') {
+        let line: Vec<u8> = _partial.drain(..=end).collect();
+        if line.len() > MAX_LINE_LENGTH {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "a line was too long"));
+        }
+        lines.push(String::from_utf8_lossy(&line).trim_end().to_string());
+    }
+
+    if _partial.len() > MAX_LINE_LENGTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "a line was too long"));
+    }
+
+    Ok(lines)
+}
+
+/// ## `lan_port`
+/// The port of a LAN address, i.e. 7879 of "192.168.1.20:7879"
+/// ### Return
+/// `DEFAULT_LAN_PORT` when the address names no port
+pub fn lan_port(_address: &str) -> u16 {
+    _address.rsplit(':').next().and_then(|_port| _port.parse().ok()).unwrap_or(DEFAULT_LAN_PORT)
+}
+
+/// ## `relay`
+/// Sends the lines handed to a connection and passes on the messages that arrive, until either end closes. Runs on
+/// the connection's thread.
+/// ### Parameters
+/// - `_stream`: The stream, with a read timeout
+/// - `_outgoing: Receiver<String>`: Lines to send, disconnected once the connection was dropped
+/// - `_sender: Sender<Message>`: Where the messages that arrive go, `LEFT` last when the other end is gone
+/// - `_close: fn(&mut S)`: Closes the stream when the connection was dropped
+fn relay<S: Read + Write>(mut _stream: S, _outgoing: Receiver<String>, _sender: Sender<Message>, _close: fn(&mut S)) {
+    let mut partial = vec![];
+    'connection: loop {
+        // Lines of the player go out first
+        loop {
+            match _outgoing.try_recv() {
+                Ok(line) => if writeln!(_stream, "{}", line).and_then(|_| _stream.flush()).is_err() {
+                    break 'connection;
+                },
+                Err(TryRecvError::Empty) => break,
+                // Nobody listens anymore once the connection was dropped
+                Err(TryRecvError::Disconnected) => {
+                    _close(&mut _stream);
+                    return;
+                }
+            }
+        }
+
+        match read_lines(&mut _stream, &mut partial) {
+            Ok(lines) => for _message in lines.iter().filter_map(|_line| Message::parse(_line)) {
+                if _sender.send(_message).is_err() {
+                    return;
+                }
+            },
+            Err(_) => break
+        }
+    }
+    _sender.send(Message::Left).ok();
+}
+
+/// ## `greet_guest`
+/// Reads the greeting of a player who connected to a LAN game and pairs them, the guest playing Black
+/// ### Parameters
+/// - `_stream: &mut impl Read + Write`: The guest's connection, with a read timeout
+/// - `_name: &str`: The host's name as a word of the protocol
+/// - `_time_control: &str`: The host's clock, see `time_control_key`
+/// ### Return
+/// The guest's name and when the game started, or `Err` when the guest said nothing in time or plays another clock
+fn greet_guest(_stream: &mut (impl Read + Write), _name: &str, _time_control: &str) -> Result<(String, u64), String> {
+    let mut partial = vec![];
+    let mut waited = Duration::from_secs(0);
+
+    while waited < GREETING_TIMEOUT {
+        let lines = read_lines(_stream, &mut partial).map_err(|_error| _error.to_string())?;
+        if let Some(line) = lines.iter().find(|_line| _line.starts_with("HELLO ")) {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (guest, clock) = (words.get(1).copied().unwrap_or("Anonymous"), words.get(2).copied().unwrap_or("-"));
+            if clock != _time_control {
+                writeln!(_stream, "ERROR the host plays with the clock {}", _time_control).ok();
+                return Err(format!("{} has another clock", guest));
+            }
+
+            let started = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.as_secs());
+            writeln!(_stream, "PAIRED black {} {}", _name, started).and_then(|_| _stream.flush()).map_err(|_error| _error.to_string())?;
+            return Ok((guest.to_string(), started));
+        }
+        waited += POLL_INTERVAL;
+    }
+
+    Err("no greeting".to_string())
+}
+
+/// A connection to the opponent, through the matchmaker or straight over the local network, encrypted with TLS either
+/// way. The stream is owned by a thread of its own, which sends the lines handed to it and reads the lines picked up with `poll`,
+/// so the update loop never waits on the network. Dropping the connection closes it, which the opponent hears as `LEFT`.
+pub struct Connection {
+    outgoing: Sender<String>,
+    incoming: Receiver<Message>,
+    fingerprint: Option<String> // Of the certificate of a LAN game hosted, for the guest to check, see `tls::fingerprint`
+}
+
+impl Connection {
+    /// ## `connect`
+    /// Connects to the matchmaker and asks for an opponent. Blocks, so it is meant for a `Task`.
+    /// ### Parameters
+    /// - `_address: &str`: Of the matchmaker, as "host:port"
+    /// - `_certificate: &str`: PEM file of the matchmaker's certificate when it is self-signed, empty otherwise
+    /// - `_name: &str`: The name the opponent sees
+    /// - `_time_control: Option<TimeControl>`: The clock to play with, only players with the same are paired
+    pub fn connect(_address: &str, _certificate: &str, _name: &str, _time_control: Option<TimeControl>) -> Result<Connection, String> {
+        let mut stream = tls::connect(_address, _certificate)?;
+        writeln!(stream, "HELLO {} {}", name_token(_name), time_control_key(_time_control)).and_then(|_| stream.flush()).map_err(|_error| _error.to_string())?;
+        stream.sock.set_read_timeout(Some(POLL_INTERVAL)).map_err(|_error| _error.to_string())?;
+
+        let (to_thread, outgoing) = mpsc::channel::<String>();
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || relay(stream, outgoing, sender, |_stream| {
+            _stream.conn.send_close_notify();
+            _stream.flush().ok();
+            _stream.sock.shutdown(Shutdown::Both).ok();
+        }));
+
+        Ok(Connection { outgoing: to_thread, incoming, fingerprint: None })
+    }
+
+    /// ## `host`
+    /// Hosts a game on the local network: listens on a port until a player with the same clock joins, who plays Black.
+    /// Returns once listening, the pairing arrives as `PAIRED` like from the matchmaker. Dropping the connection
+    /// before then stops listening. The game is encrypted with a certificate made for it, whose fingerprint
+    /// (see `Connection::fingerprint`) the guest types to join.
+    /// ### Parameters
+    /// - `_port: u16`: The port to listen on, on every interface
+    /// - `_name: &str`: The name the opponent sees
+    /// - `_time_control: Option<TimeControl>`: The clock to play with, players with another are turned away
+    pub fn host(_port: u16, _name: &str, _time_control: Option<TimeControl>) -> Result<Connection, String> {
+        let (config, fingerprint) = tls::host_config()?;
+        let listener = TcpListener::bind(("0.0.0.0", _port)).map_err(|_error| format!("port {}: {}", _port, _error))?;
+        listener.set_nonblocking(true).map_err(|_error| _error.to_string())?;
+        let (name, time_control) = (name_token(_name), time_control_key(_time_control));
+
+        let (to_thread, outgoing) = mpsc::channel::<String>();
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            let (stream, guest, started) = loop {
+                if let Err(TryRecvError::Disconnected) = outgoing.try_recv() {
+                    return;
+                }
+
+                match listener.accept() {
+                    Ok((socket, _)) => {
+                        // The handshake has as long as the greeting, a guest who refused the key breaks it off
+                        let ready = socket.set_nonblocking(false).and_then(|_| socket.set_read_timeout(Some(GREETING_TIMEOUT)));
+                        let encrypted = ready.map_err(|_error| _error.to_string()).and_then(|_| tls::accept(socket, config.clone()));
+
+                        // A player who cannot be paired is hung up on, and the next one is waited for
+                        if let Ok(mut stream) = encrypted {
+                            stream.sock.set_read_timeout(Some(POLL_INTERVAL)).ok();
+                            if let Ok((guest, started)) = greet_guest(&mut stream, &name, &time_control) {
+                                break (stream, guest, started);
+                            }
+                        }
+                    },
+                    Err(error) if error.kind() == io::ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(error) => {
+                        sender.send(Message::Error(error.to_string())).ok();
+                        return;
+                    }
+                }
+            };
+
+            if sender.send(Message::Paired { colour: Colour::White, opponent: guest, started }).is_ok() {
+                relay(stream, outgoing, sender, |_stream| {
+                    _stream.conn.send_close_notify();
+                    _stream.flush().ok();
+                    _stream.sock.shutdown(Shutdown::Both).ok();
+                });
+            }
+        });
+
+        Ok(Connection { outgoing: to_thread, incoming, fingerprint: Some(fingerprint) })
+    }
+
+    /// ## `join`
+    /// Joins a game hosted on the local network, see `host`. Blocks, so it is meant for a `Task`.
+    /// ### Parameters
+    /// - `_address: &str`: Of the host, as "host:port"
+    /// - `_fingerprint: &str`: Of the host's certificate as its lobby shows it, nothing is sent to a host with another
+    /// - `_name: &str`: The name the opponent sees
+    /// - `_time_control: Option<TimeControl>`: The clock to play with, the host's has to be the same
+    pub fn join(_address: &str, _fingerprint: &str, _name: &str, _time_control: Option<TimeControl>) -> Result<Connection, String> {
+        let mut stream = tls::join(_address, _fingerprint)?;
+        writeln!(stream, "HELLO {} {}", name_token(_name), time_control_key(_time_control)).and_then(|_| stream.flush()).map_err(|_error| _error.to_string())?;
+        stream.sock.set_read_timeout(Some(POLL_INTERVAL)).map_err(|_error| _error.to_string())?;
+
+        let (to_thread, outgoing) = mpsc::channel::<String>();
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || relay(stream, outgoing, sender, |_stream| {
+            _stream.conn.send_close_notify();
+            _stream.flush().ok();
+            _stream.sock.shutdown(Shutdown::Both).ok();
+        }));
+
+        Ok(Connection { outgoing: to_thread, incoming, fingerprint: None })
+    }
+
+    /// The fingerprint of the certificate of a LAN game hosted, which the guest has to type to join. None for
+    /// other connections.
+    pub fn fingerprint(&self) -> Option<&str> {
+        self.fingerprint.as_deref()
+    }
+
+    /// The next message, if one arrived
+    pub fn poll(&self) -> Option<Message> {
+        self.incoming.try_recv().ok()
+    }
+
+    /// Sends a move of the player to the opponent, with what is left on the player's clock and the hash of the position
+    /// it led to, see `fen::position_hash`
+    pub fn send_move(&mut self, _uci: &str, _clock: Option<f32>, _hash: &str) -> Result<(), String> {
+        let line = match _clock {
+            // Rounded like `pgn::clock_comment`, so the opponent writes the clock the same way in an export
+            Some(clock) => format!("MOVE {} {:.1} {}", _uci, (clock * 10.0).round() / 10.0, _hash),
+            None => format!("MOVE {} - {}", _uci, _hash)
+        };
+        self.send_line(line)
+    }
+
+    /// Asks the opponent for the moves of their game, once the positions differ, see `Message::Resync`
+    pub fn request_resync(&mut self) -> Result<(), String> {
+        self.send_line("RESYNC".to_string())
+    }
+
+    /// Sends the moves of the player's game with their times and clocks, for the opponent to rebuild theirs from, see
+    /// `Message::Moves`
+    pub fn send_moves(&mut self, _moves: &[SavedMove]) -> Result<(), String> {
+        let tokens: Vec<String> = _moves.iter().map(move_token).collect();
+        self.send_line(format!("MOVES {}", tokens.join(" ")))
+    }
+
+    /// Hands a line of the protocol to the connection's thread
+    fn send_line(&mut self, _line: String) -> Result<(), String> {
+        self.outgoing.send(_line).map_err(|_| "the connection is closed".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Every line of the protocol, and lines this version does not know
+    #[test]
+    fn parse_reads_the_protocol() {
+        assert_eq!(Message::parse("WAITING"), Some(Message::Waiting));
+        assert_eq!(Message::parse("PAIRED white Ada 1700000000"), Some(Message::Paired { colour: Colour::White, opponent: "Ada".to_string(), started: 1700000000 }));
+        assert_eq!(Message::parse("PAIRED black Ada 1700000000"), Some(Message::Paired { colour: Colour::Black, opponent: "Ada".to_string(), started: 1700000000 }));
+        assert_eq!(Message::parse("MOVE e2e4 59.5 0123456789abcdef"),
+            Some(Message::Move { uci: "e2e4".to_string(), clock: Some(59.5), hash: Some("0123456789abcdef".to_string()) }));
+        assert_eq!(Message::parse("MOVE e7e8q - 0123456789abcdef"),
+            Some(Message::Move { uci: "e7e8q".to_string(), clock: None, hash: Some("0123456789abcdef".to_string()) }));
+        assert_eq!(Message::parse("MOVE e2e4"), Some(Message::Move { uci: "e2e4".to_string(), clock: None, hash: None }));
+        assert_eq!(Message::parse("RESYNC"), Some(Message::Resync));
+        assert_eq!(Message::parse("MOVES e2e4,3.5,297.25 e7e5,9,-"), Some(Message::Moves(vec![
+            SavedMove { uci: "e2e4".to_string(), elapsed: 3.5, clock: Some(297.25) },
+            SavedMove { uci: "e7e5".to_string(), elapsed: 9.0, clock: None }
+        ])));
+        assert_eq!(Message::parse("MOVES"), Some(Message::Moves(vec![])));
+        assert_eq!(Message::parse("LEFT"), Some(Message::Left));
+        assert_eq!(Message::parse("ERROR the host plays with the clock 300+3"), Some(Message::Error("the host plays with the clock 300+3".to_string())));
+
+        assert_eq!(Message::parse(""), None);
+        assert_eq!(Message::parse("HELLO Ada -"), None);
+        assert_eq!(Message::parse("PAIRED red Ada 1700000000"), None);
+        assert_eq!(Message::parse("PAIRED white Ada"), None);
+        assert_eq!(Message::parse("MOVE"), None);
+        assert_eq!(Message::parse("MOVES e2e4"), None);
+        assert_eq!(Message::parse("MOVES e2e4,3.5,-,1"), None);
+    }
+
+    // Lines are read across reads, and whoever sends too long a line is dropped
+    #[test]
+    fn read_lines_caps_the_length() {
+        let mut partial = vec![];
+        assert_eq!(read_lines(&mut &b"WAITING\nPAI"[..], &mut partial).unwrap(), vec!["WAITING".to_string()]);
+        assert_eq!(read_lines(&mut &b"RED"[..], &mut partial).unwrap(), Vec::<String>::new());
+        assert_eq!(partial, b"PAIRED");
+
+        let mut partial = vec![b'x'; MAX_LINE_LENGTH];
+        assert!(read_lines(&mut &b"x"[..], &mut partial).is_err());
+        let mut partial = vec![b'x'; MAX_LINE_LENGTH];
+        assert!(read_lines(&mut &b"x\n"[..], &mut partial).is_err());
+        assert!(read_lines(&mut &b""[..], &mut vec![]).is_err());
+    }
+
+    // The moves sent to rebuild a game are read back with the same times
+    #[test]
+    fn move_token_round_trips() {
+        let moves = vec![
+            SavedMove { uci: "e2e4".to_string(), elapsed: 1.0 / 3.0, clock: Some(299.87654) },
+            SavedMove { uci: "e7e8q".to_string(), elapsed: 12.25, clock: None }
+        ];
+        let tokens: Vec<String> = moves.iter().map(move_token).collect();
+        assert_eq!(tokens[1], "e7e8q,12.25,-");
+        assert_eq!(Message::parse(&format!("MOVES {}", tokens.join(" "))), Some(Message::Moves(moves)));
+    }
+
+    // Names are one word of the protocol
+    #[test]
+    fn name_token_is_one_word() {
+        assert_eq!(name_token("  Ada Lovelace "), "Ada_Lovelace");
+        assert_eq!(name_token("   "), "Anonymous");
+    }
+}
@@ -0,0 +1,173 @@
+use rustls::client::{ServerCertVerified, ServerCertVerifier};
+use rustls::{Certificate, ClientConfig, ClientConnection, OwnedTrustAnchor, PrivateKey, RootCertStore, ServerConfig, ServerConnection, ServerName, StreamOwned};
+use std::{convert::TryFrom, fs::File, io::BufReader, net::TcpStream, sync::Arc, time::SystemTime};
+
+/// A connection to the matchmaker or to the host of a LAN game, encrypted
+pub type TlsStream = StreamOwned<ClientConnection, TcpStream>;
+
+/// The connection of the guest of a LAN game, encrypted
+pub type HostStream = StreamOwned<ServerConnection, TcpStream>;
+
+/// Name the certificates of LAN hosts are made for. Guests check the fingerprint instead, so it is never looked up.
+const LAN_NAME: &str = "schack-lan";
+
+/// Bytes of the SHA-256 of a certificate shown as its fingerprint, few enough to read out and type
+const FINGERPRINT_BYTES: usize = 12;
+
+/// ## `load_certificates`
+/// Reads the certificates of a PEM file, i.e. the self-signed certificate of a matchmaker
+/// ### Parameters
+/// - `_path: &str`: Of the PEM file
+pub fn load_certificates(_path: &str) -> Result<Vec<Certificate>, String> {
+    let file = File::open(_path).map_err(|_error| format!("{}: {}", _path, _error))?;
+    let certificates = rustls_pemfile::certs(&mut BufReader::new(file)).map_err(|_error| format!("{}: {}", _path, _error))?;
+    if certificates.is_empty() {
+        return Err(format!("{} holds no certificate", _path));
+    }
+
+    Ok(certificates.into_iter().map(Certificate).collect())
+}
+
+/// ## `client_config`
+/// The TLS settings of the client: the matchmaker has to show a certificate signed by one of the public
+/// certificate authorities, or the one certificate given
+/// ### Parameters
+/// - `_certificate: &str`: PEM file of the certificate to trust instead of the public authorities, empty for none
+pub fn client_config(_certificate: &str) -> Result<Arc<ClientConfig>, String> {
+    let mut roots = RootCertStore::empty();
+    if _certificate.is_empty() {
+        roots.add_trust_anchors(webpki_roots::TLS_SERVER_ROOTS.iter().map(|_anchor| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(_anchor.subject, _anchor.spki, _anchor.name_constraints)
+        }));
+    }
+    else {
+        for _trusted in load_certificates(_certificate)? {
+            roots.add(&_trusted).map_err(|_error| format!("{}: {}", _certificate, _error))?;
+        }
+    }
+
+    Ok(Arc::new(ClientConfig::builder().with_safe_defaults().with_root_certificates(roots).with_no_client_auth()))
+}
+
+/// ## `connect`
+/// Opens an encrypted connection, the handshake done before returning so a certificate that is not trusted is
+/// reported here rather than on the first line sent. Blocks.
+/// ### Parameters
+/// - `_address: &str`: As "host:port", the host is what the certificate is checked against
+/// - `_certificate: &str`: See `client_config`
+pub fn connect(_address: &str, _certificate: &str) -> Result<TlsStream, String> {
+    let host = _address.rsplitn(2, ':').last().unwrap_or(_address).trim_start_matches('[').trim_end_matches(']');
+    let name = ServerName::try_from(host).map_err(|_| format!("{} is no host name a certificate can be checked against", host))?;
+    let connection = ClientConnection::new(client_config(_certificate)?, name).map_err(|_error| _error.to_string())?;
+
+    let socket = TcpStream::connect(_address).map_err(|_error| format!("{}: {}", _address, _error))?;
+    let mut stream = StreamOwned::new(connection, socket);
+    while stream.conn.is_handshaking() {
+        stream.conn.complete_io(&mut stream.sock).map_err(|_error| format!("{}: {}", _address, _error))?;
+    }
+
+    Ok(stream)
+}
+
+/// ## `fingerprint`
+/// What two players compare to know the guest reached the host they meant: the start of the SHA-256 of the host's
+/// certificate, in groups of four hex digits, i.e. "3f2a 91c0 77de 0b14 c2e9 5a60"
+pub fn fingerprint(_certificate: &Certificate) -> String {
+    let digest = ring::digest::digest(&ring::digest::SHA256, &_certificate.0);
+
+    digest.as_ref()[..FINGERPRINT_BYTES].chunks(2)
+        .map(|_pair| _pair.iter().map(|_byte| format!("{:02x}", _byte)).collect::<String>())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// A fingerprint as typed, without spaces or colons and in lower case, to compare with another
+fn normalise(_fingerprint: &str) -> String {
+    _fingerprint.chars().filter(|_char| _char.is_ascii_hexdigit()).map(|_char| _char.to_ascii_lowercase()).collect()
+}
+
+/// ## `host_config`
+/// The TLS settings of a LAN host, with a self-signed certificate made for this one game
+/// ### Return
+/// The settings, and the fingerprint of the certificate for the host to show the guest
+pub fn host_config() -> Result<(Arc<ServerConfig>, String), String> {
+    let generated = rcgen::generate_simple_self_signed(vec![LAN_NAME.to_string()]).map_err(|_error| _error.to_string())?;
+    let certificate = Certificate(generated.serialize_der().map_err(|_error| _error.to_string())?);
+    let key = PrivateKey(generated.serialize_private_key_der());
+    let shown = fingerprint(&certificate);
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(vec![certificate], key)
+        .map_err(|_error| _error.to_string())?;
+
+    Ok((Arc::new(config), shown))
+}
+
+/// ## `accept`
+/// Encrypts the connection of a guest who connected to a LAN game, the handshake done before returning. Blocks up to
+/// the read timeout of the socket.
+/// ### Parameters
+/// - `_socket: TcpStream`: The guest's connection
+/// - `_config: Arc<ServerConfig>`: See `host_config`
+pub fn accept(_socket: TcpStream, _config: Arc<ServerConfig>) -> Result<HostStream, String> {
+    let connection = ServerConnection::new(_config).map_err(|_error| _error.to_string())?;
+
+    let mut stream = StreamOwned::new(connection, _socket);
+    while stream.conn.is_handshaking() {
+        stream.conn.complete_io(&mut stream.sock).map_err(|_error| _error.to_string())?;
+    }
+
+    Ok(stream)
+}
+
+/// Trusts the certificate of a LAN host when its fingerprint is the one the guest typed, no authority vouching for it
+struct PinnedCertificate {
+    fingerprint: String // Normalised, empty when the guest typed none
+}
+
+impl ServerCertVerifier for PinnedCertificate {
+    fn verify_server_cert(&self, _end_entity: &Certificate, _intermediates: &[Certificate], _server_name: &ServerName,
+                          _scts: &mut dyn Iterator<Item = &[u8]>, _ocsp_response: &[u8], _now: SystemTime) -> Result<ServerCertVerified, rustls::Error> {
+        let presented = fingerprint(_end_entity);
+
+        if self.fingerprint.is_empty() {
+            Err(rustls::Error::General(format!("the host's key is {}, check it against the host's screen and type it under Key", presented)))
+        }
+        else if normalise(&presented) != self.fingerprint {
+            Err(rustls::Error::General(format!("the host's key is {}, not the one typed, someone else may be answering", presented)))
+        }
+        else {
+            Ok(ServerCertVerified::assertion())
+        }
+    }
+}
+
+/// ## `join`
+/// Opens an encrypted connection to the host of a LAN game, refused before anything is sent unless the host's
+/// certificate has the fingerprint typed. Blocks.
+/// ### Parameters
+/// - `_address: &str`: As "host:port"
+/// - `_fingerprint: &str`: The fingerprint of the host's certificate, as the host's lobby shows it
+pub fn join(_address: &str, _fingerprint: &str) -> Result<TlsStream, String> {
+    let verifier = PinnedCertificate { fingerprint: normalise(_fingerprint) };
+    let config = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    let name = ServerName::try_from(LAN_NAME).map_err(|_error| _error.to_string())?;
+    let connection = ClientConnection::new(Arc::new(config), name).map_err(|_error| _error.to_string())?;
+
+    let socket = TcpStream::connect(_address).map_err(|_error| format!("{}: {}", _address, _error))?;
+    let mut stream = StreamOwned::new(connection, socket);
+    while stream.conn.is_handshaking() {
+        // The reason a key was refused is meant for the player as it is
+        stream.conn.complete_io(&mut stream.sock).map_err(|_error| match _error.get_ref().and_then(|_inner| _inner.downcast_ref::<rustls::Error>()) {
+            Some(rustls::Error::General(reason)) => reason.clone(),
+            _ => format!("{}: {}", _address, _error)
+        })?;
+    }
+
+    Ok(stream)
+}
@@ -0,0 +1,495 @@
+use eliasfl_chess::Color as Colour;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::{collections::{HashMap, HashSet, VecDeque}, io::{self, BufRead, BufReader, Read, Write}, net::{TcpListener, TcpStream}};
+use std::{sync::{Arc, Mutex}, thread, time::{Duration, Instant}};
+use super::consts::{SPECTATOR_TIMEOUT, MAX_CHAT_LENGTH};
+use super::controller::GameController;
+use super::export::colour_name;
+use super::fen::position_hash;
+use super::saves::{SavedGame, SavedMove};
+
+/// The state the endpoints serve, rebuilt by the GUI and read by the server thread
+#[derive(Default)]
+struct Published {
+    position: Value,
+    clocks: Value,
+    last_move: Value,
+    game: Value,
+    sync: Value
+}
+
+/// What a mirror window checks at every look: the last move with the position it leads to, and the clocks.
+/// When the position differs from its own, it takes the whole game from `/game` instead.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SyncState {
+    pub ply: usize, // Moves played
+    pub hash: String, // Of the position after them, see `position_hash`
+    pub last_move: Option<SavedMove>,
+    pub game_time: f32,
+    pub clocks: Option<(f32, f32)> // Seconds left for white and black
+}
+
+impl SyncState {
+    /// The sync state of the game being played
+    pub fn from_controller(_controller: &GameController) -> SyncState {
+        let game = SavedGame::from_controller("sync", _controller);
+
+        SyncState {
+            ply: game.moves.len(),
+            hash: position_hash(&game.fen),
+            last_move: game.moves.last().cloned(),
+            game_time: game.game_time,
+            clocks: game.clocks
+        }
+    }
+}
+
+/// Longest name a spectator can go by
+const MAX_NAME_LENGTH: usize = 20;
+
+/// How long a connection may go quiet while its request is read or its answer written, before it is dropped
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Longest request line or header, in bytes. Whoever sends more without a line break is dropped.
+const MAX_HEADER_LENGTH: usize = 8 * 1024;
+
+/// Most headers read of a request
+const MAX_HEADERS: usize = 64;
+
+/// Most lines of the spectator chat kept, older ones are dropped
+const MAX_CHAT_LINES: usize = 500;
+
+/// Seconds between two updates of the endpoints while no move is played, often enough for the clocks of an overlay
+const PUBLISH_INTERVAL: f32 = 0.25;
+
+/// A line of the spectator chat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatLine {
+    pub name: String, // Of the spectator who wrote it
+    pub text: String,
+    #[serde(skip)]
+    pub token: String // Of the spectator who wrote it, kept on the server for muting and never sent
+}
+
+/// A mirror window that joined with `POST /join`
+#[derive(Debug, Clone)]
+struct Spectator {
+    name: String, // The name it asked for, several spectators can share one
+    seen: Instant // When it last asked for anything
+}
+
+/// The mirror windows watching the game and their chat, kept apart from the players. Shared between the server
+/// thread, which hears from the spectators, and the GUI, where the host mutes them.
+///
+/// Spectators are told apart by the token the server gives each when it joins, not by their names: a muted
+/// spectator stays muted under any name, and has to join again to talk, as a new spectator the host sees join.
+#[derive(Debug, Default)]
+pub struct Spectators {
+    joined: HashMap<String, Spectator>, // Every spectator by its token
+    chat: VecDeque<ChatLine>, // The last `MAX_CHAT_LINES` lines, oldest first, muted spectators' lines included
+    dropped: usize, // Lines dropped from the front of `chat`, the first kept one is the `dropped`th line written
+    muted: HashSet<String> // Tokens of the spectators whose lines are refused and hidden
+}
+
+impl Spectators {
+    /// ## `join`
+    /// Lets a mirror window in under a name. Spectators gone for a while are forgotten, unless they were muted.
+    /// ### Parameters
+    /// - `_name: &str`: The name asked for, see `spectator_name`
+    /// ### Return
+    /// The token the spectator sends with every request from then on, `Err` if no random token could be made
+    pub fn join(&mut self, _name: &str) -> Result<String, String> {
+        let mut bytes = [0; 16];
+        SystemRandom::new().fill(&mut bytes).map_err(|_| "no token could be made".to_string())?;
+        let token: String = bytes.iter().map(|_byte| format!("{:02x}", _byte)).collect();
+
+        let muted = &self.muted;
+        self.joined.retain(|_token, _spectator| muted.contains(_token) || _spectator.seen.elapsed().as_secs_f32() < SPECTATOR_TIMEOUT);
+        self.joined.insert(token.clone(), Spectator { name: spectator_name(_name), seen: Instant::now() });
+
+        Ok(token)
+    }
+
+    /// Notes that the spectator of the token asked for something, returning its name. None for tokens never given out.
+    fn seen(&mut self, _token: &str) -> Option<String> {
+        let spectator = self.joined.get_mut(_token)?;
+        spectator.seen = Instant::now();
+
+        Some(spectator.name.clone())
+    }
+
+    /// Tokens and names of the spectators heard from in the last `SPECTATOR_TIMEOUT` seconds, in alphabetical order of the names
+    pub fn present(&self) -> Vec<(String, String)> {
+        let mut present: Vec<(String, String)> = self.joined.iter()
+            .filter(|(_, _spectator)| _spectator.seen.elapsed().as_secs_f32() < SPECTATOR_TIMEOUT)
+            .map(|(_token, _spectator)| (_token.clone(), _spectator.name.clone()))
+            .collect();
+        present.sort_by(|_a, _b| (&_a.1, &_a.0).cmp(&(&_b.1, &_b.0)));
+
+        present
+    }
+
+    /// If the host muted the spectator of the token
+    pub fn is_muted(&self, _token: &str) -> bool {
+        self.muted.contains(_token)
+    }
+
+    /// Mutes the spectator of the token, or lets them talk again
+    pub fn toggle_mute(&mut self, _token: &str) {
+        if !self.muted.remove(_token) {
+            self.muted.insert(_token.to_string());
+        }
+    }
+
+    /// Adds a line to the chat, dropping the oldest one once there are `MAX_CHAT_LINES`
+    fn write(&mut self, _line: ChatLine) {
+        self.chat.push_back(_line);
+        if self.chat.len() > MAX_CHAT_LINES {
+            self.chat.pop_front();
+            self.dropped += 1;
+        }
+    }
+
+    /// The lines of the chat from the `_since`th on, as far as they are kept, without those of muted spectators
+    pub fn chat_since(&self, _since: usize) -> Vec<ChatLine> {
+        self.chat.iter().skip(_since.saturating_sub(self.dropped)).filter(|_line| !self.is_muted(&_line.token)).cloned().collect()
+    }
+
+    /// Number of lines written so far, where the next look at the chat starts
+    pub fn chat_len(&self) -> usize {
+        self.dropped + self.chat.len()
+    }
+}
+
+/// ## `spectator_name`
+/// The name a spectator goes by, kept to letters, digits, `-` and `_` so it can be sent in a URL as it is
+/// ### Parameters
+/// - `_name: &str`: The name asked for
+/// ### Return
+/// The name, "spectator" when nothing of it is left
+pub fn spectator_name(_name: &str) -> String {
+    let name: String = _name.chars().filter(|_char| _char.is_ascii_alphanumeric() || *_char == '-' || *_char == '_').take(MAX_NAME_LENGTH).collect();
+    if name.is_empty() { "spectator".to_string() } else { name }
+}
+
+/// A small HTTP server on localhost mirroring the game as JSON, for browser-source overlays of streams.
+///
+/// Endpoints (`GET` unless said otherwise, CORS open to any origin for the game, closed for the spectators):
+/// - `/position`: `{"fen": ..., "to_move": ..., "status": ...}`
+/// - `/clocks`: `{"white": seconds, "black": seconds}`, null for untimed games
+/// - `/last-move`: the last move as in the JSON export, null before the first move
+/// - `/state`: the three above in one object
+/// - `/game`: the whole game as a save (see `SavedGame`), for mirror windows to rebuild it from
+/// - `/sync`: the last move with the hash of the position after it and the clocks (see `SyncState`), for mirror windows to follow the game move by move
+/// - `POST /join?name=<name>`: `{"token": ...}`, lets a mirror window in as a spectator, see `Spectators`
+/// - `/chat?since=<n>`: `{"next": n, "lines": [{"name": ..., "text": ...}]}`, the spectator chat from the `n`th line on
+/// - `POST /chat?token=<token>`: adds the body of the request to the spectator chat, refused with 403 for muted spectators
+///
+/// Mirror windows add `?token=<token>` to every request, which keeps them listed as spectators. The server is plain
+/// HTTP and only listens on localhost, so mirrors and spectators are windows on the same machine. Requests naming
+/// another host (i.e. a DNS name rebound to localhost) are refused, and so are requests of web pages to the spectator
+/// endpoints, so a page open in the host's browser cannot join or write in the chat.
+pub struct OverlayServer {
+    published: Arc<Mutex<Published>>,
+    spectators: Arc<Mutex<Spectators>>,
+    port: u16,
+    since_publish: f32, // Seconds since the endpoints were last updated
+    published_moves: (usize, Option<String>) // Number of moves and the last one when the endpoints were last updated
+}
+
+impl OverlayServer {
+    /// Starts serving on `127.0.0.1:<_port>` in a background thread. Port 0 picks a free port.
+    pub fn start(_port: u16) -> io::Result<OverlayServer> {
+        let listener = TcpListener::bind(("127.0.0.1", _port))?;
+        let port = listener.local_addr()?.port();
+        let published = Arc::new(Mutex::new(Published::default()));
+        let spectators = Arc::new(Mutex::new(Spectators::default()));
+
+        let (served, watching) = (published.clone(), spectators.clone());
+        thread::spawn(move || {
+            for _stream in listener.incoming() {
+                if let Ok(stream) = _stream {
+                    // Each on its own thread, so a connection that stays open (i.e. preconnected by a browser source) holds up no other.
+                    // A broken connection only concerns that one request.
+                    let (served, watching) = (served.clone(), watching.clone());
+                    thread::spawn(move || OverlayServer::respond(stream, &served, &watching).ok());
+                }
+            }
+        });
+
+        Ok(OverlayServer { published, spectators, port, since_publish: 0.0, published_moves: (0, None) })
+    }
+
+    /// The spectators of the game, for the host to see and mute
+    pub fn spectators(&self) -> Arc<Mutex<Spectators>> {
+        self.spectators.clone()
+    }
+
+    /// The port the server listens on
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// ## `update`
+    /// Updates the endpoints as soon as a move is played or taken back, otherwise every `PUBLISH_INTERVAL` for the
+    /// clocks. Called every tick, where building the save and the FEN each time would be wasted.
+    /// ### Parameters
+    /// - `_controller: &GameController`: The game being served
+    /// - `_dt: f32`: Seconds since the last tick
+    pub fn update(&mut self, _controller: &GameController, _dt: f32) {
+        self.since_publish += _dt;
+        let moves = (_controller.history.len(), _controller.history.last().map(|_move| _move.notation()));
+
+        if moves != self.published_moves || self.since_publish >= PUBLISH_INTERVAL {
+            self.publish(_controller);
+            self.since_publish = 0.0;
+            self.published_moves = moves;
+        }
+    }
+
+    /// Updates what the endpoints serve to the current state of the game
+    pub fn publish(&self, _controller: &GameController) {
+        let fen = _controller.fen();
+        let position = json!({
+            "hash": position_hash(&fen),
+            "fen": fen,
+            "to_move": colour_name(_controller.game.active_colour()),
+            "status": format!("{:?}", _controller.game.status())
+        });
+
+        let clocks = if _controller.clocks.is_empty() { Value::Null } else {
+            json!({
+                "white": _controller.clocks.get(&Colour::White).map(|_clock| _clock.remaining()),
+                "black": _controller.clocks.get(&Colour::Black).map(|_clock| _clock.remaining())
+            })
+        };
+
+        let last_move = match _controller.history.last() {
+            Some(last) => json!({
+                "ply": _controller.history.len(),
+                "from": last.from,
+                "to": last.to,
+                "notation": last.notation()
+            }),
+            None => Value::Null
+        };
+
+        let game = serde_json::to_value(SavedGame::from_controller("mirror", _controller)).unwrap_or(Value::Null);
+        let sync = serde_json::to_value(SyncState::from_controller(_controller)).unwrap_or(Value::Null);
+
+        if let Ok(mut published) = self.published.lock() {
+            *published = Published { position, clocks, last_move, game, sync };
+        }
+    }
+
+    /// Reads one request and answers it
+    fn respond(mut _stream: TcpStream, _published: &Mutex<Published>, _spectators: &Mutex<Spectators>) -> io::Result<()> {
+        _stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        _stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+
+        let mut reader = BufReader::new(&_stream);
+        let request_line = read_header_line(&mut reader)?;
+
+        // Headers up to the empty line, the length of the body and where the request comes from matter
+        let (mut length, mut host, mut origin) = (0, None, None);
+        for _ in 0..MAX_HEADERS {
+            let header = read_header_line(&mut reader)?;
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = header.split_once(':') {
+                let (name, value) = (name.trim(), value.trim());
+                if name.eq_ignore_ascii_case("content-length") {
+                    length = value.parse().unwrap_or(0);
+                }
+                else if name.eq_ignore_ascii_case("host") {
+                    host = Some(value.to_string());
+                }
+                else if name.eq_ignore_ascii_case("origin") {
+                    origin = Some(value.to_string());
+                }
+            }
+        }
+        let mut request_body = vec![0; length.min(MAX_CHAT_LENGTH * 4)];
+        reader.read_exact(&mut request_body)?;
+
+        // "GET /path?key=value HTTP/1.1"
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or(""));
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+        let parameter = |_key: &str| query.split('&')
+            .filter_map(|_pair| _pair.split_once('='))
+            .find(|(_name, _)| *_name == _key)
+            .map(|(_, _value)| _value);
+
+        // The spectators' endpoints are for mirror windows, which are no web pages
+        let for_spectators = path == "/chat" || path == "/join";
+        let (status, body) = match (_published.lock(), _spectators.lock()) {
+            _ if !host.as_deref().map_or(true, is_local_host) => ("403 Forbidden", json!({ "error": "only served to localhost" })),
+            _ if for_spectators && origin.is_some() => ("403 Forbidden", json!({ "error": "not served to web pages" })),
+            (Ok(published), Ok(mut spectators)) => {
+                let name = parameter("token").and_then(|_token| spectators.seen(_token));
+
+                match (method, path) {
+                    ("GET", "/position") => ("200 OK", published.position.clone()),
+                    ("GET", "/clocks") => ("200 OK", published.clocks.clone()),
+                    ("GET", "/last-move") => ("200 OK", published.last_move.clone()),
+                    ("GET", "/game") => ("200 OK", published.game.clone()),
+                    ("GET", "/sync") => ("200 OK", published.sync.clone()),
+                    ("GET", "/state") => ("200 OK", json!({
+                        "position": published.position,
+                        "clocks": published.clocks,
+                        "last_move": published.last_move
+                    })),
+                    ("GET", "/chat") => {
+                        let since = parameter("since").and_then(|_since| _since.parse().ok()).unwrap_or(0);
+                        ("200 OK", json!({ "next": spectators.chat_len(), "lines": spectators.chat_since(since) }))
+                    },
+                    ("POST", "/join") => match spectators.join(parameter("name").unwrap_or("")) {
+                        Ok(token) => ("200 OK", json!({ "token": token })),
+                        Err(error) => ("500 Internal Server Error", json!({ "error": error }))
+                    },
+                    ("POST", "/chat") => {
+                        let text: String = String::from_utf8_lossy(&request_body).trim().chars().take(MAX_CHAT_LENGTH).collect();
+                        let token = parameter("token").unwrap_or("");
+                        match name {
+                            Some(_) if spectators.is_muted(token) => ("403 Forbidden", json!({ "error": "muted by the host" })),
+                            Some(name) if !text.is_empty() => {
+                                spectators.write(ChatLine { name, text, token: token.to_string() });
+                                ("200 OK", json!({ "next": spectators.chat_len() }))
+                            },
+                            Some(_) => ("400 Bad Request", json!({ "error": "a line needs some text" })),
+                            None => ("403 Forbidden", json!({ "error": "join with POST /join first" }))
+                        }
+                    },
+                    _ => ("404 Not Found", json!({ "error": "unknown endpoint" }))
+                }
+            },
+            _ => ("500 Internal Server Error", json!({ "error": "the game is not readable" }))
+        };
+        let body = body.to_string();
+        let cors = if for_spectators { "" } else { "Access-Control-Allow-Origin: *\r\n" };
+
+        write!(_stream,
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status, cors, body.len(), body)?;
+        _stream.flush()
+    }
+}
+
+/// If the `Host` header of a request names this machine, with or without the port
+fn is_local_host(_host: &str) -> bool {
+    let name = match _host.rsplit_once(':') {
+        // "[::1]:8080", "127.0.0.1:8080" or "localhost:8080", an IPv6 address without a port ends with ']'
+        Some((name, port)) if !port.contains(']') => name,
+        _ => _host
+    };
+    matches!(name.to_ascii_lowercase().as_str(), "127.0.0.1" | "localhost" | "[::1]")
+}
+
+/// Reads a line of a request, empty at the end of the stream. `Err` for a line longer than `MAX_HEADER_LENGTH`.
+fn read_header_line(_reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    _reader.by_ref().take(MAX_HEADER_LENGTH as u64).read_line(&mut line)?;
+
+    if line.len() >= MAX_HEADER_LENGTH && !line.ends_with('\n') {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "a header was too long"));
+    }
+
+    Ok(line)
+}
+
+/// ## `fetch`
+/// Asks a server on localhost for an endpoint, i.e. the `/game` of another window. Blocks, so it is meant for a `Task`.
+/// ### Parameters
+/// - `_port: u16`: Port of the server
+/// - `_path: &str`: The endpoint, i.e. "/game"
+/// ### Return
+/// The body of the answer, `Err` if there was no server or it did not answer with 200 OK
+pub fn fetch(_port: u16, _path: &str) -> Result<String, String> {
+    request(_port, "GET", _path, "")
+}
+
+/// ## `post`
+/// Sends text to an endpoint of a server on localhost, i.e. a line to the spectator chat. Blocks like `fetch`.
+/// ### Parameters
+/// - `_port: u16`: Port of the server
+/// - `_path: &str`: The endpoint, i.e. "/chat?token=<token>"
+/// - `_body: &str`: The text to send
+/// ### Return
+/// The body of the answer, `Err` with the reason the server gave if it did not answer with 200 OK
+pub fn post(_port: u16, _path: &str, _body: &str) -> Result<String, String> {
+    request(_port, "POST", _path, _body)
+}
+
+/// A request to a server on localhost, and the body of its answer
+fn request(_port: u16, _method: &str, _path: &str, _body: &str) -> Result<String, String> {
+    let mut stream = TcpStream::connect(("127.0.0.1", _port)).map_err(|_error| _error.to_string())?;
+    stream.set_read_timeout(Some(Duration::from_secs(2))).ok();
+
+    write!(stream, "{} {} HTTP/1.1\r\nHost: 127.0.0.1:{}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", _method, _path, _port, _body.len(), _body)
+        .map_err(|_error| _error.to_string())?;
+
+    // The server closes the connection after the body
+    let mut response = String::new();
+    stream.read_to_string(&mut response).map_err(|_error| _error.to_string())?;
+
+    let (head, body) = response.split_once("\r\n\r\n").ok_or("the answer has no body")?;
+    match head.lines().next() {
+        Some(status) if status.contains(" 200 ") => Ok(body.to_string()),
+        // The server says why in the body, when it is one of ours
+        Some(status) => Err(serde_json::from_str::<Value>(body).ok()
+            .and_then(|_answer| _answer["error"].as_str().map(|_error| _error.to_string()))
+            .unwrap_or_else(|| status.to_string())),
+        None => Err("empty answer".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A muted spectator stays muted under another name, and joining again is a new spectator
+    #[test]
+    fn mutes_follow_the_token() {
+        let mut spectators = Spectators::default();
+        let ada = spectators.join("Ada").unwrap();
+        let other = spectators.join("Ada").unwrap();
+        assert_ne!(ada, other);
+        assert_eq!(spectators.present().len(), 2);
+        assert_eq!(spectators.seen(&ada), Some("Ada".to_string()));
+        assert_eq!(spectators.seen("not given out"), None);
+
+        spectators.toggle_mute(&ada);
+        spectators.write(ChatLine { name: "Ada".to_string(), text: "hidden".to_string(), token: ada.clone() });
+        spectators.write(ChatLine { name: "Ada".to_string(), text: "shown".to_string(), token: other.clone() });
+        assert!(spectators.is_muted(&ada) && !spectators.is_muted(&other));
+        assert_eq!(spectators.chat_since(0).iter().map(|_line| _line.text.as_str()).collect::<Vec<&str>>(), ["shown"]);
+    }
+
+    // The chat keeps its last lines, and the count of lines written goes on
+    #[test]
+    fn chat_is_capped() {
+        let mut spectators = Spectators::default();
+        for _line in 0..MAX_CHAT_LINES + 10 {
+            spectators.write(ChatLine { name: "Ada".to_string(), text: _line.to_string(), token: String::new() });
+        }
+
+        assert_eq!(spectators.chat_len(), MAX_CHAT_LINES + 10);
+        assert_eq!(spectators.chat_since(0).len(), MAX_CHAT_LINES);
+        assert_eq!(spectators.chat_since(MAX_CHAT_LINES + 8)[0].text, (MAX_CHAT_LINES + 8).to_string());
+    }
+
+    // Only requests naming this machine are answered
+    #[test]
+    fn local_hosts() {
+        for _host in ["127.0.0.1", "127.0.0.1:8080", "localhost:8080", "LOCALHOST", "[::1]", "[::1]:8080"].iter() {
+            assert!(is_local_host(_host), "{}", _host);
+        }
+        for _host in ["evil.example:8080", "127.0.0.1.evil.example", "192.168.1.2:8080", ""].iter() {
+            assert!(!is_local_host(_host), "{}", _host);
+        }
+    }
+}
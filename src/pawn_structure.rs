@@ -0,0 +1,90 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use super::backend::ChessBackend;
+use super::controller::GameController;
+
+/// Something worth knowing about a pawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PawnFlag {
+    /// Another pawn of its side stands on the same file
+    Doubled,
+    /// No pawn of its side on the files next to it
+    Isolated,
+    /// No enemy pawn in front of it on its file or the files next to it
+    Passed
+}
+
+impl PawnFlag {
+    /// Letter on the icon
+    pub fn letter(&self) -> &'static str {
+        match self {
+            PawnFlag::Doubled => "D",
+            PawnFlag::Isolated => "I",
+            PawnFlag::Passed => "P"
+        }
+    }
+}
+
+/// The flags of every pawn on the board, and the move they were worked out after
+#[derive(Debug, Clone, PartialEq)]
+pub struct PawnStructure {
+    pub marks: Vec<((u8, u8), Vec<PawnFlag>)>, // Pawns with at least one flag, by square in GUI coordinates
+    moves: usize, // Length of the history when worked out
+    last_move: Option<String> // The last move then, in case a move was taken back and another one played
+}
+
+impl PawnStructure {
+    /// Works out the flags of the pawns in the current position of a game
+    pub fn new(_controller: &GameController) -> PawnStructure {
+        PawnStructure {
+            marks: analyse(_controller.game.as_ref()),
+            moves: _controller.history.len(),
+            last_move: _controller.history.last().map(|_move| _move.uci())
+        }
+    }
+
+    /// If a move was made or taken back since the flags were worked out
+    pub fn is_outdated(&self, _controller: &GameController) -> bool {
+        self.moves != _controller.history.len() || self.last_move != _controller.history.last().map(|_move| _move.uci())
+    }
+}
+
+/// ## `analyse`
+/// Marks the doubled, isolated and passed pawns of a position
+/// ### Parameters
+/// - `_game: &dyn ChessBackend`: The position
+/// ### Return
+/// Every pawn with at least one flag, by square in GUI coordinates
+pub fn analyse(_game: &dyn ChessBackend) -> Vec<((u8, u8), Vec<PawnFlag>)> {
+    let mut pawns = vec![]; // (square, colour)
+    for _col in 0..8 {
+        for _row in 0..8 {
+            if let Some(Piece::Pawn(colour)) = _game.piece_at((_col, _row)) {
+                pawns.push(((_col, _row), colour));
+            }
+        }
+    }
+
+    let mut marks = vec![];
+    for &((col, row), colour) in &pawns {
+        let near = |_file: u8| (_file as i16 - col as i16).abs() <= 1;
+        // Rows are counted from the top, white pawns move up the screen
+        let ahead = |_row: u8| if colour == Colour::White { _row < row } else { _row > row };
+
+        let mut flags = vec![];
+        if pawns.iter().any(|&((c, r), p)| p == colour && c == col && r != row) {
+            flags.push(PawnFlag::Doubled);
+        }
+        if !pawns.iter().any(|&((c, _), p)| p == colour && c != col && near(c)) {
+            flags.push(PawnFlag::Isolated);
+        }
+        if !pawns.iter().any(|&((c, r), p)| p != colour && near(c) && ahead(r)) {
+            flags.push(PawnFlag::Passed);
+        }
+
+        if !flags.is_empty() {
+            marks.push(((col, row), flags));
+        }
+    }
+
+    marks
+}
@@ -0,0 +1,160 @@
+use eliasfl_chess::Piece;
+use super::backend::{ChessBackend, PromotionKind};
+use super::help_funcs::{num_to_filerank, filerank_to_num, get_piece_colour};
+
+/// A move as played through `ChessBackend::make_move`
+type PerftMove = (String, String, Option<PromotionKind>);
+
+/// A position with known perft counts
+pub struct PerftPosition {
+    pub name: &'static str,
+    pub fen: Option<&'static str>, // None is the starting position, which every backend can set up
+    pub counts: &'static [u64] // Number of leaf nodes at depth 1, 2, 3, ...
+}
+
+/// Canonical perft numbers, from the Chess Programming Wiki
+pub const PERFT_POSITIONS: [PerftPosition; 5] = [
+    PerftPosition {
+        name: "Start position",
+        fen: None,
+        counts: &[20, 400, 8902, 197281, 4865609]
+    },
+    PerftPosition {
+        name: "Kiwipete",
+        fen: Some("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1"),
+        counts: &[48, 2039, 97862, 4085603]
+    },
+    PerftPosition {
+        name: "Position 3",
+        fen: Some("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1"),
+        counts: &[14, 191, 2812, 43238, 674624]
+    },
+    PerftPosition {
+        name: "Position 4",
+        fen: Some("r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1"),
+        counts: &[6, 264, 9467, 422333]
+    },
+    PerftPosition {
+        name: "Position 5",
+        fen: Some("rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8"),
+        counts: &[44, 1486, 62379, 2103487]
+    }
+];
+
+/// Result of running perft on one position
+pub struct PerftResult {
+    pub name: &'static str,
+    pub depth: usize,
+    pub expected: u64,
+    pub counted: Result<u64, String> // Err if the backend could not set up the position
+}
+
+impl PerftResult {
+    /// If the backend counted the canonical number of nodes
+    pub fn is_match(&self) -> bool {
+        self.counted == Ok(self.expected)
+    }
+}
+
+/// Sets up the starting position of a perft position and replays `_path` on it.
+/// Backends can not undo or copy positions, so every node is reached by replaying from the root.
+fn replay(_backend: &mut dyn ChessBackend, _fen: Option<&str>, _path: &[PerftMove]) -> Result<(), String> {
+    match _fen {
+        Some(fen) => _backend.load_fen(fen)?,
+        None => _backend.reset()
+    }
+
+    for (from, to, promotion) in _path {
+        _backend.make_move(from, to, *promotion)?;
+    }
+
+    Ok(())
+}
+
+/// All legal moves for the side to move, promotions expanded to one move per piece type
+fn all_moves(_backend: &dyn ChessBackend) -> Vec<PerftMove> {
+    let mut moves = vec![];
+
+    for _row in 0..8 {
+        for _col in 0..8 {
+            let piece = match _backend.piece_at((_col, _row)) {
+                Some(piece) if *get_piece_colour(&piece) == _backend.active_colour() => piece,
+                _ => continue
+            };
+
+            let from = num_to_filerank(&(_col, _row));
+            for _to in _backend.legal_moves(&from) {
+                let target_row = filerank_to_num(&_to).1;
+
+                if let (Piece::Pawn(_), 0) | (Piece::Pawn(_), 7) = (piece, target_row) {
+                    for _kind in [PromotionKind::Queen, PromotionKind::Knight, PromotionKind::Rook, PromotionKind::Bishop].iter() {
+                        moves.push((from.clone(), _to.clone(), Some(*_kind)));
+                    }
+                }
+                else {
+                    moves.push((from.clone(), _to, None));
+                }
+            }
+        }
+    }
+
+    moves
+}
+
+/// Counts the leaf nodes `_depth` moves below the position reached by `_path`
+fn count(_backend: &mut dyn ChessBackend, _fen: Option<&str>, _path: &mut Vec<PerftMove>, _depth: usize) -> Result<u64, String> {
+    replay(_backend, _fen, _path)?;
+    let moves = all_moves(_backend);
+
+    if _depth == 1 {
+        return Ok(moves.len() as u64);
+    }
+
+    let mut nodes = 0;
+    for _move in moves {
+        _path.push(_move);
+        nodes += count(_backend, _fen, _path, _depth - 1)?;
+        _path.pop();
+    }
+
+    Ok(nodes)
+}
+
+/// ## `perft`
+/// Counts the leaf nodes of the move generation tree of a backend.
+/// ### Parameters
+/// - `_backend: &mut dyn ChessBackend`: The backend to count with, left at an unspecified position
+/// - `_fen: Option<&str>`: The position to count from, None for the starting position
+/// - `_depth: usize`: How many moves deep to count
+pub fn perft(_backend: &mut dyn ChessBackend, _fen: Option<&str>, _depth: usize) -> Result<u64, String> {
+    if _depth == 0 {
+        return Ok(1);
+    }
+
+    count(_backend, _fen, &mut vec![], _depth)
+}
+
+/// ## `run_perft_suite`
+/// Runs perft on every known position up to `_depth` (or the deepest known count) and compares against the canonical numbers.
+/// ### Parameters
+/// - `_backend: &mut dyn ChessBackend`: The backend to validate
+/// - `_depth: usize`: Maximum depth to count
+pub fn run_perft_suite(_backend: &mut dyn ChessBackend, _depth: usize) -> Vec<PerftResult> {
+    let mut results = vec![];
+
+    for _position in PERFT_POSITIONS.iter() {
+        let depth = _depth.min(_position.counts.len());
+        if depth == 0 {
+            continue;
+        }
+
+        results.push(PerftResult {
+            name: _position.name,
+            depth,
+            expected: _position.counts[depth - 1],
+            counted: perft(_backend, _position.fen, depth)
+        });
+    }
+
+    results
+}
@@ -0,0 +1,443 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::backend::{ChessBackend, GameStatus, PromotionKind};
+use super::controller::GameController;
+use super::history::{captured_by, MoveRecord};
+use super::bot::{legal_uci_moves, parse_uci};
+use super::help_funcs::{filerank_to_num, num_to_filerank, format_timestamp};
+use super::profile::player_name;
+use super::export;
+
+/// Where `F7` exports the game to, in the GGEZ user data directory
+pub const PGN_FILE: &str = "/game.pgn";
+
+/// Longest line of moves, as PGN asks for
+const LINE_LENGTH: usize = 80;
+
+/// The headers of a PGN export that can be edited before exporting. The result, and the position of games that
+/// did not start from the usual one, are always taken from the game.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PgnHeaders {
+    pub event: String,
+    pub site: String,
+    pub date: String, // "YYYY.MM.DD", with "??" for unknown parts
+    pub round: String,
+    pub white: String,
+    pub black: String
+}
+
+impl PgnHeaders {
+    /// Names of the editable headers, in the order of `field`
+    pub const FIELDS: [&'static str; 6] = ["Event", "Site", "Date", "Round", "White", "Black"];
+
+    /// ## `defaults`
+    /// Headers for a game: the players under the names their profiles are kept under, and today's date
+    pub fn defaults(_controller: &GameController) -> PgnHeaders {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.as_secs());
+
+        PgnHeaders {
+            event: "Casual game".to_string(),
+            site: "Schack".to_string(),
+            date: format_timestamp(now)[..10].replace('-', "."),
+            round: "-".to_string(),
+            white: player_name(_controller, Colour::White),
+            black: player_name(_controller, Colour::Black)
+        }
+    }
+
+    /// ## `online`
+    /// Headers of an online game, made only of what both players know from the matchmaker so both export the same
+    /// ### Parameters
+    /// - `_colour: Colour`: The player's side
+    /// - `_name: &str`: The player's name, as the opponent was told it
+    /// - `_opponent: &str`: The opponent's name, as the matchmaker told it
+    /// - `_started: u64`: When the matchmaker paired the players, in seconds since the Unix epoch
+    pub fn online(_colour: Colour, _name: &str, _opponent: &str, _started: u64) -> PgnHeaders {
+        let (white, black) = if _colour == Colour::White { (_name, _opponent) } else { (_opponent, _name) };
+
+        PgnHeaders {
+            event: "Online game".to_string(),
+            site: "Schack online".to_string(),
+            date: format_timestamp(_started)[..10].replace('-', "."),
+            round: "-".to_string(),
+            white: white.to_string(),
+            black: black.to_string()
+        }
+    }
+
+    /// A header by its index in `FIELDS`
+    pub fn field(&self, _index: usize) -> &str {
+        match _index {
+            0 => &self.event,
+            1 => &self.site,
+            2 => &self.date,
+            3 => &self.round,
+            4 => &self.white,
+            _ => &self.black
+        }
+    }
+
+    /// A header by its index in `FIELDS`, to be edited
+    pub fn field_mut(&mut self, _index: usize) -> &mut String {
+        match _index {
+            0 => &mut self.event,
+            1 => &mut self.site,
+            2 => &mut self.date,
+            3 => &mut self.round,
+            4 => &mut self.white,
+            _ => &mut self.black
+        }
+    }
+}
+
+/// A header line, with quotes and backslashes in the value escaped
+fn tag(_name: &str, _value: &str) -> String {
+    format!("[{} \"{}\"]\n", _name, _value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// ## `clock_comment`
+/// What was left on a clock after a move as a PGN comment, i.e. "{[%clk 0:04:57.3]}". Rounded to the tenth of a
+/// second, which is what online games send of the clocks, so both players write the same.
+pub fn clock_comment(_seconds: f32) -> String {
+    let tenths = (_seconds.max(0.0) * 10.0).round() as u64;
+    format!("{{[%clk {}:{:02}:{:02}.{}]}}", tenths / 36000, tenths / 600 % 60, tenths / 10 % 60, tenths % 10)
+}
+
+/// Letter of a piece in SAN, none for pawns
+fn piece_letter(_piece: &Piece) -> &'static str {
+    match _piece {
+        Piece::Pawn(_) => "",
+        Piece::Knight(_) => "N",
+        Piece::Bishop(_) => "B",
+        Piece::Rook(_) => "R",
+        Piece::Queen(_) => "Q",
+        Piece::King(_) => "K"
+    }
+}
+
+/// ## `san`
+/// Writes a move in standard algebraic notation, i.e. "Nbd7", "exd6", "O-O" or "e8=Q#"
+/// ### Parameters
+/// - `_before: &dyn ChessBackend`: The position the move is played in
+/// - `_move: &MoveRecord`: The move
+/// - `_after: &dyn ChessBackend`: The position after the move, for check and mate
+pub fn san(_before: &dyn ChessBackend, _move: &MoveRecord, _after: &dyn ChessBackend) -> String {
+    let (from, to) = (filerank_to_num(&_move.from), filerank_to_num(&_move.to));
+    let file_of = |_square: &str| _square[0..1].to_string();
+
+    let suffix = match _after.status() {
+        GameStatus::CheckMate => "#",
+        GameStatus::Check => "+",
+        _ => ""
+    };
+
+    // A king moving two files is castling, and so is a king taking its own rook in Chess960
+    if let Piece::King(colour) = _move.piece {
+        if (from.0 as i8 - to.0 as i8).abs() == 2 || _before.piece_at(to) == Some(Piece::Rook(colour)) {
+            return format!("{}{}", if to.0 > from.0 { "O-O" } else { "O-O-O" }, suffix);
+        }
+    }
+
+    let promotion = match _move.promotion {
+        Some(PromotionKind::Queen) => "=Q",
+        Some(PromotionKind::Knight) => "=N",
+        Some(PromotionKind::Rook) => "=R",
+        Some(PromotionKind::Bishop) => "=B",
+        None => ""
+    };
+
+    if let Piece::Pawn(_) = _move.piece {
+        // A pawn changing files always captures, en passant too
+        return if from.0 != to.0 {
+            format!("{}x{}{}{}", file_of(&_move.from), _move.to, promotion, suffix)
+        } else {
+            format!("{}{}{}", _move.to, promotion, suffix)
+        };
+    }
+
+    // Other pieces of the same kind that could go to the same square
+    let mut rivals = vec![];
+    for _col in 0..8 {
+        for _row in 0..8 {
+            let square = (_col, _row);
+            match _before.piece_at(square) {
+                Some(piece) if piece == _move.piece && square != from && _before.legal_moves(&num_to_filerank(&square)).contains(&_move.to) => rivals.push(square),
+                _ => {}
+            }
+        }
+    }
+
+    let disambiguation = if rivals.is_empty() {
+        String::new()
+    } else if rivals.iter().all(|_rival| _rival.0 != from.0) {
+        file_of(&_move.from)
+    } else if rivals.iter().all(|_rival| _rival.1 != from.1) {
+        _move.from[1..2].to_string()
+    } else {
+        _move.from.clone()
+    };
+
+    let capture = if _move.captured.is_some() { "x" } else { "" };
+
+    format!("{}{}{}{}{}", piece_letter(&_move.piece), disambiguation, capture, _move.to, suffix)
+}
+
+/// ## `san_moves`
+/// Writes every move of the game in standard algebraic notation
+/// ### Return
+/// `Err` if the moves could not be replayed to work out their notation
+pub fn san_moves(_controller: &GameController) -> Result<Vec<String>, String> {
+    // The moves are replayed on two boards, one a move behind the other, to write each from the position it was played in
+    let mut before = _controller.position_at(0)?;
+    let mut after = _controller.position_at(0)?;
+    let mut moves = vec![];
+
+    for _move in &_controller.history {
+        after.make_move(&_move.from, &_move.to, _move.promotion)
+            .map_err(|_error| format!("{}: {}", _move.notation(), _error))?;
+        moves.push(san(before.as_ref(), _move, after.as_ref()));
+        before.make_move(&_move.from, &_move.to, _move.promotion)
+            .map_err(|_error| format!("{}: {}", _move.notation(), _error))?;
+    }
+
+    Ok(moves)
+}
+
+/// ## `game_to_pgn`
+/// Writes the game as PGN, with the given headers, and the clocks after each move as comments in timed games
+/// ### Return
+/// `Err` if the moves could not be replayed to work out their notation
+pub fn game_to_pgn(_controller: &GameController, _headers: &PgnHeaders) -> Result<String, String> {
+    let (result, _) = export::result(_controller);
+
+    let mut pgn = String::new();
+    for (_index, _name) in PgnHeaders::FIELDS.iter().enumerate() {
+        pgn.push_str(&tag(_name, _headers.field(_index)));
+    }
+    pgn.push_str(&tag("Result", result));
+    // Time odds have no standard header, each side's clock gets its own
+    match _controller.time_controls() {
+        Some((white, black)) if white != black => {
+            pgn.push_str(&tag("WhiteTimeControl", &format!("{}+{}", white.initial, white.increment)));
+            pgn.push_str(&tag("BlackTimeControl", &format!("{}+{}", black.initial, black.increment)));
+        },
+        Some((control, _)) => pgn.push_str(&tag("TimeControl", &format!("{}+{}", control.initial, control.increment))),
+        None => {}
+    }
+    if _controller.variant.chess960().is_some() {
+        pgn.push_str(&tag("Variant", "Chess960"));
+    }
+    if let Some(fen) = &_controller.start_fen {
+        pgn.push_str(&tag("SetUp", "1"));
+        pgn.push_str(&tag("FEN", fen));
+    }
+    pgn.push('\n');
+
+    let first_black = _controller.position_at(0)?.active_colour() == Colour::Black;
+    let mut tokens = vec![];
+
+    for (_ply, _san) in san_moves(_controller)?.into_iter().enumerate() {
+        // Counting as if the game started with White, a game starting with Black to move opens with "1..."
+        let ply = _ply + if first_black { 1 } else { 0 };
+        if ply % 2 == 0 {
+            tokens.push(format!("{}.", ply / 2 + 1));
+        }
+        else if _ply == 0 {
+            tokens.push(format!("{}...", ply / 2 + 1));
+        }
+        tokens.push(_san);
+        if let Some(clock) = _controller.history[_ply].clock {
+            tokens.push(clock_comment(clock));
+        }
+    }
+    tokens.push(result.to_string());
+
+    // Moves wrapped at the usual line length
+    let mut line = String::new();
+    for _token in tokens {
+        if !line.is_empty() && line.len() + 1 + _token.len() > LINE_LENGTH {
+            pgn.push_str(&line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&_token);
+    }
+    pgn.push_str(&line);
+    pgn.push('\n');
+
+    Ok(pgn)
+}
+
+/// A game read from a PGN file: its headers, and its main line in SAN without comments, variations or move numbers
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PgnGame {
+    pub tags: Vec<(String, String)>, // (name, value), in the order of the file
+    pub moves: Vec<String> // i.e. "e4", "Nf3", "O-O", with check marks and annotations as written
+}
+
+impl PgnGame {
+    /// The value of a header, if the game has it
+    pub fn tag(&self, _name: &str) -> Option<&str> {
+        self.tags.iter().find(|(_tag, _)| _tag == _name).map(|(_, _value)| _value.as_str())
+    }
+}
+
+/// Reads the name and value of a header line, i.e. `[White "Ada"]`
+fn read_tag(_line: &str) -> Option<(String, String)> {
+    let inner = _line.trim().strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_at(inner.find(' ')?);
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    Some((name.to_string(), value.replace("\\\"", "\"").replace("\\\\", "\\")))
+}
+
+/// ## `read_games`
+/// Reads every game of a PGN file, i.e. one collected from exports. Comments, variations, move numbers and NAGs
+/// are skipped, results end a game.
+pub fn read_games(_text: &str) -> Vec<PgnGame> {
+    let mut games = vec![];
+    let mut game = PgnGame::default();
+    let (mut comment, mut variations) = (false, 0);
+
+    for _line in _text.lines() {
+        // A header after moves starts the next game, which a missing result would otherwise merge into this one
+        if !comment && variations == 0 && _line.trim_start().starts_with('[') {
+            if let Some(tag) = read_tag(_line) {
+                if !game.moves.is_empty() {
+                    games.push(std::mem::take(&mut game));
+                }
+                game.tags.push(tag);
+                continue;
+            }
+        }
+        // Escaped lines are skipped
+        if _line.starts_with('%') {
+            continue;
+        }
+
+        let mut word = String::new();
+        for _char in _line.chars().chain(std::iter::once(' ')) {
+            match _char {
+                '}' if comment => comment = false,
+                _ if comment => {},
+                '{' => comment = true,
+                '(' => variations += 1,
+                ')' => variations = (variations - 1).max(0),
+                _ if variations > 0 => {},
+                _ if _char.is_whitespace() || _char == '.' || _char == ';' => {
+                    let token = std::mem::take(&mut word);
+                    match token.as_str() {
+                        "" => {},
+                        "1-0" | "0-1" | "1/2-1/2" | "*" => games.push(std::mem::take(&mut game)),
+                        _ if token.starts_with('$') || token.chars().all(|_char| _char.is_ascii_digit()) => {},
+                        _ => game.moves.push(token)
+                    }
+                },
+                _ => word.push(_char)
+            }
+
+            // The rest of the line after a ';' is a comment
+            if _char == ';' && !comment && variations == 0 {
+                break;
+            }
+        }
+    }
+
+    if !game.moves.is_empty() {
+        games.push(game);
+    }
+    games
+}
+
+/// A SAN move without its check mark and annotations, and with castling written with letters, to compare moves by
+fn plain_san(_san: &str) -> String {
+    _san.trim_end_matches(|_char| "+#!?".contains(_char)).replace('0', "O").replace('=', "")
+}
+
+/// ## `parse_san`
+/// Finds the legal move a SAN move stands for
+/// ### Parameters
+/// - `_game: &dyn ChessBackend`: The position the move is played in
+/// - `_san: &str`: The move, i.e. "Nbd7", "exd6", "O-O" or "e8=Q+"
+/// ### Return
+/// The move, untimed. `Err` if no legal move is written that way.
+pub fn parse_san(_game: &dyn ChessBackend, _san: &str) -> Result<MoveRecord, String> {
+    let wanted = plain_san(_san);
+
+    for _move in legal_uci_moves(_game) {
+        let (from, to, promotion) = match parse_uci(&_move) {
+            Some(parts) => parts,
+            None => continue
+        };
+        let (from_square, to_square) = (filerank_to_num(&from), filerank_to_num(&to));
+        let piece = match _game.piece_at(from_square) {
+            Some(piece) => piece,
+            None => continue
+        };
+
+        let captured = captured_by(_game, from_square, to_square);
+        let record = MoveRecord { from, to, promotion, piece, captured, elapsed: 0.0, clock: None };
+        // The position after the move only decides the check mark, which is not compared
+        if plain_san(&san(_game, &record, _game)) == wanted {
+            return Ok(record);
+        }
+    }
+
+    Err(format!("{} is no legal move here", _san))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::backend::fen_board::FenBoard;
+
+    /// A legal move of a position in SAN, without a check mark
+    fn san_of(_fen: &str, _from: &str, _to: &str) -> String {
+        let board = FenBoard::new(_fen);
+        let (piece, captured) = (board.piece_at(filerank_to_num(&_from.to_string())), board.piece_at(filerank_to_num(&_to.to_string())));
+        let record = MoveRecord { from: _from.to_string(), to: _to.to_string(), promotion: None, piece: piece.expect("No piece to move."), captured, elapsed: 0.0, clock: None };
+        san(&board, &record, &board)
+    }
+
+    /// The squares of the move a SAN move stands for in a position
+    fn parsed(_fen: &str, _san: &str) -> Result<(String, String), String> {
+        parse_san(&FenBoard::new(_fen), _san).map(|_move| (_move.from, _move.to))
+    }
+
+    // Pieces of the same kind that reach the same square are told apart by file, then by rank, then by both
+    #[test]
+    fn san_disambiguates() {
+        let knights = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+        assert_eq!(san_of(knights, "b1", "d2"), "Nbd2");
+        assert_eq!(san_of(knights, "f3", "d2"), "Nfd2");
+        assert_eq!(san_of(knights, "f3", "g5"), "Ng5");
+
+        let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(san_of(rooks, "a1", "a3"), "R1a3");
+        assert_eq!(san_of(rooks, "a5", "a3"), "R5a3");
+
+        let queens = "4k3/8/8/8/8/Q7/8/Q1Q1K3 w - - 0 1";
+        assert_eq!(san_of(queens, "a1", "b2"), "Qa1b2");
+
+        assert_eq!(san_of("4k3/8/8/3p4/4P3/8/8/4K3 w - - 0 1", "e4", "d5"), "exd5");
+        assert_eq!(san_of("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "g1"), "O-O");
+    }
+
+    // SAN is read back to the move it was written for, check marks, annotations and castling with zeroes allowed
+    #[test]
+    fn parse_san_disambiguates() {
+        let knights = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+        assert_eq!(parsed(knights, "Nbd2"), Ok(("b1".to_string(), "d2".to_string())));
+        assert_eq!(parsed(knights, "Nfd2+"), Ok(("f3".to_string(), "d2".to_string())));
+        assert!(parsed(knights, "Nd2").is_err());
+
+        let rooks = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(parsed(rooks, "R5a3!?"), Ok(("a5".to_string(), "a3".to_string())));
+
+        assert_eq!(parsed("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "0-0"), Ok(("e1".to_string(), "g1".to_string())));
+        assert!(parsed("4k3/8/8/8/8/8/8/4K2R w K - 0 1", "O-O-O").is_err());
+    }
+}
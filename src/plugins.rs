@@ -0,0 +1,197 @@
+use serde::Deserialize;
+use std::{fs, path::{Path, PathBuf}};
+use super::graphics_funcs::renderer::Rgba;
+use super::theme::Theme;
+use super::variant::{ConfigVariant, WinCondition};
+use super::messages::MessagePack;
+
+/// Directory plugins are looked for in, next to `resources`
+pub const PLUGIN_DIR: &str = "plugins";
+
+/// Name of the manifest file in every plugin directory
+pub const MANIFEST_FILE: &str = "plugin.toml";
+
+/// `plugin.toml`: what a plugin adds. Every section but `[plugin]` is optional.
+///
+/// ```toml
+/// [plugin]
+/// name = "Wooden"
+/// version = "1.0"
+///
+/// [theme]
+/// name = "wood"
+/// light_tile = [0.94, 0.85, 0.71, 1.0]
+/// dark_tile = [0.71, 0.53, 0.39, 1.0]
+/// background = [0.3, 0.2, 0.1, 1.0]
+///
+/// [pieces]
+/// name = "Wooden pieces"
+/// directory = "pieces" # white_king.png, black_pawn.png... as in resources
+///
+/// [variant]
+/// name = "Pawn storm"
+/// start_fen = "4k3/pppppppp/8/8/8/8/PPPPPPPP/4K3 w - - 0 1"
+///
+/// [[variant.win]] # See `WinCondition`
+/// type = "capture"
+/// piece = "pawn"
+/// count = 3
+///
+/// [messages] # See `MessagePack`, texts left out are the taunts
+/// name = "deutsch"
+/// white_to_move = "Weiß am Zug"
+/// black_to_move = "Schwarz am Zug"
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    pub plugin: PluginInfo,
+    pub theme: Option<ThemeManifest>,
+    pub pieces: Option<PiecesManifest>,
+    pub variant: Option<VariantManifest>,
+    pub messages: Option<MessagePack>
+}
+
+/// `[plugin]` section
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub author: Option<String>
+}
+
+/// `[theme]` section, colours left out are taken from the classic theme
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThemeManifest {
+    pub name: String,
+    pub light_tile: Option<Rgba>,
+    pub dark_tile: Option<Rgba>,
+    pub background: Option<Rgba>,
+    pub text: Option<Rgba>,
+    pub legal_indicator: Option<Rgba>,
+    pub outline: Option<f32>, // Width of the rings around pieces and legal indicators
+    pub outline_colour: Option<Rgba>,
+    pub mark_red: Option<Rgba>, // Squares marked with a right click, by the colour picked with the modifier keys
+    pub mark_green: Option<Rgba>,
+    pub mark_blue: Option<Rgba>,
+    pub mark_yellow: Option<Rgba>
+}
+
+/// `[pieces]` section
+#[derive(Debug, Clone, Deserialize)]
+pub struct PiecesManifest {
+    pub name: String,
+    pub directory: String // Relative to the plugin, holds the 12 sprites named as in `resources`
+}
+
+/// `[variant]` section
+#[derive(Debug, Clone, Deserialize)]
+pub struct VariantManifest {
+    pub name: String,
+    pub start_fen: Option<String>,
+    #[serde(default)]
+    pub win: Vec<WinCondition> // Ways to win besides checkmate
+}
+
+/// A piece set a plugin adds
+#[derive(Debug, Clone, PartialEq)]
+pub struct PieceSet {
+    pub name: String,
+    pub resource_dir: String // Path of the sprites in the GGEZ filesystem, with the `plugins` directory mounted
+}
+
+/// A plugin found in the plugins directory
+#[derive(Debug, Clone)]
+pub struct Plugin {
+    pub folder: String, // Name of the plugin's directory within the plugins directory
+    pub manifest: Manifest
+}
+
+impl Plugin {
+    /// Reads the manifest of the plugin in `_directory`
+    pub fn load(_directory: &Path) -> Result<Plugin, String> {
+        let path = _directory.join(MANIFEST_FILE);
+        let text = fs::read_to_string(&path).map_err(|_error| format!("{}: {}", path.display(), _error))?;
+        let manifest = toml::from_str(&text).map_err(|_error| format!("{}: {}", path.display(), _error))?;
+
+        Ok(Plugin {
+            folder: _directory.file_name().map_or(String::new(), |_name| _name.to_string_lossy().to_string()),
+            manifest
+        })
+    }
+
+    /// The message pack the plugin adds, if any
+    pub fn messages(&self) -> Option<MessagePack> {
+        self.manifest.messages.clone()
+    }
+
+    /// The theme the plugin adds, if any
+    pub fn theme(&self) -> Option<Theme> {
+        let theme = self.manifest.theme.as_ref()?;
+        let classic = Theme::classic();
+
+        Some(Theme {
+            name: theme.name.clone(),
+            light_tile: theme.light_tile.unwrap_or(classic.light_tile),
+            dark_tile: theme.dark_tile.unwrap_or(classic.dark_tile),
+            background: theme.background.unwrap_or(classic.background),
+            text: theme.text.unwrap_or(classic.text),
+            legal_indicator: theme.legal_indicator.unwrap_or(classic.legal_indicator),
+            outline: theme.outline.unwrap_or(classic.outline),
+            outline_colour: theme.outline_colour.unwrap_or(classic.outline_colour),
+            marks: [
+                theme.mark_red.unwrap_or(classic.marks[0]),
+                theme.mark_green.unwrap_or(classic.marks[1]),
+                theme.mark_blue.unwrap_or(classic.marks[2]),
+                theme.mark_yellow.unwrap_or(classic.marks[3])
+            ]
+        })
+    }
+
+    /// The piece set the plugin adds, if any
+    pub fn piece_set(&self) -> Option<PieceSet> {
+        let pieces = self.manifest.pieces.as_ref()?;
+
+        Some(PieceSet {
+            name: pieces.name.clone(),
+            resource_dir: format!("/{}/{}", self.folder, pieces.directory.trim_matches('/'))
+        })
+    }
+
+    /// The variant the plugin adds, if any
+    pub fn variant(&self) -> Option<ConfigVariant> {
+        let variant = self.manifest.variant.as_ref()?;
+
+        Some(ConfigVariant {
+            name: variant.name.clone(),
+            start_fen: variant.start_fen.clone(),
+            win_conditions: variant.win.clone()
+        })
+    }
+}
+
+/// ## `discover`
+/// Loads every plugin in a directory: each subdirectory with a `plugin.toml`. A missing directory has no plugins.
+/// ### Return
+/// The plugins that loaded, and an error message for each that did not
+pub fn discover(_directory: &Path) -> (Vec<Plugin>, Vec<String>) {
+    let mut folders: Vec<PathBuf> = match fs::read_dir(_directory) {
+        Ok(entries) => entries
+            .filter_map(|_entry| _entry.ok().map(|_entry| _entry.path()))
+            .filter(|_path| _path.join(MANIFEST_FILE).is_file())
+            .collect(),
+        Err(_) => vec![]
+    };
+    folders.sort();
+
+    let mut plugins = vec![];
+    let mut errors = vec![];
+
+    for _folder in folders {
+        match Plugin::load(&_folder) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(error) => errors.push(error)
+        }
+    }
+
+    (plugins, errors)
+}
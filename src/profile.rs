@@ -0,0 +1,146 @@
+use eliasfl_chess::Color as Colour;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use super::controller::GameController;
+use super::stats::SideStats;
+use super::speedrun::{Difficulty, SpeedrunTime, LEADERBOARD_SIZE};
+
+/// File the profiles are kept in, in the GGEZ user data directory
+pub const PROFILES_FILE: &str = "/profiles.json";
+
+/// Stats of a player summed up over every finished game
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PlayerStats {
+    pub games: u32,
+    pub moves: u32,
+    pub rated_games: u32, // Games with an accuracy, `accuracy_total` is summed over these
+    pub accuracy_total: f32,
+    pub think_total: f32, // Seconds thought over every move
+    pub longest_think: f32 // Seconds
+}
+
+impl PlayerStats {
+    /// Average accuracy over the rated games, in percent
+    pub fn average_accuracy(&self) -> Option<f32> {
+        if self.rated_games == 0 { None } else { Some(self.accuracy_total / self.rated_games as f32) }
+    }
+
+    /// Average think time per move, in seconds
+    pub fn average_think(&self) -> f32 {
+        if self.moves == 0 { 0.0 } else { self.think_total / self.moves as f32 }
+    }
+}
+
+/// How a player is shown next to their clock and on the result screen
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Identity {
+    pub avatar: Option<String>, // Image in the resources or a plugin, i.e. "/avatars/ferris.png"
+    pub flag: Option<String> // Country code, drawn from "/flags/<code>.png", i.e. "se"
+}
+
+impl Identity {
+    /// Path of the flag image in the resources
+    pub fn flag_image(&self) -> Option<String> {
+        self.flag.as_ref().map(|_code| format!("/flags/{}.png", _code.to_lowercase()))
+    }
+
+    /// Every image the identity is drawn with, to load
+    pub fn images(&self) -> Vec<String> {
+        self.avatar.iter().cloned().chain(self.flag_image()).collect()
+    }
+
+    /// If there is nothing to draw
+    pub fn is_empty(&self) -> bool {
+        self.avatar.is_none() && self.flag.is_none()
+    }
+}
+
+/// Everyone who played, by name, and the best speedrun times
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Profiles {
+    pub players: BTreeMap<String, PlayerStats>,
+    #[serde(default)]
+    pub identities: BTreeMap<String, Identity>, // Avatars and flags by player name, set in the file or the console
+    #[serde(default)]
+    pub speedruns: BTreeMap<String, Vec<SpeedrunTime>>, // Fastest first, by the label of the difficulty
+    #[serde(skip)]
+    pub unsaved: bool // Changed since the file was last written
+}
+
+impl Profiles {
+    /// No players yet
+    pub fn new() -> Profiles {
+        Profiles::default()
+    }
+
+    /// Reads the profiles file. Anything unreadable counts as no players.
+    pub fn from_json(_text: &str) -> Profiles {
+        serde_json::from_str(_text).unwrap_or_default()
+    }
+
+    /// Writes the profiles file
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Adds a finished game to the stats of a player
+    pub fn record(&mut self, _name: &str, _stats: &SideStats) {
+        let player = self.players.entry(_name.to_string()).or_default();
+
+        player.games += 1;
+        player.moves += _stats.moves as u32;
+        player.think_total += _stats.average_think * _stats.moves as f32;
+        player.longest_think = player.longest_think.max(_stats.longest_think);
+
+        if let Some(accuracy) = _stats.accuracy {
+            player.rated_games += 1;
+            player.accuracy_total += accuracy;
+        }
+
+        self.unsaved = true;
+    }
+
+    /// The avatar and flag of a player, None when they have neither
+    pub fn identity(&self, _name: &str) -> Option<&Identity> {
+        self.identities.get(_name).filter(|_identity| !_identity.is_empty())
+    }
+
+    /// Mutable access to the avatar and flag of a player, marking the profiles to be written
+    pub fn identity_mut(&mut self, _name: &str) -> &mut Identity {
+        self.unsaved = true;
+        self.identities.entry(_name.to_string()).or_default()
+    }
+
+    /// The leaderboard of a difficulty, fastest first
+    pub fn speedrun_times(&self, _difficulty: Difficulty) -> &[SpeedrunTime] {
+        self.speedruns.get(_difficulty.label()).map(|_times| _times.as_slice()).unwrap_or(&[])
+    }
+
+    /// ## `record_speedrun`
+    /// Adds a speedrun to the leaderboard of its difficulty, if it is fast enough
+    /// ### Return
+    /// The place it took, from 1, or None if it did not make the leaderboard
+    pub fn record_speedrun(&mut self, _difficulty: Difficulty, _time: SpeedrunTime) -> Option<usize> {
+        let times = self.speedruns.entry(_difficulty.label().to_string()).or_default();
+        let place = times.iter().position(|_other| _time.seconds < _other.seconds).unwrap_or(times.len());
+        if place >= LEADERBOARD_SIZE {
+            return None;
+        }
+
+        times.insert(place, _time);
+        times.truncate(LEADERBOARD_SIZE);
+        self.unsaved = true;
+
+        Some(place + 1)
+    }
+}
+
+/// Name the stats of a side are kept under: the bot playing it, or the side itself for humans
+pub fn player_name(_controller: &GameController, _colour: Colour) -> String {
+    match _controller.bots.get(&_colour) {
+        Some(bot) => format!("bot {}", bot.name()),
+        None if _colour == Colour::White => "Rustaceans".to_string(),
+        None => "Haskellers".to_string()
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::saves::SavedGame;
+
+/// File the recent games are kept in, in the GGEZ user data directory
+pub const RECENT_FILE: &str = "/recent.json";
+
+/// Games kept on the list, the oldest drops off
+pub const RECENT_GAMES: usize = 10;
+
+/// How a game came onto the list, which decides how it is reopened
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RecentKind {
+    Save, // Saved or loaded from a slot, reopened to carry on playing
+    Import, // Opened from the PGN database, reopened in the analysis
+    Finished // Played to the end, reopened in the analysis
+}
+
+impl RecentKind {
+    /// What happened to the game, shown under its name
+    pub fn label(&self) -> &'static str {
+        match self {
+            RecentKind::Save => "Saved",
+            RecentKind::Import => "Imported",
+            RecentKind::Finished => "Finished"
+        }
+    }
+}
+
+/// A game on the list, with a copy of its moves so it can be reopened whatever happened to where it came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecentGame {
+    pub kind: RecentKind,
+    pub game: SavedGame // `saved` is when the game came onto the list
+}
+
+/// The last games saved, loaded, imported or finished, newest first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RecentGames {
+    pub games: Vec<RecentGame>,
+    #[serde(skip)]
+    pub unsaved: bool // Changed since the file was last written
+}
+
+impl RecentGames {
+    /// Reads the recent games file. Anything unreadable counts as no games.
+    pub fn from_json(_text: &str) -> RecentGames {
+        serde_json::from_str(_text).unwrap_or_default()
+    }
+
+    /// Writes the recent games file
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// ## `add`
+    /// Puts a game at the top of the list. A save slot or imported game already on it moves up rather than
+    /// showing twice, finished games are all kept.
+    /// ### Parameters
+    /// - `_kind: RecentKind`: How the game came onto the list
+    /// - `_game: SavedGame`: The game, named as it is shown
+    pub fn add(&mut self, _kind: RecentKind, mut _game: SavedGame) {
+        _game.saved = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.as_secs());
+
+        if _kind != RecentKind::Finished {
+            self.games.retain(|_recent| _recent.kind != _kind || _recent.game.name != _game.name);
+        }
+        self.games.insert(0, RecentGame { kind: _kind, game: _game });
+        self.games.truncate(RECENT_GAMES);
+        self.unsaved = true;
+    }
+}
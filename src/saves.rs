@@ -0,0 +1,174 @@
+use eliasfl_chess::Color as Colour;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::controller::GameController;
+
+/// Directory the save slots are kept in, in the GGEZ user data directory
+pub const SAVES_DIR: &str = "/saves";
+
+/// Slot written by quick save (F5) and read by quick load (F9)
+pub const QUICK_SAVE: &str = "quicksave";
+
+/// Longest file name of a slot before `.json`, in bytes, well under the 255 of most file systems
+const MAX_STEM_LENGTH: usize = 200;
+
+/// Slot the game being played is kept in when the window closes, and carried on from by Continue on the main menu
+pub const AUTO_SAVE: &str = "autosave";
+
+/// A move of a saved game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedMove {
+    pub uci: String, // i.e. "e2e4" or "e7e8q"
+    pub elapsed: f32, // Seconds into the game the move was played at
+    pub clock: Option<f32> // Seconds left on the mover's clock after the move
+}
+
+/// A game written to a save slot, enough to carry on playing it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedGame {
+    pub name: String, // Name of the slot
+    pub saved: u64, // When the game was saved, seconds since the Unix epoch
+    pub start_fen: Option<String>, // None for the starting position
+    pub fen: String, // The position when saved, for previews
+    pub moves: Vec<SavedMove>,
+    pub game_time: f32,
+    pub time_control: Option<(f32, f32)>, // Initial and increment seconds, None for untimed games
+    #[serde(default)]
+    pub time_odds: Option<(f32, f32)>, // Black's initial and increment seconds when they differ from White's
+    pub clocks: Option<(f32, f32)> // Seconds left for white and black
+}
+
+impl SavedGame {
+    /// ## `from_controller`
+    /// Takes the game being played into a save
+    /// ### Parameters
+    /// - `_name: &str`: Name of the slot
+    /// - `_controller: &GameController`: The game to save
+    pub fn from_controller(_name: &str, _controller: &GameController) -> SavedGame {
+        let remaining = |_colour| _controller.clocks.get(&_colour).map(|_clock| _clock.remaining());
+
+        SavedGame {
+            name: _name.to_string(),
+            saved: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.as_secs()),
+            start_fen: _controller.start_fen.clone(),
+            fen: _controller.fen(),
+            moves: _controller.history.iter().map(|_move| SavedMove {
+                uci: _move.uci(),
+                elapsed: _move.elapsed,
+                clock: _move.clock
+            }).collect(),
+            game_time: _controller.game_time,
+            time_control: _controller.time_control.map(|_control| (_control.initial, _control.increment)),
+            time_odds: _controller.time_odds.map(|_control| (_control.initial, _control.increment)),
+            clocks: remaining(Colour::White).zip(remaining(Colour::Black))
+        }
+    }
+
+    /// Reads a save file
+    pub fn from_json(_text: &str) -> Result<SavedGame, String> {
+        serde_json::from_str(_text).map_err(|_error| _error.to_string())
+    }
+
+    /// Writes a save file
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap_or_default()
+    }
+
+    /// Name of the save file in `SAVES_DIR`: the slot name with lowercase letters, digits and `-` kept and the bytes of
+    /// anything else percent-encoded, i.e. "a b" is "a%20b" and "a_b" is "a%5Fb". Capitals are encoded as well, so two
+    /// slots never share a file, not even on file systems that ignore case. Names encoding to more than
+    /// `MAX_STEM_LENGTH` bytes are cut there and told apart by `~` and the FNV-1a hash of the whole name.
+    pub fn file_name(&self) -> String {
+        let mut stem: String = self.name.bytes()
+            .map(|_byte| match _byte {
+                b'a'..=b'z' | b'0'..=b'9' | b'-' => (_byte as char).to_string(),
+                _ => format!("%{:02X}", _byte)
+            })
+            .collect();
+
+        // `~` is encoded in names, so cut names never share a file with whole ones
+        if stem.len() > MAX_STEM_LENGTH {
+            let hash = self.name.bytes().fold(0xcbf29ce484222325u64, |_hash, _byte| (_hash ^ _byte as u64).wrapping_mul(0x100000001b3));
+            stem.truncate(MAX_STEM_LENGTH - 17);
+            stem = format!("{}~{:016x}", stem, hash);
+        }
+
+        format!("{}/{}.json", SAVES_DIR, stem)
+    }
+}
+
+/// Every save slot, newest first. Scenes change the slots, `AppState` writes the changed ones to disk.
+#[derive(Debug, Clone, Default)]
+pub struct SaveSlots {
+    pub games: Vec<SavedGame>,
+    pub unsaved: Vec<String>, // Names of the slots changed since they were last written
+    pub deleted: Vec<String> // Files of the slots deleted since, to be removed from disk
+}
+
+impl SaveSlots {
+    /// Slots read from disk, sorted newest first. Of several files holding the same slot the newest is kept.
+    pub fn new(mut _games: Vec<SavedGame>) -> SaveSlots {
+        _games.sort_by(|_a, _b| _b.saved.cmp(&_a.saved));
+        let mut names = std::collections::HashSet::new();
+        _games.retain(|_game| names.insert(_game.name.clone()));
+        SaveSlots { games: _games, unsaved: vec![], deleted: vec![] }
+    }
+
+    /// The slot with the given name
+    pub fn get(&self, _name: &str) -> Option<&SavedGame> {
+        self.games.iter().find(|_game| _game.name == _name)
+    }
+
+    /// Puts a game in its slot, replacing what was saved there before
+    pub fn save(&mut self, _game: SavedGame) {
+        self.games.retain(|_saved| _saved.name != _game.name);
+        self.unsaved.push(_game.name.clone());
+        self.games.insert(0, _game);
+    }
+
+    /// Empties a slot, returning the game that was saved there
+    pub fn delete(&mut self, _name: &str) -> Option<SavedGame> {
+        let index = self.games.iter().position(|_game| _game.name == _name)?;
+        let game = self.games.remove(index);
+
+        self.unsaved.retain(|_unsaved| _unsaved != _name);
+        self.deleted.push(game.file_name());
+        Some(game)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(_name: &str) -> SavedGame {
+        SavedGame { name: _name.to_string(), saved: 0, start_fen: None, fen: String::new(), moves: vec![], game_time: 0.0, time_control: None, time_odds: None, clocks: None }
+    }
+
+    // Slots whose names differ never share a file
+    #[test]
+    fn file_names_differ() {
+        assert_eq!(slot(AUTO_SAVE).file_name(), "/saves/autosave.json");
+        assert_eq!(slot("a b").file_name(), "/saves/a%20b.json");
+        assert_eq!(slot("a_b").file_name(), "/saves/a%5Fb.json");
+
+        let long = "ä".repeat(200);
+        let names = ["a b", "a_b", "a%20b", "A b", "a/b", "ä b", "a-b", "", long.as_str(), &long[..398], &long[..396]];
+        let files: std::collections::HashSet<String> = names.iter().map(|_name| slot(_name).file_name()).collect();
+        assert_eq!(files.len(), names.len());
+        assert!(files.iter().all(|_file| _file.len() <= SAVES_DIR.len() + 1 + MAX_STEM_LENGTH + ".json".len()));
+    }
+
+    // Of two files of the same slot, the newest is kept
+    #[test]
+    fn slots_are_read_once() {
+        let (mut old, mut new) = (slot("a b"), slot("a b"));
+        old.saved = 1;
+        new.saved = 2;
+        new.fen = "newer".to_string();
+
+        let slots = SaveSlots::new(vec![old, new, slot("other")]);
+        assert_eq!(slots.games.len(), 2);
+        assert_eq!(slots.get("a b").map(|_game| _game.fen.as_str()), Some("newer"));
+    }
+}
@@ -0,0 +1,52 @@
+use super::{Scene, Shared, Transition};
+use super::super::achievements::Achievement;
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer, text_layout::TextLayout};
+use super::super::help_funcs::in_rect;
+
+const BACK_BUTTON: (f32, f32, f32, f32) = (230.0, 770.0, 260.0, 60.0);
+
+/// Every achievement, unlocked or not
+pub struct AchievementsScene;
+
+impl Scene for AchievementsScene {
+    fn name(&self) -> &'static str {
+        "achievements"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Achievements", 120.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        for (_index, _achievement) in Achievement::ALL.iter().enumerate() {
+            let y = 220.0 + 110.0 * _index as f32;
+            let unlocked = _shared.achievements.unlocked_at(*_achievement).is_some();
+
+            // Locked achievements are greyed out
+            let (accent, text) = if unlocked {
+                ([0.97, 0.3, 0.0, 1.0], [1.0, 1.0, 1.0, 1.0])
+            } else {
+                ([0.4, 0.4, 0.4, 1.0], [0.5, 0.5, 0.5, 1.0])
+            };
+
+            _renderer.draw_rect((100.0, y, 520.0, 90.0), [0.22, 0.22, 0.22, 1.0]);
+            _renderer.draw_rect((100.0, y, 8.0, 90.0), accent);
+            _renderer.draw_text(_achievement.title(), (130.0, y + 18.0), 26.0, accent);
+            TextLayout::new().fragment(_achievement.description(), 18.0, text).draw(_renderer, (130.0, y + 50.0, 470.0, 36.0));
+        }
+
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Click { x, y } if in_rect(BACK_BUTTON, x, y) => Transition::Pop,
+            _ => Transition::None
+        }
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        vec![BACK_BUTTON]
+    }
+}
@@ -0,0 +1,157 @@
+use eliasfl_chess::Color as Colour;
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Transition};
+use super::super::backend::ChessBackend;
+use super::super::controller::{GameController, InputEvent};
+use super::super::graphics_funcs::{draw_funcs, layers::Layer, renderer::Renderer, text_layout::TextLayout};
+use super::super::help_funcs::{in_rect, board_square, flip_square, num_to_filerank, filerank_to_num, material};
+use super::super::consts::{MOVE_LIST_ROW_HEIGHT, MOVE_LIST_NUMBER_WIDTH, MOVE_LIST_MOVE_WIDTH};
+
+const BACK_BUTTON: (f32, f32, f32, f32) = (480.0, 800.0, 180.0, 50.0);
+const MOVE_LIST: (f32, f32, f32, f32) = (10.0, 772.0, 450.0, 88.0);
+
+/// Look at the game without playing: step through its moves, click any piece to see its moves, the panel shows the material balance
+pub struct AnalysisScene {
+    ply: usize, // How many moves of the game are played on the shown position
+    scroll: usize, // Rows of the move list scrolled past
+    view: Box<dyn ChessBackend>, // The shown position, rebuilt from the history when stepping through the game
+    legal: Vec<(u8, u8)> // Moves of the clicked piece in the shown position
+}
+
+impl AnalysisScene {
+    /// Creates the scene showing the current position of the game
+    pub fn new(_controller: &GameController) -> AnalysisScene {
+        let ply = _controller.history.len();
+
+        let mut scene = AnalysisScene {
+            ply,
+            scroll: 0,
+            view: _controller.game.new_game(),
+            legal: vec![]
+        };
+        scene.go_to(_controller, ply);
+
+        scene
+    }
+
+    /// Number of rows the move list can show at once
+    fn visible_rows() -> usize {
+        (MOVE_LIST.3 / MOVE_LIST_ROW_HEIGHT) as usize
+    }
+
+    /// Number of rows needed for every move of the game
+    fn total_rows(_controller: &GameController) -> usize {
+        (_controller.history.len() + 1) / 2
+    }
+
+    /// Scrolls the move list by `_rows` (negative is up), staying within the list
+    fn scroll_by(&mut self, _controller: &GameController, _rows: i32) {
+        let max_scroll = AnalysisScene::total_rows(_controller).saturating_sub(AnalysisScene::visible_rows());
+        self.scroll = (self.scroll as i32 + _rows).max(0).min(max_scroll as i32) as usize;
+    }
+
+    /// Shows the position after the first `_ply` moves and scrolls the move list to the last of them
+    fn go_to(&mut self, _controller: &GameController, _ply: usize) {
+        let ply = _ply.min(_controller.history.len());
+
+        match _controller.position_at(ply) {
+            Ok(view) => {
+                self.view = view;
+                self.ply = ply;
+                self.legal.clear();
+            },
+            Err(_error) => return
+        }
+
+        if ply > 0 {
+            let row = (ply - 1) / 2;
+            if row < self.scroll {
+                self.scroll = row;
+            }
+            else if row >= self.scroll + AnalysisScene::visible_rows() {
+                self.scroll = row + 1 - AnalysisScene::visible_rows();
+            }
+        }
+    }
+
+    /// The move under a point of the move list, as its index in the history
+    fn move_at(&self, _controller: &GameController, _x: f32, _y: f32) -> Option<usize> {
+        if !in_rect(MOVE_LIST, _x, _y) || _x < MOVE_LIST.0 + MOVE_LIST_NUMBER_WIDTH {
+            return None;
+        }
+
+        let row = self.scroll + ((_y - MOVE_LIST.1) / MOVE_LIST_ROW_HEIGHT) as usize;
+        let side = ((_x - MOVE_LIST.0 - MOVE_LIST_NUMBER_WIDTH) / MOVE_LIST_MOVE_WIDTH) as usize;
+        let index = row * 2 + side;
+
+        if side < 2 && index < _controller.history.len() { Some(index) } else { None }
+    }
+}
+
+impl Scene for AnalysisScene {
+    fn name(&self) -> &'static str {
+        "analysis"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_position(_renderer, &_shared.theme, self.view.as_ref(), &self.legal, draw_funcs::Handling::default(), _shared.controller.flipped, _shared.board_style());
+        if let Some(last) = self.ply.checked_sub(1).and_then(|_index| _shared.controller.history.get(_index)) {
+            _shared.layers.draw(Layer::Annotations, || draw_funcs::draw_last_move_arrow(_renderer, last, _shared.controller.flipped));
+        }
+        draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+
+        let material = format!("Material: Rustaceans {} - Haskellers {}",
+            material(self.view.as_ref(), Colour::White), material(self.view.as_ref(), Colour::Black));
+        TextLayout::centred(&material, 24.0, _shared.theme.text).draw(_renderer, (0.0, 727.0, 720.0, 40.0));
+
+        draw_funcs::draw_move_list(_renderer, &_shared.controller.history, MOVE_LIST, self.scroll, self.ply.checked_sub(1));
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        let controller = &_shared.controller;
+
+        match _event {
+            InputEvent::Click { x, y } if y < 720.0 => {
+                // Show the moves of the clicked piece without being able to play them
+                if let Some(square) = board_square(x, y).map(|_square| flip_square(_square, controller.flipped)) {
+                    self.legal = self.view.legal_moves(&num_to_filerank(&square)).iter()
+                        .map(|_move| filerank_to_num(_move))
+                        .collect();
+                }
+
+                Transition::None
+            },
+            InputEvent::Click { x, y } if in_rect(MOVE_LIST, x, y) => {
+                if let Some(index) = self.move_at(controller, x, y) {
+                    self.go_to(controller, index + 1);
+                }
+
+                Transition::None
+            },
+            InputEvent::Click { x, y } if in_rect(BACK_BUTTON, x, y) => Transition::Pop,
+            InputEvent::Scroll { y } => {
+                self.scroll_by(controller, if y > 0.0 { -1 } else { 1 });
+                Transition::None
+            },
+            // Step through the game with the arrow keys
+            InputEvent::Key(KeyCode::Left) => {
+                self.go_to(controller, self.ply.saturating_sub(1));
+                Transition::None
+            },
+            InputEvent::Key(KeyCode::Right) => {
+                self.go_to(controller, self.ply + 1);
+                Transition::None
+            },
+            InputEvent::Key(KeyCode::Home) => {
+                self.go_to(controller, 0);
+                Transition::None
+            },
+            InputEvent::Key(KeyCode::End) => {
+                self.go_to(controller, controller.history.len());
+                Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use eliasfl_chess::Color as Colour;
+use super::{Scene, Shared, Transition};
+use super::game_over::GameOverScene;
+use super::super::controller::{InputEvent, Command};
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer};
+use super::super::help_funcs::in_rect;
+use ggez::event::KeyCode;
+
+// Results and the takeback on the top row
+const WHITE_WINS_BUTTON: (f32, f32, f32, f32) = (15.0, 735.0, 165.0, 50.0);
+const DRAW_BUTTON: (f32, f32, f32, f32) = (190.0, 735.0, 165.0, 50.0);
+const BLACK_WINS_BUTTON: (f32, f32, f32, f32) = (365.0, 735.0, 165.0, 50.0);
+const TAKE_BACK_BUTTON: (f32, f32, f32, f32) = (540.0, 735.0, 165.0, 50.0);
+
+// Clocks and closing on the bottom row
+const WHITE_LESS_BUTTON: (f32, f32, f32, f32) = (15.0, 800.0, 130.0, 50.0);
+const WHITE_MORE_BUTTON: (f32, f32, f32, f32) = (155.0, 800.0, 130.0, 50.0);
+const BLACK_LESS_BUTTON: (f32, f32, f32, f32) = (295.0, 800.0, 130.0, 50.0);
+const BLACK_MORE_BUTTON: (f32, f32, f32, f32) = (435.0, 800.0, 130.0, 50.0);
+const CLOSE_BUTTON: (f32, f32, f32, f32) = (575.0, 800.0, 130.0, 50.0);
+
+/// Seconds a clock button adds or takes off
+const CLOCK_STEP: f32 = 30.0;
+
+/// Controls for an arbiter or teacher over the panel: declare the result, adjust the clocks and take moves back
+/// whatever the settings. Only reachable when turned on in the settings.
+pub struct ArbiterScene;
+
+impl ArbiterScene {
+    /// The command behind a button, if the click hit one
+    fn command_at(_x: f32, _y: f32) -> Option<Command> {
+        [
+            (WHITE_WINS_BUTTON, Command::Adjudicate(Some(Colour::White))),
+            (DRAW_BUTTON, Command::Adjudicate(None)),
+            (BLACK_WINS_BUTTON, Command::Adjudicate(Some(Colour::Black))),
+            (TAKE_BACK_BUTTON, Command::Undo),
+            (WHITE_LESS_BUTTON, Command::AdjustClock(Colour::White, -CLOCK_STEP)),
+            (WHITE_MORE_BUTTON, Command::AdjustClock(Colour::White, CLOCK_STEP)),
+            (BLACK_LESS_BUTTON, Command::AdjustClock(Colour::Black, -CLOCK_STEP)),
+            (BLACK_MORE_BUTTON, Command::AdjustClock(Colour::Black, CLOCK_STEP))
+        ]
+            .iter()
+            .find(|(_button, _)| in_rect(*_button, _x, _y))
+            .map(|(_, _command)| _command.clone())
+    }
+}
+
+impl Scene for ArbiterScene {
+    fn name(&self) -> &'static str {
+        "arbiter"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+
+        draw_funcs::draw_button(_renderer, WHITE_WINS_BUTTON, "Rustaceans win");
+        draw_funcs::draw_button(_renderer, DRAW_BUTTON, "Draw");
+        draw_funcs::draw_button(_renderer, BLACK_WINS_BUTTON, "Haskellers win");
+        draw_funcs::draw_button(_renderer, TAKE_BACK_BUTTON, "Take back");
+
+        draw_funcs::draw_button(_renderer, WHITE_LESS_BUTTON, "White -30s");
+        draw_funcs::draw_button(_renderer, WHITE_MORE_BUTTON, "White +30s");
+        draw_funcs::draw_button(_renderer, BLACK_LESS_BUTTON, "Black -30s");
+        draw_funcs::draw_button(_renderer, BLACK_MORE_BUTTON, "Black +30s");
+        draw_funcs::draw_button(_renderer, CLOSE_BUTTON, "Close");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Key(KeyCode::A) | InputEvent::Key(KeyCode::Escape) => Transition::Pop,
+            InputEvent::Click { x, y } if in_rect(CLOSE_BUTTON, x, y) => Transition::Pop,
+            InputEvent::Click { x, y } => {
+                let command = match ArbiterScene::command_at(x, y) {
+                    Some(command) => command,
+                    None => return Transition::None
+                };
+
+                if let Err(error) = _shared.controller.try_execute(command) {
+                    _shared.controller.banner = Some(format!("Arbiter: {}", error));
+                }
+
+                if _shared.controller.is_over() {
+                    Transition::Replace(Box::new(GameOverScene::new(_shared)))
+                }
+                else {
+                    Transition::None
+                }
+            },
+            _ => Transition::None
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        vec![
+            WHITE_WINS_BUTTON, DRAW_BUTTON, BLACK_WINS_BUTTON, TAKE_BACK_BUTTON,
+            WHITE_LESS_BUTTON, WHITE_MORE_BUTTON, BLACK_LESS_BUTTON, BLACK_MORE_BUTTON, CLOSE_BUTTON
+        ]
+    }
+}
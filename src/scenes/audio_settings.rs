@@ -0,0 +1,147 @@
+use super::{Scene, Shared, Transition};
+use super::super::audio::{self, Sound, SOUND_PACKS};
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer};
+use super::super::help_funcs::in_rect;
+
+// Slider tracks, the area that reacts to the mouse reaches a bit above and below
+const MASTER_SLIDER: (f32, f32, f32, f32) = (160.0, 300.0, 400.0, 12.0);
+const EFFECTS_SLIDER: (f32, f32, f32, f32) = (160.0, 420.0, 400.0, 12.0);
+const VOICE_SLIDER: (f32, f32, f32, f32) = (160.0, 540.0, 400.0, 12.0);
+const PACK_BUTTON: (f32, f32, f32, f32) = (160.0, 630.0, 260.0, 60.0);
+const PREVIEW_BUTTON: (f32, f32, f32, f32) = (440.0, 630.0, 120.0, 60.0);
+const BACK_BUTTON: (f32, f32, f32, f32) = (230.0, 770.0, 260.0, 60.0);
+
+/// Which volume a slider sets
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Slider {
+    Master,
+    Effects,
+    Voice
+}
+
+impl Slider {
+    const ALL: [Slider; 3] = [Slider::Master, Slider::Effects, Slider::Voice];
+
+    fn geometry(&self) -> (f32, f32, f32, f32) {
+        match self {
+            Slider::Master => MASTER_SLIDER,
+            Slider::Effects => EFFECTS_SLIDER,
+            Slider::Voice => VOICE_SLIDER
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            Slider::Master => "Master",
+            Slider::Effects => "Effects",
+            Slider::Voice => "Voice"
+        }
+    }
+
+    fn value<'a>(&self, _shared: &'a mut Shared) -> &'a mut f32 {
+        match self {
+            Slider::Master => &mut _shared.config.audio.master,
+            Slider::Effects => &mut _shared.config.audio.effects,
+            Slider::Voice => &mut _shared.config.audio.voice
+        }
+    }
+
+    /// The slider under a point, with some room around the thin track
+    fn at(_x: f32, _y: f32) -> Option<Slider> {
+        Slider::ALL.iter().copied().find(|_slider| {
+            let (x, y, width, height) = _slider.geometry();
+            in_rect((x - 10.0, y - 15.0, width + 20.0, height + 30.0), _x, _y)
+        })
+    }
+}
+
+/// Volumes of the master, effects and voice channels and the sound pack, saved to the configuration file
+pub struct AudioSettingsScene {
+    dragging: Option<Slider>
+}
+
+impl AudioSettingsScene {
+    pub fn new() -> AudioSettingsScene {
+        AudioSettingsScene { dragging: None }
+    }
+
+    /// Sets the volume of a slider from the mouse position along its track
+    fn set(_shared: &mut Shared, _slider: Slider, _x: f32) {
+        let (x, _, width, _) = _slider.geometry();
+        *_slider.value(_shared) = ((_x - x) / width).max(0.0).min(1.0);
+    }
+
+    /// Switches to the next sound pack and lets it be heard
+    fn next_pack(_shared: &mut Shared) {
+        let current = SOUND_PACKS.iter().position(|_pack| *_pack == audio::sound_pack(&_shared.config.sound_pack)).unwrap_or(0);
+        _shared.config.sound_pack = SOUND_PACKS[(current + 1) % SOUND_PACKS.len()].to_string();
+        _shared.config.unsaved = true;
+        _shared.preview = Some(Sound::Capture);
+    }
+}
+
+impl Scene for AudioSettingsScene {
+    fn name(&self) -> &'static str {
+        "audio_settings"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Audio", 150.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        let volumes = &_shared.config.audio;
+        draw_funcs::draw_slider(_renderer, MASTER_SLIDER, Slider::Master.label(), volumes.master);
+        draw_funcs::draw_slider(_renderer, EFFECTS_SLIDER, Slider::Effects.label(), volumes.effects);
+        draw_funcs::draw_slider(_renderer, VOICE_SLIDER, Slider::Voice.label(), volumes.voice);
+
+        let pack = audio::sound_pack(&_shared.config.sound_pack);
+        let mut label = pack.to_string();
+        label[..1].make_ascii_uppercase();
+        draw_funcs::draw_button(_renderer, PACK_BUTTON, &format!("Sounds: {}", label));
+        draw_funcs::draw_button(_renderer, PREVIEW_BUTTON, "Preview");
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Press { x, y } => {
+                self.dragging = Slider::at(x, y);
+                if let Some(slider) = self.dragging {
+                    AudioSettingsScene::set(_shared, slider, x);
+                }
+                Transition::None
+            },
+            InputEvent::Motion { x, .. } => {
+                if let Some(slider) = self.dragging {
+                    AudioSettingsScene::set(_shared, slider, x);
+                }
+                Transition::None
+            },
+            InputEvent::Click { x, y } => {
+                // Saved once the slider is let go rather than on every motion
+                if self.dragging.take().is_some() {
+                    _shared.config.unsaved = true;
+                    Transition::None
+                }
+                else if in_rect(PACK_BUTTON, x, y) {
+                    AudioSettingsScene::next_pack(_shared);
+                    Transition::None
+                }
+                else if in_rect(PREVIEW_BUTTON, x, y) {
+                    _shared.preview = Some(Sound::Capture);
+                    Transition::None
+                }
+                else if in_rect(BACK_BUTTON, x, y) { Transition::Pop }
+                else { Transition::None }
+            },
+            _ => Transition::None
+        }
+    }
+
+    // The sliders are dragged with the mouse
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        vec![PACK_BUTTON, PREVIEW_BUTTON, BACK_BUTTON]
+    }
+}
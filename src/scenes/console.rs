@@ -0,0 +1,189 @@
+use eliasfl_chess::Color as Colour;
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Transition};
+use super::super::controller::{Command, InputEvent};
+use super::super::bot::parse_uci;
+use super::super::graphics_funcs::{layers::Layer, renderer::Renderer};
+use super::super::help_funcs::material;
+use super::super::profile::player_name;
+use super::super::theme::Theme;
+
+/// Lines of the log shown above the input line
+const VISIBLE_LINES: usize = 14;
+
+/// What a console line asks for
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConsoleAction {
+    /// A command for the game, run through `GameController::try_execute`
+    Game(Command),
+    /// Print the material balance and the status
+    Eval,
+    /// Switch to a built-in theme or one from a plugin
+    Theme(String),
+    /// Give the player of a side an avatar image, None takes it off
+    Avatar(Colour, Option<String>),
+    /// Give the player of a side a flag by country code, None takes it off
+    Flag(Colour, Option<String>),
+    /// Hide a shown layer of the screen or show a hidden one
+    Layer(Layer),
+    /// List the commands
+    Help
+}
+
+/// Reads "white \<value\>" or "black none" for the identity commands
+fn parse_side_value(_rest: &str) -> Option<(Colour, Option<String>)> {
+    let mut words = _rest.split_whitespace();
+    let colour = match words.next()? {
+        "white" => Colour::White,
+        "black" => Colour::Black,
+        _ => return None
+    };
+
+    match words.next()? {
+        "none" => Some((colour, None)),
+        value => Some((colour, Some(value.to_string())))
+    }
+}
+
+/// ## `parse_line`
+/// Parses a console line, i.e. "move e2e4", "fen \<fen\>", "undo", "redo", "eval", "theme dark", "flag white se" or "layer hud"
+/// ### Return
+/// `Err` with a message for the log if the line means nothing
+pub fn parse_line(_line: &str) -> Result<ConsoleAction, String> {
+    let line = _line.trim();
+    let (word, rest) = match line.find(' ') {
+        Some(index) => (&line[..index], line[index + 1..].trim()),
+        None => (line, "")
+    };
+
+    match word {
+        // UCI style: e2e4, or e7e8q for a promotion
+        "move" => match parse_uci(rest) {
+            Some((from, to, promotion)) => Ok(ConsoleAction::Game(Command::PlayMove(from, to, promotion))),
+            None => Err("usage: move e2e4".to_string())
+        },
+        "undo" => Ok(ConsoleAction::Game(Command::Undo)),
+        "redo" => Ok(ConsoleAction::Game(Command::Redo)),
+        "resync" => Ok(ConsoleAction::Game(Command::Resync)),
+        "restart" => Ok(ConsoleAction::Game(Command::Restart)),
+        "fen" if !rest.is_empty() => Ok(ConsoleAction::Game(Command::LoadFen(rest.to_string()))),
+        "fen" => Err("usage: fen <position>".to_string()),
+        "eval" => Ok(ConsoleAction::Eval),
+        "theme" if !rest.is_empty() => Ok(ConsoleAction::Theme(rest.to_string())),
+        "theme" => Err(format!("usage: theme <{}>", Theme::BUILTIN.join("|"))),
+        "avatar" => parse_side_value(rest).map(|(colour, path)| ConsoleAction::Avatar(colour, path))
+            .ok_or_else(|| "usage: avatar <white|black> </avatars/name.png|none>".to_string()),
+        "flag" => parse_side_value(rest).map(|(colour, code)| ConsoleAction::Flag(colour, code))
+            .ok_or_else(|| "usage: flag <white|black> <country code|none>".to_string()),
+        "layer" => Layer::from_name(rest).map(ConsoleAction::Layer).ok_or_else(|| {
+            let names: Vec<&str> = Layer::ALL.iter().map(|_layer| _layer.name()).collect();
+            format!("usage: layer <{}>", names.join("|"))
+        }),
+        "help" => Ok(ConsoleAction::Help),
+        "" => Err(String::new()),
+        _ => Err(format!("unknown command {}, try help", word))
+    }
+}
+
+/// ## `run_line`
+/// Parses and carries out a console line
+/// ### Return
+/// The lines to add to the log
+pub fn run_line(_shared: &mut Shared, _line: &str) -> Vec<String> {
+    let mut output = vec![format!("> {}", _line)];
+
+    match parse_line(_line) {
+        Ok(ConsoleAction::Game(command)) => match _shared.controller.try_execute(command) {
+            Ok(()) => output.push(_shared.controller.fen()),
+            Err(error) => output.push(format!("error: {}", error))
+        },
+        Ok(ConsoleAction::Eval) => {
+            let game = _shared.controller.game.as_ref();
+            let (white, black) = (material(game, Colour::White), material(game, Colour::Black));
+            output.push(format!("material {:+} (Rustaceans {} - Haskellers {}), {:?}", white as i32 - black as i32, white, black, game.status()));
+        },
+        Ok(ConsoleAction::Theme(name)) => match _shared.find_theme(&name) {
+            Some(theme) => {
+                _shared.theme = theme;
+                output.push(format!("theme {}", name));
+            },
+            None => {
+                let names: Vec<&str> = _shared.themes.iter().map(|_theme| _theme.name.as_str()).collect();
+                output.push(format!("error: no theme called {}, try {}", name, names.join(", ")));
+            }
+        },
+        Ok(ConsoleAction::Avatar(colour, path)) => {
+            let name = player_name(&_shared.controller, colour);
+            output.push(format!("avatar of {}: {}", name, path.as_deref().unwrap_or("none")));
+            _shared.profiles.identity_mut(&name).avatar = path;
+        },
+        Ok(ConsoleAction::Flag(colour, code)) => {
+            let name = player_name(&_shared.controller, colour);
+            output.push(format!("flag of {}: {}", name, code.as_deref().unwrap_or("none")));
+            _shared.profiles.identity_mut(&name).flag = code;
+        },
+        Ok(ConsoleAction::Layer(layer)) => {
+            let shown = _shared.layers.toggle(layer);
+            output.push(format!("layer {} {}", layer.name(), if shown { "shown" } else { "hidden" }));
+        },
+        Ok(ConsoleAction::Help) => {
+            output.push("move e2e4 | move e7e8q | undo | redo | resync | restart | fen <position> | eval | theme <name>".to_string());
+            output.push("avatar <white|black> <path|none> | flag <white|black> <code|none> | layer <name>".to_string());
+        },
+        Err(error) if error.is_empty() => {},
+        Err(error) => output.push(format!("error: {}", error))
+    }
+
+    output
+}
+
+/// Developer console opened with the tilde key, drawn over the top of the board
+pub struct ConsoleScene {
+    line: String // What is being typed
+}
+
+impl ConsoleScene {
+    /// Creates a console with an empty input line
+    pub fn new() -> ConsoleScene {
+        ConsoleScene { line: String::new() }
+    }
+}
+
+impl Scene for ConsoleScene {
+    fn name(&self) -> &'static str {
+        "console"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        let height = (VISIBLE_LINES + 1) as f32 * 20.0 + 16.0;
+        _renderer.draw_rect((0.0, 0.0, 720.0, height), [0.0, 0.0, 0.0, 0.85]);
+
+        let log = &_shared.console_log;
+        for (_index, _line) in log.iter().skip(log.len().saturating_sub(VISIBLE_LINES)).enumerate() {
+            _renderer.draw_text(_line, (8.0, 8.0 + _index as f32 * 20.0), 16.0, [0.8, 0.8, 0.8, 1.0]);
+        }
+
+        _renderer.draw_text(&format!("> {}_", self.line), (8.0, 8.0 + VISIBLE_LINES as f32 * 20.0), 16.0, [0.4, 1.0, 0.4, 1.0]);
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Key(KeyCode::Grave) | InputEvent::Key(KeyCode::Escape) => return Transition::Pop,
+            InputEvent::Key(KeyCode::Back) => { self.line.pop(); },
+            InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) => {
+                let line = std::mem::take(&mut self.line);
+                let output = run_line(_shared, &line);
+                _shared.console_log.extend(output);
+            },
+            // The tilde opening the console is typed too, it is not part of the line
+            InputEvent::Text(character) if !character.is_control() && character != '`' && character != '~' => self.line.push(character),
+            _ => {}
+        }
+
+        Transition::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,248 @@
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Transition};
+use super::analysis::AnalysisScene;
+use super::super::controller::InputEvent;
+use super::super::database::{self, DatabaseFilter, DATABASE_FILE, RESULTS};
+use super::super::recent::RecentKind;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer, text_layout::TextLayout};
+use super::super::help_funcs::in_rect;
+
+/// Longest search or ECO code that can be typed
+const MAX_FIELD_LENGTH: usize = 40;
+
+// The filters: two text fields and the result button
+const SEARCH_FIELD: (f32, f32, f32, f32) = (200.0, 100.0, 460.0, 44.0);
+const ECO_FIELD: (f32, f32, f32, f32) = (200.0, 156.0, 150.0, 44.0);
+const RESULT_BUTTON: (f32, f32, f32, f32) = (370.0, 156.0, 290.0, 44.0);
+
+// One row per game on the page: the players, then the event, date and ECO code
+const FIRST_ROW: f32 = 220.0;
+const ROW_HEIGHT: f32 = 52.0;
+const PAGE_SIZE: usize = 9;
+
+const PREVIOUS_BUTTON: (f32, f32, f32, f32) = (60.0, 770.0, 140.0, 60.0);
+const BACK_BUTTON: (f32, f32, f32, f32) = (230.0, 770.0, 260.0, 60.0);
+const NEXT_BUTTON: (f32, f32, f32, f32) = (520.0, 770.0, 140.0, 60.0);
+
+/// Text field of the filters
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Search,
+    Eco
+}
+
+/// Browses the games of the PGN database in the user data directory a page at a time, searched by players and
+/// event and filtered by result and ECO code. Clicking a game opens it in the analysis.
+pub struct DatabaseScene {
+    filter: DatabaseFilter,
+    field: Option<Field>, // Field being typed in
+    found: Vec<usize>, // Indices into `Shared::database` of the games through the filter
+    page: usize // Page of `found` shown, from 0
+}
+
+impl DatabaseScene {
+    /// Shows every game of the database
+    pub fn new(_shared: &Shared) -> DatabaseScene {
+        let filter = DatabaseFilter::default();
+        let found = filter.search(&_shared.database);
+
+        DatabaseScene { filter, field: None, found, page: 0 }
+    }
+
+    /// Number of pages the games through the filter fill, at least one
+    fn pages(&self) -> usize {
+        ((self.found.len() + PAGE_SIZE - 1) / PAGE_SIZE).max(1)
+    }
+
+    /// Searches again after the filter changed, from the first page
+    fn refresh(&mut self, _shared: &Shared) {
+        self.found = self.filter.search(&_shared.database);
+        self.page = 0;
+    }
+
+    /// What is typed in a text field of the filter
+    fn field_value(&self, _field: Field) -> &str {
+        match _field {
+            Field::Search => &self.filter.query,
+            Field::Eco => &self.filter.eco
+        }
+    }
+
+    /// A text field of the filter, to be edited
+    fn field_mut(&mut self, _field: Field) -> &mut String {
+        match _field {
+            Field::Search => &mut self.filter.query,
+            Field::Eco => &mut self.filter.eco
+        }
+    }
+
+    /// Rectangle of a row on screen, by its position on the page
+    fn row(_index: usize) -> (f32, f32, f32, f32) {
+        (60.0, FIRST_ROW + _index as f32 * ROW_HEIGHT, 600.0, ROW_HEIGHT - 4.0)
+    }
+
+    /// ## `open`
+    /// Replays a game of the database on the board, with both sides played by clicking, and looks at it in the analysis
+    /// ### Return
+    /// `Err` if a move of the game could not be played
+    fn open(_shared: &mut Shared, _index: usize) -> Result<(), String> {
+        let game = &_shared.database[_index];
+        let saved = database::to_saved(game, _shared.controller.game.new_game())?;
+
+        _shared.review(&saved)?;
+        _shared.recent.add(RecentKind::Import, saved);
+        Ok(())
+    }
+}
+
+impl Scene for DatabaseScene {
+    fn name(&self) -> &'static str {
+        "database"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Database", 30.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        let fields = [(Field::Search, "Search", SEARCH_FIELD, "Players or event"), (Field::Eco, "ECO", ECO_FIELD, "Any")];
+        for (_field, _label, _rect, _placeholder) in fields.iter() {
+            _renderer.draw_text(_label, (60.0, _rect.1 + 10.0), 24.0, [0.7, 0.7, 0.7, 1.0]);
+
+            let typing = self.field == Some(*_field);
+            if typing {
+                _renderer.draw_rect((_rect.0 - 3.0, _rect.1 - 3.0, _rect.2 + 6.0, _rect.3 + 6.0), [0.97, 0.3, 0.0, 1.0]);
+            }
+            _renderer.draw_rect(*_rect, [0.1, 0.1, 0.1, 1.0]);
+
+            let value = self.field_value(*_field);
+            let (text, colour) = match (value.is_empty(), typing) {
+                (_, true) => (format!("{}_", value), [1.0, 1.0, 1.0, 1.0]),
+                (true, false) => (_placeholder.to_string(), [0.5, 0.5, 0.5, 1.0]),
+                (false, false) => (value.to_string(), [1.0, 1.0, 1.0, 1.0])
+            };
+            _renderer.draw_text(&text, (_rect.0 + 10.0, _rect.1 + 10.0), 24.0, colour);
+        }
+        draw_funcs::draw_button(_renderer, RESULT_BUTTON, &format!("Result: {}", self.filter.result.unwrap_or("Any")));
+
+        if _shared.database.is_empty() {
+            TextLayout::centred(&format!("No games yet, put a PGN file at {} in the user data directory", DATABASE_FILE), 20.0, [0.7, 0.7, 0.7, 1.0])
+                .draw(_renderer, (60.0, FIRST_ROW, 600.0, 60.0));
+        }
+        else if self.found.is_empty() {
+            TextLayout::centred("No game matches", 20.0, [0.7, 0.7, 0.7, 1.0]).draw(_renderer, (60.0, FIRST_ROW, 600.0, 60.0));
+        }
+
+        for (_row, _index) in self.found.iter().skip(self.page * PAGE_SIZE).take(PAGE_SIZE).enumerate() {
+            let game = &_shared.database[*_index];
+            let (x, y, width, height) = DatabaseScene::row(_row);
+            _renderer.draw_rect((x, y, width, height), [0.22, 0.22, 0.22, 1.0]);
+
+            _renderer.draw_text(&database::title(game), (x + 10.0, y + 4.0), 20.0, [1.0, 1.0, 1.0, 1.0]);
+            _renderer.draw_text(game.tag("Result").unwrap_or("*"), (x + width - 80.0, y + 4.0), 20.0, [1.0, 1.0, 1.0, 1.0]);
+
+            let details: Vec<&str> = ["Event", "Date", "ECO"].iter().filter_map(|_tag| game.tag(_tag)).filter(|_value| !_value.is_empty() && *_value != "?").collect();
+            _renderer.draw_text(&details.join("  -  "), (x + 10.0, y + 28.0), 15.0, [0.7, 0.7, 0.7, 1.0]);
+        }
+
+        TextLayout::centred(&format!("Page {} of {}, {} of {} games", self.page + 1, self.pages(), self.found.len(), _shared.database.len()), 18.0, [0.7, 0.7, 0.7, 1.0])
+            .draw(_renderer, (60.0, 700.0, 600.0, 40.0));
+
+        draw_funcs::draw_button(_renderer, PREVIOUS_BUTTON, "<");
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+        draw_funcs::draw_button(_renderer, NEXT_BUTTON, ">");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        // Typing in a filter field, the list follows every key
+        if let Some(field) = self.field {
+            match _event {
+                InputEvent::Text(character) if !character.is_control() && self.field_value(field).chars().count() < MAX_FIELD_LENGTH => {
+                    self.field_mut(field).push(character);
+                    self.refresh(_shared);
+                    return Transition::None;
+                },
+                InputEvent::Key(KeyCode::Back) => {
+                    self.field_mut(field).pop();
+                    self.refresh(_shared);
+                    return Transition::None;
+                },
+                InputEvent::Key(KeyCode::Tab) => {
+                    self.field = Some(if field == Field::Search { Field::Eco } else { Field::Search });
+                    return Transition::None;
+                },
+                InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) | InputEvent::Key(KeyCode::Escape) => {
+                    self.field = None;
+                    return Transition::None;
+                },
+                _ => {}
+            }
+        }
+
+        match _event {
+            InputEvent::Key(KeyCode::Escape) => Transition::Pop,
+            InputEvent::Scroll { y } => {
+                self.page = if y > 0.0 { self.page.saturating_sub(1) } else { (self.page + 1).min(self.pages() - 1) };
+                Transition::None
+            },
+            InputEvent::Click { x, y } => {
+                self.field = None;
+
+                if in_rect(SEARCH_FIELD, x, y) {
+                    self.field = Some(Field::Search);
+                }
+                else if in_rect(ECO_FIELD, x, y) {
+                    self.field = Some(Field::Eco);
+                }
+                else if in_rect(RESULT_BUTTON, x, y) {
+                    // Any, then every result in turn
+                    let current = self.filter.result.and_then(|_result| RESULTS.iter().position(|_known| *_known == _result));
+                    self.filter.result = match current {
+                        None => Some(RESULTS[0]),
+                        Some(index) => RESULTS.get(index + 1).copied()
+                    };
+                    self.refresh(_shared);
+                }
+                else if in_rect(PREVIOUS_BUTTON, x, y) {
+                    self.page = self.page.saturating_sub(1);
+                }
+                else if in_rect(NEXT_BUTTON, x, y) {
+                    self.page = (self.page + 1).min(self.pages() - 1);
+                }
+                else if in_rect(BACK_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+                else {
+                    let on_page = self.found.iter().skip(self.page * PAGE_SIZE).take(PAGE_SIZE).count();
+                    let clicked = (0..on_page).find(|_row| in_rect(DatabaseScene::row(*_row), x, y));
+
+                    if let Some(index) = clicked.map(|_row| self.found[self.page * PAGE_SIZE + _row]) {
+                        return match DatabaseScene::open(_shared, index) {
+                            Ok(()) => Transition::Push(Box::new(AnalysisScene::new(&_shared.controller))),
+                            Err(error) => {
+                                _shared.controller.banner = Some(format!("{} could not be opened: {}", database::title(&_shared.database[index]), error));
+                                Transition::None
+                            }
+                        };
+                    }
+                }
+
+                Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+
+    // The fields, the games of the page, then the buttons. Typing keeps Tab and Enter.
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        if self.field.is_some() {
+            return vec![];
+        }
+
+        let on_page = self.found.iter().skip(self.page * PAGE_SIZE).take(PAGE_SIZE).count();
+        vec![SEARCH_FIELD, ECO_FIELD, RESULT_BUTTON].into_iter()
+            .chain((0..on_page).map(DatabaseScene::row))
+            .chain(vec![PREVIOUS_BUTTON, BACK_BUTTON, NEXT_BUTTON])
+            .collect()
+    }
+}
@@ -0,0 +1,99 @@
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Toast, Transition};
+use super::super::consts::TOAST_TIME;
+use super::super::controller::InputEvent;
+use super::super::diagram::{self, DiagramOptions, DIAGRAM_FILE};
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer};
+use super::super::help_funcs::{in_rect, flip_square};
+
+const PREVIEW_AT: (f32, f32) = (200.0, 150.0);
+const PREVIEW_SIZE: f32 = 320.0;
+const COORDINATES_BUTTON: (f32, f32, f32, f32) = (160.0, 520.0, 400.0, 60.0);
+const ARROW_BUTTON: (f32, f32, f32, f32) = (160.0, 600.0, 400.0, 60.0);
+const EXPORT_BUTTON: (f32, f32, f32, f32) = (80.0, 770.0, 260.0, 60.0);
+const CANCEL_BUTTON: (f32, f32, f32, f32) = (380.0, 770.0, 260.0, 60.0);
+
+/// Exports the current position as an SVG diagram to `DIAGRAM_FILE`, after choosing what goes on it.
+/// C and A toggle the coordinates and the arrow, Enter exports and Escape goes back without exporting.
+pub struct DiagramScene {
+    options: DiagramOptions
+}
+
+impl DiagramScene {
+    /// Coordinates and the arrow of the last move are on by default
+    pub fn new() -> DiagramScene {
+        DiagramScene { options: DiagramOptions { coordinates: true, arrow: true } }
+    }
+
+    /// Writes the diagram for `AppState` to save, and says so
+    fn export(&self, _shared: &mut Shared) {
+        let svg = diagram::to_svg(&_shared.controller, &_shared.theme, self.options);
+        _shared.exports.push((DIAGRAM_FILE.to_string(), svg));
+        _shared.toasts.push(Toast {
+            title: "Diagram exported".to_string(),
+            message: format!("Written to {} in the user data directory", DIAGRAM_FILE.trim_start_matches('/')),
+            remaining: TOAST_TIME
+        });
+    }
+}
+
+impl Scene for DiagramScene {
+    fn name(&self) -> &'static str {
+        "diagram"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Export diagram", 80.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        // The position as the diagram will show it, from the side the board is turned to
+        let controller = &_shared.controller;
+        let pieces: Vec<((u8, u8), _)> = (0..8u8).flat_map(|_col| (0..8u8).map(move |_row| (_col, _row)))
+            .filter_map(|_square| controller.game.piece_at(flip_square(_square, controller.flipped)).map(|_piece| (_square, _piece)))
+            .collect();
+        draw_funcs::draw_small_board(_renderer, &_shared.theme, &pieces, PREVIEW_AT, PREVIEW_SIZE);
+
+        let on_off = |_on: bool| if _on { "On" } else { "Off" };
+        draw_funcs::draw_button(_renderer, COORDINATES_BUTTON, &format!("Coordinates (C): {}", on_off(self.options.coordinates)));
+        draw_funcs::draw_button(_renderer, ARROW_BUTTON, &format!("Last move arrow (A): {}", on_off(self.options.arrow)));
+
+        draw_funcs::draw_button(_renderer, EXPORT_BUTTON, "Export");
+        draw_funcs::draw_button(_renderer, CANCEL_BUTTON, "Cancel");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Key(KeyCode::Escape) => return Transition::Pop,
+            InputEvent::Key(KeyCode::C) => self.options.coordinates = !self.options.coordinates,
+            InputEvent::Key(KeyCode::A) => self.options.arrow = !self.options.arrow,
+            InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) => {
+                self.export(_shared);
+                return Transition::Pop;
+            },
+            InputEvent::Click { x, y } => {
+                if in_rect(COORDINATES_BUTTON, x, y) {
+                    self.options.coordinates = !self.options.coordinates;
+                }
+                else if in_rect(ARROW_BUTTON, x, y) {
+                    self.options.arrow = !self.options.arrow;
+                }
+                else if in_rect(EXPORT_BUTTON, x, y) {
+                    self.export(_shared);
+                    return Transition::Pop;
+                }
+                else if in_rect(CANCEL_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+            },
+            _ => {}
+        }
+
+        Transition::None
+    }
+
+    // Enter exports straight away while nothing is focused
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        vec![COORDINATES_BUTTON, ARROW_BUTTON, EXPORT_BUTTON, CANCEL_BUTTON]
+    }
+}
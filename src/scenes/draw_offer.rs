@@ -0,0 +1,55 @@
+use eliasfl_chess::Color as Colour;
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Transition};
+use super::super::controller::{InputEvent, Command};
+use super::super::graphics_funcs::{renderer::Renderer, widgets::{Button, Dialog, Label}};
+
+const OFFER_PANEL: (f32, f32, f32, f32) = (160.0, 300.0, 400.0, 200.0);
+const ACCEPT_BUTTON: (f32, f32, f32, f32) = (180.0, 420.0, 170.0, 50.0);
+const DECLINE_BUTTON: (f32, f32, f32, f32) = (370.0, 420.0, 170.0, 50.0);
+
+/// Asks the player of the other side if they take the draw offered, see `Command::OfferDraw`. Y accepts, N or Escape declines.
+pub struct DrawOfferScene;
+
+impl DrawOfferScene {
+    /// The question and the answers, true to accept
+    fn dialog(_shared: &Shared) -> Dialog<bool> {
+        let white_asked = _shared.controller.draw_offer == Some(Colour::Black);
+        let question = _shared.messages().fill(&_shared.messages().draw_offered, white_asked, "");
+
+        Dialog::new(Some(OFFER_PANEL))
+            .label(Label::new((OFFER_PANEL.0, OFFER_PANEL.1 + 20.0, OFFER_PANEL.2, 40.0), &question, 24.0, _shared.theme.text))
+            .button(Button::new(ACCEPT_BUTTON, "Accept", true).with_key(KeyCode::Y))
+            .button(Button::new(DECLINE_BUTTON, "Decline", false).with_key(KeyCode::N))
+            .cancel(false)
+    }
+}
+
+impl Scene for DrawOfferScene {
+    fn name(&self) -> &'static str {
+        "draw_offer"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        DrawOfferScene::dialog(_shared).draw(_renderer);
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match DrawOfferScene::dialog(_shared).input(_event) {
+            // An accepted draw ends the game, the game scene shows the result from its next update
+            Some(accepted) => {
+                _shared.controller.notice = _shared.controller.try_execute(Command::AnswerDraw(accepted)).err();
+                Transition::Pop
+            },
+            None => Transition::None
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        DrawOfferScene::dialog(_shared).focusables()
+    }
+}
@@ -0,0 +1,74 @@
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Transition};
+use super::super::clipboard;
+use super::super::controller::{InputEvent, Command};
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer};
+
+/// Longest FEN that can be typed, the longest legal one is under 100 characters
+const MAX_FEN_LENGTH: usize = 100;
+
+/// Size of the text in the box, small enough for most positions to fit on one line
+const FEN_TEXT_SIZE: f32 = 18.0;
+
+/// Shows the position as FEN over the panel, to be copied or replaced by another position to start from
+pub struct FenScene {
+    fen: String, // What is being typed, the current position at first
+    error: Option<String> // Why the last FEN was refused
+}
+
+impl FenScene {
+    /// Creates the box with the current position in it
+    pub fn new(_shared: &Shared) -> FenScene {
+        FenScene { fen: _shared.controller.fen(), error: None }
+    }
+}
+
+impl Scene for FenScene {
+    fn name(&self) -> &'static str {
+        "fen"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+
+        _renderer.draw_text("FEN (Enter: start from it, Insert: paste, Delete: clear, Escape: cancel):", (20.0, 745.0), 18.0, _shared.theme.text);
+        _renderer.draw_rect((20.0, 780.0, 680.0, 40.0), [0.1, 0.1, 0.1, 1.0]);
+
+        // The end being typed stays in sight when the FEN is wider than the box
+        let mut shown = format!("{}_", self.fen);
+        while _renderer.text_size(&shown, FEN_TEXT_SIZE).0 > 660.0 {
+            shown.remove(0);
+        }
+        _renderer.draw_text(&shown, (30.0, 790.0), FEN_TEXT_SIZE, [1.0, 1.0, 1.0, 1.0]);
+
+        if let Some(error) = &self.error {
+            _renderer.draw_text(&format!("Not started: {}", error), (20.0, 835.0), 16.0, [0.97, 0.3, 0.0, 1.0]);
+        }
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Key(KeyCode::Escape) => return Transition::Pop,
+            InputEvent::Key(KeyCode::Back) => { self.fen.pop(); },
+            InputEvent::Key(KeyCode::Delete) => self.fen.clear(),
+            InputEvent::Key(KeyCode::Insert) => match clipboard::paste() {
+                Ok(text) => self.fen = text.trim().chars().take(MAX_FEN_LENGTH).collect(),
+                Err(error) => self.error = Some(format!("nothing pasted, {}", error))
+            },
+            InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) if !self.fen.trim().is_empty() => {
+                match _shared.controller.try_execute(Command::LoadFen(self.fen.trim().to_string())) {
+                    Ok(()) => return Transition::Pop,
+                    Err(error) => self.error = Some(error)
+                }
+            },
+            InputEvent::Text(character) if !character.is_control() && self.fen.chars().count() < MAX_FEN_LENGTH => self.fen.push(character),
+            _ => {}
+        }
+
+        Transition::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,189 @@
+use super::{Scene, Shared, Toast, Transition};
+use super::promotion::PromotionScene;
+use super::game_over::GameOverScene;
+use super::hand_over::HandOverScene;
+use super::save::SaveScene;
+use super::arbiter::ArbiterScene;
+use super::pgn_headers::PgnHeadersScene;
+use super::diagram::DiagramScene;
+use super::spectators::SpectatorsScene;
+use super::fen_entry::FenScene;
+use super::game_menu::GameMenuScene;
+use super::super::controller::{InputEvent, Command};
+use super::super::saves::{SavedGame, QUICK_SAVE};
+use super::super::recent::RecentKind;
+use super::super::pawn_structure::PawnStructure;
+use super::super::clipboard;
+use super::super::consts::TOAST_TIME;
+use ggez::event::KeyCode;
+use super::super::graphics_funcs::{draw_funcs, layers::Layer, renderer::{Renderer, ZoomRenderer}};
+use super::super::help_funcs::in_rect;
+
+// With casual takebacks, at both ends of the line under the status
+const UNDO_BUTTON: (f32, f32, f32, f32) = (165.0, 832.0, 60.0, 28.0);
+const REDO_BUTTON: (f32, f32, f32, f32) = (495.0, 832.0, 60.0, 28.0);
+
+/// The board being played on
+pub struct GameScene;
+
+impl GameScene {
+    /// Draws the marks over the board, moved and zoomed with it
+    fn draw_annotations(_renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_square_marks(_renderer, &_shared.theme, &_shared.controller.marks, _shared.controller.flipped);
+        if let Some(structure) = &_shared.pawn_structure {
+            draw_funcs::draw_pawn_marks(_renderer, &structure.marks, _shared.controller.flipped);
+        }
+    }
+
+    /// Draws the status panel, its buttons and the labels over the board
+    fn draw_hud(_renderer: &mut dyn Renderer, _shared: &Shared) {
+        // A zoomed in board reaches down behind the panel
+        if _shared.controller.view.is_zoomed() {
+            draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+        }
+        if _shared.controller.vote.is_some() {
+            draw_funcs::draw_vote_panel(_renderer, &_shared.theme, &_shared.controller, _shared.turn_indicator, _shared.messages(), _shared.config.reduced_motion);
+        }
+        else {
+            draw_funcs::draw_status_panel(_renderer, &_shared.theme, &_shared.controller, _shared.turn_indicator, _shared.messages(), _shared.config.reduced_motion);
+            draw_funcs::draw_player_identities(_renderer, &_shared.profiles, &_shared.controller, 778.0);
+
+            if _shared.controller.casual_takebacks && !_shared.controller.is_over() {
+                draw_funcs::draw_button(_renderer, UNDO_BUTTON, "Undo");
+                draw_funcs::draw_button(_renderer, REDO_BUTTON, "Redo");
+            }
+        }
+
+        if let Some(square) = _shared.controller.hover.filter(|_| _shared.config.square_labels) {
+            draw_funcs::draw_square_label(_renderer, square);
+        }
+        // The toast of the result takes the corner once the game is over
+        if let Some(run) = _shared.speedrun.filter(|_run| _run.is_running(&_shared.controller) && !_shared.controller.is_over()) {
+            draw_funcs::draw_speedrun_timer(_renderer, run.time(&_shared.controller));
+        }
+    }
+
+    /// The scene to show on top of the game after an input, if the game moved on to a promotion, ended or the turn passed
+    /// ### Parameters
+    /// - `_shared: &mut Shared`: The state after the input
+    /// - `_turn_passed: bool`: If the input finished a move
+    pub fn follow_up(_shared: &mut Shared, _turn_passed: bool) -> Transition {
+        if _shared.controller.promoting {
+            Transition::Push(Box::new(PromotionScene))
+        }
+        else if _shared.controller.is_over() {
+            Transition::Push(Box::new(GameOverScene::new(_shared)))
+        }
+        else if _turn_passed && _shared.hand_over {
+            Transition::Push(Box::new(HandOverScene))
+        }
+        else {
+            Transition::None
+        }
+    }
+}
+
+impl Scene for GameScene {
+    fn name(&self) -> &'static str {
+        "game"
+    }
+
+    fn update(&mut self, _shared: &mut Shared, _dt: f32) -> Transition {
+        // The pawn marks are worked out again after every move
+        if _shared.pawn_structure.as_ref().map_or(false, |_structure| _structure.is_outdated(&_shared.controller)) {
+            _shared.pawn_structure = Some(PawnStructure::new(&_shared.controller));
+        }
+
+        // Bots move from the update loop, their moves can end the game too
+        if _shared.controller.is_over() {
+            Transition::Push(Box::new(GameOverScene::new(_shared)))
+        }
+        else {
+            Transition::None
+        }
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        let view = _shared.controller.view;
+        {
+            let mut board = ZoomRenderer::new(_renderer, view.zoom, view.offset);
+            draw_funcs::draw_board(&mut board, &_shared.theme, &_shared.controller, _shared.board_style());
+            _shared.layers.draw(Layer::Annotations, || GameScene::draw_annotations(&mut board, _shared));
+        }
+
+        _shared.layers.draw(Layer::Hud, || GameScene::draw_hud(_renderer, _shared));
+    }
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            // P shows or hides the pawn structure
+            InputEvent::Key(KeyCode::P) => {
+                _shared.pawn_structure = match _shared.pawn_structure {
+                    Some(_) => None,
+                    None => Some(PawnStructure::new(&_shared.controller))
+                };
+                return Transition::None;
+            },
+            // L shows or hides the name of the square under the cursor
+            InputEvent::Key(KeyCode::L) => {
+                _shared.config.square_labels = !_shared.config.square_labels;
+                _shared.config.unsaved = true;
+                return Transition::None;
+            },
+            // F5 quick saves, F6 saves to a named slot and F9 loads the quick save
+            InputEvent::Key(KeyCode::F5) => {
+                let game = SavedGame::from_controller(QUICK_SAVE, &_shared.controller);
+                _shared.recent.add(RecentKind::Save, game.clone());
+                _shared.saves.save(game);
+                return Transition::None;
+            },
+            // F7 exports the game as PGN, after editing its headers
+            InputEvent::Key(KeyCode::F7) => return Transition::Push(Box::new(PgnHeadersScene::new(_shared))),
+            // S shows who watches through mirror windows, and their chat, when the game is served
+            InputEvent::Key(KeyCode::S) if _shared.spectators.is_some() => return Transition::Push(Box::new(SpectatorsScene)),
+            // Escape drops the selection first, a second one opens the game menu
+            InputEvent::Key(KeyCode::Escape) if _shared.controller.previous_click.is_none() && !_shared.controller.promoting =>
+                return Transition::Push(Box::new(GameMenuScene::new(_shared))),
+            // C copies the position as FEN, F opens it in a box to start from another position
+            InputEvent::Key(KeyCode::C) => {
+                let fen = _shared.controller.fen();
+                _shared.toasts.push(match clipboard::copy(&fen) {
+                    Ok(()) => Toast { title: "Position copied as FEN".to_string(), message: fen, remaining: TOAST_TIME },
+                    Err(error) => Toast { title: "Position not copied".to_string(), message: error, remaining: TOAST_TIME }
+                });
+                return Transition::None;
+            },
+            InputEvent::Key(KeyCode::F) => return Transition::Push(Box::new(FenScene::new(_shared))),
+            // F8 exports the position as an SVG diagram
+            InputEvent::Key(KeyCode::F8) => return Transition::Push(Box::new(DiagramScene::new())),
+            // A opens the arbiter controls, when they are turned on in the settings
+            InputEvent::Key(KeyCode::A) if _shared.config.arbiter => return Transition::Push(Box::new(ArbiterScene)),
+            InputEvent::Key(KeyCode::F6) => return Transition::Push(Box::new(SaveScene::new())),
+            InputEvent::Key(KeyCode::F9) => {
+                let loaded = match _shared.saves.get(QUICK_SAVE).cloned() {
+                    Some(game) => _shared.controller.load_saved(&game).map(|_| _shared.recent.add(RecentKind::Save, game)),
+                    None => Err("nothing was quick saved".to_string())
+                };
+                if let Err(error) = loaded {
+                    _shared.controller.banner = Some(format!("Quick load failed: {}", error));
+                }
+                return Transition::None;
+            },
+            _ => {}
+        }
+
+        let mover = _shared.controller.game.active_colour();
+
+        // The buttons do what Left and Right do, a refused takeback tells why in the status bar
+        let takebacks = _shared.controller.casual_takebacks && _shared.controller.vote.is_none() && !_shared.controller.is_over();
+        match _event {
+            InputEvent::Click { x, y } if takebacks && in_rect(UNDO_BUTTON, x, y) =>
+                _shared.controller.notice = _shared.controller.try_execute(Command::TakeBack).err(),
+            InputEvent::Click { x, y } if takebacks && in_rect(REDO_BUTTON, x, y) =>
+                _shared.controller.notice = _shared.controller.try_execute(Command::Redo).err(),
+            _ => _shared.controller.handle_input(_event)
+        }
+
+        let turn_passed = _shared.controller.game.active_colour() != mover;
+        GameScene::follow_up(_shared, turn_passed)
+    }
+}
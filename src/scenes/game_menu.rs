@@ -0,0 +1,95 @@
+use super::{Scene, Shared, Transition};
+use super::draw_offer::DrawOfferScene;
+use super::super::controller::{InputEvent, Command};
+use super::super::graphics_funcs::{renderer::Renderer, widgets::{Button, Dialog, Label}};
+
+// A column of buttons in the middle of the board, Resign and Offer draw only while a player can still use them
+const MENU_PANEL: (f32, f32, f32, f32) = (210.0, 190.0, 300.0, 420.0);
+const MENU_TOP: f32 = 265.0;
+const MENU_ROW_HEIGHT: f32 = 65.0;
+
+/// What the buttons of the menu do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Choice {
+    Resume,
+    NewGame,
+    Resign,
+    OfferDraw,
+    Quit
+}
+
+/// The menu Escape opens during a game: carry on, start over, resign, offer a draw or quit. The clocks and bots wait while it is open.
+pub struct GameMenuScene;
+
+impl GameMenuScene {
+    /// Opens the menu, pausing the game under it
+    pub fn new(_shared: &mut Shared) -> GameMenuScene {
+        _shared.paused = true;
+        GameMenuScene
+    }
+
+    /// The buttons, with the ones for the player's side left out when bots play both sides or the game is over
+    fn dialog(_shared: &Shared) -> Dialog<Choice> {
+        let playing = _shared.controller.player_side().is_some() && !_shared.controller.is_over();
+        let choices = [
+            (Choice::Resume, "Resume", true),
+            (Choice::NewGame, "New game", true),
+            (Choice::Resign, "Resign", playing),
+            (Choice::OfferDraw, "Offer draw", playing),
+            (Choice::Quit, "Quit", true)
+        ];
+
+        let dialog = Dialog::new(Some(MENU_PANEL))
+            .label(Label::new((MENU_PANEL.0, MENU_PANEL.1 + 15.0, MENU_PANEL.2, 40.0), "Game menu", 30.0, _shared.theme.text))
+            .cancel(Choice::Resume);
+
+        choices.iter().filter(|(_, _, _shown)| *_shown).enumerate().fold(dialog, |_dialog, (_row, (_choice, _label, _))| {
+            _dialog.button(Button::new((240.0, MENU_TOP + _row as f32 * MENU_ROW_HEIGHT, 240.0, 50.0), _label, *_choice))
+        })
+    }
+}
+
+impl Scene for GameMenuScene {
+    fn name(&self) -> &'static str {
+        "game_menu"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        _renderer.draw_rect((0.0, 0.0, 720.0, 870.0), [0.0, 0.0, 0.0, 0.6]);
+        GameMenuScene::dialog(_shared).draw(_renderer);
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        // Whatever is chosen, the game goes on from here or is left
+        let choice = match GameMenuScene::dialog(_shared).input(_event) {
+            Some(choice) => choice,
+            None => return Transition::None
+        };
+        _shared.paused = false;
+
+        let side = _shared.controller.player_side();
+        match (choice, side) {
+            (Choice::NewGame, _) => _shared.controller.execute(Command::Restart),
+            (Choice::Resign, Some(side)) => _shared.controller.notice = _shared.controller.try_execute(Command::Resign(side)).err(),
+            (Choice::OfferDraw, Some(side)) => {
+                _shared.controller.notice = _shared.controller.try_execute(Command::OfferDraw(side)).err();
+                // A player on the other side answers at once, a bot already did
+                if _shared.controller.draw_offer.is_some() {
+                    return Transition::Replace(Box::new(DrawOfferScene));
+                }
+            },
+            (Choice::Quit, _) => return Transition::Quit,
+            _ => {}
+        }
+
+        Transition::Pop
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        GameMenuScene::dialog(_shared).focusables()
+    }
+}
@@ -0,0 +1,215 @@
+use super::{Scene, Shared, Transition};
+use super::main_menu::MainMenuScene;
+use super::analysis::AnalysisScene;
+use super::pgn_headers::PgnHeadersScene;
+use super::super::controller::{InputEvent, Command};
+use super::super::graphics_funcs::{draw_funcs, renderer::{Renderer, ZoomRenderer}, widgets::{Button, Dialog, Label}};
+use super::super::stats::{self, GameStats};
+use super::super::profile::player_name;
+use super::super::consts::TOAST_TIME;
+use super::super::pgn::PgnHeaders;
+use super::super::recent::RecentKind;
+use super::super::saves::SavedGame;
+use super::super::scoresheet::{self, SCORESHEET_FILE};
+use super::Toast;
+use eliasfl_chess::Color as Colour;
+use ggez::event::KeyCode;
+
+/// Where the graph of the clocks is drawn, over the board
+const CLOCK_GRAPH: (f32, f32, f32, f32) = (40.0, 140.0, 640.0, 440.0);
+
+/// Rectangle of a button of the row below the result, which the buttons share evenly
+fn button_slot(_index: usize, _count: usize) -> (f32, f32, f32, f32) {
+    let width = (660.0 - (_count - 1) as f32 * 20.0) / _count as f32;
+    (30.0 + _index as f32 * (width + 20.0), 800.0, width, 50.0)
+}
+
+/// What the buttons of the result do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Choice {
+    Replay,
+    Analyse,
+    Heatmap,
+    Clocks,
+    Menu
+}
+
+/// The result of the game and how both sides played, with the choice to replay, analyse or go back to the menu
+pub struct GameOverScene {
+    stats: GameStats,
+    heatmap: Option<Colour>, // Side whose piece activity is shown over the board, if any
+    clock_graph: bool, // The time both sides had left is plotted over the board
+    headers: Option<PgnHeaders> // Of an online game, agreed on with the opponent and exported as they are
+}
+
+impl GameOverScene {
+    /// Rates the game that just ended and adds it to the profiles of both players and the recent games, and a won speedrun to its leaderboard
+    pub fn new(_shared: &mut Shared) -> GameOverScene {
+        let stats = stats::game_stats(&_shared.controller);
+
+        for _colour in [Colour::White, Colour::Black].iter() {
+            let name = player_name(&_shared.controller, *_colour);
+            _shared.profiles.record(&name, stats.side(*_colour));
+        }
+
+        // Mirror windows show a game played in another window, which lists it itself
+        if !_shared.read_only {
+            let name = format!("{} – {}", player_name(&_shared.controller, Colour::White), player_name(&_shared.controller, Colour::Black));
+            _shared.recent.add(RecentKind::Finished, SavedGame::from_controller(&name, &_shared.controller));
+        }
+
+        if let Some(run) = _shared.speedrun {
+            if let Some(time) = run.result(&_shared.controller) {
+                let seconds = time.seconds;
+                let message = match _shared.profiles.record_speedrun(run.difficulty, time) {
+                    Some(1) => format!("New best on {}!", run.difficulty.label()),
+                    Some(place) => format!("Number {} on {}", place, run.difficulty.label()),
+                    None => format!("Not fast enough for the {} leaderboard", run.difficulty.label())
+                };
+                _shared.toasts.push(Toast { title: format!("Checkmate in {:.1} s", seconds), message, remaining: TOAST_TIME });
+            }
+        }
+
+        GameOverScene { stats, heatmap: None, clock_graph: false, headers: None }
+    }
+
+    /// The result of an online game. Its exports are written with the headers both players know, rather than edited,
+    /// so both export the same game.
+    pub fn online(_shared: &mut Shared, _headers: PgnHeaders) -> GameOverScene {
+        GameOverScene { headers: Some(_headers), ..GameOverScene::new(_shared) }
+    }
+
+    /// Writes the scoresheet of the game for `AppState` to save, with the headers a PGN export starts with
+    fn export_scoresheet(&self, _shared: &mut Shared) {
+        let headers = self.headers.clone().unwrap_or_else(|| PgnHeaders::defaults(&_shared.controller));
+        match scoresheet::to_html(&_shared.controller, &headers) {
+            Ok(html) => {
+                _shared.exports.push((SCORESHEET_FILE.to_string(), html));
+                _shared.toasts.push(Toast {
+                    title: "Scoresheet exported".to_string(),
+                    message: format!("Written to {} in the user data directory, open it in a browser to print", SCORESHEET_FILE.trim_start_matches('/')),
+                    remaining: TOAST_TIME
+                });
+            },
+            // The banner is hidden under the result, so failures are toasted too
+            Err(error) => _shared.toasts.push(Toast { title: "Scoresheet export failed".to_string(), message: error, remaining: TOAST_TIME })
+        }
+    }
+
+    /// The result and the buttons under it, the stats lines with their avatars are drawn around it
+    fn dialog(&self, _shared: &Shared) -> Dialog<Choice> {
+        let result_text = draw_funcs::prepare_status_text(&_shared.controller, _shared.messages());
+        let heatmap = match self.heatmap {
+            None => "Heatmap",
+            Some(Colour::White) => "Rustaceans",
+            Some(Colour::Black) => "Haskellers"
+        };
+
+        let clocks = if self.clock_graph { "Hide clocks" } else { "Clocks" };
+
+        // The clocks only for timed games
+        let mut buttons = vec![("Replay", Choice::Replay), ("Analyse", Choice::Analyse), (heatmap, Choice::Heatmap)];
+        if _shared.controller.time_controls().is_some() {
+            buttons.push((clocks, Choice::Clocks));
+        }
+        buttons.push(("Menu", Choice::Menu));
+
+        let count = buttons.len();
+        buttons.into_iter().enumerate().fold(
+            Dialog::new(None).label(Label::new((0.0, 727.0, 720.0, 40.0), &result_text, 30.0, _shared.theme.text)),
+            |_dialog, (_index, (_label, _choice))| _dialog.button(Button::new(button_slot(_index, count), _label, _choice))
+        )
+    }
+
+    /// One line of stats for a side, i.e. "Rustaceans: 87% accuracy, 4.2 s avg, 31.0 s longest"
+    fn stats_line(&self, _shared: &Shared, _colour: Colour) -> String {
+        let side = self.stats.side(_colour);
+        let accuracy = side.accuracy.map_or("-".to_string(), |_accuracy| format!("{:.0}%", _accuracy));
+
+        format!("{}: {} accuracy, {:.1} s avg, {:.1} s longest",
+            player_name(&_shared.controller, _colour), accuracy, side.average_think, side.longest_think)
+    }
+}
+
+impl Scene for GameOverScene {
+    fn name(&self) -> &'static str {
+        "game_over"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        let controller = &_shared.controller;
+
+        // Under the panel, which a zoomed in board reaches behind
+        if let Some(colour) = self.heatmap {
+            let mut board = ZoomRenderer::new(_renderer, controller.view.zoom, controller.view.offset);
+            draw_funcs::draw_heatmap(&mut board, &stats::heatmap(controller, colour), draw_funcs::side_colour(colour), controller.flipped);
+        }
+        if self.clock_graph {
+            let (white, black) = (stats::clock_history(controller, Colour::White), stats::clock_history(controller, Colour::Black));
+            let start = white.iter().chain(&black).map(|(_, _seconds)| *_seconds).fold(0.0, f32::max);
+            draw_funcs::draw_clock_graph(_renderer, &white, &black, start * stats::TIME_TROUBLE, CLOCK_GRAPH);
+        }
+        draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+
+        // The line of a player with an avatar or flag moves over for it
+        for (_colour, _x) in [(Colour::White, 20.0), (Colour::Black, 370.0)].iter() {
+            let x = match _shared.profiles.identity(&player_name(controller, *_colour)) {
+                Some(identity) => {
+                    draw_funcs::draw_identity(_renderer, identity, (*_x, 768.0, 24.0, 24.0));
+                    *_x + 30.0
+                },
+                None => *_x
+            };
+            _renderer.draw_text(&self.stats_line(_shared, *_colour), (x, 772.0), 14.0, _shared.theme.text);
+        }
+
+        self.dialog(_shared).draw(_renderer);
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Key(KeyCode::F7) => match &self.headers {
+                Some(headers) => {
+                    PgnHeadersScene::export(_shared, headers);
+                    Transition::None
+                },
+                None => Transition::Push(Box::new(PgnHeadersScene::new(_shared)))
+            },
+            // F10 prints, as far as a browser is the printer
+            InputEvent::Key(KeyCode::F10) => {
+                self.export_scoresheet(_shared);
+                Transition::None
+            },
+            _ => match self.dialog(_shared).input(_event) {
+                Some(Choice::Replay) => {
+                    _shared.controller.execute(Command::Restart);
+                    Transition::Pop
+                },
+                Some(Choice::Analyse) => Transition::Push(Box::new(AnalysisScene::new(&_shared.controller))),
+                Some(Choice::Heatmap) => {
+                    // Off, then each side in turn
+                    self.heatmap = match self.heatmap {
+                        None => Some(Colour::White),
+                        Some(Colour::White) => Some(Colour::Black),
+                        Some(Colour::Black) => None
+                    };
+                    Transition::None
+                },
+                Some(Choice::Clocks) => {
+                    self.clock_graph = !self.clock_graph;
+                    Transition::None
+                },
+                Some(Choice::Menu) => Transition::Reset(Box::new(MainMenuScene)),
+                None => Transition::None
+            }
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        self.dialog(_shared).focusables()
+    }
+}
@@ -0,0 +1,38 @@
+use eliasfl_chess::Color as Colour;
+use super::{Scene, Shared, Transition};
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer};
+use super::super::help_funcs::in_rect;
+
+const READY_BUTTON: (f32, f32, f32, f32) = (230.0, 460.0, 260.0, 60.0);
+
+/// Hides the board between turns of a hot-seat game until the next player is ready
+pub struct HandOverScene;
+
+impl Scene for HandOverScene {
+    fn name(&self) -> &'static str {
+        "hand_over"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        let next = if _shared.controller.game.active_colour() == Colour::Black { "Haskellers" } else { "Rustaceans" };
+        let message = format!("Hand over to the {}", next);
+
+        draw_funcs::draw_title(_renderer, &message, 300.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        draw_funcs::draw_button(_renderer, READY_BUTTON, "Ready");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Click { x, y } if in_rect(READY_BUTTON, x, y) => Transition::Pop,
+            _ => Transition::None
+        }
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        vec![READY_BUTTON]
+    }
+}
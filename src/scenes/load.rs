@@ -0,0 +1,105 @@
+use super::{Scene, Shared, Transition};
+use super::game::GameScene;
+use super::database::DatabaseScene;
+use super::super::controller::InputEvent;
+use super::super::recent::RecentKind;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer, text_layout::{TextLayout, Align}};
+use super::super::help_funcs::{in_rect, format_timestamp};
+
+// One row per save slot: a preview of the board, the name, the time and the number of moves
+const FIRST_ROW: f32 = 110.0;
+const ROW_HEIGHT: f32 = 105.0;
+const VISIBLE_ROWS: usize = 6;
+const THUMBNAIL_SIZE: f32 = 96.0;
+const DATABASE_BUTTON: (f32, f32, f32, f32) = (80.0, 770.0, 260.0, 60.0);
+const BACK_BUTTON: (f32, f32, f32, f32) = (380.0, 770.0, 260.0, 60.0);
+
+/// The saved games, newest first. Clicking one carries on playing it, the database button browses the PGN database.
+pub struct LoadScene {
+    first: usize // Index of the slot on the top row, the list scrolls with the mouse wheel
+}
+
+impl LoadScene {
+    pub fn new() -> LoadScene {
+        LoadScene { first: 0 }
+    }
+
+    /// Rectangle of a row on screen, by its position in the visible part of the list
+    fn row(_index: usize) -> (f32, f32, f32, f32) {
+        (60.0, FIRST_ROW + _index as f32 * ROW_HEIGHT, 600.0, ROW_HEIGHT - 5.0)
+    }
+}
+
+impl Scene for LoadScene {
+    fn name(&self) -> &'static str {
+        "load"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Load game", 50.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        if _shared.saves.games.is_empty() {
+            TextLayout::new().fragment("No saved games yet. F5 saves quickly, F6 to a named slot.", 20.0, [0.7, 0.7, 0.7, 1.0])
+                .align(Align::Centre)
+                .draw(_renderer, (100.0, FIRST_ROW + 20.0, 520.0, 60.0));
+        }
+
+        for (_index, _game) in _shared.saves.games.iter().skip(self.first).take(VISIBLE_ROWS).enumerate() {
+            let (x, y, width, height) = LoadScene::row(_index);
+            _renderer.draw_rect((x, y, width, height), [0.22, 0.22, 0.22, 1.0]);
+
+            draw_funcs::draw_thumbnail(_renderer, &_shared.theme, &_game.fen, (x + 2.0, y + 2.0), THUMBNAIL_SIZE);
+
+            _renderer.draw_text(&_game.name, (x + THUMBNAIL_SIZE + 20.0, y + 15.0), 26.0, [1.0, 1.0, 1.0, 1.0]);
+            _renderer.draw_text(&format!("{}  -  {} moves", format_timestamp(_game.saved), _game.moves.len()),
+                (x + THUMBNAIL_SIZE + 20.0, y + 55.0), 18.0, [0.7, 0.7, 0.7, 1.0]);
+        }
+
+        draw_funcs::draw_button(_renderer, DATABASE_BUTTON, "Database");
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Scroll { y } => {
+                let last_first = _shared.saves.games.len().saturating_sub(VISIBLE_ROWS);
+                self.first = if y > 0.0 { self.first.saturating_sub(1) } else { (self.first + 1).min(last_first) };
+                Transition::None
+            },
+            InputEvent::Click { x, y } => {
+                if in_rect(BACK_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+                if in_rect(DATABASE_BUTTON, x, y) {
+                    return Transition::Push(Box::new(DatabaseScene::new(_shared)));
+                }
+
+                let visible = _shared.saves.games.len().saturating_sub(self.first).min(VISIBLE_ROWS);
+                let clicked = (0..visible).find(|_index| in_rect(LoadScene::row(*_index), x, y));
+
+                match clicked.and_then(|_index| _shared.saves.games.get(self.first + _index).cloned()) {
+                    Some(game) => match _shared.controller.load_saved(&game) {
+                        Ok(()) => {
+                            _shared.recent.add(RecentKind::Save, game);
+                            Transition::Reset(Box::new(GameScene))
+                        },
+                        Err(error) => {
+                            _shared.controller.banner = Some(format!("{} could not be loaded: {}", game.name, error));
+                            Transition::None
+                        }
+                    },
+                    None => Transition::None
+                }
+            },
+            _ => Transition::None
+        }
+    }
+
+    // The visible slots, then the buttons
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        let visible = _shared.saves.games.len().saturating_sub(self.first).min(VISIBLE_ROWS);
+        (0..visible).map(LoadScene::row).chain(vec![DATABASE_BUTTON, BACK_BUTTON]).collect()
+    }
+}
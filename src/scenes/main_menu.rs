@@ -0,0 +1,174 @@
+use super::{Scene, Shared, Transition};
+use super::setup::SetupScene;
+use super::settings::SettingsScene;
+use super::analysis::AnalysisScene;
+use super::achievements::AchievementsScene;
+use super::load::LoadScene;
+use super::speedrun::SpeedrunScene;
+use super::practice::PracticeScene;
+use super::online::OnlineScene;
+use super::game::GameScene;
+use super::super::controller::InputEvent;
+use super::super::recent::RecentKind;
+use super::super::saves::AUTO_SAVE;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer, text_layout::TextLayout, widgets::{Button, Regions}};
+use super::super::help_funcs::{in_rect, format_timestamp};
+
+// Buttons of the menu on the left, one under the other, the recent games on the right
+const MENU_TOP: f32 = 220.0;
+const MENU_ROW_HEIGHT: f32 = 58.0;
+
+// One row per recent game: a preview of the board, the name, and how and when it came onto the list
+const RECENT_TOP: f32 = 250.0;
+const RECENT_ROW_HEIGHT: f32 = 48.0;
+const RECENT_THUMBNAIL_SIZE: f32 = 44.0;
+
+/// Rectangle of a row of the recent games
+fn recent_row(_index: usize) -> (f32, f32, f32, f32) {
+    (330.0, RECENT_TOP + _index as f32 * RECENT_ROW_HEIGHT, 350.0, RECENT_ROW_HEIGHT - 4.0)
+}
+
+/// What the buttons of the menu do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Choice {
+    Continue,
+    Play,
+    Online,
+    Speedrun,
+    Practice,
+    Load,
+    Analysis,
+    Settings,
+    Achievements,
+    Quit
+}
+
+/// The buttons of the menu, Continue first when a game was left going when the window closed
+fn buttons(_shared: &Shared) -> Vec<Button<Choice>> {
+    let continued = _shared.saves.get(AUTO_SAVE).map(|_game| (Choice::Continue, "Continue last game"));
+    let choices = continued.into_iter().chain(vec![
+        (Choice::Play, "Play"), (Choice::Online, "Play online"), (Choice::Speedrun, "Speedrun"), (Choice::Practice, "Practice"),
+        (Choice::Load, "Load game"), (Choice::Analysis, "Analysis"), (Choice::Settings, "Settings"), (Choice::Achievements, "Achievements"),
+        (Choice::Quit, "Quit")
+    ]);
+
+    choices.enumerate()
+        .map(|(_index, (_choice, _label))| Button::new((40.0, MENU_TOP + _index as f32 * MENU_ROW_HEIGHT, 260.0, 50.0), _label, _choice))
+        .collect()
+}
+
+/// Carries on the game kept when the window closed. The slot is emptied, the game is saved there again if the window
+/// closes before it is over.
+fn continue_game(_shared: &mut Shared) -> Transition {
+    let game = match _shared.saves.delete(AUTO_SAVE) {
+        Some(game) => game,
+        None => return Transition::None
+    };
+
+    match _shared.controller.load_saved(&game) {
+        Ok(()) => Transition::Reset(Box::new(GameScene)),
+        Err(error) => {
+            _shared.controller.banner = Some(format!("The last game could not be continued: {}", error));
+            Transition::None
+        }
+    }
+}
+
+/// ## `reopen`
+/// Reopens a recent game: saves to carry on playing, imported and finished games in the analysis
+/// ### Parameters
+/// - `_shared: &mut Shared`: The shared state, the game is loaded into its controller
+/// - `_index: usize`: Position of the game on the list
+fn reopen(_shared: &mut Shared, _index: usize) -> Transition {
+    let recent = match _shared.recent.games.get(_index).cloned() {
+        Some(recent) => recent,
+        None => return Transition::None
+    };
+
+    let result = match recent.kind {
+        // The slot as it is now, the copy on the list when it was deleted since
+        RecentKind::Save => {
+            let game = _shared.saves.get(&recent.game.name).cloned().unwrap_or_else(|| recent.game.clone());
+            _shared.controller.load_saved(&game).map(|_| Transition::Reset(Box::new(GameScene)))
+        },
+        RecentKind::Import | RecentKind::Finished => {
+            _shared.review(&recent.game).map(|_| Transition::Push(Box::new(AnalysisScene::new(&_shared.controller))))
+        }
+    };
+
+    match result {
+        // Back to the top of the list, finished games would otherwise show twice
+        Ok(transition) => {
+            _shared.recent.games.remove(_index);
+            _shared.recent.add(recent.kind, recent.game);
+            transition
+        },
+        Err(error) => {
+            _shared.controller.banner = Some(format!("{} could not be opened: {}", recent.game.name, error));
+            Transition::None
+        }
+    }
+}
+
+/// The first screen: continue the last game, start or load a game, try a speedrun, practice, analyse, change settings, look at the achievements
+/// or quit, and the recent games to reopen with one click
+pub struct MainMenuScene;
+
+impl Scene for MainMenuScene {
+    fn name(&self) -> &'static str {
+        "main_menu"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Schack", 150.0, 60.0, [0.97, 0.3, 0.0, 1.0]);
+
+        for _button in buttons(_shared) {
+            _button.draw(_renderer);
+        }
+
+        _renderer.draw_text("Recent games", (330.0, RECENT_TOP - 32.0), 24.0, [1.0, 1.0, 1.0, 1.0]);
+        if _shared.recent.games.is_empty() {
+            TextLayout::new().fragment("Games you save, load, import or finish show up here", 18.0, [0.7, 0.7, 0.7, 1.0])
+                .draw(_renderer, (330.0, RECENT_TOP, 350.0, 60.0));
+        }
+        for (_index, _recent) in _shared.recent.games.iter().enumerate() {
+            let (x, y, width, height) = recent_row(_index);
+            _renderer.draw_rect((x, y, width, height), [0.22, 0.22, 0.22, 1.0]);
+
+            draw_funcs::draw_thumbnail(_renderer, &_shared.theme, &_recent.game.fen, (x, y), RECENT_THUMBNAIL_SIZE);
+            _renderer.draw_text(&_recent.game.name, (x + RECENT_THUMBNAIL_SIZE + 10.0, y + 3.0), 18.0, [1.0, 1.0, 1.0, 1.0]);
+            _renderer.draw_text(&format!("{} {}  -  {} moves", _recent.kind.label(), format_timestamp(_recent.game.saved), _recent.game.moves.len()),
+                (x + RECENT_THUMBNAIL_SIZE + 10.0, y + 25.0), 14.0, [0.7, 0.7, 0.7, 1.0]);
+        }
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Click { x, y } => match Regions::of(&buttons(_shared)).hit(x, y) {
+                Some(Choice::Continue) => continue_game(_shared),
+                Some(Choice::Play) => Transition::Push(Box::new(SetupScene::new(_shared))),
+                Some(Choice::Online) => Transition::Push(Box::new(OnlineScene::new())),
+                Some(Choice::Speedrun) => Transition::Push(Box::new(SpeedrunScene::new(_shared))),
+                Some(Choice::Practice) => Transition::Reset(Box::new(PracticeScene::new(_shared))),
+                Some(Choice::Load) => Transition::Push(Box::new(LoadScene::new())),
+                Some(Choice::Analysis) => Transition::Push(Box::new(AnalysisScene::new(&_shared.controller))),
+                Some(Choice::Settings) => Transition::Push(Box::new(SettingsScene)),
+                Some(Choice::Achievements) => Transition::Push(Box::new(AchievementsScene)),
+                Some(Choice::Quit) => Transition::Quit,
+                None => match (0.._shared.recent.games.len()).find(|_index| in_rect(recent_row(*_index), x, y)) {
+                    Some(index) => reopen(_shared, index),
+                    None => Transition::None
+                }
+            },
+            _ => Transition::None
+        }
+    }
+
+    // The buttons, then the recent games
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        let buttons = Regions::of(&buttons(_shared)).rects();
+        buttons.into_iter().chain((0.._shared.recent.games.len()).map(recent_row)).collect()
+    }
+}
@@ -0,0 +1,240 @@
+use eliasfl_chess::Color as Colour;
+use ggez::event::KeyCode;
+use std::rc::Rc;
+use super::{Scene, Shared, Toast, Transition};
+use super::super::bot::parse_uci;
+use super::super::consts::{BOT_MIN_DELAY, TOAST_TIME};
+use super::super::controller::{InputEvent, Command};
+use super::super::graphics_funcs::{draw_funcs, renderer::{Renderer, ZoomRenderer}, text_layout::TextLayout, widgets::{Button, Regions}};
+use super::super::help_funcs::in_rect;
+use super::super::mate_patterns::PATTERNS;
+
+// One row per pattern, its button with what the pattern is under it
+const FIRST_ROW: f32 = 190.0;
+const ROW_HEIGHT: f32 = 130.0;
+const BACK_BUTTON: (f32, f32, f32, f32) = (230.0, 770.0, 260.0, 60.0);
+
+/// The buttons of the patterns, by their index in `PATTERNS`
+fn pattern_buttons(_solved: &[bool]) -> Vec<Button<usize>> {
+    PATTERNS.iter().enumerate().map(|(_index, _pattern)| {
+        let label = if _solved[_index] { format!("{} (done)", _pattern.name) } else { _pattern.name.to_string() };
+        Button::new((110.0, FIRST_ROW + _index as f32 * ROW_HEIGHT, 500.0, 55.0), &label, _index)
+    }).collect()
+}
+
+/// A pattern being practised on the board
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Drill {
+    index: usize, // Of the pattern in `PATTERNS`
+    checked: usize, // Moves of the game already held against the line
+    reply: Option<f32>, // Seconds until Black's reply is played, while it is held back as a bot's would be
+    hint: bool, // The hint for the player's next move is shown
+    solved: bool
+}
+
+/// A catalog of named checkmates to practise. The position of a pattern is set up on the board, the player finds
+/// White's moves and Black answers along the line of the pattern. A move off the line is taken back and the hint for
+/// it shown. Opened from practice with M.
+pub struct MatePatternsScene {
+    drill: Option<Drill>, // None while choosing a pattern
+    solved: Vec<bool> // Patterns mated since the catalog was opened, by their index
+}
+
+impl MatePatternsScene {
+    /// The catalog, no pattern done yet
+    pub fn new() -> MatePatternsScene {
+        MatePatternsScene { drill: None, solved: vec![false; PATTERNS.len()] }
+    }
+
+    /// ## `start`
+    /// Sets a pattern up on the board, seen from White's side with both sides played by clicking
+    /// ### Parameters
+    /// - `_shared: &mut Shared`: The shared state, the position is loaded into its controller
+    /// - `_index: usize`: Of the pattern in `PATTERNS`
+    fn start(&mut self, _shared: &mut Shared, _index: usize) {
+        _shared.controller.set_bot(Colour::White, None);
+        _shared.controller.set_bot(Colour::Black, None);
+        _shared.controller.vote = None;
+        _shared.controller.variant = Rc::clone(&_shared.variants[0]);
+        _shared.controller.flipped = false;
+        _shared.speedrun = None;
+
+        match _shared.controller.try_execute(Command::LoadFen(PATTERNS[_index].fen.to_string())) {
+            Ok(()) => self.drill = Some(Drill { index: _index, checked: 0, reply: None, hint: false, solved: false }),
+            Err(error) => _shared.controller.banner = Some(format!("{} not set up: {}", PATTERNS[_index].name, error))
+        }
+    }
+
+    /// ## `follow_line`
+    /// Holds the player's moves against the line of the pattern and plays Black's replies, while practising
+    /// ### Parameters
+    /// - `_shared: &mut Shared`: The shared state, with the game of the pattern
+    /// - `_dt: f32`: Seconds since the last update
+    fn follow_line(&mut self, _shared: &mut Shared, _dt: f32) {
+        let drill = match self.drill.as_mut() {
+            Some(drill) => drill,
+            None => return
+        };
+        let pattern = &PATTERNS[drill.index];
+
+        if let Some(remaining) = drill.reply.as_mut() {
+            *remaining -= _dt;
+            if *remaining <= 0.0 {
+                drill.reply = None;
+                let reply = pattern.expected(_shared.controller.history.len()).and_then(parse_uci);
+                if let Some((from, to, promotion)) = reply {
+                    _shared.controller.notice = _shared.controller.try_execute(Command::PlayMove(from, to, promotion)).err();
+                }
+                drill.checked = _shared.controller.history.len();
+            }
+            return;
+        }
+
+        let ply = match _shared.controller.history.len() {
+            length if length > drill.checked => length - 1,
+            _ => return
+        };
+        let played = _shared.controller.history[ply].uci();
+
+        if pattern.expected(ply) == Some(played.as_str()) {
+            drill.checked = ply + 1;
+            drill.hint = false;
+
+            if drill.checked == pattern.line.len() {
+                drill.solved = true;
+                self.solved[drill.index] = true;
+                _shared.toasts.push(Toast { title: "Checkmate!".to_string(), message: format!("{} done", pattern.name), remaining: TOAST_TIME });
+            }
+            else {
+                drill.reply = Some(BOT_MIN_DELAY);
+            }
+        }
+        // Moves off the line are taken back at once, the hint tells what the pattern wants
+        else {
+            _shared.controller.try_execute(Command::Undo).ok();
+            _shared.controller.notice = Some(format!("{} is off the line of the pattern, try again", played));
+            drill.checked = _shared.controller.history.len();
+            drill.hint = true;
+        }
+    }
+
+    /// What the line under the board says while practising
+    fn drill_line(_drill: &Drill, _shared: &Shared) -> String {
+        let pattern = &PATTERNS[_drill.index];
+        if _drill.solved {
+            format!("{} done!   N: next pattern   R: again   Escape: patterns", pattern.name)
+        }
+        else if _drill.hint {
+            pattern.hint(_shared.controller.history.len()).to_string()
+        }
+        else {
+            format!("{}: White mates in {}   H: hint   R: start over   Escape: patterns", pattern.name, pattern.moves())
+        }
+    }
+
+    /// Handles input while practising a pattern
+    fn drill_input(&mut self, _shared: &mut Shared, _event: InputEvent) {
+        let drill = match self.drill {
+            Some(drill) => drill,
+            None => return
+        };
+
+        match _event {
+            // Escape drops the selection first, a second one goes back to the patterns
+            InputEvent::Key(KeyCode::Escape) if _shared.controller.previous_click.is_none() => self.drill = None,
+            InputEvent::Key(KeyCode::H) => self.drill = Some(Drill { hint: true, ..drill }),
+            InputEvent::Key(KeyCode::R) => self.start(_shared, drill.index),
+            InputEvent::Key(KeyCode::N) if drill.solved => self.start(_shared, (drill.index + 1) % PATTERNS.len()),
+            // Only White is moved by the player, and not while Black's reply is on its way or once it is mate
+            InputEvent::Click { .. } | InputEvent::Press { .. }
+                if drill.solved || drill.reply.is_some() || _shared.controller.game.active_colour() != Colour::White => {},
+            // Takebacks and restarts would leave the line
+            InputEvent::Key(KeyCode::Escape) => { _shared.controller.handle_input(_event); },
+            InputEvent::Key(_) | InputEvent::Text(_) => {},
+            _ => { _shared.controller.handle_input(_event); }
+        }
+    }
+}
+
+impl Scene for MatePatternsScene {
+    fn name(&self) -> &'static str {
+        "mate_patterns"
+    }
+
+    fn update(&mut self, _shared: &mut Shared, _dt: f32) -> Transition {
+        self.follow_line(_shared, _dt);
+        Transition::None
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        if let Some(drill) = &self.drill {
+            let view = _shared.controller.view;
+            draw_funcs::draw_board(&mut ZoomRenderer::new(_renderer, view.zoom, view.offset), &_shared.theme, &_shared.controller, _shared.board_style());
+            if view.is_zoomed() {
+                draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+            }
+            draw_funcs::draw_status_panel(_renderer, &_shared.theme, &_shared.controller, _shared.turn_indicator, _shared.messages(), _shared.config.reduced_motion);
+
+            // The notice of a move taken back takes the line when there is one
+            if _shared.controller.notice.is_none() {
+                TextLayout::centred(&MatePatternsScene::drill_line(drill, _shared), 14.0, _shared.theme.text)
+                    .draw(_renderer, (20.0, 835.0, 680.0, 25.0));
+            }
+            return;
+        }
+
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+        draw_funcs::draw_title(_renderer, "Mate patterns", 120.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        for (_index, _button) in pattern_buttons(&self.solved).iter().enumerate() {
+            _button.draw(_renderer);
+
+            let pattern = &PATTERNS[_index];
+            let (x, y, width, height) = _button.geometry;
+            TextLayout::centred(&format!("{}, mate in {}", pattern.description, pattern.moves()), 18.0, [0.7, 0.7, 0.7, 1.0])
+                .draw(_renderer, (x - 50.0, y + height + 5.0, width + 100.0, 50.0));
+        }
+
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        if self.drill.is_some() {
+            // The controller knows where the choices are, it ignores everything else while promoting
+            if _shared.controller.promoting {
+                _shared.controller.handle_input(_event);
+            }
+            else {
+                self.drill_input(_shared, _event);
+            }
+            return Transition::None;
+        }
+
+        match _event {
+            InputEvent::Key(KeyCode::Escape) => Transition::Pop,
+            InputEvent::Click { x, y } => {
+                if let Some(index) = Regions::of(&pattern_buttons(&self.solved)).hit(x, y) {
+                    self.start(_shared, index);
+                }
+                else if in_rect(BACK_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+                Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+
+    // The promotion panel while practising, the patterns and Back while choosing
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        match self.drill {
+            Some(_) if _shared.controller.promoting => _shared.controller.regions().rects(),
+            Some(_) => vec![],
+            None => {
+                let mut rects = Regions::of(&pattern_buttons(&self.solved)).rects();
+                rects.push(BACK_BUTTON);
+                rects
+            }
+        }
+    }
+}
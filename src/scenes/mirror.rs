@@ -0,0 +1,294 @@
+use eliasfl_chess::Color as Colour;
+use super::{Scene, Shared, Transition};
+use super::super::clock;
+use super::super::controller::InputEvent;
+use super::super::fen::position_hash;
+use super::super::overlay_server::{self, ChatLine, SyncState};
+use super::super::saves::SavedGame;
+use super::super::tasks::Task;
+use super::super::graphics_funcs::{draw_funcs, renderer::{Renderer, ZoomRenderer}};
+use super::super::consts::{MIRROR_POLL, MAX_CHAT_LENGTH};
+use ggez::event::KeyCode;
+use serde::Deserialize;
+use std::time::Instant;
+
+/// `--mirror <port>` mode: follows the game of another window on the same machine, served with `--overlay-port <port>`, without touching it.
+/// Meant for projecting a game to a classroom while the players use their own window.
+///
+/// Every `MIRROR_POLL` seconds the last move is fetched from `/sync` with the hash of the position it leads to. The
+/// move is played here and the hashes compared; when they differ, or moves were missed or taken back, the whole move
+/// list is fetched from `/game` and the game rebuilt from it.
+/// The other window keeps the time: the clocks here tick on between fetches and are brought back to its clocks at
+/// each one, see `clock::synced`. F turns the board around and the wheel zooms, for this window only.
+///
+/// The mirror joins as a spectator under a name, and reads and writes the spectator chat with the other mirrors, see
+/// `Spectators`. Enter starts a line and sends it.
+pub struct MirrorScene {
+    port: u16,
+    name: String, // The spectator name this window watches under
+    token: Option<String>, // Given by the other window when this one joined as a spectator, None until then
+    joining: Option<Task<Result<String, String>>>, // The request to join under way
+    chat: Vec<ChatLine>, // The spectator chat since this window joined
+    chat_next: usize, // Where the next look at the chat starts
+    chat_fetch: Option<Task<Result<String, String>>>, // The look at the chat under way
+    typing: Option<String>, // The line of chat being typed, None when not typing
+    fetch: Option<Task<(Result<String, String>, f32)>>, // The request under way, the answer comes with the seconds it took
+    fetching_game: bool, // If the request under way is for the whole game rather than the last move
+    since_fetch: f32, // Seconds since the last request was sent
+    resync: bool, // The whole game is fetched next, as nothing was fetched yet or the positions differ
+    lost: bool // If the last request failed, the banner says so
+}
+
+/// The answer to joining as a spectator, see `OverlayServer`
+#[derive(Deserialize)]
+struct JoinAnswer {
+    token: String
+}
+
+/// A look at the chat, see `OverlayServer`
+#[derive(Deserialize)]
+struct ChatAnswer {
+    next: usize,
+    lines: Vec<ChatLine>
+}
+
+/// ## `sync_clocks`
+/// Brings the clocks up to those of the other window
+/// ### Parameters
+/// - `_clocks: Option<(f32, f32)>`: Seconds left for white and black in the other window, None for untimed games
+/// - `_game_time: f32`: Seconds into the game in the other window
+/// - `_delay: f32`: Seconds the answer was on its way, the clocks of the other window ran on meanwhile
+/// - `_changed: bool`: If a move was just played, its clocks are taken without easing
+fn sync_clocks(_shared: &mut Shared, _clocks: Option<(f32, f32)>, _game_time: f32, _delay: f32, _changed: bool) {
+    let running = !_shared.controller.is_over();
+    let to_move = _shared.controller.game.active_colour();
+
+    if let Some((white, black)) = _clocks {
+        _shared.controller.game_time = if running { _game_time + _delay } else { _game_time };
+        for (_colour, _clock) in _shared.controller.clocks.iter_mut() {
+            let host = if *_colour == Colour::White { white } else { black };
+            let ticking = running && *_colour == to_move;
+
+            _clock.set_remaining(if _changed {
+                // A new move is a new start, its clocks are taken from the other window without easing
+                if ticking { host - _delay } else { host }
+            } else {
+                clock::synced(_clock.remaining(), host, _delay, ticking)
+            });
+        }
+    }
+}
+
+impl MirrorScene {
+    /// Follows the game served on a port, as the spectator of the name
+    pub fn new(_port: u16, _name: &str) -> MirrorScene {
+        MirrorScene {
+            port: _port,
+            name: overlay_server::spectator_name(_name),
+            token: None,
+            joining: None,
+            chat: vec![],
+            chat_next: 0,
+            chat_fetch: None,
+            typing: None,
+            fetch: None,
+            fetching_game: false,
+            since_fetch: MIRROR_POLL,
+            resync: true,
+            lost: false
+        }
+    }
+
+    /// Sends a line to the spectator chat, it comes back with the next look at the chat
+    fn send(&self, _shared: &mut Shared, _text: String) {
+        let token = match &self.token {
+            Some(token) => token,
+            None => {
+                _shared.controller.banner = Some("Not sent: not joined as a spectator yet".to_string());
+                return;
+            }
+        };
+
+        let (port, path) = (self.port, format!("/chat?token={}", token));
+        _shared.tasks.spawn(move || overlay_server::post(port, &path, &_text), |_shared: &mut Shared, _sent| {
+            if let Err(error) = _sent.and_then(|_answer| _answer) {
+                _shared.controller.banner = Some(format!("Not sent: {}", error));
+            }
+        });
+    }
+
+    /// Adds the new lines of a look at the chat
+    fn read_chat(&mut self, _answer: Result<String, String>) {
+        if let Ok(answer) = _answer.and_then(|_text| serde_json::from_str::<ChatAnswer>(&_text).map_err(|_error| _error.to_string())) {
+            self.chat.extend(answer.lines);
+            self.chat_next = answer.next;
+        }
+    }
+
+    /// Fetches the whole game at the next update, instead of waiting for the next look
+    fn request_resync(&mut self) {
+        self.resync = true;
+        self.since_fetch = MIRROR_POLL;
+    }
+
+    /// ## `show_game`
+    /// Rebuilds the board from the whole game of the other window
+    /// ### Parameters
+    /// - `_game: SavedGame`: The game as the other window has it
+    /// - `_delay: f32`: Seconds the answer was on its way
+    fn show_game(&mut self, _shared: &mut Shared, _game: SavedGame, _delay: f32) -> Result<(), String> {
+        _shared.controller.load_saved(&_game)?;
+
+        if position_hash(&_shared.controller.fen()) != position_hash(&_game.fen) {
+            return Err("the moves of the game there lead to another position here".to_string());
+        }
+
+        sync_clocks(_shared, _game.clocks, _game.game_time, _delay, true);
+        self.resync = false;
+        Ok(())
+    }
+
+    /// ## `follow`
+    /// Plays the last move of the other window, if it is the next one here, and checks both windows are at the same
+    /// position. When they are not, the whole game is fetched next.
+    /// ### Parameters
+    /// - `_state: SyncState`: The last move and clocks of the other window
+    /// - `_delay: f32`: Seconds the answer was on its way
+    fn follow(&mut self, _shared: &mut Shared, _state: SyncState, _delay: f32) {
+        let ply = _shared.controller.history.len();
+        let changed = match &_state.last_move {
+            Some(last) if _state.ply == ply + 1 => _shared.controller.play_saved_move(last).is_ok(),
+            _ => false
+        };
+
+        if _state.ply != _shared.controller.history.len() || _state.hash != position_hash(&_shared.controller.fen()) {
+            self.request_resync();
+            return;
+        }
+
+        sync_clocks(_shared, _state.clocks, _state.game_time, _delay, changed);
+    }
+}
+
+impl Scene for MirrorScene {
+    fn name(&self) -> &'static str {
+        "mirror"
+    }
+
+    fn update(&mut self, _shared: &mut Shared, _dt: f32) -> Transition {
+        self.since_fetch += _dt;
+
+        // Joining as a spectator is tried again until the other window answers
+        if let Some(answer) = self.joining.as_ref().and_then(|_joining| _joining.poll()) {
+            self.joining = None;
+            self.token = answer.and_then(|_answer| _answer)
+                .and_then(|_text| serde_json::from_str::<JoinAnswer>(&_text).map_err(|_error| _error.to_string()))
+                .map(|_answer| _answer.token)
+                .ok();
+        }
+
+        if self.fetch.is_none() && self.since_fetch >= MIRROR_POLL {
+            let port = self.port;
+            if self.token.is_none() && self.joining.is_none() {
+                let path = format!("/join?name={}", self.name);
+                self.joining = Some(Task::spawn(move || overlay_server::post(port, &path, "")));
+            }
+
+            let token = self.token.clone().unwrap_or_default();
+            let path = format!("{}?token={}", if self.resync { "/game" } else { "/sync" }, token);
+            self.fetch = Some(Task::spawn(move || {
+                let sent = Instant::now();
+                let answer = overlay_server::fetch(port, &path);
+                (answer, sent.elapsed().as_secs_f32() / 2.0)
+            }));
+            self.fetching_game = self.resync;
+            self.since_fetch = 0.0;
+
+            // The chat is looked at as often as the game
+            if self.chat_fetch.is_none() {
+                let path = format!("/chat?since={}&token={}", self.chat_next, token);
+                self.chat_fetch = Some(Task::spawn(move || overlay_server::fetch(port, &path)));
+            }
+        }
+
+        if let Some(answer) = self.chat_fetch.as_ref().and_then(|_fetch| _fetch.poll()) {
+            self.chat_fetch = None;
+            self.read_chat(answer.and_then(|_answer| _answer));
+        }
+
+        let (fetched, delay) = match self.fetch.as_ref().and_then(|_fetch| _fetch.poll()) {
+            Some(Ok((answer, delay))) => (answer, delay),
+            Some(Err(error)) => (Err(error), 0.0),
+            None => return Transition::None
+        };
+        self.fetch = None;
+
+        let shown = if self.fetching_game {
+            fetched.and_then(|_text| SavedGame::from_json(&_text)).and_then(|_game| self.show_game(_shared, _game, delay))
+        } else {
+            fetched.and_then(|_text| serde_json::from_str::<SyncState>(&_text).map_err(|_error| _error.to_string()))
+                .map(|_state| self.follow(_shared, _state, delay))
+        };
+
+        match shown {
+            Ok(()) => {
+                if self.lost {
+                    _shared.controller.banner = None;
+                    self.lost = false;
+                }
+            },
+            Err(error) => {
+                _shared.controller.banner = Some(format!("Nothing to mirror on port {}: {}", self.port, error));
+                self.lost = true;
+            }
+        }
+
+        Transition::None
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        let view = _shared.controller.view;
+        draw_funcs::draw_board(&mut ZoomRenderer::new(_renderer, view.zoom, view.offset), &_shared.theme, &_shared.controller, _shared.board_style());
+
+        if view.is_zoomed() {
+            draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+        }
+        draw_funcs::draw_status_panel(_renderer, &_shared.theme, &_shared.controller, _shared.turn_indicator, _shared.messages(), _shared.config.reduced_motion);
+
+        // Over the bottom of the board, only once there is something to read
+        if !self.chat.is_empty() || self.typing.is_some() {
+            draw_funcs::draw_chat(_renderer, &self.chat, self.typing.as_deref(), (10.0, 570.0, 700.0, 140.0));
+        }
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        if let Some(typing) = self.typing.as_mut() {
+            match _event {
+                InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) => {
+                    let text = self.typing.take().unwrap_or_default();
+                    if !text.trim().is_empty() {
+                        self.send(_shared, text);
+                    }
+                },
+                InputEvent::Key(KeyCode::Escape) => self.typing = None,
+                InputEvent::Key(KeyCode::Back) => { typing.pop(); },
+                InputEvent::Text(character) if !character.is_control() && typing.chars().count() < MAX_CHAT_LENGTH => typing.push(character),
+                _ => {}
+            }
+            return Transition::None;
+        }
+        if let InputEvent::Key(KeyCode::Return) = _event {
+            self.typing = Some(String::new());
+            return Transition::None;
+        }
+
+        // Only how the board is seen can be changed, never the game
+        if !_shared.controller.view.input(_event) {
+            if let InputEvent::Key(KeyCode::F) = _event {
+                _shared.controller.flipped = !_shared.controller.flipped;
+            }
+        }
+
+        Transition::None
+    }
+}
@@ -0,0 +1,529 @@
+use eliasfl_chess::Color as Colour;
+use ggez::event::KeyCode;
+use super::controller::{GameController, InputEvent};
+use super::graphics_funcs::{draw_funcs::{self, TurnIndicator, BoardStyle}, layers::{Layer, Layers}, renderer::{Renderer, OffsetRenderer}};
+use super::animation::{Animated, animated, Animator, Tween, Easing};
+use super::theme::Theme;
+use super::bot::discover_bots;
+use super::plugins::{self, PieceSet};
+use super::variant::{Variant, Standard, Armageddon, Chess960};
+use super::achievements::Achievements;
+use super::profile::{Profiles, player_name};
+use super::config::Config;
+use super::saves::{SaveSlots, SavedGame};
+use super::recent::RecentGames;
+use super::pawn_structure::PawnStructure;
+use super::tasks::TaskQueue;
+use super::simul::Simul;
+use super::messages::MessagePack;
+use super::speedrun::Speedrun;
+use super::audio::Sound;
+use super::overlay_server::Spectators;
+use super::polyglot::Book;
+use super::pgn::PgnGame;
+use std::{path::{Path, PathBuf}, rc::Rc, sync::{Arc, Mutex}};
+use super::consts::{SCREEN_SIZE, FADE_TIME, SLIDE_TIME, SLIDE_DISTANCE, TOAST_TIME};
+
+pub mod main_menu;
+pub mod game;
+pub mod promotion;
+pub mod settings;
+pub mod game_over;
+pub mod analysis;
+pub mod hand_over;
+pub mod overlay;
+pub mod console;
+pub mod achievements;
+pub mod audio_settings;
+pub mod save;
+pub mod load;
+pub mod database;
+pub mod setup;
+pub mod mirror;
+pub mod online;
+pub mod arbiter;
+pub mod simul;
+pub mod pgn_headers;
+pub mod diagram;
+pub mod spectators;
+pub mod speedrun;
+pub mod paused;
+pub mod practice;
+pub mod mate_patterns;
+pub mod fen_entry;
+pub mod game_menu;
+pub mod draw_offer;
+
+/// State shared by all scenes
+pub struct Shared {
+    pub controller: GameController,
+    pub hand_over: bool, // Hot-seat privacy: hide the board between turns until the next player is ready
+    pub turn_indicator: TurnIndicator, // How the game shows whose turn it is
+    pub theme: Theme,
+    pub console_log: Vec<String>, // Everything the developer console printed
+    pub bot_scripts: Vec<PathBuf>, // Bot scripts and UCI engines found in the `bots/` directory, to choose opponents from
+    pub themes: Vec<Theme>, // Built-in themes followed by the ones added by plugins
+    pub piece_sets: Vec<PieceSet>, // Piece sets added by plugins
+    pub piece_set: Option<usize>, // Index into `piece_sets`, None for the pieces in `resources`
+    pub variants: Vec<Rc<dyn Variant>>, // Standard chess, armageddon and Chess960 (on backends that castle its way) followed by the variants added by plugins
+    pub message_packs: Vec<MessagePack>, // Built-in message packs followed by the ones added by plugins, chosen in `Config::messages`
+    pub achievements: Achievements,
+    pub profiles: Profiles, // Stats of every player over their finished games
+    pub config: Config, // Settings kept between sessions
+    pub saves: SaveSlots,
+    pub pawn_structure: Option<PawnStructure>, // Marks on doubled, isolated and passed pawns, None while the overlay is off
+    pub toasts: Vec<Toast>, // Popups shown over every scene, oldest first
+    pub exports: Vec<(String, String)>, // Files to write to the user data directory, as (path, contents)
+    pub preview: Option<Sound>, // Sound to play once, picking a sound pack plays one of its effects
+    pub simul: Option<Simul>, // The boards of a simul against a bot, while one is played
+    pub speedrun: Option<Speedrun>, // The last speedrun started, it counts for as long as the game is still against its bot
+    pub paused: bool, // Clocks and bots stand still while the window is away, until `PausedScene` is left, and while the game menu is open
+    pub spectators: Option<Arc<Mutex<Spectators>>>, // Mirror windows watching and their chat, while the game is served
+    pub book: Option<Arc<Book>>, // The opening book in the user data directory, if there is one
+    pub recent: RecentGames, // The last games saved, loaded, imported or finished, on the main menu
+    pub database: Vec<PgnGame>, // Games of the PGN database in the user data directory, browsed from the load screen
+    pub read_only: bool, // Mirror window: shows a game played in another window, nothing is unlocked from it
+    pub layers: Layers, // Which layers of the screen are drawn, toggled from the console
+    pub tasks: TaskQueue<Shared> // Background work whose results change the shared state, polled every frame
+}
+
+/// A short message popping up over every scene, i.e. an unlocked achievement
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub title: String,
+    pub message: String,
+    pub remaining: f32 // Seconds left on screen
+}
+
+impl Shared {
+    /// Shared state around a controller, with the default settings
+    pub fn new(_controller: GameController) -> Shared {
+        let mut shared = Shared {
+            controller: _controller,
+            hand_over: false,
+            turn_indicator: TurnIndicator::Badge,
+            theme: Theme::default(),
+            console_log: vec![],
+            bot_scripts: discover_bots(Path::new("bots")),
+            themes: Theme::BUILTIN.iter().filter_map(|_name| Theme::builtin(_name)).collect(),
+            piece_sets: vec![],
+            piece_set: None,
+            variants: vec![Rc::new(Standard), Rc::new(Armageddon)],
+            message_packs: MessagePack::BUILTIN.iter().filter_map(|_name| MessagePack::builtin(_name)).collect(),
+            achievements: Achievements::new(),
+            profiles: Profiles::new(),
+            config: Config::default(),
+            saves: SaveSlots::default(),
+            pawn_structure: None,
+            toasts: vec![],
+            exports: vec![],
+            preview: None,
+            simul: None,
+            speedrun: None,
+            paused: false,
+            spectators: None,
+            book: None,
+            recent: RecentGames::default(),
+            database: vec![],
+            read_only: false,
+            layers: Layers::default(),
+            tasks: TaskQueue::new()
+        };
+
+        // Chess960 castles by taking the king onto its rook, which not every backend knows
+        if shared.controller.game.chess960() {
+            shared.variants.push(Rc::new(Chess960::new()));
+        }
+
+        shared.add_plugins(Path::new(plugins::PLUGIN_DIR));
+        shared
+    }
+
+    /// The opening book for the built-in bot, when the settings turn it on and there is one
+    pub fn opening_book(&self) -> Option<Arc<Book>> {
+        self.book.clone().filter(|_| self.config.opening_book)
+    }
+
+    /// ## `review`
+    /// Loads a game to look at in the analysis: both sides are played by clicking, in standard chess
+    /// ### Return
+    /// `Err` if a move of the game could not be played
+    pub fn review(&mut self, _game: &SavedGame) -> Result<(), String> {
+        self.controller.set_bot(Colour::White, None);
+        self.controller.set_bot(Colour::Black, None);
+        self.controller.vote = None;
+        self.controller.variant = Rc::clone(&self.variants[0]);
+        self.speedrun = None;
+        self.controller.load_saved(_game)
+    }
+
+    /// How the settings and the visible layers have the board drawn
+    pub fn board_style(&self) -> BoardStyle {
+        BoardStyle { margin: self.config.piece_margin, layers: self.layers }
+    }
+
+    /// The avatars and flags of both players of the game, for the application to load
+    pub fn player_images(&self) -> Vec<String> {
+        [Colour::White, Colour::Black].iter()
+            .filter_map(|_colour| self.profiles.identity(&player_name(&self.controller, *_colour)))
+            .flat_map(|_identity| _identity.images())
+            .collect()
+    }
+
+    /// Adds the themes, piece sets and variants of every plugin in a directory. Broken manifests are shown in the banner.
+    pub fn add_plugins(&mut self, _directory: &Path) {
+        let (found, errors) = plugins::discover(_directory);
+
+        for _plugin in found {
+            self.themes.extend(_plugin.theme());
+            self.piece_sets.extend(_plugin.piece_set());
+            self.message_packs.extend(_plugin.messages());
+            if let Some(variant) = _plugin.variant() {
+                self.variants.push(Rc::new(variant));
+            }
+        }
+
+        if let Some(error) = errors.into_iter().next() {
+            self.controller.banner = Some(format!("Plugin not loaded: {}", error));
+        }
+    }
+
+    /// Unlocks what the game earned since the last call, with a toast for each. Returns if anything was unlocked, to be saved.
+    pub fn check_achievements(&mut self) -> bool {
+        // The players earn them in their own window
+        if self.read_only {
+            return false;
+        }
+
+        let earned = self.achievements.check(&self.controller);
+
+        for _achievement in &earned {
+            self.toasts.push(Toast {
+                title: format!("Achievement unlocked: {}", _achievement.title()),
+                message: _achievement.description().to_string(),
+                remaining: TOAST_TIME
+            });
+        }
+
+        !earned.is_empty()
+    }
+
+    /// Counts down the toasts, dropping the ones whose time is up
+    pub fn update_toasts(&mut self, _dt: f32) {
+        for _toast in self.toasts.iter_mut() {
+            _toast.remaining -= _dt;
+        }
+        self.toasts.retain(|_toast| _toast.remaining > 0.0);
+    }
+
+    /// The message pack chosen in the settings, the taunts when it is gone
+    pub fn messages(&self) -> &MessagePack {
+        self.message_packs.iter()
+            .find(|_pack| _pack.name == self.config.messages)
+            .unwrap_or(&self.message_packs[0])
+    }
+
+    /// Looks up a theme by name, built-in or from a plugin
+    pub fn find_theme(&self, _name: &str) -> Option<Theme> {
+        self.themes.iter().find(|_theme| _theme.name == _name).cloned()
+    }
+}
+
+/// What the scene stack should do after a scene has handled an update or input
+pub enum Transition {
+    /// Stay on the current scene
+    None,
+    /// Put a new scene on top of the current one
+    Push(Box<dyn Scene>),
+    /// Remove the current scene, going back to the one below
+    Pop,
+    /// Swap the current scene for another one
+    Replace(Box<dyn Scene>),
+    /// Throw away every scene and start over from a new one
+    Reset(Box<dyn Scene>),
+    /// Close the application
+    Quit
+}
+
+/// One screen of the application (menu, game, settings...). Only the top scene of the stack gets updates and input.
+pub trait Scene {
+    /// Name of the scene, for debugging and tests
+    fn name(&self) -> &'static str;
+
+    /// Advances the scene by one fixed step of `_dt` seconds
+    fn update(&mut self, _shared: &mut Shared, _dt: f32) -> Transition {
+        Transition::None
+    }
+
+    /// Draws the scene
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared);
+
+    /// Handles an input event
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition;
+
+    /// Overlays only cover part of the screen, the scene below them is drawn first
+    fn is_overlay(&self) -> bool {
+        false
+    }
+
+    /// Rectangles of the buttons and other elements that can be clicked, in the order the keyboard focus moves
+    /// through them (see `SceneStack::input`). Empty while the scene needs Tab, the arrows and Enter itself, i.e. when typing.
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        vec![]
+    }
+}
+
+/// How a transition is animated
+enum Effect {
+    /// Happens at once
+    Immediate,
+    /// Fades to black, switches scenes, then fades back
+    Fade,
+    /// Switches at once and slides the new overlay in from below
+    SlideIn,
+    /// Slides the overlay out below the screen before removing it
+    SlideOut
+}
+
+/// Stack of scenes, the top one is the active one
+pub struct SceneStack {
+    scenes: Vec<Box<dyn Scene>>,
+    animator: Animator, // Plays the transitions
+    cover: Animated<f32>, // Opacity of the black fading over the whole screen
+    slide: Animated<f32>, // How far the top overlay is moved down
+    pending: Option<Transition>, // Transition waiting for its out animation to finish
+    focus: Option<usize> // Index into the focusables of the top scene, None until the keyboard is used
+}
+
+impl SceneStack {
+    /// Creates a stack with a single scene
+    pub fn new(_first: Box<dyn Scene>) -> SceneStack {
+        SceneStack {
+            scenes: vec![_first],
+            animator: Animator::new(),
+            cover: animated(0.0),
+            slide: animated(0.0),
+            pending: None,
+            focus: None
+        }
+    }
+
+    /// Names of the scenes from the bottom to the top
+    pub fn names(&self) -> Vec<&'static str> {
+        self.scenes.iter().map(|_scene| _scene.name()).collect()
+    }
+
+    /// Name of the active scene
+    pub fn top_name(&self) -> &'static str {
+        self.scenes.last().map(|_scene| _scene.name()).unwrap_or("")
+    }
+
+    /// If a transition is still animating
+    pub fn is_transitioning(&self) -> bool {
+        !self.animator.is_idle()
+    }
+
+    /// Switches scenes at once from now on, without fades or slides
+    pub fn set_reduced_motion(&mut self, _reduced: bool) {
+        self.animator.set_reduced_motion(_reduced);
+    }
+
+    /// Jumps to the end of any transition being animated. Returns true if the application should quit.
+    pub fn finish_transition(&mut self) -> bool {
+        self.animator.finish_all();
+        let quit = self.apply_pending();
+        self.animator.finish_all();
+
+        quit
+    }
+
+    /// Applies a transition at once, without animating it. Returns true if the application should quit.
+    pub fn apply(&mut self, _transition: Transition) -> bool {
+        // Every scene starts without focus, the elements of the scene left mean nothing to it
+        if !matches!(_transition, Transition::None) {
+            self.focus = None;
+        }
+
+        match _transition {
+            Transition::None => {},
+            Transition::Push(scene) => self.scenes.push(scene),
+            Transition::Pop => {
+                // The last scene is never popped, there would be nothing left to show
+                if self.scenes.len() > 1 {
+                    self.scenes.pop();
+                }
+            },
+            Transition::Replace(scene) => {
+                self.scenes.pop();
+                self.scenes.push(scene);
+            },
+            Transition::Reset(scene) => self.scenes = vec![scene],
+            Transition::Quit => return true
+        }
+
+        false
+    }
+
+    /// How a transition should be animated. Overlays slide, full screen scenes fade.
+    fn effect(&self, _transition: &Transition) -> Effect {
+        // Not even a frame of black between the scenes
+        if self.animator.reduced_motion() {
+            return Effect::Immediate;
+        }
+
+        let top_is_overlay = self.scenes.last().map_or(false, |_scene| _scene.is_overlay());
+
+        match _transition {
+            Transition::Push(scene) | Transition::Replace(scene) | Transition::Reset(scene) =>
+                if scene.is_overlay() { Effect::SlideIn } else { Effect::Fade },
+            Transition::Pop if self.scenes.len() > 1 =>
+                if top_is_overlay { Effect::SlideOut } else { Effect::Fade },
+            _ => Effect::Immediate
+        }
+    }
+
+    /// Starts a transition, animating it if it changes the scenes. Returns true if the application should quit.
+    fn begin(&mut self, _transition: Transition) -> bool {
+        match self.effect(&_transition) {
+            Effect::Immediate => return self.apply(_transition),
+            Effect::SlideIn => {
+                let quit = self.apply(_transition);
+                self.animator.play(Tween::new(&self.slide, SLIDE_DISTANCE, 0.0, SLIDE_TIME, Easing::EaseOut));
+                return quit;
+            },
+            Effect::SlideOut => self.animator.play(Tween::new(&self.slide, 0.0, SLIDE_DISTANCE, SLIDE_TIME, Easing::EaseIn)),
+            Effect::Fade => self.animator.play(Tween::new(&self.cover, 0.0, 1.0, FADE_TIME, Easing::EaseIn))
+        }
+
+        self.pending = Some(_transition);
+        false
+    }
+
+    /// Applies the pending transition once its out animation is done, then fades back in if it faded out
+    fn apply_pending(&mut self) -> bool {
+        if !self.animator.is_idle() {
+            return false;
+        }
+
+        match self.pending.take() {
+            Some(transition) => {
+                let quit = self.apply(transition);
+                self.slide.set(0.0);
+
+                if self.cover.get() > 0.0 {
+                    self.animator.play(Tween::new(&self.cover, 1.0, 0.0, FADE_TIME, Easing::EaseOut));
+                }
+
+                quit
+            },
+            None => false
+        }
+    }
+
+    /// Updates the active scene. Returns true if the application should quit.
+    pub fn update(&mut self, _shared: &mut Shared, _dt: f32) -> bool {
+        self.animator.update(_dt);
+        if self.apply_pending() {
+            return true;
+        }
+
+        // The scene being left is frozen while it animates out
+        if self.pending.is_some() {
+            return false;
+        }
+
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.update(_shared, _dt),
+            None => Transition::None
+        };
+
+        self.begin(transition)
+    }
+
+    /// ## `move_focus`
+    /// Keyboard navigation of the top scene: Tab, Down and Right focus the next element, Up and Left the one before,
+    /// and Enter clicks the focused one. Scenes without focusable elements get the keys as they are.
+    /// ### Return
+    /// The event for the scene, None when the key only moved the focus
+    fn move_focus(&mut self, _shared: &Shared, _event: InputEvent) -> Option<InputEvent> {
+        let targets = self.scenes.last().map_or(vec![], |_scene| _scene.focusables(_shared));
+        let focus = self.focus.filter(|_focus| *_focus < targets.len());
+
+        let key = match _event {
+            // The mouse takes over from the keyboard
+            InputEvent::Click { .. } => {
+                self.focus = None;
+                return Some(_event);
+            },
+            InputEvent::Key(key) if !targets.is_empty() => key,
+            _ => return Some(_event)
+        };
+
+        match key {
+            KeyCode::Tab | KeyCode::Down | KeyCode::Right => self.focus = Some(focus.map_or(0, |_focus| (_focus + 1) % targets.len())),
+            KeyCode::Up | KeyCode::Left => self.focus = Some(focus.map_or(targets.len() - 1, |_focus| (_focus + targets.len() - 1) % targets.len())),
+            KeyCode::Return | KeyCode::NumpadEnter if focus.is_some() => {
+                let (x, y, width, height) = targets[focus.unwrap()];
+                return Some(InputEvent::Click { x: x + width / 2.0, y: y + height / 2.0 });
+            },
+            _ => return Some(_event)
+        }
+
+        None
+    }
+
+    /// Gives an input event to the active scene. Returns true if the application should quit.
+    pub fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> bool {
+        // Input meant for the scene being left is dropped
+        if self.pending.is_some() {
+            return false;
+        }
+
+        let event = match self.move_focus(_shared, _event) {
+            Some(event) => event,
+            None => return false
+        };
+
+        let transition = match self.scenes.last_mut() {
+            Some(scene) => scene.input(_shared, event),
+            None => Transition::None
+        };
+
+        self.begin(transition)
+    }
+
+    /// Draws the active scene, and the scenes below it if it is an overlay
+    pub fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        // Walk down past overlays to the first scene covering the whole screen
+        let mut first = self.scenes.len().saturating_sub(1);
+        while first > 0 && self.scenes[first].is_overlay() {
+            first -= 1;
+        }
+
+        let top = self.scenes.len() - 1;
+        for (_index, _scene) in self.scenes.iter().enumerate().skip(first) {
+            // Hidden modals leave the scene below them in view, but never the console the layers are shown again from
+            if _index > first && !_shared.layers.is_visible(Layer::Modals) && _scene.name() != "console" {
+                continue;
+            }
+
+            if _index == top && _scene.is_overlay() && self.slide.get() != 0.0 {
+                _scene.draw(&mut OffsetRenderer::new(_renderer, (0.0, self.slide.get())), _shared);
+            }
+            else {
+                _scene.draw(_renderer, _shared);
+            }
+        }
+
+        // The focus is only shown once the scene is in place
+        let focused = self.focus.and_then(|_focus| self.scenes.get(top).and_then(|_scene| _scene.focusables(_shared).get(_focus).copied()));
+        if let Some(rect) = focused.filter(|_| self.slide.get() == 0.0) {
+            draw_funcs::draw_focus(_renderer, rect);
+        }
+
+        let cover = self.cover.get();
+        if cover > 0.0 {
+            _renderer.draw_rect((0.0, 0.0, SCREEN_SIZE.0, SCREEN_SIZE.1), [0.0, 0.0, 0.0, cover]);
+        }
+    }
+}
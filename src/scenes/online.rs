@@ -0,0 +1,539 @@
+use eliasfl_chess::Color as Colour;
+use ggez::event::KeyCode;
+use std::rc::Rc;
+use super::{Scene, Shared, Transition};
+use super::game_over::GameOverScene;
+use super::main_menu::MainMenuScene;
+use super::super::clock;
+use super::super::controller::{InputEvent, Command, Adjudication};
+use super::super::graphics_funcs::{draw_funcs, renderer::{Renderer, ZoomRenderer}, text_layout::TextLayout};
+use super::super::fen::position_hash;
+use super::super::help_funcs::in_rect;
+use super::super::net::{Connection, Message, DEFAULT_MATCHMAKER, DEFAULT_LAN_PORT, lan_port, name_token};
+use super::super::pgn::PgnHeaders;
+use super::super::saves::SavedMove;
+use super::super::tasks::Task;
+
+/// Longest name, server address, token or key that can be typed
+const MAX_FIELD_LENGTH: usize = 40;
+
+/// Characters of the Lichess token shown, the rest is hidden from anyone watching the screen
+const TOKEN_SHOWN: usize = 4;
+
+// The lobby: five text fields, label on the left
+const NAME_FIELD: (f32, f32, f32, f32) = (220.0, 200.0, 440.0, 50.0);
+const SERVER_FIELD: (f32, f32, f32, f32) = (220.0, 265.0, 440.0, 50.0);
+const LAN_FIELD: (f32, f32, f32, f32) = (220.0, 330.0, 440.0, 50.0);
+const TOKEN_FIELD: (f32, f32, f32, f32) = (220.0, 395.0, 440.0, 50.0);
+const HOST_BUTTON: (f32, f32, f32, f32) = (80.0, 570.0, 260.0, 60.0);
+const JOIN_BUTTON: (f32, f32, f32, f32) = (380.0, 570.0, 260.0, 60.0);
+const FIND_BUTTON: (f32, f32, f32, f32) = (80.0, 650.0, 260.0, 60.0);
+const LICHESS_BUTTON: (f32, f32, f32, f32) = (380.0, 650.0, 260.0, 60.0);
+const KEY_FIELD: (f32, f32, f32, f32) = (220.0, 718.0, 440.0, 40.0);
+const BACK_BUTTON: (f32, f32, f32, f32) = (230.0, 770.0, 260.0, 60.0);
+
+/// How the opponent is looked for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Via {
+    Matchmaker,
+    Host, // Listening on the local network for a player to join
+    Join, // Connecting straight to a player hosting on the local network
+    Lichess // Seeking a game on lichess.org with the player's account
+}
+
+/// Where an online game is at
+enum Stage {
+    /// Choosing a name and server
+    Lobby,
+    Connecting(Task<Result<Connection, String>>),
+    /// Connected, no opponent with the same clock yet
+    Waiting(Connection),
+    Playing {
+        connection: Connection,
+        colour: Colour, // The player's side
+        opponent: String,
+        headers: PgnHeaders, // What both players export the game with
+        sent: usize // Moves of the game passed on to the opponent or received from them
+    }
+}
+
+/// A game against another player over the matchmaking server (see the `schack_matchmaker` binary), which pairs
+/// players with the same clock and relays their moves, straight over the local network with one player hosting, or on
+/// Lichess with the player's account. Only the player's own side can be moved, and takebacks, restarts and saves are left out as they would need the
+/// opponent's consent.
+///
+/// Every move goes with the hash of the position it led to. When the position here differs, the opponent's moves are
+/// asked for with `RESYNC` and the game is rebuilt from them. The clock of each side is brought to the time its player
+/// sent through `clock::synced`, so small drifts are eased out rather than jumped.
+pub struct OnlineScene {
+    stage: Stage,
+    via: Via, // How the opponent of the stage is looked for
+    field: Option<usize>, // Lobby field being typed in, 0 for the name, 1 for the server, 2 for the LAN address, 3 for the Lichess token and 4 for the host's key
+    status: Option<String>, // What the lobby says about the last attempt
+    account: Option<String> // Name of the Lichess account logged in to, which the game is exported under
+}
+
+impl OnlineScene {
+    /// The lobby, with the name and server of the last online game
+    pub fn new() -> OnlineScene {
+        OnlineScene { stage: Stage::Lobby, via: Via::Matchmaker, field: None, status: None, account: None }
+    }
+
+    /// The server to connect to, the default one when none was typed
+    fn server(_shared: &Shared) -> String {
+        if _shared.config.matchmaker.trim().is_empty() { DEFAULT_MATCHMAKER.to_string() } else { _shared.config.matchmaker.trim().to_string() }
+    }
+
+    /// A lobby field by its index, to be edited
+    fn field_mut(_shared: &mut Shared, _field: usize) -> &mut String {
+        match _field {
+            0 => &mut _shared.config.online_name,
+            1 => &mut _shared.config.matchmaker,
+            2 => &mut _shared.config.lan_address,
+            3 => &mut _shared.config.lichess_token,
+            _ => &mut _shared.config.lan_fingerprint
+        }
+    }
+
+    /// The LAN game to join, on the default port when the address names none
+    fn lan_address(_shared: &Shared) -> String {
+        let address = _shared.config.lan_address.trim();
+        if address.contains(':') { address.to_string() } else { format!("{}:{}", address, DEFAULT_LAN_PORT) }
+    }
+
+    /// Connects to the matchmaker on a background thread
+    fn find_opponent(&mut self, _shared: &Shared) {
+        let (server, certificate) = (OnlineScene::server(_shared), _shared.config.matchmaker_certificate.clone());
+        let (name, time_control) = (_shared.config.online_name.clone(), _shared.controller.time_control);
+        self.stage = Stage::Connecting(Task::spawn(move || Connection::connect(&server, &certificate, &name, time_control)));
+        self.via = Via::Matchmaker;
+        self.field = None;
+        self.status = None;
+    }
+
+    /// Hosts a game on the local network, on the port of the LAN address
+    fn host(&mut self, _shared: &Shared) {
+        self.via = Via::Host;
+        self.field = None;
+        self.status = None;
+        match Connection::host(lan_port(&_shared.config.lan_address), &_shared.config.online_name, _shared.controller.time_control) {
+            Ok(connection) => self.stage = Stage::Waiting(connection),
+            Err(error) => self.status = Some(format!("Could not host: {}", error))
+        }
+    }
+
+    /// Connects to the game hosted at the LAN address on a background thread
+    fn join(&mut self, _shared: &Shared) {
+        self.via = Via::Join;
+        self.field = None;
+        if _shared.config.lan_address.trim().is_empty() {
+            self.stage = Stage::Lobby;
+            self.status = Some("Type the address of the host under LAN first".to_string());
+            return;
+        }
+
+        let (address, fingerprint) = (OnlineScene::lan_address(_shared), _shared.config.lan_fingerprint.clone());
+        let (name, time_control) = (_shared.config.online_name.clone(), _shared.controller.time_control);
+        self.stage = Stage::Connecting(Task::spawn(move || Connection::join(&address, &fingerprint, &name, time_control)));
+        self.status = None;
+    }
+
+    /// Logs in to Lichess and seeks a game there on a background thread
+    fn lichess(&mut self, _shared: &Shared) {
+        let (token, time_control) = (_shared.config.lichess_token.clone(), _shared.controller.time_control);
+        self.stage = Stage::Connecting(Task::spawn(move || Connection::lichess(&token, time_control)));
+        self.via = Via::Lichess;
+        self.field = None;
+        self.status = None;
+        self.account = None;
+    }
+
+    /// ## `start`
+    /// Starts the game once paired: standard chess with the clock of the settings on both sides, the board turned to the player
+    fn start(_shared: &mut Shared, _colour: Colour) {
+        _shared.controller.set_bot(Colour::White, None);
+        _shared.controller.set_bot(Colour::Black, None);
+        _shared.controller.vote = None;
+        _shared.controller.variant = Rc::clone(&_shared.variants[0]);
+        _shared.controller.time_odds = None;
+        _shared.speedrun = None;
+        _shared.controller.flipped = _colour == Colour::Black;
+        _shared.controller.execute(Command::Restart);
+    }
+
+    /// ## `rebuild`
+    /// Plays the game again from the start with the moves of the opponent's game, once the positions differed. The
+    /// moves keep the times and clocks they were played with on the opponent's side, so both export the same game.
+    /// ### Parameters
+    /// - `_shared: &mut Shared`: The shared state, with the game to rebuild
+    /// - `_moves: &[SavedMove]`: The moves of the opponent's game, see `Message::Moves`
+    fn rebuild(_shared: &mut Shared, _moves: &[SavedMove]) -> Result<(), String> {
+        let remaining: Vec<(Colour, f32)> = _shared.controller.clocks.iter().map(|(_colour, _clock)| (*_colour, _clock.remaining())).collect();
+        let game_time = _shared.controller.game_time;
+
+        _shared.controller.execute(Command::Restart);
+        for _move in _moves {
+            _shared.controller.play_saved_move(_move)?;
+        }
+
+        // The clocks running now are this side's, the opponent's moves only tell where they stood after each move
+        for (_colour, _remaining) in remaining {
+            if let Some(clock) = _shared.controller.clocks.get_mut(&_colour) {
+                clock.set_remaining(_remaining);
+            }
+        }
+        _shared.controller.game_time = game_time;
+        Ok(())
+    }
+
+    /// ## `exchange`
+    /// Plays the moves of the opponent and passes the player's moves on, while playing
+    /// ### Return
+    /// The scene to go to when the game went away under the scene, i.e. replayed from the result screen
+    fn exchange(&mut self, _shared: &mut Shared) -> Transition {
+        let (connection, colour, opponent, sent) = match &mut self.stage {
+            Stage::Playing { connection, colour, opponent, sent, .. } => (connection, *colour, opponent, sent),
+            _ => return Transition::None
+        };
+
+        // Replaying from the result screen restarts the game here only, a new opponent is needed for that
+        if _shared.controller.history.len() < *sent {
+            return Transition::Replace(Box::new(OnlineScene::new()));
+        }
+
+        while let Some(message) = connection.poll() {
+            match message {
+                Message::Move { uci, clock, hash } => {
+                    let played = _shared.controller.play_saved_move(&SavedMove { uci: uci.clone(), elapsed: _shared.controller.game_time, clock });
+                    if let Err(error) = played {
+                        _shared.controller.banner = Some(format!("{}'s move {} could not be played: {}", opponent, uci, error));
+                    }
+                    // Each side keeps the time of its own clock, which stopped with the move
+                    if let (Some(clock), Some(remaining)) = (_shared.controller.clocks.get_mut(&!colour), clock) {
+                        clock.set_remaining(clock::synced(clock.remaining(), remaining, 0.0, false));
+                    }
+                    *sent = _shared.controller.history.len();
+
+                    // The positions differ, the opponent's moves are asked for to rebuild the game from
+                    if hash.map_or(false, |_hash| _hash != position_hash(&_shared.controller.fen())) {
+                        _shared.controller.banner = Some(format!("The board is out of step with {}'s, fetching their moves…", opponent));
+                        if let Err(error) = connection.request_resync() {
+                            _shared.controller.banner = Some(format!("The board is out of step with {}'s: {}", opponent, error));
+                        }
+                    }
+                },
+                Message::Resync => {
+                    let moves: Vec<SavedMove> = _shared.controller.history.iter().map(|_move| SavedMove {
+                        uci: _move.uci(),
+                        elapsed: _move.elapsed,
+                        clock: _move.clock
+                    }).collect();
+                    if let Err(error) = connection.send_moves(&moves) {
+                        _shared.controller.banner = Some(format!("The moves did not reach {}: {}", opponent, error));
+                    }
+                },
+                Message::Moves(moves) => {
+                    match OnlineScene::rebuild(_shared, &moves) {
+                        Ok(()) => _shared.controller.banner = None,
+                        Err(error) => _shared.controller.banner = Some(format!("The game could not be rebuilt from {}'s moves: {}", opponent, error))
+                    }
+                    *sent = _shared.controller.history.len();
+                },
+                Message::Left => {
+                    if !_shared.controller.is_over() {
+                        _shared.controller.adjudication = Some(Adjudication::Win(colour));
+                        _shared.controller.banner = Some(format!("{} left the game", opponent));
+                    }
+                },
+                Message::Error(error) => _shared.controller.banner = Some(error),
+                // Checkmates and stalemates the board has already seen
+                Message::Ended { winner, reason } => {
+                    if !_shared.controller.is_over() {
+                        _shared.controller.adjudication = Some(winner.map_or(Adjudication::Draw, Adjudication::Win));
+                        _shared.controller.banner = Some(format!("Lichess ended the game: {}", reason));
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        // The player's moves, which is at most one per update
+        if _shared.controller.history.len() > *sent {
+            if let Some(last) = _shared.controller.history.last() {
+                if let Err(error) = connection.send_move(&last.uci(), last.clock, &position_hash(&_shared.controller.fen())) {
+                    _shared.controller.banner = Some(format!("The move did not reach {}: {}", opponent, error));
+                }
+            }
+            *sent = _shared.controller.history.len();
+        }
+
+        Transition::None
+    }
+}
+
+impl Scene for OnlineScene {
+    fn name(&self) -> &'static str {
+        "online"
+    }
+
+    fn update(&mut self, _shared: &mut Shared, _dt: f32) -> Transition {
+        let message = match &self.stage {
+            Stage::Connecting(task) => match task.poll() {
+                Some(Ok(Ok(connection))) => {
+                    self.stage = Stage::Waiting(connection);
+                    return Transition::None;
+                },
+                Some(Ok(Err(error))) | Some(Err(error)) => {
+                    self.stage = Stage::Lobby;
+                    self.status = Some(format!("Could not {}: {}", if self.via == Via::Lichess { "log in" } else { "connect" }, error));
+                    return Transition::None;
+                },
+                None => return Transition::None
+            },
+            Stage::Waiting(connection) => connection.poll(),
+            Stage::Playing { .. } => {
+                let transition = self.exchange(_shared);
+                return match (&self.stage, transition) {
+                    (Stage::Playing { headers, .. }, Transition::None) if _shared.controller.is_over() =>
+                        Transition::Push(Box::new(GameOverScene::online(_shared, headers.clone()))),
+                    (_, transition) => transition
+                };
+            },
+            Stage::Lobby => return Transition::None
+        };
+
+        match message {
+            Some(Message::Paired { colour, opponent, started }) => {
+                if let Stage::Waiting(connection) = std::mem::replace(&mut self.stage, Stage::Lobby) {
+                    OnlineScene::start(_shared, colour);
+                    let name = self.account.clone().unwrap_or_else(|| name_token(&_shared.config.online_name));
+                    let mut headers = PgnHeaders::online(colour, &name, &opponent, started);
+                    match self.via {
+                        Via::Matchmaker => {},
+                        Via::Lichess => {
+                            headers.event = "Casual game".to_string();
+                            headers.site = "lichess.org".to_string();
+                        },
+                        Via::Host | Via::Join => {
+                            headers.event = "LAN game".to_string();
+                            headers.site = "Schack LAN".to_string();
+                        }
+                    }
+                    self.stage = Stage::Playing { connection, colour, opponent, headers, sent: 0 };
+                }
+            },
+            Some(Message::LoggedIn(account)) => self.account = Some(account),
+            Some(Message::Error(error)) => {
+                self.stage = Stage::Lobby;
+                self.status = Some(match self.via {
+                    Via::Matchmaker => format!("The server refused: {}", error),
+                    Via::Host => format!("Hosting stopped: {}", error),
+                    Via::Join => format!("The host refused: {}", error),
+                    Via::Lichess => format!("Lichess refused: {}", error)
+                });
+            },
+            Some(Message::Left) => {
+                self.stage = Stage::Lobby;
+                self.status = Some(match self.via {
+                    Via::Matchmaker => "The connection to the server was lost",
+                    Via::Lichess => "The connection to Lichess was lost",
+                    Via::Host | Via::Join => "The host hung up"
+                }.to_string());
+            },
+            _ => {}
+        }
+
+        Transition::None
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        if let Stage::Playing { colour, opponent, .. } = &self.stage {
+            let view = _shared.controller.view;
+            draw_funcs::draw_board(&mut ZoomRenderer::new(_renderer, view.zoom, view.offset), &_shared.theme, &_shared.controller, _shared.board_style());
+            if view.is_zoomed() {
+                draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+            }
+            draw_funcs::draw_status_panel(_renderer, &_shared.theme, &_shared.controller, _shared.turn_indicator, _shared.messages(), _shared.config.reduced_motion);
+            draw_funcs::draw_player_identities(_renderer, &_shared.profiles, &_shared.controller, 778.0);
+
+            // Promotions are chosen here rather than over a promotion scene, which would end the game on a result screen
+            // that knows nothing of the opponent
+            if _shared.controller.promoting {
+                draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+                draw_funcs::draw_promotion_panel(_renderer, _shared.controller.game.active_colour());
+            }
+            else if _shared.controller.notice.is_none() {
+                let side = if *colour == Colour::White { "White" } else { "Black" };
+                TextLayout::centred(&format!("You play {} against {}   Escape: leave", side, opponent), 14.0, _shared.theme.text)
+                    .draw(_renderer, (160.0, 835.0, 400.0, 25.0));
+            }
+            return;
+        }
+
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+        draw_funcs::draw_title(_renderer, "Play online", 120.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        // The token is a password, only its start is shown
+        let token = &_shared.config.lichess_token;
+        let hidden: String = token.chars().take(TOKEN_SHOWN).chain(token.chars().skip(TOKEN_SHOWN).map(|_| '•')).collect();
+        let values = [&_shared.config.online_name, &_shared.config.matchmaker, &_shared.config.lan_address, &hidden, &_shared.config.lan_fingerprint];
+        let lan_placeholder = format!("192.168.1.20:{}", DEFAULT_LAN_PORT);
+        let placeholders = ["Anonymous", DEFAULT_MATCHMAKER, lan_placeholder.as_str(), "API token, board:play scope", "Host's key, to join on LAN"];
+        let fields = [("Name", NAME_FIELD), ("Server", SERVER_FIELD), ("LAN", LAN_FIELD), ("Lichess", TOKEN_FIELD), ("Key", KEY_FIELD)];
+        for (_index, (_label, _rect)) in fields.iter().enumerate() {
+            _renderer.draw_text(_label, (60.0, _rect.1 + 12.0), 26.0, [0.7, 0.7, 0.7, 1.0]);
+
+            if self.field == Some(_index) {
+                _renderer.draw_rect((_rect.0 - 3.0, _rect.1 - 3.0, _rect.2 + 6.0, _rect.3 + 6.0), [0.97, 0.3, 0.0, 1.0]);
+            }
+            _renderer.draw_rect(*_rect, [0.1, 0.1, 0.1, 1.0]);
+
+            let (text, colour) = match (values[_index].is_empty(), self.field == Some(_index)) {
+                (_, true) => (format!("{}_", values[_index]), [1.0, 1.0, 1.0, 1.0]),
+                (true, false) => (placeholders[_index].to_string(), [0.5, 0.5, 0.5, 1.0]),
+                (false, false) => (values[_index].clone(), [1.0, 1.0, 1.0, 1.0])
+            };
+            _renderer.draw_text(&text, (_rect.0 + 12.0, _rect.1 + 12.0), 26.0, colour);
+        }
+
+        let clock = match _shared.controller.time_control {
+            Some(time_control) => format!("Clock: {}+{}, from the settings", time_control.initial / 60.0, time_control.increment),
+            None => "Clock: Off, from the settings".to_string()
+        };
+        TextLayout::centred(&clock, 20.0, [0.7, 0.7, 0.7, 1.0]).draw(_renderer, (60.0, 460.0, 600.0, 30.0));
+
+        let status = match (&self.stage, self.via, &self.status) {
+            (Stage::Connecting(_), Via::Join, _) => format!("Connecting to {}…", OnlineScene::lan_address(_shared)),
+            (Stage::Connecting(_), Via::Lichess, _) => "Logging in to Lichess…".to_string(),
+            (Stage::Connecting(_), _, _) => format!("Connecting to {}…", OnlineScene::server(_shared)),
+            (Stage::Waiting(connection), Via::Host, _) => format!("Hosting on port {} with the key {}, waiting for a player to join…",
+                lan_port(&_shared.config.lan_address), connection.fingerprint().unwrap_or("?")),
+            (Stage::Waiting(_), Via::Join, _) => "Waiting for the host…".to_string(),
+            (Stage::Waiting(_), Via::Matchmaker, _) => "Waiting for an opponent with the same clock…".to_string(),
+            (Stage::Waiting(_), Via::Lichess, _) => match &self.account {
+                Some(account) => format!("Seeking a game on Lichess as {}…", account),
+                None => "Seeking a game on Lichess…".to_string()
+            },
+            (_, _, Some(status)) => status.clone(),
+            _ => "Players with the same clock are paired in the order they arrive".to_string()
+        };
+        TextLayout::centred(&status, 22.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, (60.0, 500.0, 600.0, 40.0));
+
+        // The button that started a search cancels it
+        let searching = matches!(self.stage, Stage::Connecting(_) | Stage::Waiting(_));
+        let label = |_via: Via, _label: &'static str| if searching && self.via == _via { "Cancel" } else { _label };
+        draw_funcs::draw_button(_renderer, HOST_BUTTON, label(Via::Host, "Host on LAN"));
+        draw_funcs::draw_button(_renderer, JOIN_BUTTON, label(Via::Join, "Join on LAN"));
+        draw_funcs::draw_button(_renderer, FIND_BUTTON, label(Via::Matchmaker, "Find opponent"));
+        draw_funcs::draw_button(_renderer, LICHESS_BUTTON, label(Via::Lichess, "Play on Lichess"));
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        if let Stage::Playing { colour, .. } = &self.stage {
+            let colour = *colour;
+            return match _event {
+                // Leaving hangs up, which ends the game for the opponent
+                InputEvent::Key(KeyCode::Escape) => Transition::Reset(Box::new(MainMenuScene)),
+                // The promotion shortcuts pick the piece like a click on it
+                InputEvent::Key(_) if matches!(_shared.controller.command_for(_event), Some(Command::Promote(_))) => {
+                    _shared.controller.handle_input(_event);
+                    Transition::None
+                },
+                // Other keys would take back or restart, which the opponent has no say in here
+                InputEvent::Key(_) | InputEvent::Text(_) => Transition::None,
+                InputEvent::Click { .. } | InputEvent::Press { .. } if _shared.controller.game.active_colour() != colour => Transition::None,
+                // The update passes the move on, and shows the result once the game is over
+                _ => {
+                    _shared.controller.handle_input(_event);
+                    Transition::None
+                }
+            };
+        }
+
+        // Typing in a lobby field
+        if let Some(field) = self.field {
+            match _event {
+                InputEvent::Text(character) if !character.is_control() && OnlineScene::field_mut(_shared, field).chars().count() < MAX_FIELD_LENGTH => {
+                    OnlineScene::field_mut(_shared, field).push(character);
+                    _shared.config.unsaved = true;
+                    return Transition::None;
+                },
+                InputEvent::Key(KeyCode::Back) => {
+                    OnlineScene::field_mut(_shared, field).pop();
+                    _shared.config.unsaved = true;
+                    return Transition::None;
+                },
+                InputEvent::Key(KeyCode::Tab) => {
+                    self.field = Some((field + 1) % 5);
+                    return Transition::None;
+                },
+                InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) | InputEvent::Key(KeyCode::Escape) => {
+                    self.field = None;
+                    return Transition::None;
+                },
+                _ => {}
+            }
+        }
+
+        match _event {
+            InputEvent::Key(KeyCode::Escape) => return Transition::Pop,
+            InputEvent::Click { x, y } => {
+                let searching = matches!(self.stage, Stage::Connecting(_) | Stage::Waiting(_));
+                let buttons = [(FIND_BUTTON, Via::Matchmaker), (HOST_BUTTON, Via::Host), (JOIN_BUTTON, Via::Join), (LICHESS_BUTTON, Via::Lichess)];
+                let clicked = buttons.iter()
+                    .find(|(_rect, _)| in_rect(*_rect, x, y))
+                    .map(|(_, _via)| *_via);
+
+                // Dropping the connection takes the player off the waiting list, or stops hosting. Another search
+                // replaces the one running.
+                if let Some(via) = clicked {
+                    match via {
+                        _ if searching && via == self.via => self.stage = Stage::Lobby,
+                        Via::Matchmaker => self.find_opponent(_shared),
+                        Via::Host => {
+                            self.stage = Stage::Lobby;
+                            self.host(_shared);
+                        },
+                        Via::Join => self.join(_shared),
+                        Via::Lichess => self.lichess(_shared)
+                    }
+                }
+                else if in_rect(BACK_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+                else if in_rect(NAME_FIELD, x, y) {
+                    self.field = Some(0);
+                }
+                else if in_rect(SERVER_FIELD, x, y) {
+                    self.field = Some(1);
+                }
+                else if in_rect(LAN_FIELD, x, y) {
+                    self.field = Some(2);
+                }
+                else if in_rect(TOKEN_FIELD, x, y) {
+                    self.field = Some(3);
+                }
+                else if in_rect(KEY_FIELD, x, y) {
+                    self.field = Some(4);
+                }
+                else {
+                    self.field = None;
+                }
+            },
+            _ => {}
+        }
+
+        Transition::None
+    }
+
+    // The promotion panel while playing, the lobby when not typing in it
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        match self.stage {
+            Stage::Playing { .. } if _shared.controller.promoting => _shared.controller.regions().rects(),
+            Stage::Playing { .. } => vec![],
+            _ if self.field.is_some() => vec![],
+            _ => vec![NAME_FIELD, SERVER_FIELD, LAN_FIELD, TOKEN_FIELD, HOST_BUTTON, JOIN_BUTTON, FIND_BUTTON, LICHESS_BUTTON, KEY_FIELD, BACK_BUTTON]
+        }
+    }
+}
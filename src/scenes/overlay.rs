@@ -0,0 +1,30 @@
+use super::{Scene, Shared, Transition};
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{draw_funcs, renderer::{Renderer, ZoomRenderer}};
+use super::super::consts::{GRID_SIZE, GRID_CELL_SIZE, OVERLAY_SIZE, CHROMA_KEY};
+
+/// `--overlay` mode: only the board and the clocks on a chroma key background, for capturing in OBS.
+/// The game can still be played, promotions are picked with Q, N, R or B.
+pub struct OverlayScene;
+
+impl Scene for OverlayScene {
+    fn name(&self) -> &'static str {
+        "overlay"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        let view = _shared.controller.view;
+        draw_funcs::draw_board(&mut ZoomRenderer::new(_renderer, view.zoom, view.offset), &_shared.theme, &_shared.controller, _shared.board_style());
+
+        // Everything around the board is keyed out
+        let board_height = (GRID_SIZE * GRID_CELL_SIZE.1) as f32;
+        _renderer.draw_rect((0.0, board_height, OVERLAY_SIZE.0, OVERLAY_SIZE.1 - board_height), CHROMA_KEY);
+
+        draw_funcs::draw_clocks(_renderer, &_shared.controller, board_height + 13.0, true, _shared.config.reduced_motion);
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        _shared.controller.handle_input(_event);
+        Transition::None
+    }
+}
@@ -0,0 +1,34 @@
+use super::{Scene, Shared, Transition};
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{renderer::Renderer, text_layout::TextLayout};
+
+/// Covers the game while it is paused, see `Shared::paused`. Pushed when the window loses focus, a click or a key
+/// resumes the game.
+pub struct PausedScene;
+
+impl Scene for PausedScene {
+    fn name(&self) -> &'static str {
+        "paused"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        _renderer.draw_rect((0.0, 0.0, 720.0, 870.0), [0.0, 0.0, 0.0, 0.6]);
+
+        TextLayout::centred("Paused — click to resume", 40.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, (0.0, 380.0, 720.0, 60.0));
+        TextLayout::centred("The clocks and bots wait until you are back", 20.0, [0.7, 0.7, 0.7, 1.0]).draw(_renderer, (0.0, 440.0, 720.0, 30.0));
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Click { .. } | InputEvent::Key(_) => {
+                _shared.paused = false;
+                Transition::Pop
+            },
+            _ => Transition::None
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,111 @@
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Toast, Transition};
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer};
+use super::super::help_funcs::in_rect;
+use super::super::pgn::{self, PgnHeaders, PGN_FILE};
+use super::super::consts::TOAST_TIME;
+
+/// Longest header that can be typed
+const MAX_FIELD_LENGTH: usize = 48;
+
+// One text field per header, label on the left
+const FIRST_FIELD: f32 = 180.0;
+const FIELD_SPACING: f32 = 70.0;
+const EXPORT_BUTTON: (f32, f32, f32, f32) = (80.0, 770.0, 260.0, 60.0);
+const CANCEL_BUTTON: (f32, f32, f32, f32) = (380.0, 770.0, 260.0, 60.0);
+
+/// The headers of the PGN export, edited before the game is written to `PGN_FILE`.
+/// Tab or the arrows move between the fields, Enter exports and Escape goes back without exporting.
+pub struct PgnHeadersScene {
+    headers: PgnHeaders,
+    field: usize // Field being typed in, an index into `PgnHeaders::FIELDS`
+}
+
+impl PgnHeadersScene {
+    /// The form, filled in with the defaults for the game
+    pub fn new(_shared: &Shared) -> PgnHeadersScene {
+        PgnHeadersScene { headers: PgnHeaders::defaults(&_shared.controller), field: 0 }
+    }
+
+    /// Rectangle of a text field
+    fn field_rect(_index: usize) -> (f32, f32, f32, f32) {
+        (220.0, FIRST_FIELD + _index as f32 * FIELD_SPACING, 440.0, 50.0)
+    }
+
+    /// Writes the game as PGN with the given headers for `AppState` to save, and says how that went
+    pub fn export(_shared: &mut Shared, _headers: &PgnHeaders) {
+        match pgn::game_to_pgn(&_shared.controller, _headers) {
+            Ok(text) => {
+                _shared.exports.push((PGN_FILE.to_string(), text));
+                _shared.toasts.push(Toast {
+                    title: "Game exported".to_string(),
+                    message: format!("Written to {} in the user data directory", PGN_FILE.trim_start_matches('/')),
+                    remaining: TOAST_TIME
+                });
+            },
+            Err(error) => _shared.controller.banner = Some(format!("PGN export failed: {}", error))
+        }
+    }
+}
+
+impl Scene for PgnHeadersScene {
+    fn name(&self) -> &'static str {
+        "pgn_headers"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Export PGN", 80.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        for (_index, _name) in PgnHeaders::FIELDS.iter().enumerate() {
+            let rect = PgnHeadersScene::field_rect(_index);
+            _renderer.draw_text(_name, (60.0, rect.1 + 12.0), 26.0, [0.7, 0.7, 0.7, 1.0]);
+
+            if _index == self.field {
+                _renderer.draw_rect((rect.0 - 3.0, rect.1 - 3.0, rect.2 + 6.0, rect.3 + 6.0), [0.97, 0.3, 0.0, 1.0]);
+            }
+            _renderer.draw_rect(rect, [0.1, 0.1, 0.1, 1.0]);
+
+            let cursor = if _index == self.field { "_" } else { "" };
+            _renderer.draw_text(&format!("{}{}", self.headers.field(_index), cursor), (rect.0 + 12.0, rect.1 + 12.0), 26.0, [1.0, 1.0, 1.0, 1.0]);
+        }
+
+        draw_funcs::draw_button(_renderer, EXPORT_BUTTON, "Export");
+        draw_funcs::draw_button(_renderer, CANCEL_BUTTON, "Cancel");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        let fields = PgnHeaders::FIELDS.len();
+
+        match _event {
+            InputEvent::Key(KeyCode::Escape) => return Transition::Pop,
+            InputEvent::Key(KeyCode::Tab) | InputEvent::Key(KeyCode::Down) => self.field = (self.field + 1) % fields,
+            InputEvent::Key(KeyCode::Up) => self.field = (self.field + fields - 1) % fields,
+            InputEvent::Key(KeyCode::Back) => { self.headers.field_mut(self.field).pop(); },
+            InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) => {
+                PgnHeadersScene::export(_shared, &self.headers);
+                return Transition::Pop;
+            },
+            InputEvent::Text(character) if !character.is_control() && self.headers.field(self.field).chars().count() < MAX_FIELD_LENGTH => {
+                self.headers.field_mut(self.field).push(character);
+            },
+            InputEvent::Click { x, y } => {
+                if in_rect(EXPORT_BUTTON, x, y) {
+                    PgnHeadersScene::export(_shared, &self.headers);
+                    return Transition::Pop;
+                }
+                else if in_rect(CANCEL_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+                else if let Some(index) = (0..fields).find(|_index| in_rect(PgnHeadersScene::field_rect(*_index), x, y)) {
+                    self.field = index;
+                }
+            },
+            _ => {}
+        }
+
+        Transition::None
+    }
+}
@@ -0,0 +1,393 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use ggez::event::KeyCode;
+use std::rc::Rc;
+use super::{Scene, Shared, Toast, Transition};
+use super::main_menu::MainMenuScene;
+use super::mate_patterns::MatePatternsScene;
+use super::super::bot::{Bot, load_bot, minimax_bot::MinimaxBot};
+use super::super::clipboard;
+use super::super::clock::TimeControl;
+use super::super::consts::{GRID_SIZE, GRID_CELL_SIZE, TOAST_TIME};
+use super::super::controller::{InputEvent, Command};
+use super::super::fen;
+use super::super::graphics_funcs::{draw_funcs, renderer::{Renderer, ZoomRenderer}, text_layout::TextLayout, widgets::{Button, Regions}};
+use super::super::help_funcs::{board_square, in_rect};
+use super::super::speedrun::Difficulty;
+use super::super::validator::validate_setup;
+
+/// Pieces of the editor's palette, in the order they are drawn. The slot after them takes pieces off.
+const PALETTE: [Piece; 12] = [
+    Piece::Pawn(Colour::White), Piece::Knight(Colour::White), Piece::Bishop(Colour::White),
+    Piece::Rook(Colour::White), Piece::Queen(Colour::White), Piece::King(Colour::White),
+    Piece::Pawn(Colour::Black), Piece::Knight(Colour::Black), Piece::Bishop(Colour::Black),
+    Piece::Rook(Colour::Black), Piece::Queen(Colour::Black), Piece::King(Colour::Black)
+];
+
+/// Placement of the standard starting position, for the editor's "Start" button
+const START_PLACEMENT: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR";
+
+/// Where the FEN of the edited position is shown, clicking it copies it
+const FEN_FIELD: (f32, f32, f32, f32) = (20.0, 830.0, 680.0, 22.0);
+
+/// Positions the editor sets up or changes in one click
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Preset {
+    Empty,
+    Start, // The standard starting position, White to move
+    Mirror, // The board upside down with the sides swapped, the same position for the other side
+    NoPawns
+}
+
+/// The buttons of the presets, under the palette
+fn preset_buttons() -> Vec<Button<Preset>> {
+    [(Preset::Empty, "Empty"), (Preset::Start, "Start"), (Preset::Mirror, "Mirror"), (Preset::NoPawns, "No pawns")].iter()
+        .enumerate()
+        .map(|(_index, (_preset, _label))| Button::new((20.0 + _index as f32 * 172.0, 792.0, 160.0, 32.0), _label, *_preset))
+        .collect()
+}
+
+/// The same piece of the other side
+fn other_side(_piece: Piece) -> Piece {
+    let letter = fen::piece_char(&_piece);
+    let swapped = if letter.is_ascii_uppercase() { letter.to_ascii_lowercase() } else { letter.to_ascii_uppercase() };
+    fen::char_piece(swapped).unwrap_or(_piece)
+}
+
+/// A piece carried with the mouse while editing
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Carried {
+    piece: Piece,
+    from: Option<(u8, u8)>, // Square it was picked up from, None when it was taken from the palette
+    at: (f32, f32) // Where the mouse is
+}
+
+/// A study workbench: takebacks are free, either side can be handed to the engine and back at any time, and the
+/// position can be edited on the fly. Games here are untimed and count for no stats.
+pub struct PracticeScene {
+    editing: Option<Vec<((u8, u8), Piece)>>, // The position being edited in GUI coordinates, None while playing
+    brush: Option<Piece>, // What clicks on the board put down while editing, None takes pieces off
+    carried: Option<Carried>, // The piece dragged while editing, off the board until it is dropped
+    to_move: Colour, // Side to move in the position being edited
+    time_control: Option<TimeControl> // Clock of the settings, given back when leaving
+}
+
+impl PracticeScene {
+    /// Starts an untimed game of standard chess with both sides played by clicking
+    pub fn new(_shared: &mut Shared) -> PracticeScene {
+        let time_control = _shared.controller.time_control.take();
+
+        _shared.controller.set_bot(Colour::White, None);
+        _shared.controller.set_bot(Colour::Black, None);
+        _shared.controller.vote = None;
+        _shared.controller.variant = Rc::clone(&_shared.variants[0]);
+        _shared.speedrun = None;
+        _shared.controller.execute(Command::Restart);
+
+        PracticeScene { editing: None, brush: Some(PALETTE[0]), carried: None, to_move: Colour::White, time_control }
+    }
+
+    /// Rectangle of a slot of the palette, the slot after the pieces is the eraser
+    fn palette_slot(_index: usize) -> (f32, f32, f32, f32) {
+        (20.0 + _index as f32 * 52.0, 735.0, 48.0, 48.0)
+    }
+
+    /// The engine a side is handed to: the first bot in `bots/` at full strength, or the built-in minimax bot on hard when there are none
+    fn engine(_shared: &Shared) -> Box<dyn Bot> {
+        match _shared.bot_scripts.first().map(|_path| load_bot(_path, None)) {
+            Some(Ok(bot)) => bot,
+            _ => Box::new(MinimaxBot::new(Difficulty::Hard))
+        }
+    }
+
+    /// Hands a side to the engine, or back to the player when the engine has it
+    fn toggle_engine(_shared: &mut Shared, _colour: Colour) {
+        let bot = if _shared.controller.bots.contains_key(&_colour) { None } else { Some(PracticeScene::engine(_shared)) };
+        _shared.controller.set_bot(_colour, bot);
+    }
+
+    /// Puts the brush on a square while editing, a click with the piece already there takes it off
+    fn paint(&mut self, _square: (u8, u8)) {
+        let brush = self.brush;
+        if let Some(pieces) = self.editing.as_mut() {
+            let before = pieces.iter().position(|(_at, _)| *_at == _square).map(|_index| pieces.remove(_index).1);
+
+            if let Some(piece) = brush.filter(|_piece| before != Some(*_piece)) {
+                pieces.push((_square, piece));
+            }
+        }
+    }
+
+    /// Puts a piece on a square while editing, or takes off what is there with None
+    fn place(&mut self, _square: (u8, u8), _piece: Option<Piece>) {
+        if let Some(pieces) = self.editing.as_mut() {
+            pieces.retain(|(_at, _)| *_at != _square);
+            if let Some(piece) = _piece {
+                pieces.push((_square, piece));
+            }
+        }
+    }
+
+    /// ## `drop`
+    /// Lets go of the piece carried, on the square under the mouse. Dropped off the board it is taken off.
+    /// A piece let go where it was picked up was clicked rather than dragged, and the click paints the square as usual.
+    fn drop(&mut self, _carried: Carried, _x: f32, _y: f32) {
+        match (_carried.from, board_square(_x, _y)) {
+            (Some(from), Some(square)) if from == square => {
+                self.place(square, Some(_carried.piece));
+                self.paint(square);
+            },
+            (_, Some(square)) => self.place(square, Some(_carried.piece)),
+            (_, None) => {}
+        }
+    }
+
+    /// Sets up or changes the edited position with a preset
+    fn apply(&mut self, _preset: Preset) {
+        let pieces = match self.editing.as_mut() {
+            Some(pieces) => pieces,
+            None => return
+        };
+
+        match _preset {
+            Preset::Empty => pieces.clear(),
+            Preset::Start => {
+                *pieces = fen::placement(START_PLACEMENT).unwrap_or_default();
+                self.to_move = Colour::White;
+            },
+            Preset::Mirror => {
+                for (_square, _piece) in pieces.iter_mut() {
+                    *_square = (_square.0, 7 - _square.1);
+                    *_piece = other_side(*_piece);
+                }
+                self.to_move = !self.to_move;
+            },
+            Preset::NoPawns => pieces.retain(|(_, _piece)| !matches!(_piece, Piece::Pawn(_)))
+        }
+    }
+
+    /// Copies the FEN of the edited position, shown in a toast
+    fn copy_fen(&self, _shared: &mut Shared) {
+        let fen = match &self.editing {
+            Some(pieces) => fen::from_placement(pieces, self.to_move),
+            None => return
+        };
+
+        _shared.toasts.push(match clipboard::copy(&fen) {
+            Ok(()) => Toast { title: "Position copied as FEN".to_string(), message: fen, remaining: TOAST_TIME },
+            Err(error) => Toast { title: "Position not copied".to_string(), message: error, remaining: TOAST_TIME }
+        });
+    }
+
+    /// Plays on from the edited position, if it is valid and the backend can set it up. What is wrong with an
+    /// invalid position is shown on the board while editing.
+    fn finish_editing(&mut self, _shared: &mut Shared) {
+        let pieces = match self.editing.take() {
+            Some(pieces) => pieces,
+            None => return
+        };
+        if !validate_setup(&pieces, self.to_move).is_empty() {
+            self.editing = Some(pieces);
+            return;
+        }
+
+        if let Err(error) = _shared.controller.try_execute(Command::LoadFen(fen::from_placement(&pieces, self.to_move))) {
+            _shared.controller.banner = Some(format!("Position not set up: {}", error));
+            self.editing = Some(pieces);
+        }
+    }
+
+    /// Handles input while editing the position
+    fn edit_input(&mut self, _shared: &mut Shared, _event: InputEvent) {
+        match _event {
+            // Pieces are picked up off the board, or taken from the palette, which chooses them as the brush too
+            InputEvent::Press { x, y } => {
+                let on_board = board_square(x, y).and_then(|_square| {
+                    let piece = self.editing.as_ref()?.iter().find(|(_at, _)| *_at == _square)?.1;
+                    Some(Carried { piece, from: Some(_square), at: (x, y) })
+                });
+                let from_palette = (0..PALETTE.len()).find(|_index| in_rect(PracticeScene::palette_slot(*_index), x, y)).map(|_index| {
+                    self.brush = Some(PALETTE[_index]);
+                    Carried { piece: PALETTE[_index], from: None, at: (x, y) }
+                });
+
+                self.carried = on_board.or(from_palette);
+                if let Some(square) = self.carried.and_then(|_carried| _carried.from) {
+                    self.place(square, None);
+                }
+            },
+            InputEvent::Motion { x, y } => {
+                if let Some(carried) = self.carried.as_mut() {
+                    carried.at = (x, y);
+                }
+            },
+            InputEvent::Click { x, y } => match (self.carried.take(), board_square(x, y)) {
+                (Some(carried), _) => self.drop(carried, x, y),
+                (None, Some(square)) => self.paint(square),
+                (None, None) => {
+                    if let Some(index) = (0..=PALETTE.len()).find(|_index| in_rect(PracticeScene::palette_slot(*_index), x, y)) {
+                        self.brush = PALETTE.get(index).copied();
+                    }
+                    else if let Some(preset) = Regions::of(&preset_buttons()).hit(x, y) {
+                        self.apply(preset);
+                    }
+                    else if in_rect(FEN_FIELD, x, y) {
+                        self.copy_fen(_shared);
+                    }
+                }
+            },
+            InputEvent::RightClick { x, y, .. } => {
+                if let Some(square) = board_square(x, y) {
+                    self.place(square, None);
+                }
+            },
+            InputEvent::Key(KeyCode::T) => self.to_move = !self.to_move,
+            InputEvent::Key(KeyCode::C) => self.editing = Some(vec![]),
+            InputEvent::Key(KeyCode::E) | InputEvent::Key(KeyCode::Return) => self.finish_editing(_shared),
+            InputEvent::Key(KeyCode::Escape) => {
+                self.editing = None;
+                self.carried = None;
+            },
+            _ => {}
+        }
+    }
+}
+
+impl Scene for PracticeScene {
+    fn name(&self) -> &'static str {
+        "practice"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        let pieces = match &self.editing {
+            Some(pieces) => pieces,
+            None => {
+                let view = _shared.controller.view;
+                draw_funcs::draw_board(&mut ZoomRenderer::new(_renderer, view.zoom, view.offset), &_shared.theme, &_shared.controller, _shared.board_style());
+                if view.is_zoomed() {
+                    draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+                }
+                draw_funcs::draw_status_panel(_renderer, &_shared.theme, &_shared.controller, _shared.turn_indicator, _shared.messages(), _shared.config.reduced_motion);
+
+                // Promotions are chosen here rather than over a promotion scene, which would end the game on a result screen
+                if _shared.controller.promoting {
+                    draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+                    draw_funcs::draw_promotion_panel(_renderer, _shared.controller.game.active_colour());
+                }
+                // The notice of a refused move takes the line when there is one
+                else if _shared.controller.notice.is_none() {
+                    TextLayout::centred("Left / Right: take back / redo   W / B: engine   E: edit   R: restart   M: mate patterns", 14.0, _shared.theme.text)
+                        .draw(_renderer, (20.0, 835.0, 680.0, 25.0));
+                }
+                return;
+            }
+        };
+
+        // The edited position, always seen from White's side
+        let board_size = (GRID_SIZE * GRID_CELL_SIZE.0) as f32;
+        draw_funcs::draw_background(_renderer, _shared.theme.background);
+        draw_funcs::draw_small_board(_renderer, &_shared.theme, pieces, (0.0, 0.0), board_size);
+
+        // What keeps the position from being played, along the bottom of the board as it is edited
+        let errors = validate_setup(pieces, self.to_move);
+        if !errors.is_empty() {
+            let top = board_size - 10.0 - errors.len() as f32 * 26.0;
+            _renderer.draw_rect((0.0, top, board_size, board_size - top), [0.6, 0.05, 0.05, 0.85]);
+            for (_index, _error) in errors.iter().enumerate() {
+                TextLayout::centred(_error, 18.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, (0.0, top + 5.0 + _index as f32 * 26.0, board_size, 26.0));
+            }
+        }
+        draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+
+        for _index in 0..=PALETTE.len() {
+            let slot = PracticeScene::palette_slot(_index);
+            let selected = PALETTE.get(_index).copied() == self.brush;
+
+            if selected {
+                _renderer.draw_rect((slot.0 - 3.0, slot.1 - 3.0, slot.2 + 6.0, slot.3 + 6.0), [0.97, 0.3, 0.0, 1.0]);
+            }
+            _renderer.draw_rect(slot, [0.3, 0.3, 0.3, 1.0]);
+
+            match PALETTE.get(_index) {
+                Some(piece) => draw_funcs::draw_icon(_renderer, (slot.0, slot.1), piece, slot.2 / GRID_CELL_SIZE.0 as f32),
+                None => TextLayout::centred("x", 28.0, [1.0, 0.5, 0.5, 1.0]).draw(_renderer, slot)
+            }
+        }
+
+        for _button in preset_buttons() {
+            _button.draw(_renderer);
+        }
+
+        // The FEN follows every change, to copy it out with a click
+        _renderer.draw_rect(FEN_FIELD, [0.1, 0.1, 0.1, 1.0]);
+        TextLayout::centred(&fen::from_placement(pieces, self.to_move), 14.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, FEN_FIELD);
+
+        let side = if self.to_move == Colour::White { "White" } else { "Black" };
+        TextLayout::centred(&format!("{} to move (T)   E: play from here   C: clear   Escape: cancel", side), 12.0, _shared.theme.text)
+            .draw(_renderer, (20.0, 853.0, 680.0, 16.0));
+
+        // The carried piece under the mouse, over everything else
+        if let Some(carried) = self.carried {
+            let half = GRID_CELL_SIZE.0 as f32 / 2.0;
+            draw_funcs::draw_icon(_renderer, (carried.at.0 - half, carried.at.1 - half), &carried.piece, 1.0);
+        }
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        if self.editing.is_some() {
+            self.edit_input(_shared, _event);
+            return Transition::None;
+        }
+        // The controller knows where the choices are, it ignores everything else while promoting
+        if _shared.controller.promoting {
+            _shared.controller.handle_input(_event);
+            return Transition::None;
+        }
+
+        let command = match _event {
+            // Against the engine a takeback goes back to the player's move, otherwise one move at a time
+            InputEvent::Key(KeyCode::Left) if _shared.controller.bots.is_empty() => Some(Command::Undo),
+            InputEvent::Key(KeyCode::Left) => Some(Command::TakeBack),
+            InputEvent::Key(KeyCode::Right) => Some(Command::Redo),
+            InputEvent::Key(KeyCode::R) => Some(Command::Restart),
+            _ => None
+        };
+        if let Some(command) = command {
+            _shared.controller.notice = _shared.controller.try_execute(command).err();
+            return Transition::None;
+        }
+
+        match _event {
+            InputEvent::Key(KeyCode::W) => PracticeScene::toggle_engine(_shared, Colour::White),
+            InputEvent::Key(KeyCode::B) => PracticeScene::toggle_engine(_shared, Colour::Black),
+            InputEvent::Key(KeyCode::M) => return Transition::Push(Box::new(MatePatternsScene::new())),
+            InputEvent::Key(KeyCode::E) => {
+                let game = &_shared.controller.game;
+                self.editing = Some((0..8u8).flat_map(|_col| (0..8u8).map(move |_row| (_col, _row)))
+                    .filter_map(|_square| game.piece_at(_square).map(|_piece| (_square, _piece)))
+                    .collect());
+                self.to_move = game.active_colour();
+            },
+            // Escape drops the selection first, a second one leaves practice
+            InputEvent::Key(KeyCode::Escape) if _shared.controller.previous_click.is_none() => {
+                _shared.controller.time_control = self.time_control.take();
+                return Transition::Reset(Box::new(MainMenuScene));
+            },
+            _ => { _shared.controller.handle_input(_event); }
+        }
+
+        Transition::None
+    }
+
+    // The promotion panel, or the presets and the FEN while editing. The keys are the workbench's otherwise.
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        match &self.editing {
+            Some(_) => {
+                let mut rects = Regions::of(&preset_buttons()).rects();
+                rects.push(FEN_FIELD);
+                rects
+            },
+            None if _shared.controller.promoting => _shared.controller.regions().rects(),
+            None => vec![]
+        }
+    }
+}
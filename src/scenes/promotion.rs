@@ -0,0 +1,45 @@
+use super::{Scene, Shared, Transition};
+use super::game_over::GameOverScene;
+use super::hand_over::HandOverScene;
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer};
+
+/// The choice of piece to promote to, shown over the panel below the board
+pub struct PromotionScene;
+
+impl Scene for PromotionScene {
+    fn name(&self) -> &'static str {
+        "promotion"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+        draw_funcs::promotion_dialog(_shared.controller.game.active_colour()).draw(_renderer);
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        // The controller takes the choices of the promotion dialog, it ignores everything else while promoting
+        _shared.controller.handle_input(_event);
+
+        if _shared.controller.promoting {
+            Transition::None
+        }
+        else if _shared.controller.is_over() {
+            Transition::Replace(Box::new(GameOverScene::new(_shared)))
+        }
+        else if _shared.hand_over {
+            Transition::Replace(Box::new(HandOverScene))
+        }
+        else {
+            Transition::Pop
+        }
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        draw_funcs::promotion_dialog(_shared.controller.game.active_colour()).focusables()
+    }
+}
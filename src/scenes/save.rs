@@ -0,0 +1,61 @@
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Transition};
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer};
+use super::super::saves::SavedGame;
+use super::super::recent::RecentKind;
+
+/// Longest slot name that can be typed
+const MAX_NAME_LENGTH: usize = 32;
+
+/// Asks for the name of a save slot over the panel, then saves the game there
+pub struct SaveScene {
+    name: String // What is being typed
+}
+
+impl SaveScene {
+    /// Creates the prompt with an empty name
+    pub fn new() -> SaveScene {
+        SaveScene { name: String::new() }
+    }
+}
+
+impl Scene for SaveScene {
+    fn name(&self) -> &'static str {
+        "save"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+
+        _renderer.draw_text("Save as (Enter to save, Escape to cancel):", (20.0, 745.0), 20.0, _shared.theme.text);
+        _renderer.draw_rect((20.0, 785.0, 680.0, 50.0), [0.1, 0.1, 0.1, 1.0]);
+        _renderer.draw_text(&format!("{}_", self.name), (32.0, 797.0), 26.0, [1.0, 1.0, 1.0, 1.0]);
+
+        // Saving over a slot that is taken replaces it
+        if _shared.saves.get(self.name.trim()).is_some() {
+            _renderer.draw_text("replaces the saved game", (500.0, 748.0), 16.0, [0.97, 0.3, 0.0, 1.0]);
+        }
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Key(KeyCode::Escape) => return Transition::Pop,
+            InputEvent::Key(KeyCode::Back) => { self.name.pop(); },
+            InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) if !self.name.trim().is_empty() => {
+                let game = SavedGame::from_controller(self.name.trim(), &_shared.controller);
+                _shared.recent.add(RecentKind::Save, game.clone());
+                _shared.saves.save(game);
+                return Transition::Pop;
+            },
+            InputEvent::Text(character) if !character.is_control() && self.name.chars().count() < MAX_NAME_LENGTH => self.name.push(character),
+            _ => {}
+        }
+
+        Transition::None
+    }
+
+    fn is_overlay(&self) -> bool {
+        true
+    }
+}
@@ -0,0 +1,307 @@
+use std::rc::Rc;
+use super::{Scene, Shared, Transition};
+use super::audio_settings::AudioSettingsScene;
+use super::super::controller::InputEvent;
+use super::super::clock::TimeControl;
+use super::super::graphics_funcs::{draw_funcs::{self, TurnIndicator}, renderer::Renderer, widgets::{Button, Regions}};
+
+// One button per setting, clicking cycles through the values
+const CLOCK_BUTTON: (f32, f32, f32, f32) = (160.0, 150.0, 260.0, 40.0);
+const ODDS_BUTTON: (f32, f32, f32, f32) = (430.0, 150.0, 130.0, 40.0);
+const VALIDATION_BUTTON: (f32, f32, f32, f32) = (160.0, 196.0, 400.0, 40.0);
+const CASUAL_BUTTON: (f32, f32, f32, f32) = (160.0, 242.0, 400.0, 40.0);
+const HAND_OVER_BUTTON: (f32, f32, f32, f32) = (160.0, 288.0, 400.0, 40.0);
+const TURN_INDICATOR_BUTTON: (f32, f32, f32, f32) = (160.0, 334.0, 400.0, 40.0);
+const THEME_BUTTON: (f32, f32, f32, f32) = (160.0, 380.0, 260.0, 40.0);
+const TEXT_SIZE_BUTTON: (f32, f32, f32, f32) = (430.0, 380.0, 130.0, 40.0);
+const PIECES_BUTTON: (f32, f32, f32, f32) = (160.0, 426.0, 260.0, 40.0);
+const MARGIN_BUTTON: (f32, f32, f32, f32) = (430.0, 426.0, 130.0, 40.0);
+const VARIANT_BUTTON: (f32, f32, f32, f32) = (160.0, 472.0, 400.0, 40.0);
+const ARBITER_BUTTON: (f32, f32, f32, f32) = (160.0, 518.0, 400.0, 40.0);
+const MESSAGES_BUTTON: (f32, f32, f32, f32) = (160.0, 564.0, 400.0, 40.0);
+const UNFOCUSED_BUTTON: (f32, f32, f32, f32) = (160.0, 610.0, 400.0, 40.0);
+const LOW_SPEC_BUTTON: (f32, f32, f32, f32) = (160.0, 656.0, 260.0, 40.0);
+const MOTION_BUTTON: (f32, f32, f32, f32) = (430.0, 656.0, 130.0, 40.0);
+const BOOK_BUTTON: (f32, f32, f32, f32) = (160.0, 702.0, 400.0, 40.0);
+const AUDIO_BUTTON: (f32, f32, f32, f32) = (80.0, 770.0, 260.0, 60.0);
+const BACK_BUTTON: (f32, f32, f32, f32) = (380.0, 770.0, 260.0, 60.0);
+
+/// Time controls to choose from, as (minutes, increment seconds). None is an untimed game.
+const TIME_CONTROLS: [Option<(f32, f32)>; 5] = [None, Some((1.0, 0.0)), Some((5.0, 0.0)), Some((5.0, 3.0)), Some((15.0, 10.0))];
+
+/// Sizes of the text to choose from, as a scale of the normal size
+const TEXT_SCALES: [f32; 3] = [1.0, 1.25, 1.5];
+
+/// Margins around the pieces to choose from, in pixels to each edge of the tile
+const PIECE_MARGINS: [f32; 4] = [0.0, 4.0, 8.0, 12.0];
+
+/// What each button of the settings changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Setting {
+    Clock,
+    Odds,
+    Validation,
+    Casual,
+    HandOver,
+    TurnIndicator,
+    Theme,
+    TextSize,
+    Pieces,
+    Margin,
+    Variant,
+    Arbiter,
+    Messages,
+    Unfocused,
+    LowSpec,
+    Motion,
+    Book,
+    Audio,
+    Back
+}
+
+/// Settings, applied from the next game on
+pub struct SettingsScene;
+
+impl SettingsScene {
+    /// Label of the clock button
+    fn clock_label(_shared: &Shared) -> String {
+        if let Some((white, black)) = _shared.controller.variant.time_controls() {
+            // The variant's name is on its own button
+            return format!("Clock: {} vs {} min", white.initial / 60.0, black.initial / 60.0);
+        }
+
+        match (_shared.controller.time_control, _shared.controller.time_odds) {
+            // Under time odds this is White's clock
+            (Some(time_control), Some(_)) => format!("White: {}", time_control.label()),
+            (Some(time_control), None) => format!("Clock: {}", time_control.label()),
+            (None, _) => "Clock: Off".to_string()
+        }
+    }
+
+    /// Label of the time odds button, Black's clock when it differs from White's
+    fn odds_label(_shared: &Shared) -> String {
+        match (_shared.controller.time_control, _shared.controller.time_odds) {
+            (Some(_), Some(odds)) => format!("Black: {}", odds.label()),
+            _ => "Odds: Off".to_string()
+        }
+    }
+
+    /// Label of the piece set button
+    fn pieces_label(_shared: &Shared) -> String {
+        match _shared.piece_set.and_then(|_index| _shared.piece_sets.get(_index)) {
+            Some(set) => format!("Pieces: {}", set.name),
+            None => "Pieces: Default".to_string()
+        }
+    }
+
+    /// Label of the variant button, with the position chosen for Chess960
+    fn variant_label(_shared: &Shared) -> String {
+        match _shared.controller.variant.chess960() {
+            Some(chess960) => format!("Variant: Chess960 ({})", chess960.chosen().map_or("random".to_string(), |_position| format!("#{}", _position))),
+            None => format!("Variant: {}", _shared.controller.variant.name())
+        }
+    }
+
+    /// Moves on to the next theme, built-in themes first and then the ones from plugins
+    fn next_theme(_shared: &mut Shared) {
+        let current = _shared.themes.iter().position(|_theme| *_theme == _shared.theme);
+        let next = current.map_or(0, |_index| (_index + 1) % _shared.themes.len());
+
+        if let Some(theme) = _shared.themes.get(next) {
+            _shared.theme = theme.clone();
+        }
+    }
+
+    /// Moves on to the next text size in `TEXT_SCALES`
+    fn next_text_scale(_shared: &mut Shared) {
+        let current = TEXT_SCALES.iter().position(|_scale| *_scale == _shared.config.text_scale).unwrap_or(0);
+
+        _shared.config.text_scale = TEXT_SCALES[(current + 1) % TEXT_SCALES.len()];
+        _shared.config.unsaved = true;
+    }
+
+    /// Moves on to the next margin in `PIECE_MARGINS`
+    fn next_piece_margin(_shared: &mut Shared) {
+        let current = PIECE_MARGINS.iter().position(|_margin| *_margin == _shared.config.piece_margin).unwrap_or(0);
+
+        _shared.config.piece_margin = PIECE_MARGINS[(current + 1) % PIECE_MARGINS.len()];
+        _shared.config.unsaved = true;
+    }
+
+    /// Moves on to the next piece set: the default pieces, then every set from plugins
+    fn next_piece_set(_shared: &mut Shared) {
+        _shared.piece_set = match _shared.piece_set {
+            None if !_shared.piece_sets.is_empty() => Some(0),
+            Some(index) if index + 1 < _shared.piece_sets.len() => Some(index + 1),
+            _ => None
+        };
+    }
+
+    /// Moves on to the next variant, standard chess first
+    fn next_variant(_shared: &mut Shared) {
+        let current = _shared.variants.iter().position(|_variant| Rc::ptr_eq(_variant, &_shared.controller.variant));
+        let next = current.map_or(0, |_index| (_index + 1) % _shared.variants.len());
+
+        if let Some(variant) = _shared.variants.get(next) {
+            _shared.controller.variant = Rc::clone(variant);
+        }
+    }
+
+    /// Moves on to the next message pack, built-in packs first and then the ones from plugins
+    fn next_messages(_shared: &mut Shared) {
+        let current = _shared.message_packs.iter().position(|_pack| _pack.name == _shared.messages().name);
+        let next = current.map_or(0, |_index| (_index + 1) % _shared.message_packs.len());
+
+        if let Some(pack) = _shared.message_packs.get(next) {
+            _shared.config.messages = pack.name.clone();
+            _shared.config.unsaved = true;
+        }
+    }
+
+    /// Moves on to the next time control in `TIME_CONTROLS`
+    fn next_time_control(_shared: &mut Shared) {
+        let current = TIME_CONTROLS.iter().position(|_choice| {
+            _choice.map(|(minutes, increment)| TimeControl::minutes(minutes, increment)) == _shared.controller.time_control
+        }).unwrap_or(0);
+
+        _shared.controller.time_control = TIME_CONTROLS[(current + 1) % TIME_CONTROLS.len()]
+            .map(|(minutes, increment)| TimeControl::minutes(minutes, increment));
+
+        // Odds of the same clock are no odds, and an untimed game has none
+        if _shared.controller.time_control.is_none() || _shared.controller.time_odds == _shared.controller.time_control {
+            _shared.controller.time_odds = None;
+        }
+    }
+
+    /// Moves Black's clock on to the next time control in `TIME_CONTROLS` that differs from White's, off after the last one
+    fn next_time_odds(_shared: &mut Shared) {
+        let white = match _shared.controller.time_control {
+            Some(white) => white,
+            None => return
+        };
+
+        let choices: Vec<Option<TimeControl>> = TIME_CONTROLS.iter()
+            .map(|_choice| _choice.map(|(minutes, increment)| TimeControl::minutes(minutes, increment)))
+            .filter(|_choice| *_choice != Some(white))
+            .collect();
+        let current = choices.iter().position(|_choice| *_choice == _shared.controller.time_odds).unwrap_or(0);
+
+        _shared.controller.time_odds = choices[(current + 1) % choices.len()];
+    }
+}
+
+impl SettingsScene {
+    /// The buttons with the current values on them, in the order the keyboard focus goes through them
+    fn buttons(_shared: &Shared) -> Vec<Button<Setting>> {
+        let on_off = |_label: &str, _on: bool| format!("{}: {}", _label, if _on { "On" } else { "Off" });
+
+        vec![
+            Button::new(CLOCK_BUTTON, &SettingsScene::clock_label(_shared), Setting::Clock),
+            Button::new(ODDS_BUTTON, &SettingsScene::odds_label(_shared), Setting::Odds),
+            Button::new(VALIDATION_BUTTON, &on_off("Move validation", _shared.controller.validate_moves), Setting::Validation),
+            Button::new(CASUAL_BUTTON, &on_off("Casual takebacks", _shared.controller.casual_takebacks), Setting::Casual),
+            Button::new(HAND_OVER_BUTTON, &on_off("Hand over screen", _shared.hand_over), Setting::HandOver),
+            Button::new(TURN_INDICATOR_BUTTON, &format!("Turn indicator: {}", _shared.turn_indicator.label()), Setting::TurnIndicator),
+            Button::new(THEME_BUTTON, &format!("Theme: {}", _shared.theme.name), Setting::Theme),
+            Button::new(TEXT_SIZE_BUTTON, &format!("Text: {}%", (_shared.config.text_scale.max(1.0) * 100.0).round()), Setting::TextSize),
+            Button::new(PIECES_BUTTON, &SettingsScene::pieces_label(_shared), Setting::Pieces),
+            Button::new(MARGIN_BUTTON, &format!("Margin: {}", _shared.config.piece_margin), Setting::Margin),
+            Button::new(VARIANT_BUTTON, &SettingsScene::variant_label(_shared), Setting::Variant),
+            Button::new(ARBITER_BUTTON, &on_off("Arbiter controls", _shared.config.arbiter), Setting::Arbiter),
+            Button::new(MESSAGES_BUTTON, &format!("Messages: {}", _shared.messages().name), Setting::Messages),
+            Button::new(UNFOCUSED_BUTTON,
+                if _shared.config.run_unfocused { "When unfocused: Keep playing" } else { "When unfocused: Pause" }, Setting::Unfocused),
+            Button::new(LOW_SPEC_BUTTON, &on_off("Low-spec mode", _shared.config.low_spec), Setting::LowSpec),
+            // Reduced motion is motion off
+            Button::new(MOTION_BUTTON, &on_off("Motion", !_shared.config.reduced_motion), Setting::Motion),
+            Button::new(BOOK_BUTTON, match (&_shared.book, _shared.config.opening_book) {
+                (None, _) => "Opening book: No book.bin",
+                (Some(_), true) => "Opening book: On",
+                (Some(_), false) => "Opening book: Off"
+            }, Setting::Book),
+            Button::new(AUDIO_BUTTON, "Audio", Setting::Audio),
+            Button::new(BACK_BUTTON, "Back", Setting::Back)
+        ]
+    }
+
+    /// ## `change`
+    /// Changes a setting to its next value
+    /// ### Parameters
+    /// - `_shared: &mut Shared`: The state holding the settings
+    /// - `_setting: Setting`: The setting whose button was clicked
+    /// ### Return
+    /// The transition to the audio settings or back, `Transition::None` for the settings changed in place
+    fn change(_shared: &mut Shared, _setting: Setting) -> Transition {
+        match _setting {
+            Setting::Clock => SettingsScene::next_time_control(_shared),
+            Setting::Odds => SettingsScene::next_time_odds(_shared),
+            Setting::Validation => _shared.controller.validate_moves = !_shared.controller.validate_moves,
+            Setting::Casual => _shared.controller.casual_takebacks = !_shared.controller.casual_takebacks,
+            Setting::HandOver => _shared.hand_over = !_shared.hand_over,
+            Setting::TurnIndicator => {
+                let current = TurnIndicator::ALL.iter().position(|_indicator| *_indicator == _shared.turn_indicator).unwrap_or(0);
+                _shared.turn_indicator = TurnIndicator::ALL[(current + 1) % TurnIndicator::ALL.len()];
+            },
+            Setting::Theme => SettingsScene::next_theme(_shared),
+            Setting::TextSize => SettingsScene::next_text_scale(_shared),
+            Setting::Pieces => SettingsScene::next_piece_set(_shared),
+            Setting::Margin => SettingsScene::next_piece_margin(_shared),
+            Setting::Variant => SettingsScene::next_variant(_shared),
+            Setting::Arbiter => {
+                _shared.config.arbiter = !_shared.config.arbiter;
+                _shared.config.unsaved = true;
+            },
+            Setting::Messages => SettingsScene::next_messages(_shared),
+            Setting::Unfocused => {
+                _shared.config.run_unfocused = !_shared.config.run_unfocused;
+                _shared.config.unsaved = true;
+            },
+            Setting::LowSpec => {
+                _shared.config.low_spec = !_shared.config.low_spec;
+                _shared.config.unsaved = true;
+            },
+            Setting::Motion => {
+                _shared.config.reduced_motion = !_shared.config.reduced_motion;
+                _shared.config.unsaved = true;
+            },
+            Setting::Book => {
+                _shared.config.opening_book = !_shared.config.opening_book;
+                _shared.config.unsaved = true;
+            },
+            Setting::Audio => return Transition::Push(Box::new(AudioSettingsScene::new())),
+            Setting::Back => return Transition::Pop
+        }
+
+        Transition::None
+    }
+}
+
+impl Scene for SettingsScene {
+    fn name(&self) -> &'static str {
+        "settings"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Settings", 120.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        for _button in SettingsScene::buttons(_shared) {
+            _button.draw(_renderer);
+        }
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Click { x, y } => match Regions::of(&SettingsScene::buttons(_shared)).hit(x, y) {
+                Some(setting) => SettingsScene::change(_shared, setting),
+                None => Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        Regions::of(&SettingsScene::buttons(_shared)).rects()
+    }
+}
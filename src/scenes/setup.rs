@@ -0,0 +1,358 @@
+use eliasfl_chess::Color as Colour;
+use ggez::event::KeyCode;
+use super::{Scene, Shared, Transition};
+use super::game::GameScene;
+use super::simul::SimulScene;
+use super::super::bot::{Bot, MIN_ELO, MAX_ELO, limit, load_bot, random_bot::RandomBot, minimax_bot::MinimaxBot};
+use super::super::simul::{Simul, MAX_BOARDS};
+use super::super::speedrun::Difficulty;
+use super::super::vote::Vote;
+use super::super::variant::CHESS960_POSITIONS;
+use super::super::controller::{InputEvent, Command};
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer, text_layout::TextLayout};
+use super::super::help_funcs::in_rect;
+
+const SIDE_BUTTON: (f32, f32, f32, f32) = (160.0, 260.0, 400.0, 60.0);
+const OPPONENT_BUTTON: (f32, f32, f32, f32) = (160.0, 340.0, 400.0, 60.0);
+const BOARDS_BUTTON: (f32, f32, f32, f32) = (160.0, 420.0, 400.0, 60.0);
+const VOTERS_BUTTON: (f32, f32, f32, f32) = (160.0, 500.0, 400.0, 60.0);
+const POSITION_BUTTON: (f32, f32, f32, f32) = (160.0, 580.0, 400.0, 60.0); // Only for Chess960
+const STRENGTH_SLIDER: (f32, f32, f32, f32) = (160.0, 715.0, 400.0, 12.0);
+
+/// Part of the strength slider up to which it sets an Elo, beyond it bots play at full strength
+const ELO_PART: f32 = 0.95;
+/// Elo the strength slider moves in
+const ELO_STEP: u32 = 50;
+
+/// Largest group that can play vote chess
+const MAX_VOTERS: usize = 8;
+const START_BUTTON: (f32, f32, f32, f32) = (80.0, 770.0, 260.0, 60.0);
+const BACK_BUTTON: (f32, f32, f32, f32) = (380.0, 770.0, 260.0, 60.0);
+
+/// Who the player sitting at the screen plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    White,
+    Black,
+    Both, // Hot-seat, both sides are played by clicking
+    Neither // The bot plays against itself
+}
+
+impl Side {
+    /// Every choice, in the order the button cycles through them
+    pub const ALL: [Side; 4] = [Side::White, Side::Black, Side::Both, Side::Neither];
+
+    /// Name shown on the side button
+    pub fn label(&self) -> &'static str {
+        match self {
+            Side::White => "Rustaceans (White)",
+            Side::Black => "Haskellers (Black)",
+            Side::Both => "Both (hot-seat)",
+            Side::Neither => "Nobody (bot game)"
+        }
+    }
+}
+
+/// Shown before every new game: which side to play, against which bot and on how many boards
+pub struct SetupScene {
+    side: Side,
+    opponent: usize, // Index into `Shared::bot_scripts`, one past them is the built-in random mover and the minimax bot at each difficulty after it
+    boards: usize, // More than one plays a simul against the bot, only when playing one side
+    voters: usize, // More than one plays vote chess on the human sides, 0 or 1 plays normally
+    position_entry: Option<String>, // Number of the Chess960 position being typed, None when not typing
+    dragging: bool // The strength slider is being dragged
+}
+
+impl SetupScene {
+    /// Starts from the sides of the last game, so playing again is a single click
+    pub fn new(_shared: &Shared) -> SetupScene {
+        let bots = &_shared.controller.bots;
+        let side = match (bots.contains_key(&Colour::White), bots.contains_key(&Colour::Black)) {
+            (false, true) => Side::White,
+            (true, false) => Side::Black,
+            (true, true) => Side::Neither,
+            (false, false) => Side::Both
+        };
+
+        // The bot of the last game if it was one of the bots in bots/ or the random mover, the first bot otherwise
+        let current = bots.values().next().map(|_bot| _bot.name());
+        let built_in = Some(RandomBot::name_for(Difficulty::Hard)).into_iter()
+            .chain(Difficulty::ALL.iter().map(|_difficulty| MinimaxBot::name_for(*_difficulty)))
+            .position(|_name| Some(_name) == current);
+        let opponent = _shared.bot_scripts.iter()
+            .position(|_path| _path.file_stem().map(|_stem| _stem.to_string_lossy().to_string()) == current)
+            .unwrap_or(built_in.map_or(0, |_index| _shared.bot_scripts.len() + _index));
+
+        let voters = _shared.controller.vote.as_ref().map_or(1, |_vote| _vote.voters);
+
+        SetupScene { side, opponent, boards: 1, voters, position_entry: None, dragging: false }
+    }
+
+    /// Label of the opponent button
+    fn opponent_label(&self, _shared: &Shared) -> String {
+        if self.side == Side::Both {
+            return "Opponent: Human".to_string();
+        }
+
+        match (_shared.bot_scripts.get(self.opponent).and_then(|_path| _path.file_stem()), self.minimax(_shared)) {
+            (Some(name), _) => format!("Opponent: bot {}", name.to_string_lossy()),
+            (None, Some(difficulty)) => format!("Opponent: minimax ({})", difficulty.label().to_lowercase()),
+            (None, None) => "Opponent: random mover".to_string()
+        }
+    }
+
+    /// Difficulty of the built-in minimax bot when it is the chosen opponent
+    fn minimax(&self, _shared: &Shared) -> Option<Difficulty> {
+        let index = self.opponent.checked_sub(_shared.bot_scripts.len() + 1)?;
+        Difficulty::ALL.get(index).copied()
+    }
+
+    /// Text above the strength slider
+    fn strength_label(_elo: Option<u32>) -> String {
+        match _elo {
+            Some(elo) => format!("Strength: ~{} Elo", elo),
+            None => "Strength: Full".to_string()
+        }
+    }
+
+    /// How far the strength slider is filled for an Elo, full strength fills it
+    fn strength_value(_elo: Option<u32>) -> f32 {
+        match _elo {
+            Some(elo) => (elo.max(MIN_ELO) - MIN_ELO) as f32 / (MAX_ELO - MIN_ELO) as f32 * ELO_PART,
+            None => 1.0
+        }
+    }
+
+    /// Sets the Elo of the bots from the mouse position along the strength slider
+    fn set_strength(_shared: &mut Shared, _x: f32) {
+        let (x, _, width, _) = STRENGTH_SLIDER;
+        let value = ((_x - x) / width).max(0.0).min(1.0);
+
+        _shared.config.bot_elo = if value > ELO_PART { None } else {
+            let elo = MIN_ELO as f32 + value / ELO_PART * (MAX_ELO - MIN_ELO) as f32;
+            Some((elo / ELO_STEP as f32).round() as u32 * ELO_STEP)
+        };
+    }
+
+    /// ## `bot`
+    /// Loads the chosen opponent, held back to the Elo of the strength slider
+    /// ### Return
+    /// `Err` if the script or engine could not be loaded
+    fn bot(&self, _shared: &Shared) -> Result<Box<dyn Bot>, String> {
+        let elo = _shared.config.bot_elo;
+        match (_shared.bot_scripts.get(self.opponent), self.minimax(_shared)) {
+            (Some(path), _) => load_bot(path, elo),
+            (None, Some(difficulty)) => Ok(limit(Box::new(MinimaxBot::new(difficulty)), elo)),
+            (None, None) => Ok(limit(Box::new(RandomBot::new(Difficulty::Hard).with_book(_shared.opening_book())), elo))
+        }
+    }
+
+    /// Label of the Chess960 position button, None when the variant is not Chess960
+    fn position_label(&self, _shared: &Shared) -> Option<String> {
+        let chess960 = _shared.controller.variant.chess960()?;
+
+        Some(match (&self.position_entry, chess960.chosen()) {
+            (Some(entry), _) => format!("960 position: {}_", entry),
+            (None, Some(position)) => format!("960 position: #{}", position),
+            (None, None) => "960 position: Random".to_string()
+        })
+    }
+
+    /// ## `enter_position`
+    /// Plays Chess960 from the typed position, or from random positions when nothing was typed
+    /// ### Return
+    /// `Err` with the reason if the number is no position
+    fn enter_position(&mut self, _shared: &Shared) -> Result<(), String> {
+        let entry = self.position_entry.take().unwrap_or_default();
+        let chess960 = match _shared.controller.variant.chess960() {
+            Some(chess960) => chess960,
+            None => return Ok(())
+        };
+
+        if entry.is_empty() {
+            chess960.choose(None);
+            return Ok(());
+        }
+
+        match entry.parse::<u16>() {
+            Ok(position) if position < CHESS960_POSITIONS => {
+                chess960.choose(Some(position));
+                Ok(())
+            },
+            _ => Err(format!("{} is no Chess960 position, they go from 0 to {}", entry, CHESS960_POSITIONS - 1))
+        }
+    }
+
+    /// If the choices make a simul: more than one board, with the player on one side and the bot on the other
+    fn is_simul(&self) -> bool {
+        self.boards > 1 && (self.side == Side::White || self.side == Side::Black)
+    }
+
+    /// Label of the boards button
+    fn boards_label(&self) -> String {
+        if self.is_simul() { format!("Boards: {} (simul)", self.boards) } else { "Boards: 1".to_string() }
+    }
+
+    /// Label of the voters button
+    fn voters_label(&self) -> String {
+        if self.voters > 1 && self.side != Side::Neither { format!("Vote chess: {} voters", self.voters) } else { "Vote chess: Off".to_string() }
+    }
+
+    /// Sets up a simul on `Shared::simul`, with a copy of the opponent
+    fn start_simul(&self, _shared: &mut Shared) -> Result<(), String> {
+        let bot = self.bot(_shared)?;
+        let human = if self.side == Side::White { Colour::White } else { Colour::Black };
+
+        _shared.simul = Some(Simul::new(&_shared.controller, self.boards, human, bot));
+        Ok(())
+    }
+
+    /// ## `start`
+    /// Hands the sides to the player and the bot, turns the board towards the player and starts a new game.
+    /// When the player takes Black the bot moves first, on the next tick.
+    fn start(&self, _shared: &mut Shared) {
+        let bot_colours: &[Colour] = match self.side {
+            Side::White => &[Colour::Black],
+            Side::Black => &[Colour::White],
+            Side::Both => &[],
+            Side::Neither => &[Colour::White, Colour::Black]
+        };
+
+        _shared.controller.set_bot(Colour::White, None);
+        _shared.controller.set_bot(Colour::Black, None);
+        _shared.speedrun = None;
+
+        // Every side gets its own copy of the bot, engines run once per side
+        for _colour in bot_colours {
+            match self.bot(_shared) {
+                Ok(bot) => _shared.controller.set_bot(*_colour, Some(bot)),
+                Err(error) => _shared.controller.banner = Some(error)
+            }
+        }
+
+        // Only bot games are adjudicated, the controller checks that both sides are bots
+        _shared.controller.adjudication_rules = Some(_shared.config.engine_adjudication.clone());
+        _shared.controller.flipped = self.side == Side::Black;
+        _shared.controller.vote = if self.voters > 1 && self.side != Side::Neither { Some(Vote::new(self.voters)) } else { None };
+        _shared.controller.execute(Command::Restart);
+    }
+}
+
+impl Scene for SetupScene {
+    fn name(&self) -> &'static str {
+        "setup"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "New game", 120.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        draw_funcs::draw_button(_renderer, SIDE_BUTTON, &format!("Play as: {}", self.side.label()));
+        draw_funcs::draw_button(_renderer, OPPONENT_BUTTON, &self.opponent_label(_shared));
+        draw_funcs::draw_button(_renderer, BOARDS_BUTTON, &self.boards_label());
+        draw_funcs::draw_button(_renderer, VOTERS_BUTTON, &self.voters_label());
+        if let Some(label) = self.position_label(_shared) {
+            draw_funcs::draw_button(_renderer, POSITION_BUTTON, &label);
+
+            let hint = if self.position_entry.is_some() { "Type 0–959 and Enter, nothing for random" } else { "Click to enter a position number" };
+            TextLayout::centred(hint, 18.0, [0.7, 0.7, 0.7, 1.0]).draw(_renderer, (160.0, 645.0, 400.0, 30.0));
+        }
+        if self.side != Side::Both {
+            let elo = _shared.config.bot_elo;
+            draw_funcs::draw_labelled_slider(_renderer, STRENGTH_SLIDER, &SetupScene::strength_label(elo), SetupScene::strength_value(elo));
+        }
+        draw_funcs::draw_button(_renderer, START_BUTTON, "Start");
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        // Typing the number of a Chess960 position
+        if let Some(entry) = self.position_entry.as_mut() {
+            match _event {
+                InputEvent::Text(character) if character.is_ascii_digit() && entry.len() < 3 => entry.push(character),
+                InputEvent::Key(KeyCode::Back) => { entry.pop(); },
+                InputEvent::Key(KeyCode::Escape) => self.position_entry = None,
+                InputEvent::Key(KeyCode::Return) | InputEvent::Key(KeyCode::NumpadEnter) | InputEvent::Click { .. } => {
+                    if let Err(error) = self.enter_position(_shared) {
+                        _shared.controller.banner = Some(error);
+                    }
+                },
+                _ => {}
+            }
+            return Transition::None;
+        }
+
+        match _event {
+            // The strength slider, with some room around the thin track
+            InputEvent::Press { x, y } => {
+                let (left, top, width, height) = STRENGTH_SLIDER;
+                self.dragging = self.side != Side::Both && in_rect((left - 10.0, top - 15.0, width + 20.0, height + 30.0), x, y);
+                if self.dragging {
+                    SetupScene::set_strength(_shared, x);
+                }
+                Transition::None
+            },
+            InputEvent::Motion { x, .. } => {
+                if self.dragging {
+                    SetupScene::set_strength(_shared, x);
+                }
+                Transition::None
+            },
+            InputEvent::Click { x, y } => {
+                // Saved once the slider is let go rather than on every motion
+                if self.dragging {
+                    self.dragging = false;
+                    _shared.config.unsaved = true;
+                }
+                else if in_rect(SIDE_BUTTON, x, y) {
+                    let current = Side::ALL.iter().position(|_side| *_side == self.side).unwrap_or(0);
+                    self.side = Side::ALL[(current + 1) % Side::ALL.len()];
+                }
+                else if in_rect(OPPONENT_BUTTON, x, y) && self.side != Side::Both {
+                    self.opponent = (self.opponent + 1) % (_shared.bot_scripts.len() + 1 + Difficulty::ALL.len());
+                }
+                else if in_rect(BOARDS_BUTTON, x, y) && (self.side == Side::White || self.side == Side::Black) {
+                    self.boards = self.boards % MAX_BOARDS + 1;
+                }
+                else if in_rect(VOTERS_BUTTON, x, y) && self.side != Side::Neither {
+                    // Off, then 2 up to the most voters
+                    self.voters = if self.voters >= MAX_VOTERS { 1 } else { self.voters.max(1) + 1 };
+                }
+                else if in_rect(POSITION_BUTTON, x, y) && _shared.controller.variant.chess960().is_some() {
+                    self.position_entry = Some(String::new());
+                }
+                else if in_rect(START_BUTTON, x, y) && self.is_simul() {
+                    match self.start_simul(_shared) {
+                        Ok(()) => return Transition::Reset(Box::new(SimulScene::new())),
+                        Err(error) => _shared.controller.banner = Some(format!("Simul not started: {}", error))
+                    }
+                }
+                else if in_rect(START_BUTTON, x, y) {
+                    self.start(_shared);
+                    return Transition::Reset(Box::new(GameScene));
+                }
+                else if in_rect(BACK_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+
+                Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+
+    // The strength slider is dragged with the mouse
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        if self.position_entry.is_some() {
+            return vec![];
+        }
+
+        let mut buttons = vec![SIDE_BUTTON, OPPONENT_BUTTON, BOARDS_BUTTON, VOTERS_BUTTON];
+        if _shared.controller.variant.chess960().is_some() {
+            buttons.push(POSITION_BUTTON);
+        }
+        buttons.extend(&[START_BUTTON, BACK_BUTTON]);
+        buttons
+    }
+}
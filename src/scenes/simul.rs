@@ -0,0 +1,135 @@
+use super::{Scene, Shared, Transition};
+use super::main_menu::MainMenuScene;
+use super::super::controller::InputEvent;
+use super::super::export;
+use super::super::graphics_funcs::{draw_funcs, renderer::{Renderer, ZoomRenderer}, text_layout::TextLayout};
+use super::super::help_funcs::in_rect;
+use super::super::consts::{GRID_SIZE, GRID_CELL_SIZE};
+use ggez::event::KeyCode;
+
+/// Keys selecting the boards, in order
+const BOARD_KEYS: [KeyCode; 6] = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4, KeyCode::Key5, KeyCode::Key6];
+
+/// A simul against the bot of `Shared::simul`: one board is shown at a time, with a tab per board in the panel.
+/// Tab or the number keys switch boards, Escape goes back to the menu.
+pub struct SimulScene {
+    time: f32 // Seconds since the simul started, turns the spinners
+}
+
+impl SimulScene {
+    pub fn new() -> SimulScene {
+        SimulScene { time: 0.0 }
+    }
+
+    /// Rectangle of the tab of a board in the panel
+    fn tab(_index: usize) -> (f32, f32, f32, f32) {
+        (5.0 + _index as f32 * 118.0, 728.0, 112.0, 44.0)
+    }
+}
+
+impl Scene for SimulScene {
+    fn name(&self) -> &'static str {
+        "simul"
+    }
+
+    fn update(&mut self, _shared: &mut Shared, _dt: f32) -> Transition {
+        self.time += _dt;
+
+        match _shared.simul.as_mut() {
+            Some(simul) => {
+                simul.tick(_dt);
+                simul.schedule(&mut _shared.tasks);
+                Transition::None
+            },
+            None => Transition::Reset(Box::new(MainMenuScene))
+        }
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        let simul = match &_shared.simul {
+            Some(simul) => simul,
+            None => return
+        };
+        let board = simul.active_board();
+
+        draw_funcs::draw_board(&mut ZoomRenderer::new(_renderer, board.view.zoom, board.view.offset), &_shared.theme, board, _shared.board_style());
+        draw_funcs::draw_panel_background(_renderer, &_shared.theme);
+
+        if board.promoting {
+            draw_funcs::draw_promotion_panel(_renderer, board.game.active_colour());
+        }
+        else {
+            for _index in 0..simul.boards.len() {
+                let geometry = SimulScene::tab(_index);
+                if _index == simul.active {
+                    let mut glow = draw_funcs::side_colour(simul.human);
+                    glow[3] = 0.6;
+                    _renderer.draw_rect((geometry.0 - 3.0, geometry.1 - 3.0, geometry.2 + 6.0, geometry.3 + 6.0), glow);
+                }
+                draw_funcs::draw_rectangle(_renderer, geometry);
+
+                let state = match export::result(&simul.boards[_index]).0 {
+                    "*" if simul.is_human_turn(_index) => "your move",
+                    "*" => "",
+                    result => result
+                };
+                TextLayout::centred(&format!("{}  {}", _index + 1, state), 18.0, [1.0, 1.0, 1.0, 1.0]).draw(_renderer, geometry);
+
+                if simul.is_thinking(_index) {
+                    let time = if _shared.config.reduced_motion { 0.0 } else { self.time };
+                    draw_funcs::draw_spinner(_renderer, (geometry.0 + geometry.2 - 12.0, geometry.1 + 12.0), time);
+                }
+            }
+
+            draw_funcs::draw_clocks(_renderer, board, 790.0, true, _shared.config.reduced_motion);
+
+            let (wins, draws, losses) = simul.score();
+            TextLayout::centred(&draw_funcs::prepare_status_text(board, _shared.messages()), 22.0, _shared.theme.text)
+                .draw(_renderer, (160.0, 785.0, 400.0, 30.0));
+            TextLayout::centred(&format!("Against {}: +{} ={} -{}", simul.bot_name(), wins, draws, losses), 16.0, _shared.theme.text)
+                .draw(_renderer, (160.0, 820.0, 400.0, 25.0));
+        }
+
+        // The bot failing on this board
+        if let Some(message) = &board.banner {
+            draw_funcs::draw_banner(_renderer, message);
+        }
+        if board.out_of_sync.is_some() {
+            draw_funcs::draw_resync_button(_renderer);
+        }
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        let simul = match _shared.simul.as_mut() {
+            Some(simul) => simul,
+            None => return Transition::None
+        };
+        let board_height = (GRID_SIZE * GRID_CELL_SIZE.1) as f32;
+
+        match _event {
+            InputEvent::Key(KeyCode::Tab) => simul.select((simul.active + 1) % simul.boards.len()),
+            InputEvent::Key(key) if BOARD_KEYS.contains(&key) => simul.select(BOARD_KEYS.iter().position(|_key| *_key == key).unwrap()),
+            // Escape drops the selection first, a second one leaves the simul
+            InputEvent::Key(KeyCode::Escape) if simul.active_board().previous_click.is_none() => {
+                _shared.simul = None;
+                return Transition::Reset(Box::new(MainMenuScene));
+            },
+            InputEvent::Click { x, y } if y >= board_height && !simul.active_board().promoting => {
+                if let Some(index) = (0..simul.boards.len()).find(|_index| in_rect(SimulScene::tab(*_index), x, y)) {
+                    simul.select(index);
+                }
+            },
+            _ => {
+                // The bot's side of a board can only be zoomed and panned
+                if simul.is_human_turn(simul.active) || simul.active_board().promoting {
+                    simul.active_board_mut().handle_input(_event);
+                }
+                else {
+                    simul.active_board_mut().view.input(_event);
+                }
+            }
+        }
+
+        Transition::None
+    }
+}
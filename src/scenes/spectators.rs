@@ -0,0 +1,89 @@
+use super::{Scene, Shared, Transition};
+use super::super::controller::InputEvent;
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer, text_layout::TextLayout};
+use super::super::help_funcs::in_rect;
+use ggez::event::KeyCode;
+
+// A row per spectator with its mute button, the chat below them
+const FIRST_ROW: f32 = 150.0;
+const ROW_HEIGHT: f32 = 50.0;
+const MAX_ROWS: usize = 6;
+const CHAT_AREA: (f32, f32, f32, f32) = (60.0, 470.0, 600.0, 270.0);
+const BACK_BUTTON: (f32, f32, f32, f32) = (230.0, 770.0, 260.0, 60.0);
+
+/// Who watches the game through mirror windows and what they write in their chat, which the players never see on
+/// the board. A spectator can be muted here: their lines are hidden and further ones refused.
+pub struct SpectatorsScene;
+
+impl SpectatorsScene {
+    /// Rectangle of the mute button of a row
+    fn mute_button(_row: usize) -> (f32, f32, f32, f32) {
+        (500.0, FIRST_ROW + _row as f32 * ROW_HEIGHT, 160.0, 40.0)
+    }
+}
+
+impl Scene for SpectatorsScene {
+    fn name(&self) -> &'static str {
+        "spectators"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Spectators", 60.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        let spectators = match _shared.spectators.as_ref().and_then(|_spectators| _spectators.lock().ok()) {
+            Some(spectators) => spectators,
+            None => {
+                TextLayout::centred("The game is not served, start with --overlay-port <port>", 20.0, [0.7, 0.7, 0.7, 1.0])
+                    .draw(_renderer, (60.0, FIRST_ROW, 600.0, ROW_HEIGHT));
+                draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+                return;
+            }
+        };
+
+        let present = spectators.present();
+        if present.is_empty() {
+            TextLayout::centred("Nobody watches yet, join with --mirror <port> --name <name>", 18.0, [0.7, 0.7, 0.7, 1.0])
+                .draw(_renderer, (60.0, FIRST_ROW, 600.0, ROW_HEIGHT));
+        }
+        for (_row, (_token, _name)) in present.iter().take(MAX_ROWS).enumerate() {
+            let y = FIRST_ROW + _row as f32 * ROW_HEIGHT;
+            let muted = spectators.is_muted(_token);
+
+            _renderer.draw_text(_name, (70.0, y + 8.0), 24.0, if muted { [0.5, 0.5, 0.5, 1.0] } else { [1.0, 1.0, 1.0, 1.0] });
+            draw_funcs::draw_button(_renderer, SpectatorsScene::mute_button(_row), if muted { "Unmute" } else { "Mute" });
+        }
+        if present.len() > MAX_ROWS {
+            _renderer.draw_text(&format!("and {} more", present.len() - MAX_ROWS), (70.0, FIRST_ROW + MAX_ROWS as f32 * ROW_HEIGHT), 18.0, [0.7, 0.7, 0.7, 1.0]);
+        }
+
+        _renderer.draw_text("Spectator chat", (CHAT_AREA.0, CHAT_AREA.1 - 30.0), 22.0, [0.7, 0.7, 0.7, 1.0]);
+        draw_funcs::draw_chat(_renderer, &spectators.chat_since(0), None, CHAT_AREA);
+
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Key(KeyCode::Escape) => Transition::Pop,
+            InputEvent::Click { x, y } => {
+                if in_rect(BACK_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+
+                if let Some(mut spectators) = _shared.spectators.as_ref().and_then(|_spectators| _spectators.lock().ok()) {
+                    let present = spectators.present();
+                    if let Some(token) = present.iter().take(MAX_ROWS).enumerate()
+                        .find(|(_row, _)| in_rect(SpectatorsScene::mute_button(*_row), x, y))
+                        .map(|(_, (_token, _))| _token) {
+                        spectators.toggle_mute(token);
+                    }
+                }
+
+                Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+}
@@ -0,0 +1,101 @@
+use eliasfl_chess::Color as Colour;
+use std::rc::Rc;
+use super::{Scene, Shared, Transition};
+use super::game::GameScene;
+use super::super::bot::random_bot::RandomBot;
+use super::super::controller::{InputEvent, Command};
+use super::super::graphics_funcs::{draw_funcs, renderer::Renderer, text_layout::TextLayout};
+use super::super::help_funcs::{in_rect, format_timestamp};
+use super::super::speedrun::{Difficulty, Speedrun};
+
+const DIFFICULTY_BUTTON: (f32, f32, f32, f32) = (160.0, 200.0, 400.0, 60.0);
+const START_BUTTON: (f32, f32, f32, f32) = (80.0, 770.0, 260.0, 60.0);
+const BACK_BUTTON: (f32, f32, f32, f32) = (380.0, 770.0, 260.0, 60.0);
+
+// Leaderboard below the difficulty
+const FIRST_ROW: f32 = 330.0;
+const ROW_HEIGHT: f32 = 38.0;
+
+/// Picks the difficulty of a speedrun against the random mover, with the best times at that difficulty
+pub struct SpeedrunScene {
+    difficulty: Difficulty
+}
+
+impl SpeedrunScene {
+    /// Starts at the difficulty of the last speedrun
+    pub fn new(_shared: &Shared) -> SpeedrunScene {
+        SpeedrunScene { difficulty: _shared.speedrun.map_or(Difficulty::Easy, |_run| _run.difficulty) }
+    }
+
+    /// ## `start`
+    /// Puts the random mover on Black and starts a new game of standard chess, whatever variant is chosen in the settings
+    fn start(&self, _shared: &mut Shared) {
+        _shared.controller.set_bot(Colour::White, None);
+        // Never with the opening book, so the times of the leaderboard stay comparable
+        _shared.controller.set_bot(Colour::Black, Some(Box::new(RandomBot::new(self.difficulty))));
+        _shared.controller.flipped = false;
+        _shared.controller.vote = None;
+        _shared.controller.variant = Rc::clone(&_shared.variants[0]);
+
+        _shared.speedrun = Some(Speedrun { difficulty: self.difficulty, human: Colour::White });
+        _shared.controller.execute(Command::Restart);
+    }
+}
+
+impl Scene for SpeedrunScene {
+    fn name(&self) -> &'static str {
+        "speedrun"
+    }
+
+    fn draw(&self, _renderer: &mut dyn Renderer, _shared: &Shared) {
+        draw_funcs::draw_background(_renderer, [0.15, 0.15, 0.15, 1.0]);
+
+        draw_funcs::draw_title(_renderer, "Speedrun", 120.0, 40.0, [1.0, 1.0, 1.0, 1.0]);
+
+        draw_funcs::draw_button(_renderer, DIFFICULTY_BUTTON, &format!("Difficulty: {}", self.difficulty.label()));
+        TextLayout::centred(self.difficulty.description(), 18.0, [0.7, 0.7, 0.7, 1.0]).draw(_renderer, (160.0, 265.0, 400.0, 30.0));
+
+        let times = _shared.profiles.speedrun_times(self.difficulty);
+        if times.is_empty() {
+            TextLayout::centred("No checkmates yet, the clock only runs on your moves", 20.0, [0.7, 0.7, 0.7, 1.0])
+                .draw(_renderer, (60.0, FIRST_ROW, 600.0, ROW_HEIGHT));
+        }
+        for (_index, _time) in times.iter().enumerate() {
+            let y = FIRST_ROW + _index as f32 * ROW_HEIGHT;
+            let colour = if _index == 0 { [0.97, 0.3, 0.0, 1.0] } else { [1.0, 1.0, 1.0, 1.0] };
+
+            _renderer.draw_text(&format!("{}.", _index + 1), (130.0, y), 24.0, colour);
+            _renderer.draw_text(&format!("{:.1} s", _time.seconds), (190.0, y), 24.0, colour);
+            _renderer.draw_text(&format!("{} moves", _time.moves), (320.0, y), 24.0, colour);
+            _renderer.draw_text(&format_timestamp(_time.when)[..10], (460.0, y), 24.0, [0.7, 0.7, 0.7, 1.0]);
+        }
+
+        draw_funcs::draw_button(_renderer, START_BUTTON, "Start");
+        draw_funcs::draw_button(_renderer, BACK_BUTTON, "Back");
+    }
+
+    fn input(&mut self, _shared: &mut Shared, _event: InputEvent) -> Transition {
+        match _event {
+            InputEvent::Click { x, y } => {
+                if in_rect(DIFFICULTY_BUTTON, x, y) {
+                    let current = Difficulty::ALL.iter().position(|_difficulty| *_difficulty == self.difficulty).unwrap_or(0);
+                    self.difficulty = Difficulty::ALL[(current + 1) % Difficulty::ALL.len()];
+                }
+                else if in_rect(START_BUTTON, x, y) {
+                    self.start(_shared);
+                    return Transition::Reset(Box::new(GameScene));
+                }
+                else if in_rect(BACK_BUTTON, x, y) {
+                    return Transition::Pop;
+                }
+
+                Transition::None
+            },
+            _ => Transition::None
+        }
+    }
+
+    fn focusables(&self, _shared: &Shared) -> Vec<(f32, f32, f32, f32)> {
+        vec![DIFFICULTY_BUTTON, START_BUTTON, BACK_BUTTON]
+    }
+}
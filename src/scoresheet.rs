@@ -0,0 +1,106 @@
+use eliasfl_chess::Color as Colour;
+use super::controller::GameController;
+use super::diagram::{self, DiagramOptions};
+use super::export;
+use super::pgn::{self, PgnHeaders};
+use super::theme::Theme;
+
+/// Where the scoresheet is exported to, in the GGEZ user data directory
+pub const SCORESHEET_FILE: &str = "/scoresheet.html";
+
+/// Moves between two diagrams
+const MOVES_PER_DIAGRAM: usize = 10;
+
+/// Styles of the sheet, printed on A4 with a block of moves and its diagram kept on one page
+const STYLE: &str = "body { font-family: serif; margin: 2em; }
+h1 { font-size: 1.6em; margin-bottom: 0.2em; }
+table.headers td { padding: 0.1em 1em 0.1em 0; }
+section { display: flex; gap: 2em; align-items: flex-start; margin-top: 1.5em; break-inside: avoid; page-break-inside: avoid; }
+table.moves { border-collapse: collapse; width: 20em; }
+table.moves th, table.moves td { border: 1px solid #555; padding: 0.2em 0.5em; text-align: left; }
+table.moves td.number { width: 2.5em; text-align: right; }
+figure { margin: 0; text-align: center; }
+figure svg { width: 14em; height: 14em; }
+@page { size: A4; margin: 1.5cm; }";
+
+/// Text with the characters HTML gives a meaning escaped
+fn escape(_text: &str) -> String {
+    _text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// ## `to_html`
+/// Writes the game as a printable scoresheet: the headers, then the moves in a White and a Black column with a
+/// diagram of the position after every ten moves, and of the final position
+/// ### Parameters
+/// - `_controller: &GameController`: The game, usually finished
+/// - `_headers: &PgnHeaders`: Event, players and so on, as for PGN
+/// ### Return
+/// `Err` if the moves could not be replayed to work out their notation
+pub fn to_html(_controller: &GameController, _headers: &PgnHeaders) -> Result<String, String> {
+    let (result, reason) = export::result(_controller);
+    let moves = pgn::san_moves(_controller)?;
+    let first_black = _controller.position_at(0)?.active_colour() == Colour::Black;
+
+    let mut html = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{} – {}</title>\n<style>\n{}\n</style>\n</head>\n<body>\n",
+        escape(&_headers.white), escape(&_headers.black), STYLE);
+
+    html.push_str(&format!("<h1>{}</h1>\n<table class=\"headers\">\n", escape(&_headers.event)));
+    for (_index, _name) in PgnHeaders::FIELDS.iter().enumerate().skip(1) {
+        html.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", _name, escape(_headers.field(_index))));
+    }
+    let result = match reason {
+        Some(reason) => format!("{} ({})", result, reason),
+        None => result.to_string()
+    };
+    html.push_str(&format!("<tr><td>Result</td><td>{}</td></tr>\n", escape(&result)));
+    if _controller.variant.chess960().is_some() {
+        html.push_str("<tr><td>Variant</td><td>Chess960</td></tr>\n");
+    }
+    if let Some(fen) = &_controller.start_fen {
+        html.push_str(&format!("<tr><td>Position</td><td>{}</td></tr>\n", escape(fen)));
+    }
+    html.push_str("</table>\n");
+
+    // A game starting with Black to move has an empty White square in its first row
+    let mut rows: Vec<(Option<&str>, Option<&str>)> = vec![];
+    for (_ply, _san) in moves.iter().enumerate() {
+        if _ply == 0 && first_black {
+            rows.push((None, Some(_san)));
+        }
+        else if (_ply + first_black as usize) % 2 == 0 {
+            rows.push((Some(_san), None));
+        }
+        else if let Some(row) = rows.last_mut() {
+            row.1 = Some(_san);
+        }
+    }
+
+    let options = DiagramOptions { coordinates: true, arrow: true };
+    let theme = Theme::classic();
+    for (_block, _rows) in rows.chunks(MOVES_PER_DIAGRAM).enumerate() {
+        let first_number = _block * MOVES_PER_DIAGRAM + 1;
+        html.push_str("<section>\n<table class=\"moves\">\n<tr><th></th><th>White</th><th>Black</th></tr>\n");
+        for (_index, (_white, _black)) in _rows.iter().enumerate() {
+            html.push_str(&format!("<tr><td class=\"number\">{}</td><td>{}</td><td>{}</td></tr>\n",
+                first_number + _index, _white.unwrap_or("…"), _black.unwrap_or("")));
+        }
+        html.push_str("</table>\n");
+
+        // The position after the last move of the block, which is the final position for the last block
+        let last_number = first_number + _rows.len() - 1;
+        let ply = ((last_number * 2) - first_black as usize).min(moves.len());
+        let position = _controller.position_at(ply)?;
+        let caption = if ply == moves.len() { "Final position".to_string() } else { format!("After move {}", last_number) };
+
+        html.push_str(&format!("<figure>\n{}<figcaption>{}</figcaption>\n</figure>\n</section>\n",
+            diagram::board_svg(position.as_ref(), ply.checked_sub(1).and_then(|_ply| _controller.history.get(_ply)), &theme, options, false),
+            caption));
+    }
+
+    if rows.is_empty() {
+        html.push_str("<p>No moves were played.</p>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    Ok(html)
+}
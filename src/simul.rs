@@ -0,0 +1,163 @@
+use eliasfl_chess::Color as Colour;
+use std::{rc::Rc, sync::atomic::{AtomicU64, Ordering}};
+use super::controller::{GameController, Command};
+use super::bot::{Bot, BotHandle, Position, parse_uci};
+use super::tasks::TaskQueue;
+use super::scenes::Shared;
+use super::export;
+
+/// Most boards a simul can have, no more tabs fit in the panel
+pub const MAX_BOARDS: usize = 6;
+
+/// Numbers every simul, so replies searched for an earlier one are recognised and dropped
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A simultaneous exhibition: one human against a bot on several boards at once.
+///
+/// The boards have no bots of their own. The simul asks its bot for a reply on every board where it is the bot's turn,
+/// each on the background task runner. There is only the one bot, so it works through the boards one at a time
+/// while the human plays on.
+pub struct Simul {
+    pub boards: Vec<GameController>,
+    pub active: usize, // Board shown and played on
+    pub human: Colour, // Side the human plays on every board
+    bot: BotHandle,
+    asked: Vec<Option<usize>>, // Per board, the ply the bot was last asked to reply at
+    id: u64
+}
+
+impl Simul {
+    /// ## `new`
+    /// Sets up the boards of a simul, all at the starting position
+    /// ### Parameters
+    /// - `_template: &GameController`: The backend, time control and variant of every board are taken from it
+    /// - `_count: usize`: Number of boards, at most `MAX_BOARDS`
+    /// - `_human: Colour`: Side the human plays, the bot plays the other one
+    /// - `_bot: Box<dyn Bot>`: The opponent on every board
+    pub fn new(_template: &GameController, _count: usize, _human: Colour, _bot: Box<dyn Bot>) -> Simul {
+        let count = _count.max(1).min(MAX_BOARDS);
+
+        let boards = (0..count).map(|_| {
+            let mut board = GameController::with_backend(_template.game.new_game());
+            board.time_control = _template.time_control;
+            board.time_odds = _template.time_odds;
+            board.variant = Rc::clone(&_template.variant);
+            board.validate_moves = _template.validate_moves;
+            board.flipped = _human == Colour::Black;
+            board.execute(Command::Restart);
+            board
+        }).collect();
+
+        Simul {
+            boards,
+            active: 0,
+            human: _human,
+            bot: BotHandle::new(_bot),
+            asked: vec![None; count],
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        }
+    }
+
+    /// Name of the bot playing every board
+    pub fn bot_name(&self) -> String {
+        self.bot.name()
+    }
+
+    /// The board shown and played on
+    pub fn active_board(&self) -> &GameController {
+        &self.boards[self.active]
+    }
+
+    /// The board shown and played on, to give it input
+    pub fn active_board_mut(&mut self) -> &mut GameController {
+        &mut self.boards[self.active]
+    }
+
+    /// Shows another board, ignored if there is no board with that index
+    pub fn select(&mut self, _index: usize) {
+        if _index < self.boards.len() {
+            self.active = _index;
+        }
+    }
+
+    /// If the human is to move on a board that is still going
+    pub fn is_human_turn(&self, _index: usize) -> bool {
+        let board = &self.boards[_index];
+        !board.is_over() && board.game.active_colour() == self.human
+    }
+
+    /// If the bot is working on its reply on a board
+    pub fn is_thinking(&self, _index: usize) -> bool {
+        let board = &self.boards[_index];
+        !board.is_over() && board.game.active_colour() != self.human
+            && self.asked[_index] == Some(board.history.len()) && board.banner.is_none()
+    }
+
+    /// If every board has ended
+    pub fn is_over(&self) -> bool {
+        self.boards.iter().all(|_board| _board.is_over())
+    }
+
+    /// ## `score`
+    /// How the human did on the boards that ended
+    /// ### Return
+    /// Wins, draws and losses of the human
+    pub fn score(&self) -> (usize, usize, usize) {
+        let won = if self.human == Colour::White { "1-0" } else { "0-1" };
+
+        self.boards.iter().fold((0, 0, 0), |(wins, draws, losses), _board| match export::result(_board).0 {
+            "*" => (wins, draws, losses),
+            "1/2-1/2" => (wins, draws + 1, losses),
+            result if result == won => (wins + 1, draws, losses),
+            _ => (wins, draws, losses + 1)
+        })
+    }
+
+    /// Runs the clocks of every board by one fixed step of `_dt` seconds
+    pub fn tick(&mut self, _dt: f32) {
+        for _board in self.boards.iter_mut() {
+            _board.tick(_dt);
+        }
+    }
+
+    /// ## `schedule`
+    /// Asks the bot for a reply on every board where it is to move and has not been asked yet.
+    /// The replies are played as they come in, whichever board is shown.
+    pub fn schedule(&mut self, _tasks: &mut TaskQueue<Shared>) {
+        for (_index, _board) in self.boards.iter().enumerate() {
+            let ply = _board.history.len();
+            if _board.is_over() || _board.promoting || _board.game.active_colour() == self.human || self.asked[_index] == Some(ply) {
+                continue;
+            }
+
+            self.asked[_index] = Some(ply);
+
+            let position = Position::of(_board.game.as_ref(), &_board.fen());
+            let bot = self.bot.clone();
+            let id = self.id;
+
+            _tasks.spawn(move || bot.choose_move(&position), move |_shared: &mut Shared, _result| {
+                if let Some(simul) = _shared.simul.as_mut().filter(|_simul| _simul.id == id) {
+                    simul.reply(_index, ply, _result.and_then(|_chosen| _chosen));
+                }
+            });
+        }
+    }
+
+    /// Plays the reply of the bot on a board, unless the board moved on since it was asked.
+    /// A failed reply is shown in the banner of the board, which then waits for nothing.
+    fn reply(&mut self, _index: usize, _ply: usize, _chosen: Result<String, String>) {
+        let board = &mut self.boards[_index];
+        if board.history.len() != _ply {
+            return;
+        }
+
+        let played = _chosen
+            .and_then(|_move| parse_uci(&_move).ok_or(format!("{} is no UCI move", _move)))
+            .and_then(|(from, to, promotion)| board.try_execute(Command::PlayMove(from, to, promotion)));
+
+        if let Err(error) = played {
+            board.banner = Some(format!("{} could not move: {}", self.bot.name(), error));
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use eliasfl_chess::Color as Colour;
+use serde::{Deserialize, Serialize};
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::backend::GameStatus;
+use super::bot::random_bot::RandomBot;
+use super::controller::GameController;
+use super::help_funcs::get_piece_colour;
+
+/// Best times kept per difficulty
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// How well the random mover of a speedrun defends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Easy,
+    Normal,
+    Hard
+}
+
+impl Difficulty {
+    /// Every difficulty, in the order the button cycles through them
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    /// Name shown on the button, and the leaderboard is kept under
+    pub fn label(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard"
+        }
+    }
+
+    /// What the random mover does at this difficulty
+    pub fn description(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Plays any legal move",
+            Difficulty::Normal => "Takes the biggest piece it can",
+            Difficulty::Hard => "Takes what it can, keeps its pieces out of reach"
+        }
+    }
+}
+
+/// A checkmate on the leaderboard
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeedrunTime {
+    pub seconds: f32, // Thought over the player's own moves
+    pub moves: u32, // Of the player
+    pub when: u64 // Seconds since the Unix epoch
+}
+
+/// A speedrun being played: checkmating the random mover as fast as possible. Only the player's own thinking is timed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Speedrun {
+    pub difficulty: Difficulty,
+    pub human: Colour
+}
+
+impl Speedrun {
+    /// ## `time`
+    /// Seconds the player thought over their moves so far, and on the move they are thinking about
+    /// ### Parameters
+    /// - `_controller: &GameController`: The game of the speedrun
+    pub fn time(&self, _controller: &GameController) -> f32 {
+        let mut total = 0.0;
+        let mut previous = 0.0;
+
+        for _move in &_controller.history {
+            if *get_piece_colour(&_move.piece) == self.human {
+                total += (_move.elapsed - previous).max(0.0);
+            }
+            previous = _move.elapsed;
+        }
+
+        if _controller.game.active_colour() == self.human && !_controller.is_over() {
+            total += (_controller.game_time - previous).max(0.0);
+        }
+
+        total
+    }
+
+    /// If the game is still the speedrun: the player against the random mover of the difficulty
+    pub fn is_running(&self, _controller: &GameController) -> bool {
+        !_controller.bots.contains_key(&self.human)
+            && _controller.bots.get(&!self.human).map(|_bot| _bot.name()) == Some(RandomBot::name_for(self.difficulty))
+    }
+
+    /// The time of the run, once the player checkmated the bot
+    pub fn result(&self, _controller: &GameController) -> Option<SpeedrunTime> {
+        let mated = _controller.game.status() == GameStatus::CheckMate && _controller.game.active_colour() != self.human;
+        if !mated || !self.is_running(_controller) || _controller.adjudication.is_some() {
+            return None;
+        }
+
+        Some(SpeedrunTime {
+            seconds: self.time(_controller),
+            moves: _controller.history.iter().filter(|_move| *get_piece_colour(&_move.piece) == self.human).count() as u32,
+            when: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.as_secs())
+        })
+    }
+}
@@ -0,0 +1,185 @@
+use eliasfl_chess::Color as Colour;
+use super::controller::GameController;
+use super::backend::{ChessBackend, GameStatus};
+use super::bot::{legal_uci_moves, parse_uci};
+use super::help_funcs::{get_piece_colour, material, piece_value, num_to_filerank, filerank_to_num};
+
+/// Pawns a move may lose against the best one before its accuracy halves
+const HALF_ACCURACY_LOSS: f32 = 2.0;
+
+/// Score of a checkmate, more than all the material on the board
+const MATE_SCORE: f32 = 100.0;
+
+/// Part of the starting time under which a side is in time trouble
+pub const TIME_TROUBLE: f32 = 0.1;
+
+/// How a side played over a game
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct SideStats {
+    pub moves: usize,
+    pub accuracy: Option<f32>, // Percent, None if no move could be rated
+    pub average_think: f32, // Seconds
+    pub longest_think: f32 // Seconds
+}
+
+/// How both sides played over a game
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GameStats {
+    pub white: SideStats,
+    pub black: SideStats
+}
+
+impl GameStats {
+    /// The stats of one side
+    pub fn side(&self, _colour: Colour) -> &SideStats {
+        if _colour == Colour::White { &self.white } else { &self.black }
+    }
+
+    fn side_mut(&mut self, _colour: Colour) -> &mut SideStats {
+        if _colour == Colour::White { &mut self.white } else { &mut self.black }
+    }
+}
+
+/// ## `game_stats`
+/// Rates every move of a game and sums up the think times of both sides.
+/// There is no engine to ask, so a move is rated by the material it leaves the mover with once the opponent has made
+/// its best capture, compared with the best move available. Good enough to tell blunders apart, nothing deeper.
+/// ### Parameters
+/// - `_controller: &GameController`: The game, usually over
+pub fn game_stats(_controller: &GameController) -> GameStats {
+    let mut stats = GameStats::default();
+    let mut accuracy_sums = [0.0, 0.0]; // White, black
+    let mut rated = [0, 0];
+    let mut previous_elapsed = 0.0;
+
+    for (_ply, _move) in _controller.history.iter().enumerate() {
+        let colour = *get_piece_colour(&_move.piece);
+        let think = (_move.elapsed - previous_elapsed).max(0.0);
+        previous_elapsed = _move.elapsed;
+
+        let side = stats.side_mut(colour);
+        side.moves += 1;
+        side.average_think += think;
+        side.longest_think = side.longest_think.max(think);
+
+        if let Some(accuracy) = move_accuracy(_controller, _ply) {
+            let index = if colour == Colour::White { 0 } else { 1 };
+            accuracy_sums[index] += accuracy;
+            rated[index] += 1;
+        }
+    }
+
+    for (_index, _colour) in [Colour::White, Colour::Black].iter().enumerate() {
+        let side = stats.side_mut(*_colour);
+        if rated[_index] > 0 {
+            side.accuracy = Some(accuracy_sums[_index] / rated[_index] as f32);
+        }
+        if side.moves > 0 {
+            side.average_think /= side.moves as f32;
+        }
+    }
+
+    stats
+}
+
+/// ## `heatmap`
+/// Counts how often the pieces of a side moved to each square over a game
+/// ### Parameters
+/// - `_controller: &GameController`: The game
+/// - `_colour: Colour`: The side
+/// ### Return
+/// Visits by square, indexed `[col][row]` in GUI coordinates
+pub fn heatmap(_controller: &GameController, _colour: Colour) -> [[u32; 8]; 8] {
+    let mut visits = [[0; 8]; 8];
+
+    for _move in _controller.history.iter().filter(|_move| *get_piece_colour(&_move.piece) == _colour) {
+        let (col, row) = filerank_to_num(&_move.to);
+        visits[col as usize][row as usize] += 1;
+    }
+
+    visits
+}
+
+/// ## `clock_history`
+/// The time a side had left over the game, for plotting time trouble
+/// ### Parameters
+/// - `_controller: &GameController`: The game
+/// - `_colour: Colour`: The side
+/// ### Return
+/// Points of (ply, seconds left): the starting time, the clock after each of the side's moves, and the clock now
+/// when it is the side to move. Empty for untimed games.
+pub fn clock_history(_controller: &GameController, _colour: Colour) -> Vec<(usize, f32)> {
+    let start = match _controller.time_controls() {
+        Some((white, black)) => if _colour == Colour::White { white.initial } else { black.initial },
+        None => return vec![]
+    };
+
+    let mut points = vec![(0, start)];
+    for (_ply, _move) in _controller.history.iter().enumerate().filter(|(_, _move)| *get_piece_colour(&_move.piece) == _colour) {
+        if let Some(clock) = _move.clock {
+            points.push((_ply + 1, clock));
+        }
+    }
+
+    // The side to move has been thinking since the last move, until it moved or ran out
+    if _controller.game.active_colour() == _colour {
+        if let Some(clock) = _controller.clocks.get(&_colour) {
+            points.push((_controller.history.len(), clock.remaining()));
+        }
+    }
+
+    points
+}
+
+/// Accuracy of the move played at `_ply` in percent, None if the position could not be rebuilt
+fn move_accuracy(_controller: &GameController, _ply: usize) -> Option<f32> {
+    let before = _controller.position_at(_ply).ok()?;
+    let mover = before.active_colour();
+    let played = score(_controller.position_at(_ply + 1).ok()?.as_ref(), mover);
+
+    let mut best = played;
+    for _candidate in legal_uci_moves(before.as_ref()) {
+        let (from, to, promotion) = match parse_uci(&_candidate) {
+            Some(parsed) => parsed,
+            None => continue
+        };
+
+        let mut position = _controller.position_at(_ply).ok()?;
+        if position.make_move(&from, &to, promotion).is_ok() {
+            best = best.max(score(position.as_ref(), mover));
+        }
+    }
+
+    Some(100.0 * 0.5f32.powf((best - played) / HALF_ACCURACY_LOSS))
+}
+
+/// Material balance for `_mover` after the opponent (to move) makes its most valuable capture
+fn score(_position: &dyn ChessBackend, _mover: Colour) -> f32 {
+    let opponent = if _mover == Colour::White { Colour::Black } else { Colour::White };
+
+    match _position.status() {
+        GameStatus::CheckMate => return MATE_SCORE,
+        GameStatus::Draw(_) => return 0.0,
+        _ => {}
+    }
+
+    // The most the opponent can take straight away
+    let mut threat = 0;
+    for _col in 0..8 {
+        for _row in 0..8 {
+            match _position.piece_at((_col, _row)) {
+                Some(piece) if *get_piece_colour(&piece) == opponent => {},
+                _ => continue
+            }
+
+            for _to in _position.legal_moves(&num_to_filerank(&(_col, _row))) {
+                let target = filerank_to_num(&_to);
+                if let Some(captured) = _position.piece_at(target) {
+                    threat = threat.max(piece_value(&captured));
+                }
+            }
+        }
+    }
+
+    material(_position, _mover) as f32 - material(_position, opponent) as f32 - threat as f32
+}
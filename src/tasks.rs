@@ -0,0 +1,93 @@
+use std::{sync::mpsc::{self, Receiver, TryRecvError}, thread};
+
+/// Work running on a thread of its own, i.e. a bot search, network or file IO, so it never holds up drawing.
+/// Nothing waits on it: the update loop polls it until the result comes in.
+pub struct Task<T> {
+    receiver: Receiver<T>
+}
+
+impl<T: Send + 'static> Task<T> {
+    /// Starts `_job` on a background thread
+    pub fn spawn<F>(_job: F) -> Task<T> where F: FnOnce() -> T + Send + 'static {
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            // A dropped task means nobody wants the result anymore
+            sender.send(_job()).ok();
+        });
+
+        Task { receiver }
+    }
+
+    /// ## `poll`
+    /// Looks if the job is done, without waiting for it
+    /// ### Return
+    /// None while the job runs, then its result once. `Err` if the job panicked.
+    pub fn poll(&self) -> Option<Result<T, String>> {
+        match self.receiver.try_recv() {
+            Ok(result) => Some(Ok(result)),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err("the background task stopped without a result".to_string()))
+        }
+    }
+}
+
+/// Tasks with what to do with their results, polled once per frame from `AppState::update`.
+/// `C` is what the callbacks get to change, the `Shared` state of the scenes.
+pub struct TaskQueue<C> {
+    pending: Vec<Box<dyn FnMut(&mut C) -> bool>> // Each polls its task, true once it handled the result
+}
+
+impl<C> Default for TaskQueue<C> {
+    fn default() -> TaskQueue<C> {
+        TaskQueue { pending: vec![] }
+    }
+}
+
+impl<C> TaskQueue<C> {
+    /// No tasks running
+    pub fn new() -> TaskQueue<C> {
+        TaskQueue::default()
+    }
+
+    /// ## `spawn`
+    /// Runs `_job` in the background and hands its result to `_done` on the main thread once it finished
+    /// ### Parameters
+    /// - `_job`: The work, run on a background thread
+    /// - `_done`: Called from the update loop with the result, or why there is none
+    pub fn spawn<T, F, D>(&mut self, _job: F, _done: D)
+    where T: Send + 'static, F: FnOnce() -> T + Send + 'static, D: FnOnce(&mut C, Result<T, String>) + 'static {
+        let task = Task::spawn(_job);
+        let mut done = Some(_done);
+
+        self.pending.push(Box::new(move |_context: &mut C| match task.poll() {
+            Some(result) => {
+                if let Some(done) = done.take() {
+                    done(_context, result);
+                }
+                true
+            },
+            None => false
+        }));
+    }
+
+    /// How many tasks are still running
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// If no task is running
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Hands the results of the tasks that finished since the last poll to their callbacks
+    pub fn poll(&mut self, _context: &mut C) {
+        self.pending.retain_mut(|_poll| !_poll(_context));
+    }
+
+    /// Takes over the tasks of another queue, i.e. ones spawned by callbacks while this queue was being polled
+    pub fn append(&mut self, _other: &mut TaskQueue<C>) {
+        self.pending.append(&mut _other.pending);
+    }
+}
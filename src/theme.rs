@@ -0,0 +1,102 @@
+use super::consts;
+use super::graphics_funcs::renderer::Rgba;
+
+/// Marks of the built-in themes, see `Theme::marks`. See-through, as they are drawn over the pieces.
+const MARKS: [Rgba; 4] = [[0.9, 0.2, 0.15, 0.5], [0.2, 0.7, 0.25, 0.5], [0.15, 0.45, 0.9, 0.5], [0.95, 0.8, 0.1, 0.5]];
+
+/// Colours of the interface
+#[derive(Debug, Clone, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub light_tile: Rgba,
+    pub dark_tile: Rgba,
+    pub background: Rgba, // Behind the board and the panel
+    pub text: Rgba, // Status text drawn on the background
+    pub legal_indicator: Rgba,
+    pub outline: f32, // Width of the rings around pieces and legal indicators, 0 for none
+    pub outline_colour: Rgba,
+    pub marks: [Rgba; 4] // Squares marked with a right click, in the order of `MarkColour`: red, green, blue and yellow
+}
+
+impl Theme {
+    /// The greys the GUI always had
+    pub fn classic() -> Theme {
+        Theme {
+            name: "classic".to_string(),
+            light_tile: consts::WHITE,
+            dark_tile: consts::BLACK,
+            background: consts::BACKGROUND,
+            text: [0.0, 0.0, 0.0, 1.0],
+            legal_indicator: [0.6, 1.0, 0.6, 0.5],
+            outline: 0.0,
+            outline_colour: [0.0, 0.0, 0.0, 1.0],
+            marks: MARKS
+        }
+    }
+
+    /// Dark background with white text, easier on the eyes at night
+    pub fn dark() -> Theme {
+        Theme {
+            name: "dark".to_string(),
+            light_tile: [0.35, 0.35, 0.38, 1.0],
+            dark_tile: [0.16, 0.16, 0.18, 1.0],
+            background: [0.08, 0.08, 0.09, 1.0],
+            text: [0.9, 0.9, 0.9, 1.0],
+            legal_indicator: [0.4, 0.8, 1.0, 0.5],
+            outline: 0.0,
+            outline_colour: [0.0, 0.0, 0.0, 1.0],
+            marks: MARKS
+        }
+    }
+
+    /// Wooden board colours on a light background
+    pub fn light() -> Theme {
+        Theme {
+            name: "light".to_string(),
+            light_tile: [0.94, 0.85, 0.71, 1.0],
+            dark_tile: [0.71, 0.53, 0.39, 1.0],
+            background: [0.96, 0.96, 0.94, 1.0],
+            text: [0.1, 0.1, 0.1, 1.0],
+            legal_indicator: [0.2, 0.6, 0.2, 0.5],
+            outline: 0.0,
+            outline_colour: [0.0, 0.0, 0.0, 1.0],
+            marks: MARKS
+        }
+    }
+
+    /// Pure black and white tiles, with thick yellow rings setting pieces and legal moves apart from them. For low vision.
+    pub fn high_contrast() -> Theme {
+        Theme {
+            name: "high contrast".to_string(),
+            light_tile: [1.0, 1.0, 1.0, 1.0],
+            dark_tile: [0.0, 0.0, 0.0, 1.0],
+            background: [0.0, 0.0, 0.0, 1.0],
+            text: [1.0, 1.0, 1.0, 1.0],
+            legal_indicator: [0.0, 0.45, 1.0, 1.0],
+            outline: 5.0,
+            outline_colour: [1.0, 0.85, 0.0, 1.0],
+            // Only the marks stand out against pure black and white
+            marks: [[1.0, 0.0, 0.0, 0.7], [0.0, 0.8, 0.0, 0.7], [0.0, 0.45, 1.0, 0.7], [1.0, 0.85, 0.0, 0.7]]
+        }
+    }
+
+    /// Names of the themes built into the GUI
+    pub const BUILTIN: [&'static str; 4] = ["classic", "dark", "light", "high contrast"];
+
+    /// A built-in theme by name
+    pub fn builtin(_name: &str) -> Option<Theme> {
+        match _name {
+            "classic" => Some(Theme::classic()),
+            "dark" => Some(Theme::dark()),
+            "light" => Some(Theme::light()),
+            "high contrast" => Some(Theme::high_contrast()),
+            _ => None
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::classic()
+    }
+}
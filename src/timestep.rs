@@ -0,0 +1,43 @@
+/// Fixed timestep accumulator. Frame times are added up and handed out as equally sized steps,
+/// so everything ticked by it behaves the same whatever the frame rate is.
+pub struct FixedTimestep {
+    step: f32,
+    accumulator: f32,
+    max_steps: u32
+}
+
+impl FixedTimestep {
+    /// Creates an accumulator handing out `_rate` steps per second
+    pub fn new(_rate: u32) -> FixedTimestep {
+        FixedTimestep {
+            step: 1.0 / _rate as f32,
+            accumulator: 0.0,
+            max_steps: _rate / 4 // Never catch up on more than a quarter of a second in one frame, the rest waits for the next ones
+        }
+    }
+
+    /// Length of one step in seconds
+    pub fn step(&self) -> f32 {
+        self.step
+    }
+
+    /// Adds the time of a frame and returns how many whole steps should be run for it
+    pub fn advance(&mut self, _frame_time: f32) -> u32 {
+        self.accumulator += _frame_time;
+
+        // After a long stall (window dragged, breakpoint) the backlog stays in the accumulator and is caught up on over
+        // the next frames, so the clocks ticked by the steps lose no time
+        let mut steps = 0;
+        while self.accumulator >= self.step && steps < self.max_steps {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+
+        steps
+    }
+
+    /// How far into the next step the accumulated time is (0.0 to 1.0), for interpolating drawing between steps
+    pub fn alpha(&self) -> f32 {
+        (self.accumulator / self.step).min(1.0)
+    }
+}
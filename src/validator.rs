@@ -0,0 +1,402 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use super::backend::ChessBackend;
+use super::help_funcs::{filerank_to_num, num_to_filerank, get_piece_colour};
+use super::export::piece_name;
+
+/// Snapshot of the board in GUI coords, indexed `[col][row]`
+pub type BoardSnapshot = [[Option<Piece>; 8]; 8];
+
+/// Copies the pieces of a backend into a snapshot
+pub fn snapshot(_backend: &dyn ChessBackend) -> BoardSnapshot {
+    let mut board = [[None; 8]; 8];
+
+    for _col in 0..8 {
+        for _row in 0..8 {
+            board[_col][_row] = _backend.piece_at((_col as u8, _row as u8));
+        }
+    }
+
+    board
+}
+
+/// The piece on a square given as signed coords, None when off the board
+fn at(_board: &BoardSnapshot, _square: (i8, i8)) -> Option<Piece> {
+    if _square.0 < 0 || _square.0 > 7 || _square.1 < 0 || _square.1 > 7 {
+        return None;
+    }
+
+    _board[_square.0 as usize][_square.1 as usize]
+}
+
+/// If every square strictly between `_from` and `_to` (on a line or diagonal) is empty
+fn path_clear(_board: &BoardSnapshot, _from: (i8, i8), _to: (i8, i8)) -> bool {
+    let step = ((_to.0 - _from.0).signum(), (_to.1 - _from.1).signum());
+    let mut square = (_from.0 + step.0, _from.1 + step.1);
+
+    while square != _to {
+        if at(_board, square).is_some() {
+            return false;
+        }
+        square = (square.0 + step.0, square.1 + step.1);
+    }
+
+    true
+}
+
+/// Row direction white or black pawns move in (the GUI's row 0 is rank 8)
+fn forward(_colour: Colour) -> i8 {
+    if _colour == Colour::White { -1 } else { 1 }
+}
+
+/// If a piece of colour `_by` attacks `_square`
+pub fn is_attacked(_board: &BoardSnapshot, _square: (i8, i8), _by: Colour) -> bool {
+    for _col in 0..8 {
+        for _row in 0..8 {
+            let from = (_col, _row);
+
+            if let Some(piece) = at(_board, from) {
+                if *get_piece_colour(&piece) == _by && attacks(_board, piece, from, _square) {
+                    return true;
+                }
+            }
+        }
+    }
+
+    false
+}
+
+/// If `_piece` standing on `_from` attacks `_to`, following only the movement pattern and blocking pieces
+fn attacks(_board: &BoardSnapshot, _piece: Piece, _from: (i8, i8), _to: (i8, i8)) -> bool {
+    let (dx, dy) = (_to.0 - _from.0, _to.1 - _from.1);
+
+    if dx == 0 && dy == 0 {
+        return false;
+    }
+
+    match _piece {
+        Piece::Pawn(colour) => dy == forward(colour) && dx.abs() == 1,
+        Piece::Knight(_) => (dx.abs() == 1 && dy.abs() == 2) || (dx.abs() == 2 && dy.abs() == 1),
+        Piece::Bishop(_) => dx.abs() == dy.abs() && path_clear(_board, _from, _to),
+        Piece::Rook(_) => (dx == 0 || dy == 0) && path_clear(_board, _from, _to),
+        Piece::Queen(_) => (dx.abs() == dy.abs() || dx == 0 || dy == 0) && path_clear(_board, _from, _to),
+        Piece::King(_) => dx.abs() <= 1 && dy.abs() <= 1
+    }
+}
+
+/// Where the king of `_colour` stands
+pub fn find_king(_board: &BoardSnapshot, _colour: Colour) -> Option<(i8, i8)> {
+    for _col in 0..8 {
+        for _row in 0..8 {
+            if at(_board, (_col, _row)) == Some(Piece::King(_colour)) {
+                return Some((_col, _row));
+            }
+        }
+    }
+
+    None
+}
+
+/// If a move is Chess960 castling, written as the king taking its own rook on the same rank
+fn is_chess960_castling(_board: &BoardSnapshot, _piece: Piece, _from: (i8, i8), _to: (i8, i8)) -> bool {
+    match _piece {
+        Piece::King(colour) => _from.1 == _to.1 && at(_board, _to) == Some(Piece::Rook(colour)),
+        _ => false
+    }
+}
+
+/// ## `check_chess960_castling`
+/// Checks castling the Chess960 way: the king and the rook end on the squares of standard castling (g and f, or c
+/// and d), every square either passes over or lands on is empty but for the two of them, and the king is not in check
+/// on any square from where it stands to where it lands
+/// ### Parameters
+/// - `_board: &BoardSnapshot`: The position
+/// - `_colour: Colour`: The side castling
+/// - `_king: (i8, i8)`: Where the king stands
+/// - `_rook: (i8, i8)`: Where the rook it castles with stands
+fn check_chess960_castling(_board: &BoardSnapshot, _colour: Colour, _king: (i8, i8), _rook: (i8, i8)) -> Result<(), String> {
+    let row = _king.1;
+    let (king_to, rook_to) = if _rook.0 > _king.0 { (6, 5) } else { (2, 3) };
+
+    let span = |_a: i8, _b: i8| _a.min(_b)..=_a.max(_b);
+    for _col in span(_king.0, king_to).chain(span(_rook.0, rook_to)) {
+        if _col != _king.0 && _col != _rook.0 && at(_board, (_col, row)).is_some() {
+            return Err("castling is blocked".to_string());
+        }
+    }
+
+    // The rook is off its square once the king gets there, it cannot shield the king on the way either
+    let mut without_rook = *_board;
+    without_rook[_rook.0 as usize][row as usize] = None;
+    for _col in span(_king.0, king_to) {
+        if is_attacked(&without_rook, (_col, row), !_colour) {
+            return Err("castling out of, through or into check".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the movement pattern of a move, castling and en passant included. En passant is only allowed onto
+/// `_en_passant`, the square passed over by the pawn that moved two squares on the move before.
+fn check_pattern(_board: &BoardSnapshot, _piece: Piece, _from: (i8, i8), _to: (i8, i8), _en_passant: Option<(i8, i8)>) -> Result<(), String> {
+    let (dx, dy) = (_to.0 - _from.0, _to.1 - _from.1);
+    let colour = *get_piece_colour(&_piece);
+    let target = at(_board, _to);
+
+    match _piece {
+        Piece::Pawn(_) => {
+            let start_row = if colour == Colour::White { 6 } else { 1 };
+
+            if dx == 0 && dy == forward(colour) && target.is_none() { Ok(()) }
+            else if dx == 0 && dy == 2 * forward(colour) && _from.1 == start_row
+                && target.is_none() && at(_board, (_from.0, _from.1 + forward(colour))).is_none() { Ok(()) }
+            else if dx == 0 && (dy == forward(colour) || (dy == 2 * forward(colour) && _from.1 == start_row)) {
+                Err("blocked".to_string())
+            }
+            else if dx.abs() == 1 && dy == forward(colour) {
+                // Diagonal moves must capture, either normally or en passant (behind the pawn that just moved two squares)
+                if target.is_some() { Ok(()) }
+                else if _en_passant == Some(_to) && at(_board, (_to.0, _from.1)) == Some(Piece::Pawn(!colour)) { Ok(()) }
+                else if at(_board, (_to.0, _from.1)) == Some(Piece::Pawn(!colour)) {
+                    Err("en passant is only possible right after the pawn moved two squares".to_string())
+                }
+                else { Err("pawns only move diagonally when capturing".to_string()) }
+            }
+            else { Err("pawns cannot move like that".to_string()) }
+        },
+        Piece::King(_) if dy == 0 && dx.abs() == 2 => {
+            // Castling: the king may not be in check, pass through an attacked square or land on one
+            let rook_col = if dx > 0 { 7 } else { 0 };
+
+            if at(_board, (rook_col, _from.1)) != Some(Piece::Rook(colour)) {
+                return Err("there is no rook to castle with".to_string());
+            }
+            if !path_clear(_board, _from, (rook_col, _from.1)) {
+                return Err("castling is blocked".to_string());
+            }
+
+            let passed = (_from.0 + dx.signum(), _from.1);
+            for _square in [_from, passed, _to].iter() {
+                if is_attacked(_board, *_square, !colour) {
+                    return Err("castling out of, through or into check".to_string());
+                }
+            }
+
+            Ok(())
+        },
+        _ => {
+            // On an empty board only the movement pattern is left to get wrong
+            if attacks(_board, _piece, _from, _to) { Ok(()) }
+            else if attacks(&[[None; 8]; 8], _piece, _from, _to) { Err("blocked".to_string()) }
+            else { Err(format!("{}s cannot move like that", piece_name(&_piece))) }
+        }
+    }
+}
+
+/// ## `validate_move`
+/// Independent of the backend, checks that a move the backend is about to play follows the rules, castling the
+/// standard way (the king two files over) and the Chess960 way (the king onto its rook). Castling rights are not
+/// known to the GUI and are not checked.
+/// ### Parameters
+/// - `_backend: &dyn ChessBackend`: The backend, at the position before the move
+/// - `_from: &str`: The square moved from as "\<file\>\<rank\>"
+/// - `_to: &str`: The square moved to as "\<file\>\<rank\>"
+/// - `_en_passant: Option<&str>`: The en passant square of the position as in its FEN (see `fen::en_passant`), None
+/// when the last move was no pawn moving two squares
+/// ### Return
+/// `Err` with the move and the reason if the move is illegal, i.e. "e2-e5: blocked"
+pub fn validate_move(_backend: &dyn ChessBackend, _from: &str, _to: &str, _en_passant: Option<&str>) -> Result<(), String> {
+    why_illegal(_backend, _from, _to, _en_passant).map_err(|_reason| format!("{}-{}: {}", _from, _to, _reason))
+}
+
+/// ## `why_illegal`
+/// Same as `validate_move`, with only the reason in the `Err`, i.e. "blocked" or "leaves the king in check"
+pub fn why_illegal(_backend: &dyn ChessBackend, _from: &str, _to: &str, _en_passant: Option<&str>) -> Result<(), String> {
+    let mut board = snapshot(_backend);
+    let mover = _backend.active_colour();
+
+    let from = filerank_to_num(&_from.to_string());
+    let to = filerank_to_num(&_to.to_string());
+    let (from, to) = ((from.0 as i8, from.1 as i8), (to.0 as i8, to.1 as i8));
+    let en_passant = _en_passant.map(|_square| filerank_to_num(&_square.to_string())).map(|(_col, _row)| (_col as i8, _row as i8));
+
+    let piece = at(&board, from).ok_or(format!("there is no piece on {}", _from))?;
+
+    if *get_piece_colour(&piece) != mover {
+        return Err(format!("the piece on {} belongs to the other side", _from));
+    }
+
+    // Chess960 castling takes the king onto its own rook, the only move onto an own piece
+    if is_chess960_castling(&board, piece, from, to) {
+        return check_chess960_castling(&board, mover, from, to);
+    }
+
+    if let Some(target) = at(&board, to) {
+        if *get_piece_colour(&target) == mover {
+            return Err(format!("{} is taken by an own piece", _to));
+        }
+        if let Piece::King(_) = target {
+            return Err("kings cannot be captured".to_string());
+        }
+    }
+
+    check_pattern(&board, piece, from, to, en_passant)?;
+
+    // Play the move on the snapshot and make sure the own king is not left in check
+    if let Piece::Pawn(_) = piece {
+        if en_passant == Some(to) && at(&board, to).is_none() {
+            board[to.0 as usize][from.1 as usize] = None; // Pawn taken en passant
+        }
+    }
+    board[to.0 as usize][to.1 as usize] = Some(piece);
+    board[from.0 as usize][from.1 as usize] = None;
+
+    if let Some(king) = find_king(&board, mover) {
+        if is_attacked(&board, king, !mover) {
+            return Err("leaves the king in check".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// ## `search_moves`
+/// The legal moves of a side on a snapshot, for bots looking ahead of the game. A snapshot knows no castling rights or
+/// en passant square, so castling and en passant are left out.
+/// ### Parameters
+/// - `_board: &BoardSnapshot`: The position
+/// - `_mover: Colour`: The side to move
+/// ### Return
+/// Every move as (from, to) in GUI coords, pawns reaching the last rank included once
+pub fn search_moves(_board: &BoardSnapshot, _mover: Colour) -> Vec<((u8, u8), (u8, u8))> {
+    let mut moves = vec![];
+
+    for _from in (0..8).flat_map(|_col| (0..8).map(move |_row| (_col, _row))) {
+        let piece = match at(_board, _from) {
+            Some(piece) if *get_piece_colour(&piece) == _mover => piece,
+            _ => continue
+        };
+
+        for _to in (0..8).flat_map(|_col| (0..8).map(move |_row| (_col, _row))) {
+            let target = at(_board, _to);
+            if target.map_or(false, |_target| *get_piece_colour(&_target) == _mover || matches!(_target, Piece::King(_))) {
+                continue;
+            }
+
+            // Pawns take diagonally and only push onto empty squares
+            let reaches = match piece {
+                Piece::Pawn(colour) if target.is_none() => {
+                    let start_row = if colour == Colour::White { 6 } else { 1 };
+                    let (dx, dy) = (_to.0 - _from.0, _to.1 - _from.1);
+                    dx == 0 && (dy == forward(colour) || (dy == 2 * forward(colour) && _from.1 == start_row && at(_board, (_from.0, _from.1 + forward(colour))).is_none()))
+                },
+                _ => attacks(_board, piece, _from, _to)
+            };
+            if !reaches {
+                continue;
+            }
+
+            let mut after = *_board;
+            after[_to.0 as usize][_to.1 as usize] = Some(piece);
+            after[_from.0 as usize][_from.1 as usize] = None;
+            if find_king(&after, _mover).map_or(true, |_king| !is_attacked(&after, _king, !_mover)) {
+                moves.push(((_from.0 as u8, _from.1 as u8), (_to.0 as u8, _to.1 as u8)));
+            }
+        }
+    }
+
+    moves
+}
+
+/// ## `validate_setup`
+/// Checks a position set up by hand before it is played from: exactly one king per side, no pawns on the first or last
+/// rank, and the side not to move not in check already, which would let the side to move take the king
+/// ### Parameters
+/// - `_pieces: &[((u8, u8), Piece)]`: The pieces on their squares, in GUI coords
+/// - `_to_move: Colour`: The side to move
+/// ### Return
+/// What is wrong with the position, one message each, i.e. "Black has no king". Empty if it can be played.
+pub fn validate_setup(_pieces: &[((u8, u8), Piece)], _to_move: Colour) -> Vec<String> {
+    let mut errors = vec![];
+    let mut board: BoardSnapshot = [[None; 8]; 8];
+    for (_square, _piece) in _pieces {
+        board[_square.0 as usize][_square.1 as usize] = Some(*_piece);
+    }
+
+    for _colour in [Colour::White, Colour::Black].iter() {
+        let side = if *_colour == Colour::White { "White" } else { "Black" };
+        match _pieces.iter().filter(|(_, _piece)| *_piece == Piece::King(*_colour)).count() {
+            0 => errors.push(format!("{} has no king", side)),
+            1 => {},
+            kings => errors.push(format!("{} has {} kings, it needs exactly one", side, kings))
+        }
+    }
+    let kings_placed = errors.is_empty();
+
+    let mut stranded = _pieces.iter()
+        .filter(|(_square, _piece)| matches!(_piece, Piece::Pawn(_)) && (_square.1 == 0 || _square.1 == 7))
+        .map(|(_square, _)| num_to_filerank(_square))
+        .collect::<Vec<String>>();
+    if !stranded.is_empty() {
+        stranded.sort();
+        errors.push(format!("Pawns cannot stand on the first or last rank: {}", stranded.join(", ")));
+    }
+
+    // With one king each, the side that just moved may not have left its king in check
+    if kings_placed {
+        if let Some(king) = find_king(&board, !_to_move) {
+            if is_attacked(&board, king, _to_move) {
+                let (mover, other) = if _to_move == Colour::White { ("White", "Black") } else { ("Black", "White") };
+                errors.push(format!("{} is in check with {} to move, {} could take the king", other, mover, mover));
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::backend::fen_board::FenBoard;
+    use super::super::fen;
+
+    /// Why a move is illegal in a position, Ok if it is legal
+    fn check(_fen: &str, _from: &str, _to: &str) -> Result<(), String> {
+        let board = FenBoard::new(_fen);
+        why_illegal(&board, _from, _to, fen::en_passant(_fen).as_deref())
+    }
+
+    // The king may not castle out of, through or into check
+    #[test]
+    fn castling_through_check() {
+        assert_eq!(check("k7/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "g1"), Ok(()));
+        assert_eq!(check("k4r2/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "g1"), Err("castling out of, through or into check".to_string()));
+        assert_eq!(check("k5r1/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "g1"), Err("castling out of, through or into check".to_string()));
+        assert_eq!(check("k3r3/8/8/8/8/8/8/4K2R w K - 0 1", "e1", "g1"), Err("castling out of, through or into check".to_string()));
+        assert_eq!(check("k7/8/8/8/8/8/8/4KB1R w K - 0 1", "e1", "g1"), Err("castling is blocked".to_string()));
+
+        // Only the squares of the king count on the queen's side, the rook may pass an attacked b1
+        assert_eq!(check("k1r5/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1", "c1"), Err("castling out of, through or into check".to_string()));
+        assert_eq!(check("kr6/8/8/8/8/8/8/R3K3 w Q - 0 1", "e1", "c1"), Ok(()));
+    }
+
+    // En passant is only allowed onto the square passed over by the pawn that just moved two squares
+    #[test]
+    fn en_passant_only_after_the_double_push() {
+        assert_eq!(check("k7/8/8/3pP3/8/8/8/4K3 w - d6 0 2", "e5", "d6"), Ok(()));
+        assert_eq!(check("k7/8/8/3pP3/8/8/8/4K3 w - - 0 2", "e5", "d6"),
+            Err("en passant is only possible right after the pawn moved two squares".to_string()));
+        assert_eq!(check("k7/8/8/2ppP3/8/8/8/4K3 w - c6 0 2", "e5", "d6"),
+            Err("en passant is only possible right after the pawn moved two squares".to_string()));
+        assert_eq!(check("k7/8/8/2ppP3/8/8/8/4K3 w - c6 0 2", "e5", "f6"),
+            Err("pawns only move diagonally when capturing".to_string()));
+    }
+
+    // The reason comes with the move
+    #[test]
+    fn validate_move_names_the_move() {
+        assert_eq!(validate_move(&FenBoard::new("k7/8/8/8/8/8/4P3/4K3 w - - 0 1"), "e2", "e5", None), Err("e2-e5: pawns cannot move like that".to_string()));
+        assert_eq!(validate_move(&FenBoard::new("k7/8/8/8/8/8/8/r3K3 w - - 0 1"), "e1", "d1", None), Err("e1-d1: leaves the king in check".to_string()));
+    }
+}
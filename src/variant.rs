@@ -0,0 +1,275 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use serde::Deserialize;
+use std::cell::Cell;
+use std::time::{SystemTime, UNIX_EPOCH};
+use super::backend::ChessBackend;
+use super::clock::TimeControl;
+use super::history::MoveRecord;
+use super::help_funcs::get_piece_colour;
+
+/// Rules on top of standard chess. The backend still plays the moves, a variant changes how the game is set up and won.
+pub trait Variant {
+    /// Name shown in the settings
+    fn name(&self) -> String;
+
+    /// Position the game starts from, None for the usual starting position
+    fn start_fen(&self) -> Option<String> {
+        None
+    }
+
+    /// Win conditions besides checkmate, looked at after every move
+    /// ### Return
+    /// The side that won and why, i.e. "reached e4", or None while the game goes on
+    fn winner(&self, _game: &dyn ChessBackend, _history: &[MoveRecord]) -> Option<(Colour, String)> {
+        None
+    }
+
+    /// Clocks of White and Black the variant is played with, instead of the time control of the settings
+    fn time_controls(&self) -> Option<(TimeControl, TimeControl)> {
+        None
+    }
+
+    /// The side a drawn game goes to instead, whichever way it was drawn: on the board, by agreement, by the arbiter
+    /// or on time against insufficient material. None when draws stay draws.
+    fn resolve_draw(&self) -> Option<Colour> {
+        None
+    }
+
+    /// The variant as Chess960, to choose its starting position. None for every other variant.
+    fn chess960(&self) -> Option<&Chess960> {
+        None
+    }
+}
+
+/// Plain chess
+pub struct Standard;
+
+impl Variant for Standard {
+    fn name(&self) -> String {
+        "Standard".to_string()
+    }
+}
+
+/// Armageddon, the tiebreak game: White has more time but has to win, a draw counts as a win for Black
+pub struct Armageddon;
+
+impl Variant for Armageddon {
+    fn name(&self) -> String {
+        "Armageddon".to_string()
+    }
+
+    fn resolve_draw(&self) -> Option<Colour> {
+        Some(Colour::Black)
+    }
+
+    fn time_controls(&self) -> Option<(TimeControl, TimeControl)> {
+        Some((TimeControl::minutes(5.0, 0.0), TimeControl::minutes(4.0, 0.0)))
+    }
+}
+
+/// Number of Chess960 starting positions
+pub const CHESS960_POSITIONS: u16 = 960;
+
+/// ## `chess960_fen`
+/// The starting position of a Chess960 game from its number, counted the usual (Scharnagl) way so that 518 is the
+/// starting position of standard chess. The castling rights are written as Shredder-FEN, the files of the rooks (i.e.
+/// "HAha" for 518), which backends that castle the Chess960 way read as such (see `ChessBackend::chess960`).
+/// ### Parameters
+/// - `_position: u16`: Number of the position, 0 to 959
+pub fn chess960_fen(_position: u16) -> String {
+    // Knights on two of the five squares left after the bishops and the queen
+    const KNIGHTS: [(usize, usize); 10] = [(0, 1), (0, 2), (0, 3), (0, 4), (1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)];
+
+    let mut rank = [' '; 8];
+    let mut number = (_position % CHESS960_POSITIONS) as usize;
+
+    // One bishop on a light square, one on a dark square
+    rank[number % 4 * 2 + 1] = 'b';
+    number /= 4;
+    rank[number % 4 * 2] = 'b';
+    number /= 4;
+
+    let empty = |_rank: &[char; 8]| -> Vec<usize> { (0..8).filter(|_file| _rank[*_file] == ' ').collect() };
+
+    let queen = empty(&rank)[number % 6];
+    rank[queen] = 'q';
+    number /= 6;
+
+    let (first, second) = KNIGHTS[number];
+    let squares = empty(&rank);
+    rank[squares[first]] = 'n';
+    rank[squares[second]] = 'n';
+
+    // The king between the rooks
+    for (_file, _piece) in empty(&rank).into_iter().zip(['r', 'k', 'r'].iter()) {
+        rank[_file] = *_piece;
+    }
+
+    // The rook on the king's side first, as in "HAha"
+    let rooks: String = (0..8).rev().filter(|_file| rank[*_file] == 'r').map(|_file| (b'a' + _file as u8) as char).collect();
+
+    let black: String = rank.iter().collect();
+    format!("{}/pppppppp/8/8/8/8/PPPPPPPP/{} w {}{} - 0 1", black, black.to_uppercase(), rooks.to_uppercase(), rooks)
+}
+
+/// Chess960: the pieces behind the pawns are shuffled, the same way for both sides, with the bishops on
+/// different colours and the king between the rooks. Castling takes the king onto its rook, and ends with king and
+/// rook on the squares of standard castling. Only offered on backends that castle this way.
+pub struct Chess960 {
+    chosen: Cell<Option<u16>>, // Position entered in the setup, None draws one at random for every game
+    current: Cell<Option<u16>> // Position the last game was set up from
+}
+
+impl Chess960 {
+    pub fn new() -> Chess960 {
+        Chess960 { chosen: Cell::new(None), current: Cell::new(None) }
+    }
+
+    /// The position entered in the setup, None when every game draws one at random
+    pub fn chosen(&self) -> Option<u16> {
+        self.chosen.get()
+    }
+
+    /// Plays the next games from a position, or from random ones with None. Numbers past 959 are left out.
+    pub fn choose(&self, _position: Option<u16>) {
+        self.chosen.set(_position.filter(|_position| *_position < CHESS960_POSITIONS));
+    }
+
+    /// Number of the position the last game was set up from, None before the first one
+    pub fn current(&self) -> Option<u16> {
+        self.current.get()
+    }
+}
+
+impl Variant for Chess960 {
+    fn name(&self) -> String {
+        "Chess960".to_string()
+    }
+
+    fn start_fen(&self) -> Option<String> {
+        let position = self.chosen.get().unwrap_or_else(|| {
+            let seed = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |_since| _since.subsec_nanos());
+            (seed % CHESS960_POSITIONS as u32) as u16
+        });
+        self.current.set(Some(position));
+
+        Some(chess960_fen(position))
+    }
+
+    fn chess960(&self) -> Option<&Chess960> {
+        Some(self)
+    }
+}
+
+/// Kind of a piece regardless of its colour, as written in plugin manifests
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PieceKind {
+    Pawn,
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+    King
+}
+
+impl PieceKind {
+    /// If a piece is of this kind
+    pub fn matches(&self, _piece: &Piece) -> bool {
+        match (self, _piece) {
+            (PieceKind::Pawn, Piece::Pawn(_)) | (PieceKind::Knight, Piece::Knight(_)) | (PieceKind::Bishop, Piece::Bishop(_))
+            | (PieceKind::Rook, Piece::Rook(_)) | (PieceKind::Queen, Piece::Queen(_)) | (PieceKind::King, Piece::King(_)) => true,
+            _ => false
+        }
+    }
+
+    /// Plural name for messages
+    fn plural(&self) -> &'static str {
+        match self {
+            PieceKind::Pawn => "pawns",
+            PieceKind::Knight => "knights",
+            PieceKind::Bishop => "bishops",
+            PieceKind::Rook => "rooks",
+            PieceKind::Queen => "queens",
+            PieceKind::King => "kings"
+        }
+    }
+}
+
+/// A simple way to win besides checkmate, for teaching mini-games. Written in plugin manifests as `[[variant.win]]` tables:
+/// ```toml
+/// [[variant.win]]
+/// type = "reach"            # King of the hill
+/// piece = "king"
+/// squares = ["d4", "d5", "e4", "e5"]
+///
+/// [[variant.win]]
+/// type = "capture"
+/// piece = "pawn"
+/// count = 3
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum WinCondition {
+    /// Moving a piece (of any kind when left out) to one of the squares wins
+    Reach { piece: Option<PieceKind>, squares: Vec<String> },
+    /// Capturing `count` pieces (of any kind when left out) wins
+    Capture { piece: Option<PieceKind>, count: usize }
+}
+
+impl WinCondition {
+    /// If the last move met the condition, the reason the mover won
+    pub fn met_by(&self, _history: &[MoveRecord]) -> Option<String> {
+        let last = _history.last()?;
+        let mover = *get_piece_colour(&last.piece);
+        let kind_matches = |_kind: &Option<PieceKind>, _piece: &Piece| _kind.map_or(true, |_kind| _kind.matches(_piece));
+
+        match self {
+            WinCondition::Reach { piece, squares } => {
+                if kind_matches(piece, &last.piece) && squares.iter().any(|_square| *_square == last.to) {
+                    Some(format!("reached {}", last.to))
+                }
+                else {
+                    None
+                }
+            },
+            WinCondition::Capture { piece, count } => {
+                let captured = _history.iter()
+                    .filter(|_move| *get_piece_colour(&_move.piece) == mover)
+                    .filter_map(|_move| _move.captured)
+                    .filter(|_captured| kind_matches(piece, _captured))
+                    .count();
+
+                if last.captured.is_some() && captured >= *count {
+                    Some(format!("captured {} {}", count, piece.map_or("pieces", |_kind| _kind.plural())))
+                }
+                else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// Variant described by a plugin manifest instead of code
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigVariant {
+    pub name: String,
+    pub start_fen: Option<String>,
+    pub win_conditions: Vec<WinCondition>
+}
+
+impl Variant for ConfigVariant {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn start_fen(&self) -> Option<String> {
+        self.start_fen.clone()
+    }
+
+    fn winner(&self, _game: &dyn ChessBackend, _history: &[MoveRecord]) -> Option<(Colour, String)> {
+        let mover = *get_piece_colour(&_history.last()?.piece);
+        self.win_conditions.iter().find_map(|_condition| _condition.met_by(_history)).map(|_reason| (mover, _reason))
+    }
+}
@@ -0,0 +1,107 @@
+use super::controller::InputEvent;
+use super::consts::{GRID_SIZE, GRID_CELL_SIZE};
+
+/// Closest the board can be zoomed in, in times its normal size
+pub const MAX_ZOOM: f32 = 3.0;
+
+/// How much one notch of the mouse wheel zooms
+const ZOOM_STEP: f32 = 1.25;
+
+/// Zoom and pan of the board. Window coordinates go through it to find the square under the cursor,
+/// and the board is drawn through it with a `ZoomRenderer`.
+///
+/// The board never gets smaller than its area of the window and never leaves a gap in it, so at the normal size
+/// nothing moves at all.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoardView {
+    pub zoom: f32, // 1.0 is the normal size
+    pub offset: (f32, f32), // Where the top left corner of the board is in the window
+    cursor: (f32, f32), // Last known cursor position, the wheel zooms around it
+    panning: bool // If the middle button is held
+}
+
+impl Default for BoardView {
+    fn default() -> BoardView {
+        BoardView { zoom: 1.0, offset: (0.0, 0.0), cursor: (0.0, 0.0), panning: false }
+    }
+}
+
+impl BoardView {
+    /// The normal, unzoomed view
+    pub fn new() -> BoardView {
+        BoardView::default()
+    }
+
+    /// If the board is seen bigger than normal
+    pub fn is_zoomed(&self) -> bool {
+        self.zoom > 1.0
+    }
+
+    /// Board coordinates (as drawn at the normal size) of a point in the window
+    pub fn to_board(&self, _at: (f32, f32)) -> (f32, f32) {
+        ((_at.0 - self.offset.0) / self.zoom, (_at.1 - self.offset.1) / self.zoom)
+    }
+
+    /// Window coordinates of a point of the board, as drawn at the normal size
+    pub fn to_window(&self, _at: (f32, f32)) -> (f32, f32) {
+        (_at.0 * self.zoom + self.offset.0, _at.1 * self.zoom + self.offset.1)
+    }
+
+    /// ## `zoom_by`
+    /// Zooms in (positive) or out (negative) by notches of the mouse wheel, keeping the point under `_around` in place
+    /// ### Parameters
+    /// - `_notches: f32`: How far the wheel turned
+    /// - `_around: (f32, f32)`: Window coordinates to zoom around
+    pub fn zoom_by(&mut self, _notches: f32, _around: (f32, f32)) {
+        let fixed = self.to_board(_around);
+        self.zoom = (self.zoom * ZOOM_STEP.powf(_notches)).max(1.0).min(MAX_ZOOM);
+        self.offset = (_around.0 - fixed.0 * self.zoom, _around.1 - fixed.1 * self.zoom);
+        self.clamp();
+    }
+
+    /// Moves the board by a distance in window coordinates
+    pub fn pan_by(&mut self, _dx: f32, _dy: f32) {
+        self.offset = (self.offset.0 + _dx, self.offset.1 + _dy);
+        self.clamp();
+    }
+
+    /// Keeps the board covering all of its area of the window
+    fn clamp(&mut self) {
+        let size = (GRID_SIZE * GRID_CELL_SIZE.0) as f32;
+        let least = size * (1.0 - self.zoom);
+
+        self.offset = (self.offset.0.max(least).min(0.0), self.offset.1.max(least).min(0.0));
+    }
+
+    /// ## `input`
+    /// Zooms with the mouse wheel and pans while the middle button is held
+    /// ### Return
+    /// If the event was used up by the view, anything else is left for the game
+    pub fn input(&mut self, _event: InputEvent) -> bool {
+        match _event {
+            InputEvent::Scroll { y } => {
+                self.zoom_by(y, self.cursor);
+                true
+            },
+            InputEvent::MiddlePress { x, y } => {
+                self.cursor = (x, y);
+                self.panning = true;
+                true
+            },
+            InputEvent::MiddleRelease { .. } => {
+                self.panning = false;
+                true
+            },
+            InputEvent::Motion { x, y } => {
+                let (dx, dy) = (x - self.cursor.0, y - self.cursor.1);
+                self.cursor = (x, y);
+
+                if self.panning {
+                    self.pan_by(dx, dy);
+                }
+                self.panning
+            },
+            _ => false
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use super::backend::PromotionKind;
+
+/// Most proposals shown in the panel at once, the oldest are dropped beyond that
+pub const MAX_PROPOSALS: usize = 5;
+
+/// A move put forward by the group, with everyone who proposed it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proposal {
+    pub from: String, // "<file><rank>"
+    pub to: String, // "<file><rank>"
+    pub promotion: Option<PromotionKind>,
+    pub voters: Vec<usize> // Who proposed it, from 0
+}
+
+impl Proposal {
+    /// The move in coordinate notation, i.e. "e2-e4" or "b7-b8=N"
+    pub fn notation(&self) -> String {
+        let promotion = match self.promotion {
+            Some(PromotionKind::Queen) => "=Q",
+            Some(PromotionKind::Knight) => "=N",
+            Some(PromotionKind::Rook) => "=R",
+            Some(PromotionKind::Bishop) => "=B",
+            None => ""
+        };
+
+        format!("{}-{}{}", self.from, self.to, promotion)
+    }
+}
+
+/// Vote chess: the people at the screen take turns proposing moves for their side, then one of the proposals is
+/// confirmed and played. Moves made on the board by a human side become proposals instead of being played,
+/// see `Command::Propose`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Vote {
+    pub voters: usize, // People in the group
+    pub turn: usize, // Voter proposing next, from 0
+    pub proposals: Vec<Proposal> // For the move being decided, in the order they were first proposed
+}
+
+impl Vote {
+    /// A vote of a group of `_voters` people, at least two
+    pub fn new(_voters: usize) -> Vote {
+        Vote { voters: _voters.max(2), turn: 0, proposals: vec![] }
+    }
+
+    /// ## `propose`
+    /// Adds a move for the voter whose turn it is, then passes the turn on.
+    /// A move that was already proposed gets one more voter instead of a second row.
+    pub fn propose(&mut self, _from: String, _to: String, _promotion: Option<PromotionKind>) {
+        let voter = self.turn;
+        self.turn = (self.turn + 1) % self.voters;
+
+        // A voter backs one move at a time
+        for _proposal in self.proposals.iter_mut() {
+            _proposal.voters.retain(|_voter| *_voter != voter);
+        }
+        self.proposals.retain(|_proposal| !_proposal.voters.is_empty());
+
+        match self.proposals.iter_mut().find(|_proposal| _proposal.from == _from && _proposal.to == _to && _proposal.promotion == _promotion) {
+            Some(proposal) => proposal.voters.push(voter),
+            None => {
+                self.proposals.push(Proposal { from: _from, to: _to, promotion: _promotion, voters: vec![voter] });
+                if self.proposals.len() > MAX_PROPOSALS {
+                    self.proposals.remove(0);
+                }
+            }
+        }
+    }
+
+    /// Index of the proposal with the most voters, the earliest of them on a tie
+    pub fn leading(&self) -> Option<usize> {
+        self.proposals.iter().enumerate()
+            .fold(None, |_best: Option<(usize, usize)>, (_index, _proposal)| match _best {
+                Some((_, votes)) if votes >= _proposal.voters.len() => _best,
+                _ => Some((_index, _proposal.voters.len()))
+            })
+            .map(|(_index, _)| _index)
+    }
+
+    /// Starts over for the next move, the turn to propose goes on where it was
+    pub fn clear(&mut self) {
+        self.proposals.clear();
+    }
+
+    /// Rectangle of a proposal's row in the panel
+    pub fn row(_index: usize) -> (f32, f32, f32, f32) {
+        (160.0, 752.0 + _index as f32 * 23.0, 400.0, 21.0)
+    }
+
+    /// Rectangle of the button withdrawing a proposal, at the end of its row
+    pub fn withdraw_button(_index: usize) -> (f32, f32, f32, f32) {
+        let row = Vote::row(_index);
+        (row.0 + row.2 - row.3, row.1, row.3, row.3)
+    }
+}
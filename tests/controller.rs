@@ -0,0 +1,188 @@
+use eliasfl_chess::{Color as Colour, Piece};
+use ggez::event::KeyCode;
+use schack_gui::backend::{ChessBackend, GameStatus};
+#[cfg(feature = "shakmaty")]
+use schack_gui::backend::BackendKind;
+use schack_gui::clock::TimeControl;
+use schack_gui::consts::PROMOTION_CHOICES;
+use schack_gui::controller::{Command, GameController};
+
+/// Middle of a rectangle in window coordinates, where a click on it lands
+fn centre(_rect: (f32, f32, f32, f32)) -> (f32, f32) {
+    (_rect.0 + _rect.2 / 2.0, _rect.1 + _rect.3 / 2.0)
+}
+
+// A whole game played through the input of the window: selecting, a capture by clicks, a move dragged, a promotion
+// chosen in the panel that mates, and the click on the panel starting a new game
+#[test]
+fn select_move_promote_mate_restart() {
+    let mut controller = GameController::new();
+    controller.execute(Command::LoadFen("k7/4P3/p7/1K6/8/7p/8/8 w - - 0 1".to_string()));
+
+    // Selecting the king shows where it can go
+    let state = controller.click_square("b5");
+    assert_eq!(state.selected, Some((1, 3)));
+    assert!(state.legal.contains(&(0, 2)));
+    assert!(!state.promoting);
+
+    // The king takes the pawn on a6
+    let state = controller.click_square("a6");
+    assert_eq!(state.selected, None);
+    assert!(state.legal.is_empty());
+    assert!(state.active_colour == Colour::Black);
+    assert!(matches!(state.deaths[&Colour::Black].as_slice(), [Piece::Pawn(Colour::Black)]));
+    assert_eq!(state.game_state, GameStatus::InProgress);
+
+    // Black drags its pawn on
+    let state = controller.drag_square("h3", "h2");
+    assert!(state.active_colour == Colour::White);
+    assert_eq!(controller.history.len(), 2);
+
+    // The pawn reaching the last rank waits for the choice of piece
+    controller.click_square("e7");
+    let state = controller.click_square("e8");
+    assert!(state.promoting);
+    assert!(state.active_colour == Colour::White);
+
+    // A queen, and it is mate
+    let (x, y) = centre(PROMOTION_CHOICES[0]);
+    let state = controller.click(x, y);
+    assert!(!state.promoting);
+    assert_eq!(state.game_state, GameStatus::CheckMate);
+    assert!(state.active_colour == Colour::Black);
+    assert!(controller.is_over());
+
+    // The mated king has nowhere to go
+    let state = controller.click_square("a8");
+    assert!(state.legal.is_empty());
+    assert_eq!(state.game_state, GameStatus::CheckMate);
+
+    // The panel under the board starts a new game from the starting position
+    let state = controller.click(360.0, 800.0);
+    assert_eq!(state.game_state, GameStatus::InProgress);
+    assert!(state.active_colour == Colour::White);
+    assert_eq!(state.selected, None);
+    assert!(state.legal.is_empty());
+    assert!(!state.promoting);
+    assert!(state.deaths.values().all(|_pieces| _pieces.is_empty()));
+    assert!(controller.history.is_empty());
+    assert!(controller.start_fen.is_none());
+
+    // And it is played like any other
+    let state = controller.click_square("e2");
+    assert_eq!(state.legal.len(), 2);
+    let state = controller.click_square("e4");
+    assert!(state.active_colour == Colour::Black);
+}
+
+// The keyboard: Escape dropping the selection, the promotion chosen by its letter, and the casual Left and Right taking
+// the moves back and playing them again
+#[test]
+fn keys_clear_promote_take_back_redo() {
+    let mut controller = GameController::new();
+    controller.execute(Command::LoadFen("k7/4P3/8/8/8/8/8/K7 w - - 0 1".to_string()));
+    controller.casual_takebacks = true;
+
+    // Escape lets go of the selected king
+    let state = controller.click_square("a1");
+    assert_eq!(state.selected, Some((0, 7)));
+    let state = controller.key_press(KeyCode::Escape);
+    assert_eq!(state.selected, None);
+    assert!(state.legal.is_empty());
+
+    // Escape does not cancel a promotion, N picks the knight
+    controller.click_square("e7");
+    let state = controller.click_square("e8");
+    assert!(state.promoting);
+    let state = controller.key_press(KeyCode::Escape);
+    assert!(state.promoting);
+    let state = controller.key_press(KeyCode::N);
+    assert!(!state.promoting);
+    assert!(state.active_colour == Colour::Black);
+    assert!(matches!(controller.game.piece_at((4, 0)), Some(Piece::Knight(Colour::White))));
+
+    // Black answers
+    let state = controller.click_square("a8");
+    assert!(state.legal.contains(&(1, 1)));
+    let state = controller.click_square("b7");
+    assert!(state.active_colour == Colour::White);
+    assert_eq!(controller.history.len(), 2);
+
+    // Left takes the whole move back, the pawn is on e7 again
+    let state = controller.key_press(KeyCode::Left);
+    assert!(state.active_colour == Colour::White);
+    assert!(controller.history.is_empty());
+    assert!(matches!(controller.game.piece_at((4, 1)), Some(Piece::Pawn(Colour::White))));
+    assert!(controller.game.piece_at((4, 0)).is_none());
+
+    // Right plays them again one at a time, with the knight that was chosen
+    let state = controller.key_press(KeyCode::Right);
+    assert!(state.active_colour == Colour::Black);
+    assert!(matches!(controller.game.piece_at((4, 0)), Some(Piece::Knight(Colour::White))));
+    let state = controller.key_press(KeyCode::Right);
+    assert!(state.active_colour == Colour::White);
+    assert!(matches!(controller.game.piece_at((1, 1)), Some(Piece::King(Colour::Black))));
+    assert_eq!(controller.history.len(), 2);
+}
+
+// Taking moves back gives the movers the time they had before them, and playing them again the time they had after,
+// so the increment is earned once per move
+#[test]
+fn takebacks_restore_the_clocks() {
+    let mut controller = GameController::new();
+    controller.time_control = Some(TimeControl::minutes(5.0, 3.0));
+    controller.execute(Command::Restart);
+
+    controller.tick(10.0);
+    controller.click_square("e2");
+    controller.click_square("e4");
+    controller.tick(4.0);
+    controller.click_square("e7");
+    controller.click_square("e5");
+    assert_eq!(controller.clocks[&Colour::White].remaining(), 293.0);
+    assert_eq!(controller.clocks[&Colour::Black].remaining(), 299.0);
+
+    // Both moves taken back, both clocks are where they started
+    controller.execute(Command::TakeBack);
+    assert!(controller.history.is_empty());
+    assert_eq!(controller.clocks[&Colour::White].remaining(), 300.0);
+    assert_eq!(controller.clocks[&Colour::Black].remaining(), 300.0);
+
+    // Played again, each with the time it was played with
+    controller.execute(Command::Redo);
+    assert_eq!(controller.clocks[&Colour::White].remaining(), 293.0);
+    assert_eq!(controller.history[0].clock, Some(293.0));
+    controller.execute(Command::Redo);
+    assert_eq!(controller.clocks[&Colour::Black].remaining(), 299.0);
+    assert_eq!(controller.history[1].clock, Some(299.0));
+
+    // Taking a move back and playing it again by hand, over and over, earns the increment once
+    for _ in 0..3 {
+        controller.execute(Command::Undo);
+        assert_eq!(controller.clocks[&Colour::Black].remaining(), 300.0);
+        controller.click_square("e7");
+        controller.click_square("e5");
+        assert_eq!(controller.clocks[&Colour::Black].remaining(), 303.0);
+    }
+}
+
+// A pawn taken en passant is a capture like any other: it is recorded, dies, and comes back when the move is taken back.
+// The Elias Engine does not play en passant, shakmaty does.
+#[cfg(feature = "shakmaty")]
+#[test]
+fn en_passant_captures() {
+    let mut controller = GameController::with_backend(BackendKind::Shakmaty.create());
+    for (_from, _to) in [("e2", "e4"), ("a7", "a6"), ("e4", "e5"), ("d7", "d5"), ("e5", "d6")].iter() {
+        controller.click_square(_from);
+        controller.click_square(_to);
+    }
+
+    assert_eq!(controller.history.len(), 5);
+    assert!(matches!(controller.history.last().unwrap().captured, Some(Piece::Pawn(Colour::Black))));
+    assert!(controller.game.piece_at((3, 3)).is_none());
+    assert!(matches!(controller.deaths[&Colour::Black].as_slice(), [Piece::Pawn(Colour::Black)]));
+
+    controller.execute(Command::Undo);
+    assert!(controller.deaths[&Colour::Black].is_empty());
+    assert!(matches!(controller.game.piece_at((3, 3)), Some(Piece::Pawn(Colour::Black))));
+}
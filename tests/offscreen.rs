@@ -0,0 +1,74 @@
+use ggez::{filesystem, graphics, Context};
+use schack_gui::AppState;
+use schack_gui::consts::SCREEN_SIZE;
+use schack_gui::controller::{Command, GameController};
+use schack_gui::graphics_funcs::offscreen::{headless_context, render_to_image, render_to_rgba};
+use schack_gui::scenes::game::GameScene;
+use std::path::PathBuf;
+
+// The position rendered: every kind of piece of both sides, a piece selected with its legal moves shown
+const POSITION: &str = "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 4 4";
+const SELECTED: &str = "f3";
+
+// The reference image, in `tests/reference`
+const REFERENCE: &str = "/reference/offscreen_position.png";
+
+// Channels further apart than this tell a pixel apart, closer ones are put down to the graphics driver
+const CHANNEL_TOLERANCE: u8 = 8;
+// Share of pixels allowed to differ, for the edges of the pieces and text drawn a little differently
+const PIXEL_TOLERANCE: f32 = 0.005;
+
+fn manifest_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+}
+
+/// Writes the rendered position as the new reference, when it was rendered with `SCHACK_BLESS=1`
+fn bless(_ctx: &mut Context, _appstate: &mut AppState) {
+    let image = render_to_image(_ctx, _appstate).expect("The position could not be rendered.");
+    image.encode(_ctx, graphics::ImageFormat::Png, "/offscreen_position.png").expect("The reference could not be encoded.");
+
+    let written = filesystem::user_config_dir(_ctx).join("offscreen_position.png");
+    let reference = manifest_dir().join("tests").join(REFERENCE.trim_start_matches('/'));
+    std::fs::copy(&written, &reference).expect("The reference could not be written.");
+    println!("Reference written to {}", reference.display());
+}
+
+// A fixed position rendered offscreen matches the reference image of it, within the tolerances
+#[test]
+fn render_matches_reference() {
+    // Machines without a display (i.e. most CI runners) cannot make a context at all
+    let (mut ctx, _event_loop) = match headless_context(manifest_dir().join("resources")) {
+        Ok(context) => context,
+        Err(error) => {
+            eprintln!("Skipped, no graphics context: {}", error);
+            return;
+        }
+    };
+    filesystem::mount(&mut ctx, &manifest_dir().join("tests"), true);
+
+    let mut controller = GameController::new();
+    controller.execute(Command::LoadFen(POSITION.to_string()));
+    controller.click_square(SELECTED);
+    let mut appstate = AppState::with_scene(&mut ctx, controller, Box::new(GameScene)).expect("The application could not be set up.");
+
+    if std::env::var("SCHACK_BLESS").is_ok() {
+        bless(&mut ctx, &mut appstate);
+        return;
+    }
+
+    // A missing reference is a failure, not a pass: render it with SCHACK_BLESS=1 and check it in
+    let path = manifest_dir().join("tests").join(REFERENCE.trim_start_matches('/'));
+    assert!(path.exists(), "No reference image at {}, render one with SCHACK_BLESS=1 and check it in", path.display());
+
+    let rendered = render_to_rgba(&mut ctx, &mut appstate).expect("The position could not be rendered.");
+    let reference = graphics::Image::new(&mut ctx, REFERENCE).expect("The reference could not be loaded.");
+    assert_eq!((reference.width() as f32, reference.height() as f32), SCREEN_SIZE, "The reference is not the size of the window");
+    let reference = reference.to_rgba8(&mut ctx).expect("The reference could not be read.");
+    assert_eq!(rendered.len(), reference.len());
+
+    let differing = rendered.chunks(4).zip(reference.chunks(4))
+        .filter(|(_rendered, _reference)| _rendered.iter().zip(_reference.iter()).any(|(_a, _b)| _a.max(_b) - _a.min(_b) > CHANNEL_TOLERANCE))
+        .count();
+    let share = differing as f32 / (rendered.len() / 4) as f32;
+    assert!(share <= PIXEL_TOLERANCE, "{} pixels ({:.2}%) differ from the reference", differing, share * 100.0);
+}